@@ -0,0 +1,51 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::hint::black_box;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use init_data_rs::parse;
+
+/// Counts heap allocations, so the benchmark also reports allocations per call.
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+const INIT_DATA: &str = "user=%7B%22id%22%3A279058397%2C%22first_name%22%3A%22Vladislav%20%2B%20-%20%3F%20%5C%2F%22%2C%22last_name%22%3A%22Kibenko%22%2C%22username%22%3A%22vdkfrost%22%2C%22language_code%22%3A%22ru%22%2C%22is_premium%22%3Atrue%2C%22allows_write_to_pm%22%3Atrue%2C%22photo_url%22%3A%22https%3A%5C%2F%5C%2Ft.me%5C%2Fi%5C%2Fuserpic%5C%2F320%5C%2F4FPEE4tmP3ATHa57u6MqTDih13LTOiMoKoLDRG4PnSA.svg%22%7D&chat_instance=8134722200314281151&chat_type=private&auth_date=1733584787&hash=2174df5b000556d044f3f020384e879c8efcab55ddea2ced4eb752e93e7080d6&signature=zL-ucjNyREiHDE8aihFwpfR9aggP2xiAo3NSpfe-p7IbCisNlDKlo7Kb6G4D0Ao2mBrSgEk4maLSdv6MLIlADQ";
+
+fn bench_parse(c: &mut Criterion) {
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    black_box(parse(black_box(INIT_DATA)).unwrap());
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed) - before;
+    println!("parse: {allocations} allocations per call");
+
+    c.bench_function("parse", |b| b.iter(|| parse(black_box(INIT_DATA))));
+
+    // Unverified input padded with thousands of unknown parameters
+    let padded = format!(
+        "{}&{INIT_DATA}",
+        (0..20_000).map(|i| format!("p{i}=x")).collect::<Vec<_>>().join("&")
+    );
+    c.bench_function("parse 20k parameters", |b| b.iter(|| parse(black_box(&padded))));
+}
+
+criterion_group!(benches, bench_parse);
+criterion_main!(benches);
@@ -0,0 +1,63 @@
+use std::hint::black_box;
+use std::time::Duration;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use init_data_rs::{sign, validate, verify, CheckOrder, Expiration, Validator};
+
+const BOT_TOKEN: &str = "5768337691:AAH5YkoiEuPk8-FZa32hStHTqXiLPtAEhx8";
+const INIT_DATA: &str = "query_id=AAHdF6IQAAAAAN0XohDhrOrc&user=%7B%22id%22%3A279058397%2C%22first_name%22%3A%22Vladislav%22%2C%22last_name%22%3A%22Kibenko%22%2C%22username%22%3A%22vdkfrost%22%2C%22language_code%22%3A%22ru%22%2C%22is_premium%22%3Atrue%7D&auth_date=1662771648&hash=c501b71e775f74ce10e377dea85a7ea24ecd640b223ea86dfe453e0eaed2e2b2";
+const FORGED_INIT_DATA: &str = "query_id=AAHdF6IQAAAAAN0XohDhrOrc&user=%7B%22id%22%3A279058397%2C%22first_name%22%3A%22Mallory%22%2C%22last_name%22%3A%22Kibenko%22%2C%22username%22%3A%22vdkfrost%22%2C%22language_code%22%3A%22ru%22%2C%22is_premium%22%3Atrue%7D&auth_date=1662771648&hash=c501b71e775f74ce10e377dea85a7ea24ecd640b223ea86dfe453e0eaed2e2b2";
+
+fn bench_validation(c: &mut Criterion) {
+    c.bench_function("sign", |b| b.iter(|| sign(black_box(INIT_DATA), black_box(BOT_TOKEN))));
+    c.bench_function("verify", |b| {
+        b.iter(|| verify(black_box(INIT_DATA), black_box(BOT_TOKEN), Expiration::Never));
+    });
+    c.bench_function("verify forged", |b| {
+        b.iter(|| verify(black_box(FORGED_INIT_DATA), black_box(BOT_TOKEN), Expiration::Never));
+    });
+    c.bench_function("verify malformed hash", |b| {
+        let init_data = INIT_DATA.replace("&hash=c501", "&hash=zzzz");
+        b.iter(|| verify(black_box(&init_data), black_box(BOT_TOKEN), Expiration::Never));
+    });
+    c.bench_function("verify expired", |b| {
+        b.iter(|| {
+            verify(
+                black_box(INIT_DATA),
+                black_box(BOT_TOKEN),
+                Expiration::After(Duration::from_secs(1)),
+            )
+        });
+    });
+    c.bench_function("validate", |b| {
+        b.iter(|| validate(black_box(INIT_DATA), black_box(BOT_TOKEN), Expiration::Never));
+    });
+
+    let validator = Validator::new(BOT_TOKEN).expiration(Expiration::Never);
+    c.bench_function("Validator::validate", |b| {
+        b.iter(|| validator.validate(black_box(INIT_DATA)))
+    });
+}
+
+/// A flood of forged payloads with a huge `user`, either expired or with a fresh `auth_date`.
+fn bench_adversarial(c: &mut Criterion) {
+    let user = format!(
+        "%7B%22id%22%3A1%2C%22first_name%22%3A%22{}%22%7D",
+        "A".repeat(64 * 1024)
+    );
+    let expired = format!("user={user}&auth_date=1662771648&hash={}", "0".repeat(64));
+    let fresh = expired.replace("auth_date=1662771648", "auth_date=99999999999");
+
+    for order in [CheckOrder::CheapestFirst, CheckOrder::Uniform] {
+        let validator = Validator::new(BOT_TOKEN).check_order(order);
+        c.bench_function(&format!("Validator::validate expired forged ({order:?})"), |b| {
+            b.iter(|| validator.validate(black_box(&expired)));
+        });
+        c.bench_function(&format!("Validator::validate fresh forged ({order:?})"), |b| {
+            b.iter(|| validator.validate(black_box(&fresh)));
+        });
+    }
+}
+
+criterion_group!(benches, bench_validation, bench_adversarial);
+criterion_main!(benches);
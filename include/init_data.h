@@ -0,0 +1,116 @@
+#ifndef INIT_DATA_H
+#define INIT_DATA_H
+
+/* This file is generated by cbindgen, do not edit it manually. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// `expires_in` selecting the default expiration of 24 hours, [`Expiration::Default`]. Any negative value does.
+#define INIT_DATA_EXPIRATION_DEFAULT -1
+
+// `expires_in` disabling the expiration check, [`Expiration::Never`].
+#define INIT_DATA_EXPIRATION_NEVER 0
+
+// Outcome of an FFI call.
+typedef enum InitDataErrorCode {
+  INIT_DATA_ERROR_CODE_OK = 0,
+  INIT_DATA_ERROR_CODE_AUTH_DATE_MISSING = 1,
+  INIT_DATA_ERROR_CODE_HASH_MISSING = 2,
+  INIT_DATA_ERROR_CODE_HASH_INVALID = 3,
+  INIT_DATA_ERROR_CODE_UNEXPECTED_FORMAT = 4,
+  INIT_DATA_ERROR_CODE_EXPIRED = 5,
+  INIT_DATA_ERROR_CODE_INTERNAL = 6,
+  INIT_DATA_ERROR_CODE_SIGNATURE_MISSING = 7,
+  INIT_DATA_ERROR_CODE_SIGNATURE_INVALID = 8,
+  INIT_DATA_ERROR_CODE_THROTTLED = 9,
+  INIT_DATA_ERROR_CODE_PAYLOAD_TOO_LARGE = 10,
+  INIT_DATA_ERROR_CODE_CONFIG = 11,
+  INIT_DATA_ERROR_CODE_DEADLINE_EXCEEDED = 12,
+  INIT_DATA_ERROR_CODE_USER_MISSING = 13,
+  INIT_DATA_ERROR_CODE_CHAT_MISSING = 14,
+  INIT_DATA_ERROR_CODE_CLIENT_NOT_ALLOWED = 15,
+  INIT_DATA_ERROR_CODE_START_PARAM_INVALID = 16,
+  INIT_DATA_ERROR_CODE_AUTH_DATE_INVALID = 17,
+  INIT_DATA_ERROR_CODE_USER_DENIED = 18,
+  INIT_DATA_ERROR_CODE_CHAT_NOT_ALLOWED = 19,
+  // A pointer argument was null or a string argument was not valid UTF-8.
+  INIT_DATA_ERROR_CODE_INVALID_ARGUMENT = 100,
+} InitDataErrorCode;
+
+// Validates init data using the bot token.
+//
+// `expires_in` is how long the init data stays valid in seconds, [`INIT_DATA_EXPIRATION_DEFAULT`] for
+// the default of 24 hours or [`INIT_DATA_EXPIRATION_NEVER`] to skip the expiration check.
+//
+// # Safety
+//
+// `init_data` and `token` must be null or point to valid NUL-terminated strings.
+enum InitDataErrorCode init_data_validate(const char *init_data,
+                                          const char *token,
+                                          int64_t expires_in);
+
+// Validates init data for third-party use with the bot id.
+//
+// `expires_in` follows the same convention as in [`init_data_validate`].
+//
+// # Safety
+//
+// `init_data` must be null or point to a valid NUL-terminated string.
+enum InitDataErrorCode init_data_validate_third_party(const char *init_data,
+                                                      int64_t bot_id,
+                                                      int64_t expires_in);
+
+// Validates init data using the bot token and writes the parsed data as JSON to `out_json`.
+//
+// On success `*out_json` must be released with [`init_data_string_free`]; on failure it is left
+// untouched.
+//
+// # Safety
+//
+// `init_data` and `token` must be null or point to valid NUL-terminated strings, `out_json` must
+// be null or valid for writes.
+enum InitDataErrorCode init_data_validate_json(const char *init_data,
+                                               const char *token,
+                                               int64_t expires_in,
+                                               char **out_json);
+
+// Parses init data without validating it and writes the result as JSON to `out_json`.
+//
+// On success `*out_json` must be released with [`init_data_string_free`]; on failure it is left
+// untouched.
+//
+// # Safety
+//
+// `init_data` must be null or point to a valid NUL-terminated string, `out_json` must be null or
+// valid for writes.
+enum InitDataErrorCode init_data_parse_json(const char *init_data, char **out_json);
+
+// Computes the hash of init data using the bot token and writes it as hex to `out_hash`.
+//
+// On success `*out_hash` must be released with [`init_data_string_free`]; on failure it is left
+// untouched.
+//
+// # Safety
+//
+// `init_data` and `token` must be null or point to valid NUL-terminated strings, `out_hash` must
+// be null or valid for writes.
+enum InitDataErrorCode init_data_sign(const char *init_data, const char *token, char **out_hash);
+
+// Releases a string previously returned by this library. Passing null is a no-op.
+//
+// # Safety
+//
+// `value` must be null or a pointer returned by this library that has not been freed yet.
+void init_data_string_free(char *value);
+
+// Returns a static, NUL-terminated description of an error code, or of an unknown one. The result must not be
+// freed.
+//
+// `code` is an `int` rather than an [`InitDataErrorCode`], as C allows any value in an enum.
+const char *init_data_error_message(int code);
+
+#endif  /* INIT_DATA_H */
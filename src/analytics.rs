@@ -0,0 +1,260 @@
+//! Usage counts over validated init data, for product dashboards.
+//!
+//! [`Aggregator`] only keeps counters by language, premium status, platform, chat type and error kind, never
+//! user ids, names or the init data itself.
+
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::Serialize;
+
+use crate::clock::{Clock, SystemClock};
+use crate::error::InitDataError;
+use crate::launch_params::{LaunchParams, Platform};
+use crate::model::InitData;
+
+/// Number of buckets a window is split into, which bounds how far past the window old counts can linger.
+const BUCKETS: u64 = 60;
+
+/// Counts of validation outcomes, see [`Aggregator::snapshot`].
+///
+/// Labels are the names Telegram uses, e.g. `supergroup` or `tdesktop`. Unrecognized platforms count as
+/// `unknown` and malformed language codes as `other`, so that clients can't inflate the number of labels.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct Snapshot {
+    /// Successful validations.
+    pub valid: u64,
+    /// Failed validations by [`InitDataError::kind`].
+    pub errors: BTreeMap<&'static str, u64>,
+    /// Successful validations by the `language_code` of the user.
+    pub language_codes: BTreeMap<String, u64>,
+    /// Successful validations of users with Telegram Premium.
+    pub premium: u64,
+    /// Successful validations of users without Telegram Premium.
+    pub non_premium: u64,
+    /// Successful validations by the platform of the client, only known from launch parameters.
+    pub platforms: BTreeMap<&'static str, u64>,
+    /// Successful validations by the type of chat the Mini App was opened from.
+    pub chat_types: BTreeMap<&'static str, u64>,
+}
+
+impl Snapshot {
+    fn add(&mut self, other: &Self) {
+        self.valid += other.valid;
+        self.premium += other.premium;
+        self.non_premium += other.non_premium;
+        merge(&mut self.errors, &other.errors);
+        merge(&mut self.language_codes, &other.language_codes);
+        merge(&mut self.platforms, &other.platforms);
+        merge(&mut self.chat_types, &other.chat_types);
+    }
+
+    fn record(&mut self, data: &InitData, platform: Option<&Platform>) {
+        self.valid += 1;
+
+        if let Some(user) = &data.user {
+            if let Some(language_code) = &user.language_code {
+                *self.language_codes.entry(language_label(language_code)).or_default() += 1;
+            }
+            if user.is_premium == Some(true) {
+                self.premium += 1;
+            } else {
+                self.non_premium += 1;
+            }
+        }
+        if let Some(platform) = platform {
+            *self.platforms.entry(platform_label(platform)).or_default() += 1;
+        }
+        if let Some(chat_type) = data.effective_chat_type() {
+            *self.chat_types.entry(chat_type.as_str()).or_default() += 1;
+        }
+    }
+}
+
+fn merge<K: Ord + Clone>(into: &mut BTreeMap<K, u64>, from: &BTreeMap<K, u64>) {
+    for (key, count) in from {
+        *into.entry(key.clone()).or_default() += count;
+    }
+}
+
+/// An IETF language tag such as `en` or `pt-br`, lowercased, or `other`.
+fn language_label(language_code: &str) -> String {
+    let valid = (2..=10).contains(&language_code.len())
+        && language_code
+            .bytes()
+            .all(|byte| byte.is_ascii_alphanumeric() || byte == b'-');
+
+    if valid {
+        language_code.to_ascii_lowercase()
+    } else {
+        "other".to_string()
+    }
+}
+
+fn platform_label(platform: &Platform) -> &'static str {
+    match platform {
+        Platform::Android => "android",
+        Platform::Ios => "ios",
+        Platform::Macos => "macos",
+        Platform::Tdesktop => "tdesktop",
+        Platform::Web => "web",
+        Platform::Weba => "weba",
+        Platform::Unknown(_) => "unknown",
+    }
+}
+
+/// Rolling counts of validation outcomes over the last `window`.
+///
+/// The window is split into 60 buckets, counts leave the snapshot once their whole bucket has left the window.
+///
+/// # Example
+/// ```
+/// use std::time::Duration;
+/// use init_data_rs::analytics::Aggregator;
+/// use init_data_rs::Validator;
+///
+/// let validator = Validator::new("BOT_TOKEN");
+/// let analytics = Aggregator::new(Duration::from_secs(3600));
+///
+/// let result = validator.validate("query_id=123&auth_date=1662771648&hash=...");
+/// analytics.record(&result);
+///
+/// let snapshot = analytics.snapshot();
+/// assert_eq!(snapshot.errors["hash_invalid"], 1);
+/// ```
+#[derive(Debug)]
+pub struct Aggregator<C: Clock = SystemClock> {
+    bucket_secs: u64,
+    clock: C,
+    buckets: Mutex<VecDeque<(u64, Snapshot)>>,
+}
+
+impl Aggregator {
+    /// Counts the outcomes of the last `window`, at least 60 seconds.
+    #[must_use]
+    pub fn new(window: Duration) -> Self {
+        Self::with_clock(window, SystemClock)
+    }
+}
+
+impl<C: Clock> Aggregator<C> {
+    /// Same as [`Aggregator::new`], but reads the current time from `clock`.
+    pub fn with_clock(window: Duration, clock: C) -> Self {
+        Self {
+            bucket_secs: (window.as_secs() / BUCKETS).max(1),
+            clock,
+            buckets: Mutex::default(),
+        }
+    }
+
+    /// Counts the outcome of a validation.
+    pub fn record(&self, outcome: &Result<InitData, InitDataError>) {
+        self.update(|counts| match outcome {
+            Ok(data) => counts.record(data, None),
+            Err(error) => *counts.errors.entry(error.kind()).or_default() += 1,
+        });
+    }
+
+    /// Counts the outcome of [`crate::Validator::validate_launch_params`], which also knows the platform.
+    pub fn record_launch(&self, outcome: &Result<(LaunchParams, InitData), InitDataError>) {
+        self.update(|counts| match outcome {
+            Ok((params, data)) => counts.record(data, Some(&params.platform)),
+            Err(error) => *counts.errors.entry(error.kind()).or_default() += 1,
+        });
+    }
+
+    /// The counts over the current window.
+    pub fn snapshot(&self) -> Snapshot {
+        let now = self.clock.now() / self.bucket_secs;
+        let mut buckets = self.buckets.lock().unwrap_or_else(std::sync::PoisonError::into_inner);
+        Self::evict(&mut buckets, now);
+
+        let mut snapshot = Snapshot::default();
+        for (_, counts) in buckets.iter() {
+            snapshot.add(counts);
+        }
+        snapshot
+    }
+
+    fn update(&self, count: impl FnOnce(&mut Snapshot)) {
+        let now = self.clock.now() / self.bucket_secs;
+        let mut buckets = self.buckets.lock().unwrap_or_else(std::sync::PoisonError::into_inner);
+        Self::evict(&mut buckets, now);
+
+        if buckets.back().is_none_or(|(bucket, _)| *bucket != now) {
+            buckets.push_back((now, Snapshot::default()));
+        }
+        if let Some((_, counts)) = buckets.back_mut() {
+            count(counts);
+        }
+    }
+
+    /// Drops the buckets that left the window ending in bucket `now`.
+    fn evict(buckets: &mut VecDeque<(u64, Snapshot)>, now: u64) {
+        while buckets.front().is_some_and(|(bucket, _)| bucket + BUCKETS <= now) {
+            buckets.pop_front();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::Arc;
+
+    use super::*;
+
+    #[test]
+    fn test_aggregator_counts() {
+        let analytics = Aggregator::new(Duration::from_secs(3600));
+        let data = |fields: &str| {
+            crate::parse(&format!(
+                "{fields}auth_date=1&hash=c501b71e775f74ce10e377dea85a7ea24ecd640b223ea86dfe453e0eaed2e2b2"
+            ))
+        };
+
+        analytics.record(&data(
+            "user=%7B%22id%22%3A1%2C%22first_name%22%3A%22A%22%2C%22language_code%22%3A%22EN%22%2C%22is_premium%22%3Atrue%7D&chat_type=group&",
+        ));
+        analytics.record(&data(
+            "user=%7B%22id%22%3A2%2C%22first_name%22%3A%22B%22%2C%22language_code%22%3A%22%3Cscript%3E%22%7D&",
+        ));
+        analytics.record(&Err(InitDataError::Expired));
+        analytics.record_launch(
+            &LaunchParams::parse("tgWebAppVersion=8.0&tgWebAppPlatform=vr-headset")
+                .map(|params| (params, data("").unwrap())),
+        );
+
+        let snapshot = analytics.snapshot();
+        assert_eq!(snapshot.valid, 3);
+        assert_eq!(snapshot.errors, BTreeMap::from([("expired", 1)]));
+        assert_eq!(
+            snapshot.language_codes,
+            BTreeMap::from([("en".to_string(), 1), ("other".to_string(), 1)])
+        );
+        assert_eq!((snapshot.premium, snapshot.non_premium), (1, 1));
+        assert_eq!(snapshot.platforms, BTreeMap::from([("unknown", 1)]));
+        assert_eq!(snapshot.chat_types, BTreeMap::from([("group", 1)]));
+    }
+
+    #[test]
+    fn test_aggregator_window() {
+        let now = Arc::new(AtomicU64::new(0));
+        let clock = {
+            let now = Arc::clone(&now);
+            move || now.load(Ordering::Relaxed)
+        };
+        let analytics = Aggregator::with_clock(Duration::from_secs(60), clock);
+
+        analytics.record(&Err(InitDataError::HashInvalid));
+        now.store(30, Ordering::Relaxed);
+        analytics.record(&Err(InitDataError::HashInvalid));
+        assert_eq!(analytics.snapshot().errors["hash_invalid"], 2);
+
+        now.store(60, Ordering::Relaxed);
+        assert_eq!(analytics.snapshot().errors["hash_invalid"], 1);
+        now.store(90, Ordering::Relaxed);
+        assert_eq!(analytics.snapshot(), Snapshot::default());
+    }
+}
@@ -0,0 +1,207 @@
+//! Command line tool for debugging Telegram Mini Apps init data.
+//!
+//! Built with the `cli` feature:
+//!
+//! ```sh
+//! cargo install init-data-rs --features cli
+//! init-data validate "$INIT_DATA" --token "$TELEGRAM_BOT_TOKEN"
+//! ```
+
+use std::io::Read;
+use std::process::ExitCode;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use clap::{Parser, Subcommand};
+use init_data_rs::{parse, sign, validate, validate_third_party, Expiration, InitDataError};
+
+#[derive(Parser)]
+#[command(
+    name = "init-data",
+    version,
+    about = "Validate, parse and sign Telegram Mini Apps init data"
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Validate init data and print the parsed fields as JSON
+    Validate {
+        /// Raw init data, or `-` to read it from stdin
+        init_data: String,
+        /// Bot token used to check the hash
+        #[arg(
+            long,
+            env = "TELEGRAM_BOT_TOKEN",
+            hide_env_values = true,
+            required_unless_present = "bot_id"
+        )]
+        token: Option<String>,
+        /// Bot id used to check the Ed25519 signature (third-party validation) instead of the hash
+        #[arg(long, conflicts_with = "token")]
+        bot_id: Option<i64>,
+        /// How long init data stays valid after its `auth_date`, in seconds [default: 24 hours]
+        #[arg(long, value_parser = clap::value_parser!(u64).range(1..), conflicts_with = "no_expiration")]
+        expires_in: Option<u64>,
+        /// Skip the expiration check, e.g. for old fixtures
+        #[arg(long)]
+        no_expiration: bool,
+    },
+    /// Parse init data without validating it and print the fields as JSON
+    Parse {
+        /// Raw init data, or `-` to read it from stdin
+        init_data: String,
+    },
+    /// Compute the hash of init data (an existing `hash` parameter is ignored)
+    Sign {
+        /// Raw init data, or `-` to read it from stdin
+        init_data: String,
+        /// Bot token used to compute the hash
+        #[arg(long, env = "TELEGRAM_BOT_TOKEN", hide_env_values = true)]
+        token: String,
+    },
+    /// Generate signed init data for tests
+    Fixture {
+        /// Bot token used to compute the hash
+        #[arg(long, env = "TELEGRAM_BOT_TOKEN", hide_env_values = true)]
+        token: String,
+        /// Value of `auth_date`, defaults to the current time
+        #[arg(long)]
+        auth_date: Option<u64>,
+        /// Value of `user` as JSON
+        #[arg(long)]
+        user: Option<String>,
+        /// Value of `query_id`
+        #[arg(long)]
+        query_id: Option<String>,
+        /// Value of `start_param`
+        #[arg(long)]
+        start_param: Option<String>,
+        /// Additional `key=value` parameters
+        #[arg(long = "param", value_name = "KEY=VALUE", value_parser = parse_key_value)]
+        params: Vec<(String, String)>,
+    },
+}
+
+fn parse_key_value(value: &str) -> Result<(String, String), String> {
+    value
+        .split_once('=')
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .ok_or_else(|| format!("expected KEY=VALUE, got `{value}`"))
+}
+
+fn read_init_data(init_data: String) -> Result<String, String> {
+    if init_data != "-" {
+        return Ok(init_data);
+    }
+
+    let mut buffer = String::new();
+    std::io::stdin()
+        .read_to_string(&mut buffer)
+        .map_err(|error| format!("failed to read stdin: {error}"))?;
+
+    Ok(buffer.trim().to_string())
+}
+
+fn to_json<T: serde::Serialize>(value: &T) -> Result<String, String> {
+    serde_json::to_string_pretty(value).map_err(|error| error.to_string())
+}
+
+/// Adds the expected hash to "hash is invalid" errors, which is what one usually needs
+/// when debugging a rejected payload.
+fn explain(error: &InitDataError, init_data: &str, token: &str) -> String {
+    if !matches!(error, InitDataError::HashInvalid) {
+        return error.to_string();
+    }
+
+    let provided = form_urlencoded::parse(init_data.as_bytes())
+        .find(|(key, _)| key == "hash")
+        .map(|(_, value)| value.into_owned())
+        .unwrap_or_default();
+
+    match sign(init_data, token) {
+        Ok(expected) => format!("{error}\n  provided: {provided}\n  expected: {expected}"),
+        Err(_) => error.to_string(),
+    }
+}
+
+fn run(command: Command) -> Result<String, String> {
+    match command {
+        Command::Validate {
+            init_data,
+            token,
+            bot_id,
+            expires_in,
+            no_expiration,
+        } => {
+            let init_data = read_init_data(init_data)?;
+            let expiration = match expires_in {
+                _ if no_expiration => Expiration::Never,
+                Some(seconds) => Expiration::After(Duration::from_secs(seconds)),
+                None => Expiration::Default,
+            };
+            let data = match (token, bot_id) {
+                (_, Some(bot_id)) => {
+                    validate_third_party(&init_data, bot_id, expiration).map_err(|error| error.to_string())
+                }
+                (Some(token), None) => {
+                    validate(&init_data, &token, expiration).map_err(|error| explain(&error, &init_data, &token))
+                }
+                (None, None) => Err("either --token or --bot-id is required".to_string()),
+            }?;
+            to_json(&data)
+        }
+        Command::Parse { init_data } => {
+            let data = parse(&read_init_data(init_data)?).map_err(|error| error.to_string())?;
+            to_json(&data)
+        }
+        Command::Sign { init_data, token } => {
+            sign(&read_init_data(init_data)?, &token).map_err(|error| error.to_string())
+        }
+        Command::Fixture {
+            token,
+            auth_date,
+            user,
+            query_id,
+            start_param,
+            params,
+        } => {
+            let auth_date = auth_date.unwrap_or_else(|| {
+                SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|duration| duration.as_secs())
+                    .unwrap_or_default()
+            });
+
+            let mut serializer = form_urlencoded::Serializer::new(String::new());
+            serializer.append_pair("auth_date", &auth_date.to_string());
+            for (key, value) in [("user", user), ("query_id", query_id), ("start_param", start_param)] {
+                if let Some(value) = value {
+                    serializer.append_pair(key, &value);
+                }
+            }
+            for (key, value) in &params {
+                serializer.append_pair(key, value);
+            }
+            let init_data = serializer.finish();
+
+            let hash = sign(&init_data, &token).map_err(|error| error.to_string())?;
+            Ok(format!("{init_data}&hash={hash}"))
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    match run(Cli::parse().command) {
+        Ok(output) => {
+            println!("{output}");
+            ExitCode::SUCCESS
+        }
+        Err(error) => {
+            eprintln!("error: {error}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,191 @@
+//! Init data posted in a request body instead of a header, as a JSON, form or multipart field.
+
+use crate::error::InitDataError;
+use crate::launch_params::extract_from_query;
+
+/// Pulls raw init data out of the string field `field` (e.g. `initData`) of a JSON object body, for frontends
+/// posting `{"initData": "..."}`. Nothing is validated here.
+///
+/// # Errors
+///
+/// Returns [`InitDataError::UnexpectedFormat`] if the body is not a JSON object, or the field is missing, empty or
+/// not a string.
+///
+/// # Example
+/// ```
+/// use init_data_rs::extract_from_json;
+///
+/// let init_data = extract_from_json(br#"{"initData": "query_id=123&auth_date=1662771648&hash=...", "page": 2}"#, "initData");
+/// assert_eq!(init_data.unwrap(), "query_id=123&auth_date=1662771648&hash=...");
+/// ```
+#[cfg(feature = "json")]
+pub fn extract_from_json(body: &[u8], field: &str) -> Result<String, InitDataError> {
+    let body: serde_json::Map<String, serde_json::Value> = serde_json::from_slice(body)
+        .map_err(|err| InitDataError::UnexpectedFormat(format!("body is not a JSON object: {err}")))?;
+
+    match body.get(field) {
+        Some(serde_json::Value::String(init_data)) if !init_data.is_empty() => Ok(init_data.clone()),
+        Some(serde_json::Value::String(_)) | None => {
+            Err(InitDataError::UnexpectedFormat(format!("{field} is missing")))
+        }
+        Some(_) => Err(InitDataError::UnexpectedFormat(format!("{field} is not a string"))),
+    }
+}
+
+/// Pulls raw init data out of the field `field` of an `application/x-www-form-urlencoded` body. Nothing is
+/// validated here.
+///
+/// Form bodies are encoded like URL query strings, see [`crate::extract_from_query`] for the details.
+///
+/// # Errors
+///
+/// Returns [`InitDataError::UnexpectedFormat`] if the field is missing or empty.
+///
+/// # Example
+/// ```
+/// use init_data_rs::extract_from_form;
+///
+/// let init_data = extract_from_form("initData=query_id%3D123%26auth_date%3D1662771648%26hash%3D...", "initData");
+/// assert_eq!(init_data.unwrap(), "query_id=123&auth_date=1662771648&hash=...");
+/// ```
+pub fn extract_from_form(body: &str, field: &str) -> Result<String, InitDataError> {
+    extract_from_query(body, field)
+}
+
+/// Pulls raw init data out of the field `field` of a `multipart/form-data` body. Nothing is validated here.
+///
+/// # Arguments
+/// * `body` - The whole request body
+/// * `content_type` - Value of the `Content-Type` header, which carries the boundary between the fields
+/// * `field` - Name of the field holding the init data
+///
+/// # Errors
+///
+/// Returns [`InitDataError::UnexpectedFormat`] if the content type is not `multipart/form-data` with a boundary,
+/// or the field is missing, empty or not UTF-8.
+///
+/// # Example
+/// ```
+/// use init_data_rs::extract_from_multipart;
+///
+/// let body = "--XyZ\r\n\
+///     Content-Disposition: form-data; name=\"initData\"\r\n\r\n\
+///     query_id=123&auth_date=1662771648&hash=...\r\n\
+///     --XyZ--\r\n";
+/// let init_data = extract_from_multipart(body.as_bytes(), "multipart/form-data; boundary=XyZ", "initData");
+/// assert_eq!(init_data.unwrap(), "query_id=123&auth_date=1662771648&hash=...");
+/// ```
+pub fn extract_from_multipart(body: &[u8], content_type: &str, field: &str) -> Result<String, InitDataError> {
+    let boundary = boundary(content_type)
+        .ok_or_else(|| InitDataError::UnexpectedFormat("not multipart/form-data with a boundary".to_string()))?;
+    let delimiter = format!("--{boundary}");
+    let missing = || InitDataError::UnexpectedFormat(format!("{field} is missing"));
+
+    // Everything before the first delimiter is a preamble, and the part after a `--` suffix an epilogue
+    let mut rest = &body[find(body, delimiter.as_bytes()).ok_or_else(missing)? + delimiter.len()..];
+    while !rest.starts_with(b"--") {
+        let next = find(rest, delimiter.as_bytes()).ok_or_else(missing)?;
+        let part = rest[..next].strip_prefix(b"\r\n").unwrap_or(&rest[..next]);
+        let part = part.strip_suffix(b"\r\n").unwrap_or(part);
+        rest = &rest[next + delimiter.len()..];
+
+        let Some(headers_end) = find(part, b"\r\n\r\n") else {
+            continue;
+        };
+        if field_name(&part[..headers_end]) != Some(field) {
+            continue;
+        }
+
+        let value = &part[headers_end + 4..];
+        if value.is_empty() {
+            return Err(missing());
+        }
+        return String::from_utf8(value.to_vec())
+            .map_err(|_| InitDataError::UnexpectedFormat(format!("{field} is not UTF-8")));
+    }
+
+    Err(missing())
+}
+
+/// The `boundary` parameter of a `multipart/form-data` content type.
+fn boundary(content_type: &str) -> Option<&str> {
+    let mut params = content_type.split(';');
+    if !params.next()?.trim().eq_ignore_ascii_case("multipart/form-data") {
+        return None;
+    }
+
+    params
+        .filter_map(|param| param.split_once('='))
+        .find(|(name, _)| name.trim().eq_ignore_ascii_case("boundary"))
+        .map(|(_, boundary)| boundary.trim().trim_matches('"'))
+        .filter(|boundary| !boundary.is_empty())
+}
+
+/// The `name` of the `Content-Disposition` header among the headers of a part.
+fn field_name(headers: &[u8]) -> Option<&str> {
+    std::str::from_utf8(headers)
+        .ok()?
+        .split("\r\n")
+        .filter_map(|header| header.split_once(':'))
+        .find(|(name, _)| name.trim().eq_ignore_ascii_case("content-disposition"))?
+        .1
+        .split(';')
+        .filter_map(|param| param.split_once('='))
+        .find(|(name, _)| name.trim() == "name")
+        .map(|(_, name)| name.trim().trim_matches('"'))
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|window| window == needle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INIT_DATA: &str = "query_id=AAHdF6IQ&auth_date=1662771648&hash=c501b71e";
+
+    #[test]
+    fn test_extract_from_multipart() {
+        let body = format!(
+            "preamble\r\n--b0undary\r\n\
+            Content-Disposition: form-data; name=\"file\"; filename=\"a.txt\"\r\n\
+            Content-Type: text/plain\r\n\r\n\
+            --not the boundary\r\n\
+            --b0undary\r\n\
+            content-disposition: form-data; name=initData\r\n\r\n\
+            {INIT_DATA}\r\n\
+            --b0undary--\r\n"
+        );
+        let content_type = "multipart/form-data; boundary=\"b0undary\"";
+
+        assert_eq!(
+            extract_from_multipart(body.as_bytes(), content_type, "initData").unwrap(),
+            INIT_DATA
+        );
+        assert!(extract_from_multipart(body.as_bytes(), content_type, "init_data").is_err());
+        assert!(extract_from_multipart(body.as_bytes(), "multipart/form-data", "initData").is_err());
+        assert!(extract_from_multipart(body.as_bytes(), "text/plain; boundary=b0undary", "initData").is_err());
+        assert!(extract_from_multipart(b"", content_type, "initData").is_err());
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_extract_from_json() {
+        let body = format!(r#"{{"initData": "{INIT_DATA}"}}"#);
+        assert_eq!(extract_from_json(body.as_bytes(), "initData").unwrap(), INIT_DATA);
+
+        for body in [
+            &br#"{"initData": 1}"#[..],
+            br#"{"initData": ""}"#,
+            b"{}",
+            b"[]",
+            b"initData=",
+        ] {
+            assert!(matches!(
+                extract_from_json(body, "initData"),
+                Err(InitDataError::UnexpectedFormat(_))
+            ));
+        }
+    }
+}
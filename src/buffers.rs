@@ -0,0 +1,27 @@
+//! Scratch buffers reused across validations.
+
+use std::ops::Range;
+
+/// Scratch space for decoding init data and assembling the data-check-string.
+///
+/// [`crate::Validator`] keeps one per thread; pass your own to
+/// [`crate::Validator::validate_with_buffers`] to manage them in a custom pool.
+/// The buffers grow to fit the largest init data seen and are never shrunk.
+#[derive(Debug, Default)]
+pub struct ValidationBuffers {
+    /// Decoded keys and values of the data-check-string.
+    pub(crate) decoded: Vec<u8>,
+    /// Key and value ranges into `decoded`.
+    pub(crate) params: Vec<(Range<usize>, Range<usize>)>,
+    /// JSON document the init data is deserialized from.
+    #[cfg_attr(not(feature = "model"), allow(dead_code))]
+    pub(crate) json: Vec<u8>,
+}
+
+impl ValidationBuffers {
+    /// Creates empty buffers, allocating only on first use.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
@@ -0,0 +1,66 @@
+//! Canonical representation of init data, so equivalent payloads share one identity.
+
+use std::borrow::Cow;
+
+use crate::crypto;
+
+/// Rewrites raw init data into a deterministic form: parameters decoded, sorted by key then value,
+/// and encoded again the way `form_urlencoded` does.
+///
+/// Payloads that differ only in parameter order or in how they are percent-encoded canonicalize to
+/// the same string. Nothing is validated, and the `hash` and `signature` are kept.
+///
+/// # Example
+/// ```
+/// use init_data_rs::canonicalize;
+///
+/// assert_eq!(
+///     canonicalize("query_id=123&auth_date=1662771648&start_param=a%20b"),
+///     canonicalize("start_param=a+b&auth_date=1662771648&query_id=%31%32%33"),
+/// );
+/// ```
+#[must_use]
+pub fn canonicalize(init_data: &str) -> String {
+    let mut pairs: Vec<(Cow<'_, str>, Cow<'_, str>)> = form_urlencoded::parse(init_data.as_bytes()).collect();
+    pairs.sort_unstable();
+
+    form_urlencoded::Serializer::new(String::new())
+        .extend_pairs(pairs)
+        .finish()
+}
+
+/// SHA-256 of the [`canonicalize`]d init data, for dedup and idempotency keys.
+///
+/// # Example
+/// ```
+/// use init_data_rs::cache_key;
+///
+/// assert_eq!(
+///     cache_key("query_id=123&auth_date=1662771648"),
+///     cache_key("auth_date=1662771648&query_id=123"),
+/// );
+/// ```
+#[must_use]
+pub fn cache_key(init_data: &str) -> [u8; 32] {
+    crypto::sha256(canonicalize(init_data).as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_canonicalize() {
+        assert_eq!(
+            canonicalize("user=%7B%22id%22%3A1%7D&auth_date=1&&hash=ab&auth_date=0"),
+            "auth_date=0&auth_date=1&hash=ab&user=%7B%22id%22%3A1%7D"
+        );
+        assert_eq!(canonicalize(""), "");
+    }
+
+    #[test]
+    fn test_cache_key() {
+        assert_eq!(cache_key("b=2&a=1"), cache_key("a=%31&b=2"));
+        assert_ne!(cache_key("a=1&b=2"), cache_key("a=1&b=3"));
+    }
+}
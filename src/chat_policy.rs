@@ -0,0 +1,154 @@
+//! Restrictions on the chat a Mini App is opened from.
+
+use std::collections::HashSet;
+use std::fmt;
+use std::sync::Arc;
+
+use crate::error::InitDataError;
+use crate::model::InitData;
+
+type Check = dyn Fn(&InitData) -> bool + Send + Sync;
+
+/// Chats init data is accepted from, checked by [`crate::Validator::chat_policy`] after the hash, e.g. for a
+/// Mini App that must only run inside official groups and channels.
+///
+/// Init data is accepted if its `chat.id` or `chat_instance` is allowed, or the check accepts it. The default
+/// policy accepts any chat, and init data opened outside of a chat too.
+///
+/// # Example
+/// ```
+/// use init_data_rs::{ChatPolicy, Validator};
+///
+/// let policy = ChatPolicy::new()
+///     .allow_chats([-1001234567890])
+///     .check(|data| data.chat_instance.is_some_and(|instance| instance % 2 == 0));
+/// let validator = Validator::new("BOT_TOKEN").chat_policy(policy);
+/// ```
+#[derive(Clone, Default)]
+pub struct ChatPolicy {
+    chats: HashSet<i64>,
+    chat_instances: HashSet<i64>,
+    check: Option<Arc<Check>>,
+}
+
+impl ChatPolicy {
+    /// Creates a policy accepting any chat.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Accepts init data whose `chat.id` is one of `chat_ids`, on top of those already allowed.
+    #[must_use]
+    pub fn allow_chats(mut self, chat_ids: impl IntoIterator<Item = i64>) -> Self {
+        self.chats.extend(chat_ids);
+        self
+    }
+
+    /// Accepts init data whose `chat_instance` is one of `chat_instances`, on top of those already allowed.
+    #[must_use]
+    pub fn allow_chat_instances(mut self, chat_instances: impl IntoIterator<Item = i64>) -> Self {
+        self.chat_instances.extend(chat_instances);
+        self
+    }
+
+    /// Accepts init data for which `check` returns `true`, e.g. chats looked up in a table. Replaces an earlier
+    /// check.
+    #[must_use]
+    pub fn check(mut self, check: impl Fn(&InitData) -> bool + Send + Sync + 'static) -> Self {
+        self.check = Some(Arc::new(check));
+        self
+    }
+
+    /// Checks the chat of `data`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`InitDataError::ChatMissing`] if the policy restricts chats and `data` has neither a `chat` nor a
+    /// `chat_instance`, and [`InitDataError::ChatNotAllowed`] if the chat is not allowed.
+    pub fn validate(&self, data: &InitData) -> Result<(), InitDataError> {
+        if self.chats.is_empty() && self.chat_instances.is_empty() && self.check.is_none() {
+            return Ok(());
+        }
+
+        let chat_id = data.chat.as_ref().map(|chat| chat.id);
+        if chat_id.is_none() && data.chat_instance.is_none() {
+            return Err(InitDataError::ChatMissing);
+        }
+
+        let allowed = chat_id.is_some_and(|id| self.chats.contains(&id))
+            || data
+                .chat_instance
+                .is_some_and(|instance| self.chat_instances.contains(&instance))
+            || self.check.as_ref().is_some_and(|check| check(data));
+        if !allowed {
+            return Err(InitDataError::ChatNotAllowed(match chat_id {
+                Some(id) => format!("chat {id}"),
+                None => "chat instance".to_string(),
+            }));
+        }
+
+        Ok(())
+    }
+}
+
+impl ChatPolicy {
+    /// Identifies the policy in the keys of the validation cache. Clones share the check, and the fingerprint.
+    pub(crate) fn fingerprint(&self) -> String {
+        let mut chats: Vec<_> = self.chats.iter().collect();
+        chats.sort_unstable();
+        let mut chat_instances: Vec<_> = self.chat_instances.iter().collect();
+        chat_instances.sort_unstable();
+
+        format!(
+            "chat:{chats:?}:{chat_instances:?}:{:?}",
+            self.check.as_ref().map(|check| Arc::as_ptr(check).cast::<()>())
+        )
+    }
+}
+
+impl fmt::Debug for ChatPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ChatPolicy")
+            .field("chats", &self.chats)
+            .field("chat_instances", &self.chat_instances)
+            .field("check", &self.check.is_some())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse;
+
+    const HASH: &str = "c501b71e775f74ce10e377dea85a7ea24ecd640b223ea86dfe453e0eaed2e2b2";
+
+    #[test]
+    fn test_chat_policy() {
+        let in_group = parse(&format!(
+            "chat=%7B%22id%22%3A-100%2C%22type%22%3A%22group%22%2C%22title%22%3A%22Official%22%7D&chat_instance=7&auth_date=1&hash={HASH}"
+        ))
+        .unwrap();
+        let from_link = parse(&format!("chat_instance=8&auth_date=1&hash={HASH}")).unwrap();
+        let outside = parse(&format!("auth_date=1&hash={HASH}")).unwrap();
+
+        assert!(ChatPolicy::new().validate(&outside).is_ok());
+
+        let policy = ChatPolicy::new().allow_chats([-100]);
+        assert!(policy.validate(&in_group).is_ok());
+        assert!(matches!(
+            policy.validate(&from_link),
+            Err(InitDataError::ChatNotAllowed(_))
+        ));
+        assert!(matches!(policy.validate(&outside), Err(InitDataError::ChatMissing)));
+
+        let policy = ChatPolicy::new().allow_chat_instances([8]);
+        assert!(policy.validate(&from_link).is_ok());
+        assert!(policy.validate(&in_group).is_err());
+        assert!(policy
+            .check(|data| data.chat.as_ref().is_some_and(|chat| chat.title == "Official"))
+            .validate(&in_group)
+            .is_ok());
+    }
+}
@@ -0,0 +1,18 @@
+//! The order in which init data checks run, trading uniform timing for cheap rejections.
+
+/// Order of the checks of [`crate::verify`] and [`crate::Validator`], see [`crate::verify_with_order`] and
+/// [`crate::Validator::check_order`].
+///
+/// The HMAC dominates the cost of a rejection, and a flood of forged or replayed payloads mostly fails the cheap
+/// checks too. Neither the structure of the payload nor its `auth_date` is secret, so rejecting them early only
+/// reveals what the sender already knows; the hash itself is always compared in constant time.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CheckOrder {
+    /// Structural checks, then the expiry, then the HMAC, then parsing, stopping at the first failure: malformed
+    /// and expired payloads are rejected without computing the HMAC.
+    #[default]
+    CheapestFirst,
+    /// Every check but parsing runs, the HMAC included, before the first failure is reported, so that a
+    /// malformed, a forged and an expired payload of the same length take the same time to reject.
+    Uniform,
+}
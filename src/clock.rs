@@ -0,0 +1,40 @@
+//! Time source used for expiration checks.
+//!
+//! `std::time::SystemTime::now` panics on `wasm32-unknown-unknown`. With the `wasm` feature
+//! [`SystemClock`] reads the time from JavaScript's `Date.now()` on that target instead, and any
+//! other environment can inject its own [`Clock`] through the `*_with_clock` functions.
+
+/// Source of the current Unix time in seconds.
+pub trait Clock {
+    /// Returns the current Unix time in seconds.
+    fn now(&self) -> u64;
+}
+
+impl<F: Fn() -> u64> Clock for F {
+    fn now(&self) -> u64 {
+        self()
+    }
+}
+
+/// The clock of the host: `SystemTime` on native targets, `Date.now()` in the browser.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    #[cfg(all(target_arch = "wasm32", target_os = "unknown", feature = "wasm"))]
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    fn now(&self) -> u64 {
+        (js_sys::Date::now() / 1000.0) as u64
+    }
+
+    /// # Panics
+    ///
+    /// Panics if the system time is before `UNIX_EPOCH`, or on `wasm32-unknown-unknown`
+    /// without the `wasm` feature.
+    #[cfg(not(all(target_arch = "wasm32", target_os = "unknown", feature = "wasm")))]
+    fn now(&self) -> u64 {
+        use std::time::{SystemTime, UNIX_EPOCH};
+
+        SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs()
+    }
+}
@@ -0,0 +1,90 @@
+//! Compact binary encoding of the model with postcard, for message queues and embedded caches.
+//!
+//! The model only uses serde features that non-self-describing formats support, so bincode, postcard and
+//! similar formats work with it as well: no `flatten`, no untagged enums, optional fields always written and
+//! [`crate::ChatType`] encoded as the index of its variant.
+
+use crate::error::InitDataError;
+use crate::model::InitData;
+
+impl InitData {
+    /// Encodes the init data with postcard, less than half the size of its JSON.
+    ///
+    /// # Example
+    /// ```
+    /// use init_data_rs::{parse, InitData};
+    ///
+    /// let data = parse("auth_date=1662771648&hash=c501b71e775f74ce10e377dea85a7ea24ecd640b223ea86dfe453e0eaed2e2b2").unwrap();
+    /// let bytes = data.to_compact_bytes().unwrap();
+    ///
+    /// assert_eq!(InitData::from_compact_bytes(&bytes).unwrap().auth_date, 1662771648);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns [`InitDataError::Internal`] if the init data can't be serialized.
+    pub fn to_compact_bytes(&self) -> Result<Vec<u8>, InitDataError> {
+        postcard::to_allocvec(self).map_err(|error| InitDataError::Internal(error.to_string()))
+    }
+
+    /// Decodes init data encoded by [`InitData::to_compact_bytes`] of the same release. Use
+    /// [`InitData::to_stored_bytes`] for data that must outlive changes to the model.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`InitDataError::UnexpectedFormat`] if `bytes` are not encoded init data.
+    pub fn from_compact_bytes(bytes: &[u8]) -> Result<Self, InitDataError> {
+        postcard::from_bytes(bytes)
+            .map_err(|error| InitDataError::UnexpectedFormat(format!("compact init data is malformed: {error}")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::ChatType;
+
+    use super::*;
+
+    /// Init data with every field set.
+    fn full() -> InitData {
+        crate::parse(concat!(
+            "query_id=AAHdF6IQAAAAAN0XohDhrOrc",
+            "&user=%7B%22id%22%3A279058397%2C%22first_name%22%3A%22Vladislav%22%2C%22last_name%22%3A%22Kibenko%22%2C%22username%22%3A%22vdkfrost%22%2C%22language_code%22%3A%22ru%22%2C%22is_premium%22%3Atrue%2C%22is_bot%22%3Afalse%2C%22added_to_attachment_menu%22%3Atrue%2C%22allows_write_to_pm%22%3Atrue%2C%22photo_url%22%3A%22https%3A%2F%2Ft.me%2Fa.svg%22%7D",
+            "&receiver=%7B%22id%22%3A1%2C%22first_name%22%3A%22R%22%7D",
+            "&chat=%7B%22id%22%3A-1001%2C%22title%22%3A%22Dev%22%2C%22type%22%3A%22supergroup%22%2C%22username%22%3A%22dev%22%7D",
+            "&chat_type=supergroup&chat_instance=-42&start_param=ref_1&can_send_after=10",
+            "&auth_date=1662771648&signature=abc",
+            "&hash=c501b71e775f74ce10e377dea85a7ea24ecd640b223ea86dfe453e0eaed2e2b2"
+        ))
+        .unwrap()
+    }
+
+    fn assert_same(decoded: &InitData, data: &InitData) {
+        assert_eq!(decoded.to_map(), data.to_map());
+    }
+
+    #[test]
+    fn test_postcard_roundtrip() {
+        let data = full();
+        let bytes = data.to_compact_bytes().unwrap();
+
+        assert_same(&InitData::from_compact_bytes(&bytes).unwrap(), &data);
+        #[cfg(feature = "json")]
+        assert!(bytes.len() < serde_json::to_vec(&data).unwrap().len() / 2);
+        assert!(InitData::from_compact_bytes(&bytes[..bytes.len() - 1]).is_err());
+
+        // One byte per chat type
+        assert_eq!(postcard::to_allocvec(&ChatType::Supergroup).unwrap(), [3]);
+    }
+
+    #[test]
+    fn test_bincode_roundtrip() {
+        let data = full();
+        let config = bincode::config::standard();
+        let bytes = bincode::serde::encode_to_vec(&data, config).unwrap();
+        let (decoded, read): (InitData, _) = bincode::serde::decode_from_slice(&bytes, config).unwrap();
+
+        assert_same(&decoded, &data);
+        assert_eq!(read, bytes.len());
+    }
+}
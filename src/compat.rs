@@ -0,0 +1,44 @@
+//! Switches reproducing how other init data implementations compute the data-check-string.
+
+/// Known differences between init data implementations, for teams moving a service between languages.
+///
+/// The default follows Telegram's reference: `+` is a space, `signature` is part of the data-check-string and
+/// empty strings are kept. Implementations that decode with `decodeURIComponent` instead of `URLSearchParams`
+/// keep `+` as is, and some written before third-party signatures existed leave `signature` out of the
+/// data-check-string. Matching the old service bit for bit lets both run side by side during the migration.
+///
+/// # Example
+/// ```
+/// use init_data_rs::{sign_with_compat, verify_with_compat, Compat, Expiration};
+///
+/// let compat = Compat {
+///     plus_as_space: false,
+///     ..Compat::default()
+/// };
+/// let base_data = "query_id=a+b&auth_date=1662771648";
+/// let init_data = format!("{base_data}&hash={}", sign_with_compat(base_data, "12345:BOT_TOKEN", compat).unwrap());
+///
+/// assert!(verify_with_compat(&init_data, "12345:BOT_TOKEN", Expiration::Never, compat).is_ok());
+/// assert!(init_data_rs::verify(&init_data, "12345:BOT_TOKEN", Expiration::Never).is_err());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Compat {
+    /// Decodes `+` as a space in the data-check-string, like `application/x-www-form-urlencoded` does. On by
+    /// default. Parsed fields always decode it as a space.
+    pub plus_as_space: bool,
+    /// Leaves the `signature` parameter out of the data-check-string of the hash. Off by default.
+    pub exclude_signature: bool,
+    /// Turns empty optional strings into `None` when parsing, see `ParseOptions::empty_strings_as_none`.
+    /// Off by default.
+    pub empty_strings_as_none: bool,
+}
+
+impl Default for Compat {
+    fn default() -> Self {
+        Self {
+            plus_as_space: true,
+            exclude_signature: false,
+            empty_strings_as_none: false,
+        }
+    }
+}
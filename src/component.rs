@@ -0,0 +1,129 @@
+//! WebAssembly Component Model bindings.
+//!
+//! Enabled with the `component` feature. The component implements the `init-data` world defined
+//! in `wit/init-data.wit`, so plugin hosts (Spin, wasmCloud, Envoy wasm filters, ...) can embed
+//! the validation through a capability-safe interface:
+//!
+//! ```sh
+//! cargo rustc --lib --release --target wasm32-wasip2 --features component --crate-type cdylib
+//! ```
+
+#[allow(clippy::all, clippy::pedantic)]
+mod bindings {
+    wit_bindgen::generate!({
+        world: "init-data",
+        path: "wit",
+    });
+}
+
+use bindings::escwxyz::init_data::types::{Chat, ChatType, Error, InitData, User};
+use bindings::exports::escwxyz::init_data::validator::Guest;
+
+use crate::InitDataError;
+
+struct Component;
+
+impl From<crate::ChatType> for ChatType {
+    fn from(chat_type: crate::ChatType) -> Self {
+        match chat_type {
+            crate::ChatType::Sender => Self::Sender,
+            crate::ChatType::Private => Self::Private,
+            crate::ChatType::Group => Self::Group,
+            crate::ChatType::Supergroup => Self::Supergroup,
+            crate::ChatType::Channel => Self::Channel,
+        }
+    }
+}
+
+impl From<crate::User> for User {
+    fn from(user: crate::User) -> Self {
+        Self {
+            added_to_attachment_menu: user.added_to_attachment_menu,
+            allows_write_to_pm: user.allows_write_to_pm,
+            first_name: user.first_name,
+            id: user.id,
+            is_bot: user.is_bot,
+            is_premium: user.is_premium,
+            last_name: user.last_name,
+            language_code: user.language_code,
+            photo_url: user.photo_url,
+            username: user.username,
+        }
+    }
+}
+
+impl From<crate::Chat> for Chat {
+    fn from(chat: crate::Chat) -> Self {
+        Self {
+            id: chat.id,
+            photo_url: chat.photo_url,
+            chat_type: chat.chat_type.into(),
+            title: chat.title,
+            username: chat.username,
+        }
+    }
+}
+
+impl From<crate::InitData> for InitData {
+    fn from(data: crate::InitData) -> Self {
+        Self {
+            auth_date: data.auth_date,
+            can_send_after: data.can_send_after,
+            chat: data.chat.map(Into::into),
+            chat_type: data.chat_type.map(Into::into),
+            chat_instance: data.chat_instance,
+            hash: data.hash,
+            query_id: data.query_id,
+            receiver: data.receiver.map(Into::into),
+            start_param: data.start_param,
+            user: data.user.map(Into::into),
+            signature: data.signature,
+        }
+    }
+}
+
+impl From<InitDataError> for Error {
+    fn from(error: InitDataError) -> Self {
+        match error {
+            InitDataError::AuthDateMissing => Self::AuthDateMissing,
+            InitDataError::HashMissing => Self::HashMissing,
+            InitDataError::HashInvalid => Self::HashInvalid,
+            InitDataError::UnexpectedFormat(message) => Self::UnexpectedFormat(message),
+            InitDataError::Expired => Self::Expired,
+            InitDataError::Internal(message) => Self::Internal(message),
+            InitDataError::SignatureMissing => Self::SignatureMissing,
+            InitDataError::SignatureInvalid(message) => Self::SignatureInvalid(message),
+            InitDataError::Throttled => Self::Throttled,
+            InitDataError::PayloadTooLarge(message) => Self::PayloadTooLarge(message),
+            InitDataError::Config(message) => Self::Config(message),
+            InitDataError::DeadlineExceeded => Self::DeadlineExceeded,
+            InitDataError::UserMissing => Self::UserMissing,
+            InitDataError::ChatMissing => Self::ChatMissing,
+            InitDataError::ClientNotAllowed(message) => Self::ClientNotAllowed(message),
+            InitDataError::StartParamInvalid(message) => Self::StartParamInvalid(message),
+            InitDataError::AuthDateInvalid { value } => Self::AuthDateInvalid(value),
+            InitDataError::UserDenied(message) => Self::UserDenied(message),
+            InitDataError::ChatNotAllowed(message) => Self::ChatNotAllowed(message),
+        }
+    }
+}
+
+impl Guest for Component {
+    fn validate(raw: String, token: String, expires_in: Option<u64>) -> Result<InitData, Error> {
+        Ok(crate::validate(&raw, &token, crate::Expiration::from_seconds(expires_in))?.into())
+    }
+
+    fn validate_third_party(raw: String, bot_id: i64, expires_in: Option<u64>) -> Result<InitData, Error> {
+        Ok(crate::validate_third_party(&raw, bot_id, crate::Expiration::from_seconds(expires_in))?.into())
+    }
+
+    fn parse(raw: String) -> Result<InitData, Error> {
+        Ok(crate::parse(&raw)?.into())
+    }
+
+    fn sign(raw: String, token: String) -> Result<String, Error> {
+        Ok(crate::sign(&raw, &token)?)
+    }
+}
+
+bindings::export!(Component with_types_in bindings);
@@ -0,0 +1,248 @@
+//! Declarative [`Validator`] settings, for services configured with TOML, YAML or JSON files.
+
+use std::fmt;
+use std::time::Duration;
+
+use serde::{de::Error as _, Deserialize, Deserializer};
+
+use crate::error::InitDataError;
+use crate::expiration::Expiration;
+use crate::privacy::PrivacyMode;
+use crate::scheme::ValidationScheme;
+use crate::start_param::StartParamPolicy;
+use crate::third_party_validation::Environment;
+use crate::validator::Validator;
+
+/// Settings of a [`Validator`], deserializable from any serde format, e.g. through `figment` or `config`.
+///
+/// Every field but `token` is optional and defaults to the behavior of [`Validator::new`]. Unknown fields are
+/// rejected, so that a typo doesn't silently disable a check.
+///
+/// # Example
+/// ```
+/// use init_data_rs::{Validator, ValidatorConfig};
+///
+/// let config: ValidatorConfig = toml::from_str(
+///     r#"
+///     token = { env = "TELEGRAM_BOT_TOKEN" }
+///     ttl = 3600
+///     leeway = 30
+///     scheme = "hash"
+///     telegram_start_param = true
+///     privacy = { photo_url = true }
+///     "#,
+/// )
+/// .unwrap();
+///
+/// # std::env::set_var("TELEGRAM_BOT_TOKEN", "12345:BOT_TOKEN");
+/// let validator = Validator::from_config(&config).unwrap();
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ValidatorConfig {
+    /// Where the bot token comes from.
+    pub token: TokenSource,
+    /// Seconds init data stays valid after its `auth_date`, `0` to disable expiration, 24 hours if unset.
+    #[serde(default)]
+    pub ttl: Option<u64>,
+    /// Seconds added to `ttl` to absorb clock skew between Telegram and the service, `0` by default.
+    #[serde(default)]
+    pub leeway: u64,
+    /// `production` or `test`, the environment of third-party signatures, production by default.
+    #[serde(default, deserialize_with = "environment")]
+    pub environment: Environment,
+    /// `hash` or `signature`, the only scheme [`Validator::validate_auto`] accepts, detected from the init data
+    /// if unset. See [`Validator::scheme`].
+    #[serde(default, deserialize_with = "scheme")]
+    pub scheme: Option<ValidationScheme>,
+    /// Enforces Telegram's constraints on `start_param`, see [`StartParamPolicy::telegram`].
+    #[serde(default)]
+    pub telegram_start_param: bool,
+    /// Rejects init data without a `start_param`, see [`StartParamPolicy::required`].
+    #[serde(default)]
+    pub require_start_param: bool,
+    /// Personal fields blanked from validated init data, see [`PrivacyMode`].
+    #[serde(default)]
+    pub privacy: PrivacyMode,
+    /// Capacity of the rejection cache, see [`Validator::with_rejection_cache`], disabled if unset.
+    #[serde(default)]
+    pub rejection_cache: Option<usize>,
+}
+
+/// Where [`ValidatorConfig`] reads the bot token from: `{ env = "NAME" }` or `{ value = "<token>" }`.
+///
+/// Prefer `env`, so that the token stays out of configuration files. A literal token is redacted from `Debug`
+/// output.
+#[derive(Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub enum TokenSource {
+    /// The name of the environment variable holding the token.
+    Env(String),
+    /// The token itself.
+    Value(String),
+}
+
+impl fmt::Debug for TokenSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Env(name) => f.debug_tuple("Env").field(name).finish(),
+            Self::Value(_) => f.write_str("Value(***)"),
+        }
+    }
+}
+
+impl ValidatorConfig {
+    /// The `ttl` extended by the `leeway`.
+    fn expiration(&self) -> Expiration {
+        match Expiration::from_seconds(self.ttl).ttl() {
+            Some(ttl) if self.leeway > 0 => Expiration::After(ttl + Duration::from_secs(self.leeway)),
+            _ => Expiration::from_seconds(self.ttl),
+        }
+    }
+}
+
+impl TokenSource {
+    fn resolve(&self, var: impl Fn(&str) -> Result<String, std::env::VarError>) -> Result<String, InitDataError> {
+        let token = match self {
+            Self::Value(token) => token.clone(),
+            Self::Env(name) => var(name).map_err(|err| match err {
+                std::env::VarError::NotPresent => InitDataError::Config(format!("{name} is not set")),
+                std::env::VarError::NotUnicode(_) => InitDataError::Config(format!("{name} is not valid UTF-8")),
+            })?,
+        };
+
+        if token.is_empty() {
+            return Err(InitDataError::Config("the bot token is empty".to_string()));
+        }
+        Ok(token)
+    }
+}
+
+impl Validator {
+    /// Creates a validator from declarative settings, reading the token from the environment if configured so.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`InitDataError::Config`] if the token is empty, or its environment variable is not set.
+    pub fn from_config(config: &ValidatorConfig) -> Result<Self, InitDataError> {
+        Self::from_config_with(config, |name| std::env::var(name))
+    }
+
+    fn from_config_with(
+        config: &ValidatorConfig,
+        var: impl Fn(&str) -> Result<String, std::env::VarError>,
+    ) -> Result<Self, InitDataError> {
+        let mut start_param_policy = if config.telegram_start_param {
+            StartParamPolicy::telegram()
+        } else {
+            StartParamPolicy::new()
+        };
+        if config.require_start_param {
+            start_param_policy = start_param_policy.required();
+        }
+
+        let mut validator = Self::new(config.token.resolve(var)?)
+            .expiration(config.expiration())
+            .environment(config.environment)
+            .start_param_policy(start_param_policy)
+            .privacy_mode(config.privacy);
+        if let Some(scheme) = config.scheme {
+            validator = validator.scheme(scheme);
+        }
+        if let Some(capacity) = config.rejection_cache {
+            validator = validator.with_rejection_cache(capacity);
+        }
+
+        Ok(validator)
+    }
+}
+
+fn environment<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Environment, D::Error> {
+    let name = String::deserialize(deserializer)?;
+    match name.to_ascii_lowercase().as_str() {
+        "production" | "prod" => Ok(Environment::Production),
+        "test" => Ok(Environment::Test),
+        _ => Err(D::Error::custom(format!(
+            "unknown environment `{name}`, expected `production` or `test`"
+        ))),
+    }
+}
+
+fn scheme<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<ValidationScheme>, D::Error> {
+    let name = String::deserialize(deserializer)?;
+    ValidationScheme::from_name(&name)
+        .map(Some)
+        .ok_or_else(|| D::Error::custom(format!("unknown scheme `{name}`, expected `hash` or `signature`")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VALID_INIT_DATA: &str = "query_id=AAHdF6IQAAAAAN0XohDhrOrc&user=%7B%22id%22%3A279058397%2C%22first_name%22%3A%22Vladislav%22%2C%22last_name%22%3A%22Kibenko%22%2C%22username%22%3A%22vdkfrost%22%2C%22language_code%22%3A%22ru%22%2C%22is_premium%22%3Atrue%7D&auth_date=1662771648&hash=c501b71e775f74ce10e377dea85a7ea24ecd640b223ea86dfe453e0eaed2e2b2";
+
+    fn vars(name: &str) -> Result<String, std::env::VarError> {
+        match name {
+            "BOT_TOKEN" => Ok("5768337691:AAH5YkoiEuPk8-FZa32hStHTqXiLPtAEhx8".to_string()),
+            _ => Err(std::env::VarError::NotPresent),
+        }
+    }
+
+    #[test]
+    fn test_from_config() {
+        let config: ValidatorConfig = toml::from_str(
+            r#"
+            token = { env = "BOT_TOKEN" }
+            ttl = 0
+            environment = "test"
+            privacy = { names = true }
+            rejection_cache = 16
+            "#,
+        )
+        .unwrap();
+        let validator = Validator::from_config_with(&config, vars).unwrap();
+
+        let data = validator.validate(VALID_INIT_DATA).unwrap();
+        assert!(data.user.unwrap().first_name.is_empty());
+        assert!(validator.rejection_cache_stats().is_some());
+
+        let config: ValidatorConfig =
+            toml::from_str("token = { value = \"123:TOKEN\" }\nscheme = \"signature\"").unwrap();
+        assert_eq!(config.scheme, Some(ValidationScheme::Ed25519ThirdParty));
+        assert!(!format!("{config:?}").contains("123:TOKEN"));
+        assert!(Validator::from_config_with(&config, vars).is_ok());
+        assert!(matches!(
+            Validator::from_config_with(
+                &toml::from_str("token = { env = \"MISSING\" }").unwrap(),
+                vars
+            ),
+            Err(InitDataError::Config(message)) if message.contains("MISSING")
+        ));
+    }
+
+    #[test]
+    fn test_config_rejects_unknown_fields() {
+        assert!(toml::from_str::<ValidatorConfig>("token = { value = \"123:TOKEN\" }\nttl_seconds = 60").is_err());
+        assert!(toml::from_str::<ValidatorConfig>("token = { value = \"123:TOKEN\" }\nscheme = \"hmac\"").is_err());
+        assert!(
+            toml::from_str::<ValidatorConfig>("token = { value = \"123:TOKEN\" }\nenvironment = \"staging\"").is_err()
+        );
+    }
+
+    #[test]
+    fn test_config_leeway() {
+        let config =
+            |toml: &str| toml::from_str::<ValidatorConfig>(&format!("token = {{ env = \"BOT_TOKEN\" }}\n{toml}"));
+
+        assert_eq!(
+            config("ttl = 60\nleeway = 30").unwrap().expiration(),
+            Expiration::After(Duration::from_secs(90))
+        );
+        assert_eq!(
+            config("leeway = 60").unwrap().expiration(),
+            Expiration::After(Expiration::DEFAULT_TTL + Duration::from_secs(60))
+        );
+        assert_eq!(config("ttl = 0\nleeway = 30").unwrap().expiration(), Expiration::Never);
+        assert_eq!(config("").unwrap().expiration(), Expiration::Default);
+    }
+}
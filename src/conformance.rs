@@ -0,0 +1,273 @@
+//! Test vectors shared by init data implementations, to prove they accept and reject the same payloads.
+//!
+//! The vectors follow the algorithm described at
+//! <https://core.telegram.org/bots/webapps#validating-data-received-via-the-mini-app>, the one
+//! `init-data-golang` and `@telegram-apps/init-data-node` implement. Wrappers around this crate (FFI,
+//! wasm, napi, ...) and other implementations run them with [`run`].
+//!
+//! # Example
+//! ```
+//! use init_data_rs::conformance::{self, Outcome};
+//! use init_data_rs::verify_with_clock;
+//!
+//! let result = conformance::run(|vector| {
+//!     Outcome::from(&verify_with_clock(vector.init_data, vector.token, vector.expiration(), &|| vector.now))
+//! });
+//! assert!(result.is_ok());
+//! ```
+
+use crate::error::InitDataError;
+use crate::expiration::Expiration;
+
+/// Version of the suite, raised whenever vectors are added or changed.
+pub const VERSION: u32 = 1;
+
+/// Result expected from validating a [`Vector`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    /// The hash matches and the init data hasn't expired.
+    Valid,
+    /// The hash matches, but `auth_date` is older than the expiration.
+    Expired,
+    /// There is no `hash` parameter.
+    HashMissing,
+    /// The hash is malformed or doesn't match the data.
+    HashInvalid,
+    /// The hash matches, but there is no `auth_date` parameter.
+    AuthDateMissing,
+    /// The init data is not a query string at all.
+    Malformed,
+    /// Any other error, by its [`InitDataError::kind`].
+    Other(&'static str),
+}
+
+impl<T> From<&Result<T, InitDataError>> for Outcome {
+    fn from(result: &Result<T, InitDataError>) -> Self {
+        match result {
+            Ok(_) => Self::Valid,
+            Err(InitDataError::Expired) => Self::Expired,
+            Err(InitDataError::HashMissing) => Self::HashMissing,
+            Err(InitDataError::HashInvalid) => Self::HashInvalid,
+            Err(InitDataError::AuthDateMissing) => Self::AuthDateMissing,
+            Err(InitDataError::UnexpectedFormat(_)) => Self::Malformed,
+            Err(error) => Self::Other(error.kind()),
+        }
+    }
+}
+
+/// One payload, the settings to validate it with and the expected outcome.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Vector {
+    /// Unique name of the vector, stable across versions.
+    pub name: &'static str,
+    /// Raw init data.
+    pub init_data: &'static str,
+    /// Bot token to validate with.
+    pub token: &'static str,
+    /// Current Unix time in seconds to validate at.
+    pub now: u64,
+    /// Expiration in seconds, 0 for none, see [`Vector::expiration`].
+    pub expires_in: u64,
+    /// Outcome every implementation must report.
+    pub expected: Outcome,
+}
+
+impl Vector {
+    /// The expiration to validate the vector with: [`Expiration::Never`] for an `expires_in` of 0, otherwise
+    /// [`Expiration::After`] `expires_in` seconds.
+    #[must_use]
+    pub fn expiration(&self) -> Expiration {
+        Expiration::from_seconds(Some(self.expires_in))
+    }
+}
+
+/// A vector for which the implementation reported another outcome.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Mismatch {
+    /// The vector.
+    pub vector: &'static Vector,
+    /// Outcome the implementation reported.
+    pub actual: Outcome,
+}
+
+const TOKEN: &str = "5768337691:AAH5YkoiEuPk8-FZa32hStHTqXiLPtAEhx8";
+const AUTH_DATE: u64 = 1_662_771_648;
+const DAY: u64 = 86400;
+
+/// Every vector of the suite.
+pub const VECTORS: &[Vector] = &[
+    Vector {
+        name: "valid",
+        init_data: "query_id=AAHdF6IQAAAAAN0XohDhrOrc&user=%7B%22id%22%3A279058397%2C%22first_name%22%3A%22Vladislav%22%2C%22last_name%22%3A%22Kibenko%22%2C%22username%22%3A%22vdkfrost%22%2C%22language_code%22%3A%22ru%22%2C%22is_premium%22%3Atrue%7D&auth_date=1662771648&hash=c501b71e775f74ce10e377dea85a7ea24ecd640b223ea86dfe453e0eaed2e2b2",
+        token: TOKEN,
+        now: AUTH_DATE + 60,
+        expires_in: DAY,
+        expected: Outcome::Valid,
+    },
+    Vector {
+        name: "valid_reordered",
+        init_data: "hash=c501b71e775f74ce10e377dea85a7ea24ecd640b223ea86dfe453e0eaed2e2b2&auth_date=1662771648&user=%7B%22id%22%3A279058397%2C%22first_name%22%3A%22Vladislav%22%2C%22last_name%22%3A%22Kibenko%22%2C%22username%22%3A%22vdkfrost%22%2C%22language_code%22%3A%22ru%22%2C%22is_premium%22%3Atrue%7D&query_id=AAHdF6IQAAAAAN0XohDhrOrc",
+        token: TOKEN,
+        now: AUTH_DATE + 60,
+        expires_in: DAY,
+        expected: Outcome::Valid,
+    },
+    Vector {
+        name: "valid_plus_and_encoded_ampersand",
+        init_data: "query_id=AAHdF6IQAAAAAN0XohDhrOrc&start_param=a+b%26c&auth_date=1662771648&hash=889c9b3848389df5cb6bc23503a29daebc031c13876cf70ef2bc11136aac3616",
+        token: TOKEN,
+        now: AUTH_DATE + 60,
+        expires_in: DAY,
+        expected: Outcome::Valid,
+    },
+    Vector {
+        name: "valid_signature_in_check_string",
+        init_data: "user=%7B%22id%22%3A279058397%2C%22first_name%22%3A%22Vladislav%22%7D&chat_instance=-8599080687359297588&chat_type=sender&auth_date=1662771648&signature=zL-ucjNyREiHDE8aihFwpfR9aggP2xiAo3NSpfe-p7IbCisNlDKlo7Kb6G4D0Ao2mBrSgEk4maLSdv6MLIlADQ&hash=4f3bb1ac23d74abe6a8060dbba3eb79a9e2733d5e7865943d096546ac799e7ab",
+        token: TOKEN,
+        now: AUTH_DATE + 60,
+        expires_in: DAY,
+        expected: Outcome::Valid,
+    },
+    Vector {
+        name: "valid_at_expiration",
+        init_data: "query_id=AAHdF6IQAAAAAN0XohDhrOrc&user=%7B%22id%22%3A279058397%2C%22first_name%22%3A%22Vladislav%22%2C%22last_name%22%3A%22Kibenko%22%2C%22username%22%3A%22vdkfrost%22%2C%22language_code%22%3A%22ru%22%2C%22is_premium%22%3Atrue%7D&auth_date=1662771648&hash=c501b71e775f74ce10e377dea85a7ea24ecd640b223ea86dfe453e0eaed2e2b2",
+        token: TOKEN,
+        now: AUTH_DATE + DAY,
+        expires_in: DAY,
+        expected: Outcome::Valid,
+    },
+    Vector {
+        name: "valid_expiration_disabled",
+        init_data: "query_id=AAHdF6IQAAAAAN0XohDhrOrc&user=%7B%22id%22%3A279058397%2C%22first_name%22%3A%22Vladislav%22%2C%22last_name%22%3A%22Kibenko%22%2C%22username%22%3A%22vdkfrost%22%2C%22language_code%22%3A%22ru%22%2C%22is_premium%22%3Atrue%7D&auth_date=1662771648&hash=c501b71e775f74ce10e377dea85a7ea24ecd640b223ea86dfe453e0eaed2e2b2",
+        token: TOKEN,
+        now: AUTH_DATE + 365 * DAY,
+        expires_in: 0,
+        expected: Outcome::Valid,
+    },
+    Vector {
+        name: "expired",
+        init_data: "query_id=AAHdF6IQAAAAAN0XohDhrOrc&user=%7B%22id%22%3A279058397%2C%22first_name%22%3A%22Vladislav%22%2C%22last_name%22%3A%22Kibenko%22%2C%22username%22%3A%22vdkfrost%22%2C%22language_code%22%3A%22ru%22%2C%22is_premium%22%3Atrue%7D&auth_date=1662771648&hash=c501b71e775f74ce10e377dea85a7ea24ecd640b223ea86dfe453e0eaed2e2b2",
+        token: TOKEN,
+        now: AUTH_DATE + DAY + 1,
+        expires_in: DAY,
+        expected: Outcome::Expired,
+    },
+    Vector {
+        name: "tampered_value",
+        init_data: "query_id=AAHdF6IQAAAAAN0XohDhrOrc&user=%7B%22id%22%3A279058397%2C%22first_name%22%3A%22Vladislav%22%2C%22last_name%22%3A%22Kibenko%22%2C%22username%22%3A%22vdkfrost%22%2C%22language_code%22%3A%22ru%22%2C%22is_premium%22%3Afalse%7D&auth_date=1662771648&hash=c501b71e775f74ce10e377dea85a7ea24ecd640b223ea86dfe453e0eaed2e2b2",
+        token: TOKEN,
+        now: AUTH_DATE + 60,
+        expires_in: DAY,
+        expected: Outcome::HashInvalid,
+    },
+    Vector {
+        name: "wrong_token",
+        init_data: "query_id=AAHdF6IQAAAAAN0XohDhrOrc&user=%7B%22id%22%3A279058397%2C%22first_name%22%3A%22Vladislav%22%2C%22last_name%22%3A%22Kibenko%22%2C%22username%22%3A%22vdkfrost%22%2C%22language_code%22%3A%22ru%22%2C%22is_premium%22%3Atrue%7D&auth_date=1662771648&hash=c501b71e775f74ce10e377dea85a7ea24ecd640b223ea86dfe453e0eaed2e2b2",
+        token: "5768337691:AAH5YkoiEuPk8-FZa32hStHTqXiLPtAEhx9",
+        now: AUTH_DATE + 60,
+        expires_in: DAY,
+        expected: Outcome::HashInvalid,
+    },
+    Vector {
+        name: "hash_malformed",
+        init_data: "query_id=AAHdF6IQAAAAAN0XohDhrOrc&auth_date=1662771648&hash=not-a-hash",
+        token: TOKEN,
+        now: AUTH_DATE + 60,
+        expires_in: DAY,
+        expected: Outcome::HashInvalid,
+    },
+    Vector {
+        name: "hash_missing",
+        init_data: "query_id=AAHdF6IQAAAAAN0XohDhrOrc&user=%7B%22id%22%3A279058397%2C%22first_name%22%3A%22Vladislav%22%2C%22last_name%22%3A%22Kibenko%22%2C%22username%22%3A%22vdkfrost%22%2C%22language_code%22%3A%22ru%22%2C%22is_premium%22%3Atrue%7D&auth_date=1662771648",
+        token: TOKEN,
+        now: AUTH_DATE + 60,
+        expires_in: DAY,
+        expected: Outcome::HashMissing,
+    },
+    Vector {
+        name: "auth_date_missing",
+        init_data: "query_id=AAHdF6IQAAAAAN0XohDhrOrc&user=%7B%22id%22%3A279058397%7D&hash=6d21a2156b33d8419cf167650a497f2644e45420a28edfbc9f69e9344fd735f3",
+        token: TOKEN,
+        now: AUTH_DATE + 60,
+        expires_in: DAY,
+        expected: Outcome::AuthDateMissing,
+    },
+    Vector {
+        name: "empty",
+        init_data: "",
+        token: TOKEN,
+        now: AUTH_DATE + 60,
+        expires_in: DAY,
+        expected: Outcome::Malformed,
+    },
+];
+
+/// Runs `check` against every vector of the suite.
+///
+/// # Errors
+///
+/// Returns every vector for which `check` reported an outcome other than the expected one.
+pub fn run(check: impl Fn(&Vector) -> Outcome) -> Result<(), Vec<Mismatch>> {
+    let mismatches: Vec<Mismatch> = VECTORS
+        .iter()
+        .filter_map(|vector| {
+            let actual = check(vector);
+            (actual != vector.expected).then_some(Mismatch { vector, actual })
+        })
+        .collect();
+
+    if mismatches.is_empty() {
+        Ok(())
+    } else {
+        Err(mismatches)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_conformance_verify() {
+        let result = run(|vector| {
+            Outcome::from(&crate::verify_with_clock(
+                vector.init_data,
+                vector.token,
+                vector.expiration(),
+                &|| vector.now,
+            ))
+        });
+        assert_eq!(result, Ok(()));
+    }
+
+    #[cfg(feature = "model")]
+    #[test]
+    fn test_conformance_validate() {
+        let result = run(|vector| {
+            Outcome::from(&crate::validate_with_clock(
+                vector.init_data,
+                vector.token,
+                vector.expiration(),
+                &|| vector.now,
+            ))
+        });
+        assert_eq!(result, Ok(()));
+    }
+
+    #[test]
+    fn test_conformance_reports_mismatches() {
+        let mismatches = run(|_| Outcome::Valid).unwrap_err();
+        assert!(mismatches
+            .iter()
+            .all(|mismatch| mismatch.vector.expected != Outcome::Valid));
+        assert_eq!(mismatches[0].actual, Outcome::Valid);
+    }
+
+    #[test]
+    fn test_conformance_names_are_unique() {
+        let mut names: Vec<_> = VECTORS.iter().map(|vector| vector.name).collect();
+        names.sort_unstable();
+        names.dedup();
+        assert_eq!(names.len(), VECTORS.len());
+    }
+}
@@ -0,0 +1,312 @@
+//! Signed session cookies carrying validated init data.
+//!
+//! Endpoints polled by the frontend can read the user from the cookie instead of receiving and
+//! validating the full init data on every request.
+
+use std::fmt::{self, Write as _};
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD as base64_engine;
+use base64::Engine as _;
+
+use crate::clock::{Clock, SystemClock};
+use crate::crypto::Hmac;
+use crate::error::InitDataError;
+use crate::model::InitData;
+
+/// Default lifetime of the cookie, counted from `auth_date` (24 hours)
+const DEFAULT_TTL: u64 = 86400;
+
+/// `SameSite` attribute of the cookie.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SameSite {
+    Strict,
+    Lax,
+    None,
+}
+
+impl fmt::Display for SameSite {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Strict => "Strict",
+            Self::Lax => "Lax",
+            Self::None => "None",
+        })
+    }
+}
+
+/// Issues and reads `HttpOnly` cookies holding validated init data, signed with a key of the service.
+///
+/// The cookie expires `ttl` seconds after the `auth_date` of the init data, like the init data itself.
+///
+/// # Example
+/// ```
+/// use init_data_rs::{parse, SessionCookie};
+///
+/// let cookie = SessionCookie::new("tg_session", b"cookie key".to_vec()).ttl(0);
+/// let data = parse("auth_date=1662771648&hash=c501b71e775f74ce10e377dea85a7ea24ecd640b223ea86dfe453e0eaed2e2b2").unwrap();
+///
+/// let set_cookie = cookie.set_cookie(&data).unwrap();
+/// assert!(set_cookie.starts_with("tg_session="));
+///
+/// let value = cookie.value(&data).unwrap();
+/// assert_eq!(cookie.from_header(&format!("theme=dark; tg_session={value}")).unwrap().auth_date, 1662771648);
+/// ```
+pub struct SessionCookie {
+    name: String,
+    key: Vec<u8>,
+    ttl: u64,
+    path: String,
+    secure: bool,
+    same_site: SameSite,
+}
+
+impl SessionCookie {
+    /// Creates a cookie named `name` signed with `key`, with `Path=/`, `Secure` and `SameSite=Lax`.
+    pub fn new(name: impl Into<String>, key: impl Into<Vec<u8>>) -> Self {
+        Self {
+            name: name.into(),
+            key: key.into(),
+            ttl: DEFAULT_TTL,
+            path: "/".to_string(),
+            secure: true,
+            same_site: SameSite::Lax,
+        }
+    }
+
+    /// Sets the lifetime in seconds counted from `auth_date`, 0 disables the expiration check
+    /// and issues a session cookie without `Max-Age`.
+    #[must_use]
+    pub fn ttl(mut self, ttl: u64) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Sets the `Path` attribute.
+    #[must_use]
+    pub fn path(mut self, path: impl Into<String>) -> Self {
+        self.path = path.into();
+        self
+    }
+
+    /// Sets whether the `Secure` attribute is sent, e.g. to disable it for local development over HTTP.
+    #[must_use]
+    pub fn secure(mut self, secure: bool) -> Self {
+        self.secure = secure;
+        self
+    }
+
+    /// Sets the `SameSite` attribute.
+    #[must_use]
+    pub fn same_site(mut self, same_site: SameSite) -> Self {
+        self.same_site = same_site;
+        self
+    }
+
+    /// Encodes and signs validated init data as a cookie value.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`InitDataError::UnexpectedFormat`] if the key is empty.
+    pub fn value(&self, data: &InitData) -> Result<String, InitDataError> {
+        let json = serde_json::to_vec(data).map_err(|error| InitDataError::Internal(error.to_string()))?;
+
+        let mut value = base64_engine.encode(json);
+        let mac = self.mac(&value)?.finalize()?;
+        value.push('.');
+        value.push_str(&base64_engine.encode(mac));
+
+        Ok(value)
+    }
+
+    /// Builds the `Set-Cookie` header value for validated init data.
+    ///
+    /// # Errors
+    ///
+    /// See [`SessionCookie::value`].
+    ///
+    /// # Panics
+    ///
+    /// See [`crate::validate`].
+    pub fn set_cookie(&self, data: &InitData) -> Result<String, InitDataError> {
+        self.set_cookie_with_clock(data, &SystemClock)
+    }
+
+    /// Same as [`SessionCookie::set_cookie`], but reads the current time from `clock`.
+    ///
+    /// # Errors
+    ///
+    /// See [`SessionCookie::value`].
+    pub fn set_cookie_with_clock(&self, data: &InitData, clock: &impl Clock) -> Result<String, InitDataError> {
+        let max_age = (self.ttl > 0).then(|| data.auth_date.saturating_add(self.ttl).saturating_sub(clock.now()));
+
+        Ok(self.header(&self.value(data)?, max_age))
+    }
+
+    /// Builds the `Set-Cookie` header value deleting the cookie, e.g. on logout.
+    #[must_use]
+    pub fn clear(&self) -> String {
+        self.header("", Some(0))
+    }
+
+    fn header(&self, value: &str, max_age: Option<u64>) -> String {
+        let mut header = format!(
+            "{}={value}; Path={}; HttpOnly; SameSite={}",
+            self.name, self.path, self.same_site
+        );
+        if let Some(max_age) = max_age {
+            let _ = write!(header, "; Max-Age={max_age}");
+        }
+        if self.secure {
+            header.push_str("; Secure");
+        }
+        header
+    }
+
+    /// Checks the signature and the expiration of a cookie value and decodes the init data.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`InitDataError::UnexpectedFormat`] if the value is malformed or the key is empty,
+    /// [`InitDataError::HashInvalid`] if it wasn't signed with the key and [`InitDataError::Expired`]
+    /// once it has expired.
+    ///
+    /// # Panics
+    ///
+    /// See [`crate::validate`].
+    pub fn verify(&self, value: &str) -> Result<InitData, InitDataError> {
+        self.verify_with_clock(value, &SystemClock)
+    }
+
+    /// Same as [`SessionCookie::verify`], but reads the current time from `clock`.
+    ///
+    /// # Errors
+    ///
+    /// See [`SessionCookie::verify`].
+    pub fn verify_with_clock(&self, value: &str, clock: &impl Clock) -> Result<InitData, InitDataError> {
+        let malformed = || InitDataError::UnexpectedFormat("cookie is malformed".to_string());
+
+        let (payload, signature) = value.split_once('.').ok_or_else(malformed)?;
+        let mut signature_bytes = [0u8; 32];
+        let signature_len = base64_engine
+            .decode_slice(signature, &mut signature_bytes)
+            .map_err(|_| malformed())?;
+        if !self.mac(payload)?.verify(&signature_bytes[..signature_len])? {
+            return Err(InitDataError::HashInvalid);
+        }
+
+        let json = base64_engine.decode(payload).map_err(|_| malformed())?;
+        let data: InitData = serde_json::from_slice(&json).map_err(|_| malformed())?;
+
+        if self.ttl > 0 && data.auth_date.saturating_add(self.ttl) < clock.now() {
+            return Err(InitDataError::Expired);
+        }
+
+        Ok(data)
+    }
+
+    /// Finds the cookie in a `Cookie` request header and verifies it, see [`SessionCookie::verify`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`InitDataError::UnexpectedFormat`] if the cookie is missing, see [`SessionCookie::verify`]
+    /// for the other errors.
+    ///
+    /// # Panics
+    ///
+    /// See [`crate::validate`].
+    pub fn from_header(&self, cookie_header: &str) -> Result<InitData, InitDataError> {
+        self.from_header_with_clock(cookie_header, &SystemClock)
+    }
+
+    /// Same as [`SessionCookie::from_header`], but reads the current time from `clock`.
+    ///
+    /// # Errors
+    ///
+    /// See [`SessionCookie::from_header`].
+    pub fn from_header_with_clock(&self, cookie_header: &str, clock: &impl Clock) -> Result<InitData, InitDataError> {
+        let value = cookie_header
+            .split(';')
+            .filter_map(|cookie| cookie.trim().split_once('='))
+            .find(|(name, _)| *name == self.name)
+            .map(|(_, value)| value)
+            .ok_or_else(|| InitDataError::UnexpectedFormat(format!("cookie `{}` is missing", self.name)))?;
+
+        self.verify_with_clock(value, clock)
+    }
+
+    fn mac(&self, payload: &str) -> Result<Hmac, InitDataError> {
+        if self.key.is_empty() {
+            return Err(InitDataError::UnexpectedFormat("cookie key is empty".to_string()));
+        }
+
+        let mut mac = Hmac::new(&self.key)?;
+        mac.update(payload.as_bytes());
+
+        Ok(mac)
+    }
+}
+
+impl fmt::Debug for SessionCookie {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SessionCookie")
+            .field("name", &self.name)
+            .field("key", &"***")
+            .field("ttl", &self.ttl)
+            .field("path", &self.path)
+            .field("secure", &self.secure)
+            .field("same_site", &self.same_site)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse;
+
+    const AUTH_DATE: u64 = 1662771648;
+
+    fn data() -> InitData {
+        parse("user=%7B%22id%22%3A279058397%2C%22first_name%22%3A%22Vladislav%22%7D&auth_date=1662771648&hash=c501b71e775f74ce10e377dea85a7ea24ecd640b223ea86dfe453e0eaed2e2b2").unwrap()
+    }
+
+    #[test]
+    fn test_session_cookie_roundtrip() {
+        let cookie = SessionCookie::new("tg", b"cookie key".to_vec()).ttl(60);
+
+        let set_cookie = cookie.set_cookie_with_clock(&data(), &|| AUTH_DATE + 20).unwrap();
+        let (value, attributes) = set_cookie.strip_prefix("tg=").unwrap().split_once("; ").unwrap();
+        assert_eq!(attributes, "Path=/; HttpOnly; SameSite=Lax; Max-Age=40; Secure");
+
+        let header = format!("a=b; tg={value}");
+        let data = cookie.from_header_with_clock(&header, &|| AUTH_DATE + 60).unwrap();
+        assert_eq!(data.user.unwrap().first_name, "Vladislav");
+        assert_eq!(
+            data.hash,
+            "c501b71e775f74ce10e377dea85a7ea24ecd640b223ea86dfe453e0eaed2e2b2"
+        );
+        assert!(matches!(
+            cookie.from_header_with_clock(&header, &|| AUTH_DATE + 61),
+            Err(InitDataError::Expired)
+        ));
+        assert!(matches!(
+            cookie.from_header("a=b"),
+            Err(InitDataError::UnexpectedFormat(_))
+        ));
+    }
+
+    #[test]
+    fn test_session_cookie_rejects_tampering() {
+        let cookie = SessionCookie::new("tg", b"cookie key".to_vec()).ttl(0);
+        let value = cookie.value(&data()).unwrap();
+
+        let other = SessionCookie::new("tg", b"other key".to_vec()).ttl(0);
+        assert!(matches!(other.verify(&value), Err(InitDataError::HashInvalid)));
+
+        let (_, signature) = value.split_once('.').unwrap();
+        let forged = format!("{}.{signature}", base64_engine.encode(br#"{"auth_date":1,"hash":"x"}"#));
+        assert!(matches!(cookie.verify(&forged), Err(InitDataError::HashInvalid)));
+
+        assert_eq!(cookie.clear(), "tg=; Path=/; HttpOnly; SameSite=Lax; Max-Age=0; Secure");
+    }
+}
@@ -0,0 +1,501 @@
+//! HMAC-SHA256, SHA-256 and Ed25519 verification from the backend selected with the `aws-lc-rs`, `ring` or
+//! `openssl` features, or from the `hmac`, `sha2` and `ed25519-dalek` crates with the default `rustcrypto`
+//! feature.
+//!
+//! When several backend features are enabled, `aws-lc-rs` wins over `ring`, which wins over `openssl`, which
+//! wins over `rustcrypto`. The `fips` feature builds aws-lc-rs in FIPS mode and refuses the backends and features
+//! using other primitives.
+
+use std::fmt;
+
+use crate::error::InitDataError;
+
+#[cfg(all(
+    feature = "fips",
+    any(
+        feature = "rustcrypto",
+        feature = "ring",
+        feature = "openssl",
+        feature = "paseto",
+        feature = "encrypt"
+    )
+))]
+compile_error!(
+    "the `fips` feature only allows primitives from aws-lc-rs in FIPS mode, disable the default features and the `ring`, `openssl`, `paseto` and `encrypt` features"
+);
+
+#[cfg(not(any(feature = "rustcrypto", feature = "aws-lc-rs", feature = "ring", feature = "openssl")))]
+compile_error!("no crypto backend selected, enable one of the `rustcrypto`, `aws-lc-rs`, `ring` or `openssl` features");
+
+/// The library providing the cryptographic primitives, see [`CRYPTO_BACKEND`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CryptoBackend {
+    /// The `hmac`, `sha2` and `ed25519-dalek` crates, the default `rustcrypto` feature.
+    RustCrypto,
+    /// The `ring` crate.
+    Ring,
+    /// The `aws-lc-rs` crate.
+    AwsLc,
+    /// The `aws-lc-rs` crate in FIPS mode, with the `fips` feature.
+    AwsLcFips,
+    /// The system's OpenSSL through the `openssl` crate.
+    OpenSsl,
+}
+
+impl CryptoBackend {
+    /// The name of the cargo feature selecting the backend.
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::RustCrypto => "rustcrypto",
+            Self::Ring => "ring",
+            Self::AwsLc => "aws-lc-rs",
+            Self::AwsLcFips => "fips",
+            Self::OpenSsl => "openssl",
+        }
+    }
+
+    /// Whether the primitives come from a FIPS 140-3 validated module.
+    #[must_use]
+    pub fn is_fips(self) -> bool {
+        self == Self::AwsLcFips
+    }
+}
+
+impl fmt::Display for CryptoBackend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// The primitives a crypto backend provides.
+trait Backend {
+    const BACKEND: CryptoBackend;
+
+    /// An HMAC-SHA256 computation in progress.
+    type Hmac: Clone;
+
+    fn hmac(key: &[u8]) -> Result<Self::Hmac, InitDataError>;
+
+    fn update(hmac: &mut Self::Hmac, data: &[u8]);
+
+    fn finalize_into(hmac: Self::Hmac, out: &mut [u8; 32]) -> Result<(), InitDataError>;
+
+    fn sha256(data: &[u8]) -> [u8; 32];
+
+    /// Whether `signature` is the Ed25519 signature of `message` for `public_key`.
+    #[cfg(feature = "model")]
+    fn verify_ed25519(public_key: &[u8; 32], message: &[u8], signature: &[u8; 64]) -> bool;
+
+    /// The Ed25519 public key of the secret key `seed`.
+    #[cfg(feature = "testing")]
+    fn ed25519_public_key(seed: &[u8; 32]) -> [u8; 32];
+
+    /// The Ed25519 signature of `message` with the secret key `seed`.
+    #[cfg(feature = "testing")]
+    fn sign_ed25519(seed: &[u8; 32], message: &[u8]) -> [u8; 64];
+}
+
+#[cfg(feature = "aws-lc-rs")]
+type Selected = AwsLc;
+#[cfg(all(feature = "ring", not(feature = "aws-lc-rs")))]
+type Selected = Ring;
+#[cfg(all(feature = "openssl", not(any(feature = "aws-lc-rs", feature = "ring"))))]
+type Selected = OpenSsl;
+#[cfg(all(
+    feature = "rustcrypto",
+    not(any(feature = "aws-lc-rs", feature = "ring", feature = "openssl"))
+))]
+type Selected = RustCrypto;
+
+/// The crypto backend the crate was built with.
+pub const CRYPTO_BACKEND: CryptoBackend = Selected::BACKEND;
+
+/// HMAC-SHA256 from the selected backend.
+#[derive(Clone)]
+pub(crate) struct Hmac(<Selected as Backend>::Hmac);
+
+impl Hmac {
+    pub(crate) fn new(key: &[u8]) -> Result<Self, InitDataError> {
+        Selected::hmac(key).map(Self)
+    }
+
+    pub(crate) fn update(&mut self, data: &[u8]) {
+        Selected::update(&mut self.0, data);
+    }
+
+    pub(crate) fn finalize(self) -> Result<[u8; 32], InitDataError> {
+        let mut out = [0; 32];
+        self.finalize_into(&mut out)?;
+        Ok(out)
+    }
+
+    /// Writes the MAC into `out`, e.g. a key that must not be copied into a temporary.
+    pub(crate) fn finalize_into(self, out: &mut [u8; 32]) -> Result<(), InitDataError> {
+        Selected::finalize_into(self.0, out)
+    }
+
+    /// Whether the MAC equals `expected`, compared in constant time.
+    pub(crate) fn verify(self, expected: &[u8]) -> Result<bool, InitDataError> {
+        Ok(constant_time_eq(&self.finalize()?, expected))
+    }
+}
+
+pub(crate) fn sha256(data: &[u8]) -> [u8; 32] {
+    Selected::sha256(data)
+}
+
+/// Whether `signature` is the Ed25519 signature of `message` for `public_key`.
+#[cfg(feature = "model")]
+pub(crate) fn verify_ed25519(public_key: &[u8; 32], message: &[u8], signature: &[u8; 64]) -> bool {
+    Selected::verify_ed25519(public_key, message, signature)
+}
+
+/// The Ed25519 public key of the secret key `seed`.
+#[cfg(feature = "testing")]
+pub(crate) fn ed25519_public_key(seed: &[u8; 32]) -> [u8; 32] {
+    Selected::ed25519_public_key(seed)
+}
+
+/// The Ed25519 signature of `message` with the secret key `seed`.
+#[cfg(feature = "testing")]
+pub(crate) fn sign_ed25519(seed: &[u8; 32], message: &[u8]) -> [u8; 64] {
+    Selected::sign_ed25519(seed, message)
+}
+
+pub(crate) fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && std::hint::black_box(a.iter().zip(b).fold(0, |acc, (a, b)| acc | (a ^ b))) == 0
+}
+
+#[cfg(feature = "rustcrypto")]
+#[cfg_attr(any(feature = "aws-lc-rs", feature = "ring", feature = "openssl"), allow(dead_code))]
+enum RustCrypto {}
+
+#[cfg(feature = "rustcrypto")]
+impl Backend for RustCrypto {
+    const BACKEND: CryptoBackend = CryptoBackend::RustCrypto;
+
+    type Hmac = hmac::Hmac<sha2::Sha256>;
+
+    fn hmac(key: &[u8]) -> Result<Self::Hmac, InitDataError> {
+        hmac::KeyInit::new_from_slice(key)
+            .map_err(|error: hmac::digest::InvalidLength| InitDataError::Internal(error.to_string()))
+    }
+
+    fn update(hmac: &mut Self::Hmac, data: &[u8]) {
+        hmac::Mac::update(hmac, data);
+    }
+
+    fn finalize_into(hmac: Self::Hmac, out: &mut [u8; 32]) -> Result<(), InitDataError> {
+        hmac::digest::FixedOutput::finalize_into(hmac, out.into());
+        Ok(())
+    }
+
+    fn sha256(data: &[u8]) -> [u8; 32] {
+        <sha2::Sha256 as sha2::Digest>::digest(data).into()
+    }
+
+    #[cfg(feature = "model")]
+    fn verify_ed25519(public_key: &[u8; 32], message: &[u8], signature: &[u8; 64]) -> bool {
+        use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+
+        VerifyingKey::from_bytes(public_key)
+            .is_ok_and(|key| key.verify(message, &Signature::from_bytes(signature)).is_ok())
+    }
+
+    #[cfg(feature = "testing")]
+    fn ed25519_public_key(seed: &[u8; 32]) -> [u8; 32] {
+        ed25519_dalek::SigningKey::from_bytes(seed).verifying_key().to_bytes()
+    }
+
+    #[cfg(feature = "testing")]
+    fn sign_ed25519(seed: &[u8; 32], message: &[u8]) -> [u8; 64] {
+        ed25519_dalek::Signer::sign(&ed25519_dalek::SigningKey::from_bytes(seed), message).to_bytes()
+    }
+}
+
+#[cfg(feature = "ring")]
+#[cfg_attr(feature = "aws-lc-rs", allow(dead_code))]
+enum Ring {}
+
+#[cfg(feature = "ring")]
+impl Backend for Ring {
+    const BACKEND: CryptoBackend = CryptoBackend::Ring;
+
+    type Hmac = ring::hmac::Context;
+
+    fn hmac(key: &[u8]) -> Result<Self::Hmac, InitDataError> {
+        Ok(ring::hmac::Context::with_key(&ring::hmac::Key::new(
+            ring::hmac::HMAC_SHA256,
+            key,
+        )))
+    }
+
+    fn update(hmac: &mut Self::Hmac, data: &[u8]) {
+        hmac.update(data);
+    }
+
+    fn finalize_into(hmac: Self::Hmac, out: &mut [u8; 32]) -> Result<(), InitDataError> {
+        out.copy_from_slice(hmac.sign().as_ref());
+        Ok(())
+    }
+
+    fn sha256(data: &[u8]) -> [u8; 32] {
+        let mut out = [0; 32];
+        out.copy_from_slice(ring::digest::digest(&ring::digest::SHA256, data).as_ref());
+        out
+    }
+
+    #[cfg(feature = "model")]
+    fn verify_ed25519(public_key: &[u8; 32], message: &[u8], signature: &[u8; 64]) -> bool {
+        ring::signature::UnparsedPublicKey::new(&ring::signature::ED25519, public_key)
+            .verify(message, signature)
+            .is_ok()
+    }
+
+    #[cfg(feature = "testing")]
+    fn ed25519_public_key(seed: &[u8; 32]) -> [u8; 32] {
+        use ring::signature::{Ed25519KeyPair, KeyPair};
+
+        let key_pair = Ed25519KeyPair::from_seed_unchecked(seed).expect("any 32 bytes are an Ed25519 seed");
+        let mut out = [0; 32];
+        out.copy_from_slice(key_pair.public_key().as_ref());
+        out
+    }
+
+    #[cfg(feature = "testing")]
+    fn sign_ed25519(seed: &[u8; 32], message: &[u8]) -> [u8; 64] {
+        let key_pair =
+            ring::signature::Ed25519KeyPair::from_seed_unchecked(seed).expect("any 32 bytes are an Ed25519 seed");
+        let mut out = [0; 64];
+        out.copy_from_slice(key_pair.sign(message).as_ref());
+        out
+    }
+}
+
+#[cfg(feature = "aws-lc-rs")]
+enum AwsLc {}
+
+#[cfg(feature = "aws-lc-rs")]
+impl Backend for AwsLc {
+    const BACKEND: CryptoBackend = if cfg!(feature = "fips") {
+        CryptoBackend::AwsLcFips
+    } else {
+        CryptoBackend::AwsLc
+    };
+
+    type Hmac = aws_lc_rs::hmac::Context;
+
+    fn hmac(key: &[u8]) -> Result<Self::Hmac, InitDataError> {
+        Ok(aws_lc_rs::hmac::Context::with_key(&aws_lc_rs::hmac::Key::new(
+            aws_lc_rs::hmac::HMAC_SHA256,
+            key,
+        )))
+    }
+
+    fn update(hmac: &mut Self::Hmac, data: &[u8]) {
+        hmac.update(data);
+    }
+
+    fn finalize_into(hmac: Self::Hmac, out: &mut [u8; 32]) -> Result<(), InitDataError> {
+        out.copy_from_slice(hmac.sign().as_ref());
+        Ok(())
+    }
+
+    fn sha256(data: &[u8]) -> [u8; 32] {
+        let mut out = [0; 32];
+        out.copy_from_slice(aws_lc_rs::digest::digest(&aws_lc_rs::digest::SHA256, data).as_ref());
+        out
+    }
+
+    #[cfg(feature = "model")]
+    fn verify_ed25519(public_key: &[u8; 32], message: &[u8], signature: &[u8; 64]) -> bool {
+        aws_lc_rs::signature::UnparsedPublicKey::new(&aws_lc_rs::signature::ED25519, public_key)
+            .verify(message, signature)
+            .is_ok()
+    }
+
+    #[cfg(feature = "testing")]
+    fn ed25519_public_key(seed: &[u8; 32]) -> [u8; 32] {
+        use aws_lc_rs::signature::{Ed25519KeyPair, KeyPair};
+
+        let key_pair = Ed25519KeyPair::from_seed_unchecked(seed).expect("any 32 bytes are an Ed25519 seed");
+        let mut out = [0; 32];
+        out.copy_from_slice(key_pair.public_key().as_ref());
+        out
+    }
+
+    #[cfg(feature = "testing")]
+    fn sign_ed25519(seed: &[u8; 32], message: &[u8]) -> [u8; 64] {
+        let key_pair =
+            aws_lc_rs::signature::Ed25519KeyPair::from_seed_unchecked(seed).expect("any 32 bytes are an Ed25519 seed");
+        let mut out = [0; 64];
+        out.copy_from_slice(key_pair.sign(message).as_ref());
+        out
+    }
+}
+
+#[cfg(feature = "openssl")]
+#[cfg_attr(any(feature = "aws-lc-rs", feature = "ring"), allow(dead_code))]
+enum OpenSsl {}
+
+/// OpenSSL signers borrow their key, so the message is buffered until the MAC is computed.
+#[cfg(feature = "openssl")]
+#[cfg_attr(any(feature = "aws-lc-rs", feature = "ring"), allow(dead_code))]
+#[derive(Clone)]
+struct OpenSslHmac {
+    key: openssl::pkey::PKey<openssl::pkey::Private>,
+    message: Vec<u8>,
+}
+
+#[cfg(feature = "openssl")]
+impl Backend for OpenSsl {
+    const BACKEND: CryptoBackend = CryptoBackend::OpenSsl;
+
+    type Hmac = OpenSslHmac;
+
+    fn hmac(key: &[u8]) -> Result<Self::Hmac, InitDataError> {
+        openssl::pkey::PKey::hmac(key)
+            .map(|key| OpenSslHmac {
+                key,
+                message: Vec::new(),
+            })
+            .map_err(|error| InitDataError::Internal(error.to_string()))
+    }
+
+    fn update(hmac: &mut Self::Hmac, data: &[u8]) {
+        hmac.message.extend_from_slice(data);
+    }
+
+    fn finalize_into(hmac: Self::Hmac, out: &mut [u8; 32]) -> Result<(), InitDataError> {
+        let mut signer = openssl::sign::Signer::new(openssl::hash::MessageDigest::sha256(), &hmac.key)
+            .map_err(|error| InitDataError::Internal(error.to_string()))?;
+        signer
+            .sign_oneshot(out, &hmac.message)
+            .map(|_| ())
+            .map_err(|error| InitDataError::Internal(error.to_string()))
+    }
+
+    fn sha256(data: &[u8]) -> [u8; 32] {
+        openssl::sha::sha256(data)
+    }
+
+    #[cfg(feature = "model")]
+    fn verify_ed25519(public_key: &[u8; 32], message: &[u8], signature: &[u8; 64]) -> bool {
+        use openssl::pkey::{Id, PKey};
+        use openssl::sign::Verifier;
+
+        PKey::public_key_from_raw_bytes(public_key, Id::ED25519)
+            .and_then(|key| Verifier::new_without_digest(&key)?.verify_oneshot(signature, message))
+            .unwrap_or(false)
+    }
+
+    #[cfg(feature = "testing")]
+    fn ed25519_public_key(seed: &[u8; 32]) -> [u8; 32] {
+        let key = openssl::pkey::PKey::private_key_from_raw_bytes(seed, openssl::pkey::Id::ED25519)
+            .expect("any 32 bytes are an Ed25519 seed");
+        let mut out = [0; 32];
+        out.copy_from_slice(&key.raw_public_key().expect("Ed25519 keys have a raw public key"));
+        out
+    }
+
+    #[cfg(feature = "testing")]
+    fn sign_ed25519(seed: &[u8; 32], message: &[u8]) -> [u8; 64] {
+        let key = openssl::pkey::PKey::private_key_from_raw_bytes(seed, openssl::pkey::Id::ED25519)
+            .expect("any 32 bytes are an Ed25519 seed");
+        let mut out = [0; 64];
+        openssl::sign::Signer::new_without_digest(&key)
+            .and_then(|mut signer| signer.sign_oneshot(&mut out, message))
+            .expect("Ed25519 signing doesn't fail");
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check_backend<B: Backend>() {
+        // RFC 4231, test case 2
+        let mut hmac = B::hmac(b"Jefe").unwrap();
+        B::update(&mut hmac, b"what do ya want ");
+        B::update(&mut hmac, b"for nothing?");
+        let mut out = [0; 32];
+        B::finalize_into(hmac, &mut out).unwrap();
+        assert_eq!(
+            hex::encode(out),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843",
+            "{}",
+            B::BACKEND
+        );
+
+        assert_eq!(
+            hex::encode(B::sha256(b"abc")),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+            "{}",
+            B::BACKEND
+        );
+
+        // RFC 8032, test 1
+        #[cfg(feature = "model")]
+        {
+            let mut public_key = [0; 32];
+            hex::decode_to_slice(
+                "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+                &mut public_key,
+            )
+            .unwrap();
+            let mut signature = [0; 64];
+            hex::decode_to_slice("e5564300c360ac729086e2cc806e828a84877f1eb8e5d974d873e065224901555fb8821590a33bacc61e39701cf9b46bd25bf5f0595bbe24655141438e7a100b", &mut signature).unwrap();
+
+            assert!(B::verify_ed25519(&public_key, b"", &signature), "{}", B::BACKEND);
+            assert!(!B::verify_ed25519(&public_key, b"x", &signature), "{}", B::BACKEND);
+
+            #[cfg(feature = "testing")]
+            {
+                let mut seed = [0; 32];
+                hex::decode_to_slice(
+                    "9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60",
+                    &mut seed,
+                )
+                .unwrap();
+                assert_eq!(B::ed25519_public_key(&seed), public_key, "{}", B::BACKEND);
+                assert_eq!(B::sign_ed25519(&seed, b""), signature, "{}", B::BACKEND);
+            }
+        }
+    }
+
+    #[test]
+    fn test_backends_agree() {
+        #[cfg(feature = "rustcrypto")]
+        check_backend::<RustCrypto>();
+        #[cfg(feature = "ring")]
+        check_backend::<Ring>();
+        #[cfg(feature = "aws-lc-rs")]
+        check_backend::<AwsLc>();
+        #[cfg(feature = "openssl")]
+        check_backend::<OpenSsl>();
+    }
+
+    #[test]
+    fn test_crypto_backend() {
+        assert_eq!(CRYPTO_BACKEND.is_fips(), cfg!(feature = "fips"));
+        #[cfg(all(
+            feature = "rustcrypto",
+            not(any(feature = "aws-lc-rs", feature = "ring", feature = "openssl"))
+        ))]
+        assert_eq!(CRYPTO_BACKEND, CryptoBackend::RustCrypto);
+        assert_eq!(CryptoBackend::AwsLcFips.to_string(), "fips");
+    }
+
+    #[test]
+    fn test_hmac_verify() {
+        let mut hmac = Hmac::new(b"key").unwrap();
+        hmac.update(b"message");
+        let mac = hmac.clone().finalize().unwrap();
+
+        assert!(hmac.clone().verify(&mac).unwrap());
+        assert!(!hmac.clone().verify(&mac[..31]).unwrap());
+        assert!(!hmac.verify(&[0; 32]).unwrap());
+    }
+}
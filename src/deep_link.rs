@@ -0,0 +1,192 @@
+//! Links opening a Mini App, the other end of `start_param`.
+//!
+//! ```
+//! use init_data_rs::deep_link::{self, Mode};
+//!
+//! let link = deep_link::builder("my_bot", "shop")
+//!     .start_param("ref_42")
+//!     .mode(Mode::Fullscreen)
+//!     .build()
+//!     .unwrap();
+//!
+//! assert_eq!(link, "https://t.me/my_bot/shop?startapp=ref_42&mode=fullscreen");
+//! ```
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD as base64_engine;
+use base64::Engine as _;
+
+use crate::error::InitDataError;
+use crate::start_param::{is_valid_start_param, MAX_START_PARAM_LEN};
+
+/// How the Mini App is displayed when opened from the link.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Mode {
+    /// A half-height sheet.
+    Compact,
+    /// The whole screen.
+    Fullscreen,
+}
+
+impl Mode {
+    /// The value of the `mode` parameter.
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Compact => "compact",
+            Self::Fullscreen => "fullscreen",
+        }
+    }
+}
+
+/// Starts a link to the Mini App `app_name` of the bot `bot_username`, with or without its `@`.
+pub fn builder(bot_username: impl Into<String>, app_name: impl Into<String>) -> DeepLinkBuilder {
+    DeepLinkBuilder {
+        bot_username: bot_username.into(),
+        app_name: app_name.into(),
+        start_param: None,
+        mode: None,
+    }
+}
+
+/// Encodes an arbitrary `payload` into a valid `start_param`, as unpadded URL-safe base64.
+///
+/// # Errors
+///
+/// Returns [`InitDataError::StartParamInvalid`] if the encoded payload is longer than
+/// [`MAX_START_PARAM_LEN`], i.e. `payload` is longer than 384 bytes.
+pub fn encode_start_param(payload: impl AsRef<[u8]>) -> Result<String, InitDataError> {
+    let start_param = base64_engine.encode(payload);
+    if start_param.len() > MAX_START_PARAM_LEN {
+        return Err(InitDataError::StartParamInvalid(format!(
+            "longer than {MAX_START_PARAM_LEN} characters once encoded"
+        )));
+    }
+    Ok(start_param)
+}
+
+/// Decodes a `start_param` produced by [`encode_start_param`].
+///
+/// # Errors
+///
+/// Returns [`InitDataError::StartParamInvalid`] if `start_param` is not unpadded URL-safe base64.
+pub fn decode_start_param(start_param: &str) -> Result<Vec<u8>, InitDataError> {
+    base64_engine
+        .decode(start_param)
+        .map_err(|_| InitDataError::StartParamInvalid("not unpadded URL-safe base64".to_string()))
+}
+
+/// Builds a `https://t.me/<bot>/<app>?startapp=...` link, see [`builder`].
+#[derive(Debug, Clone)]
+#[must_use]
+pub struct DeepLinkBuilder {
+    bot_username: String,
+    app_name: String,
+    start_param: Option<String>,
+    mode: Option<Mode>,
+}
+
+impl DeepLinkBuilder {
+    /// Passes `start_param` to the Mini App. It must already satisfy Telegram's constraints, see
+    /// [`crate::is_valid_start_param`]; encode other payloads with [`encode_start_param`].
+    pub fn start_param(mut self, start_param: impl Into<String>) -> Self {
+        self.start_param = Some(start_param.into());
+        self
+    }
+
+    /// Opens the Mini App in `mode` instead of the default full-height sheet.
+    pub fn mode(mut self, mode: Mode) -> Self {
+        self.mode = Some(mode);
+        self
+    }
+
+    /// Returns the link.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`InitDataError::Config`] if the bot username is not 5 to 32 characters out of `A-Z`, `a-z`,
+    /// `0-9` and `_`, or the app name not 3 to 30 of them, and [`InitDataError::StartParamInvalid`] if the
+    /// `start_param` breaks Telegram's constraints.
+    pub fn build(&self) -> Result<String, InitDataError> {
+        let bot_username = self.bot_username.strip_prefix('@').unwrap_or(&self.bot_username);
+        if !is_valid_name(bot_username, 5..=32) {
+            return Err(InitDataError::Config(format!("invalid bot username: {bot_username:?}")));
+        }
+        if !is_valid_name(&self.app_name, 3..=30) {
+            return Err(InitDataError::Config(format!("invalid app name: {:?}", self.app_name)));
+        }
+
+        let mut link = format!("https://t.me/{bot_username}/{}", self.app_name);
+        let mut separator = '?';
+
+        if let Some(start_param) = &self.start_param {
+            if start_param.len() > MAX_START_PARAM_LEN {
+                return Err(InitDataError::StartParamInvalid(format!(
+                    "longer than {MAX_START_PARAM_LEN} characters"
+                )));
+            }
+            if !is_valid_start_param(start_param) {
+                return Err(InitDataError::StartParamInvalid(
+                    "contains characters other than A-Z, a-z, 0-9, _ and -, see encode_start_param".to_string(),
+                ));
+            }
+            // Only URL-safe characters are left, so no percent-encoding is needed
+            link.push(separator);
+            link.push_str("startapp=");
+            link.push_str(start_param);
+            separator = '&';
+        }
+        if let Some(mode) = self.mode {
+            link.push(separator);
+            link.push_str("mode=");
+            link.push_str(mode.as_str());
+        }
+
+        Ok(link)
+    }
+}
+
+/// Whether `name` is a bot username or app short name of a length in `lengths`.
+fn is_valid_name(name: &str, lengths: std::ops::RangeInclusive<usize>) -> bool {
+    lengths.contains(&name.len()) && name.bytes().all(|byte| byte.is_ascii_alphanumeric() || byte == b'_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builder() {
+        assert_eq!(builder("@my_bot", "shop").build().unwrap(), "https://t.me/my_bot/shop");
+        assert_eq!(
+            builder("my_bot", "shop").mode(Mode::Compact).build().unwrap(),
+            "https://t.me/my_bot/shop?mode=compact"
+        );
+        assert_eq!(
+            builder("my_bot", "shop").start_param("a-b_c").build().unwrap(),
+            "https://t.me/my_bot/shop?startapp=a-b_c"
+        );
+
+        assert!(matches!(builder("bot", "shop").build(), Err(InitDataError::Config(_))));
+        assert!(matches!(
+            builder("my_bot", "a/b").build(),
+            Err(InitDataError::Config(_))
+        ));
+        assert!(matches!(
+            builder("my_bot", "shop").start_param("a b").build(),
+            Err(InitDataError::StartParamInvalid(_))
+        ));
+        assert!(builder("my_bot", "shop").start_param("a".repeat(513)).build().is_err());
+    }
+
+    #[test]
+    fn test_encode_start_param() {
+        let payload = br#"{"ref":42,"path":"/items?id=1"}"#;
+        let start_param = encode_start_param(payload).unwrap();
+
+        assert!(is_valid_start_param(&start_param));
+        assert_eq!(decode_start_param(&start_param).unwrap(), payload);
+        assert!(encode_start_param([0; 384]).is_ok());
+        assert!(encode_start_param([0; 385]).is_err());
+        assert!(decode_start_param("a=").is_err());
+    }
+}
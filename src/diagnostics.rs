@@ -0,0 +1,142 @@
+//! Samples of recently rejected init data, for on-call engineers inspecting what clients actually send.
+
+use std::collections::VecDeque;
+use std::sync::{Mutex, PoisonError};
+
+use serde::Serialize;
+
+use crate::error::InitDataError;
+use crate::query::{decoded_eq, raw_pairs};
+use crate::sanitize::strip_secrets;
+
+/// Placeholder written in place of personal data when PII is masked.
+const MASK: &str = "REDACTED";
+
+/// Longest payload kept in a sample, longer ones are cut and end with `…`.
+const MAX_PAYLOAD_LEN: usize = 4096;
+
+/// A rejected payload, see [`crate::Validator::failure_samples`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FailedPayload {
+    /// The raw init data without its `hash` and `signature`, and without `user`, `receiver` and `chat` if PII is
+    /// masked. Cut after 4 KiB.
+    pub payload: String,
+    /// Stable code of the error, see [`InitDataError::kind`].
+    pub error_kind: &'static str,
+    /// Message of the error.
+    pub error: String,
+    /// Unix time of the rejection, in seconds.
+    pub failed_at: u64,
+}
+
+/// Ring buffer of the latest [`FailedPayload`]s.
+pub(crate) struct FailureSampler {
+    capacity: usize,
+    mask_pii: bool,
+    samples: Mutex<VecDeque<FailedPayload>>,
+}
+
+impl FailureSampler {
+    pub(crate) fn new(capacity: usize, mask_pii: bool) -> Self {
+        Self {
+            capacity,
+            mask_pii,
+            samples: Mutex::new(VecDeque::with_capacity(capacity)),
+        }
+    }
+
+    /// Keeps a sample of `init_data` rejected at `now`, evicting the oldest one when the buffer is full.
+    pub(crate) fn record(&self, init_data: &str, error: &InitDataError, now: u64) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        let sample = FailedPayload {
+            payload: sanitize(init_data, self.mask_pii),
+            error_kind: error.kind(),
+            error: error.to_string(),
+            failed_at: now,
+        };
+
+        let mut samples = self.samples.lock().unwrap_or_else(PoisonError::into_inner);
+        if samples.len() == self.capacity {
+            samples.pop_front();
+        }
+        samples.push_back(sample);
+    }
+
+    /// The samples, oldest first.
+    pub(crate) fn samples(&self) -> Vec<FailedPayload> {
+        self.samples
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .cloned()
+            .collect()
+    }
+}
+
+fn sanitize(init_data: &str, mask_pii: bool) -> String {
+    let head = &init_data[..floor_char_boundary(init_data, MAX_PAYLOAD_LEN)];
+    let mut payload = strip_secrets(head, None);
+
+    if mask_pii {
+        payload = raw_pairs(&payload)
+            .map(|(key, value)| {
+                let is_personal = ["user", "receiver", "chat"]
+                    .into_iter()
+                    .any(|field| decoded_eq(key, field));
+                format!("{key}={}", if is_personal { MASK } else { value })
+            })
+            .collect::<Vec<_>>()
+            .join("&");
+    }
+    if head.len() < init_data.len() {
+        payload.push('…');
+    }
+
+    payload
+}
+
+fn floor_char_boundary(s: &str, index: usize) -> usize {
+    (0..=index.min(s.len()))
+        .rev()
+        .find(|&index| s.is_char_boundary(index))
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INIT_DATA: &str = "query_id=AAHdF6IQ&user=%7B%22id%22%3A279058397%7D&auth_date=1662771648&hash=c501b71e";
+
+    #[test]
+    fn test_failure_sampler() {
+        let ring = FailureSampler::new(2, true);
+        ring.record(INIT_DATA, &InitDataError::HashInvalid, 1);
+        ring.record("auth_date=1", &InitDataError::HashMissing, 2);
+        ring.record(&format!("query_id={}", "a".repeat(10_000)), &InitDataError::Expired, 3);
+
+        let samples = ring.samples();
+        assert_eq!(samples.len(), 2);
+        assert_eq!(samples[0].payload, "auth_date=1");
+        assert_eq!(samples[0].error_kind, "hash_missing");
+        assert_eq!(samples[0].failed_at, 2);
+        assert_eq!(samples[1].failed_at, 3);
+        assert_eq!(samples[1].payload.len(), MAX_PAYLOAD_LEN + '…'.len_utf8());
+        assert!(FailureSampler::new(0, false).samples().is_empty());
+    }
+
+    #[test]
+    fn test_sanitize() {
+        assert_eq!(
+            sanitize(INIT_DATA, false),
+            "query_id=AAHdF6IQ&user=%7B%22id%22%3A279058397%7D&auth_date=1662771648&hash=REDACTED"
+        );
+        assert_eq!(
+            sanitize(INIT_DATA, true),
+            "query_id=AAHdF6IQ&user=REDACTED&auth_date=1662771648&hash=REDACTED"
+        );
+    }
+}
@@ -0,0 +1,79 @@
+//! [`diesel`](https://docs.rs/diesel) types for storing the model in Postgres.
+//!
+//! [`User`] and [`Chat`] are stored as `Jsonb`, in the same shape Telegram sends them, and [`ChatType`]
+//! as `Text`, e.g. `group`.
+
+use std::io::Write;
+
+use diesel::deserialize::{self, FromSql};
+use diesel::pg::{Pg, PgValue};
+use diesel::serialize::{self, IsNull, Output, ToSql};
+use diesel::sql_types::{Jsonb, Text};
+
+use crate::model::{Chat, ChatType, User};
+
+/// Version byte preceding the JSON text in the binary `jsonb` format.
+const JSONB_VERSION: u8 = 1;
+
+macro_rules! jsonb_type {
+    ($ty:ty) => {
+        impl ToSql<Jsonb, Pg> for $ty {
+            fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+                out.write_all(&[JSONB_VERSION])?;
+                serde_json::to_writer(out, self)?;
+                Ok(IsNull::No)
+            }
+        }
+
+        impl FromSql<Jsonb, Pg> for $ty {
+            fn from_sql(value: PgValue<'_>) -> deserialize::Result<Self> {
+                let json = <serde_json::Value as FromSql<Jsonb, Pg>>::from_sql(value)?;
+                Ok(serde_json::from_value(json)?)
+            }
+        }
+    };
+}
+
+jsonb_type!(User);
+jsonb_type!(Chat);
+
+impl ToSql<Text, Pg> for ChatType {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+        <str as ToSql<Text, Pg>>::to_sql(self.as_str(), out)
+    }
+}
+
+impl FromSql<Text, Pg> for ChatType {
+    fn from_sql(value: PgValue<'_>) -> deserialize::Result<Self> {
+        let name = <String as FromSql<Text, Pg>>::from_sql(value)?;
+        Ok(name.parse()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use diesel::prelude::*;
+
+    use super::*;
+
+    diesel::table! {
+        sessions (id) {
+            id -> Int8,
+            user_data -> Jsonb,
+            chat_type -> Nullable<Text>,
+        }
+    }
+
+    #[test]
+    fn test_diesel_binds() {
+        let user: User = serde_json::from_str(r#"{"id":1,"first_name":"Ada"}"#).unwrap();
+        let query = diesel::insert_into(sessions::table).values((
+            sessions::user_data.eq(&user),
+            sessions::chat_type.eq(Some(ChatType::Group)),
+        ));
+
+        let sql = diesel::debug_query::<Pg, _>(&query).to_string();
+        assert!(sql.starts_with(r#"INSERT INTO "sessions" ("user_data", "chat_type") VALUES ($1, $2)"#));
+        assert!(sql.contains("first_name: \"Ada\"") && sql.contains("Some(Group)"));
+    }
+}
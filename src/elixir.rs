@@ -0,0 +1,169 @@
+//! Erlang NIF for Elixir built with Rustler.
+//!
+//! Enabled with the `rustler` feature. The NIF is the shared library built with `--crate-type cdylib`, copied to
+//! `priv/native/libinit_data_rs.so`:
+//!
+//! ```sh
+//! cargo rustc --lib --release --features rustler --crate-type cdylib
+//! ```
+//!
+//! It is loaded by the `InitData` Elixir module, which exposes `validate/3` and `parse/1`:
+//!
+//! ```elixir
+//! defmodule InitData do
+//!   use Rustler,
+//!     otp_app: :my_app,
+//!     crate: "init_data_rs",
+//!     skip_compilation?: true,
+//!     load_from: {:my_app, "priv/native/libinit_data_rs"}
+//!
+//!   def validate(_init_data, _token, _expires_in), do: :erlang.nif_error(:nif_not_loaded)
+//!   def parse(_init_data), do: :erlang.nif_error(:nif_not_loaded)
+//! end
+//! ```
+//!
+//! Init data is returned as maps with atom keys, e.g. `{:ok, %{user: %{id: 279058397, ...}, ...}}`, and errors as
+//! `{:error, kind}` tagged with the [`InitDataError::kind`] atom, e.g. `{:error, :hash_invalid}`.
+
+use rustler::{Atom, Env, NifMap, NifUnitEnum};
+
+use crate::InitDataError;
+
+#[derive(NifUnitEnum)]
+pub enum ChatType {
+    Sender,
+    Private,
+    Group,
+    Supergroup,
+    Channel,
+}
+
+#[derive(NifMap)]
+pub struct User {
+    pub added_to_attachment_menu: Option<bool>,
+    pub allows_write_to_pm: Option<bool>,
+    pub first_name: String,
+    pub id: i64,
+    pub is_bot: Option<bool>,
+    pub is_premium: Option<bool>,
+    pub last_name: Option<String>,
+    pub language_code: Option<String>,
+    pub photo_url: Option<String>,
+    pub username: Option<String>,
+}
+
+#[derive(NifMap)]
+pub struct Chat {
+    pub id: i64,
+    pub photo_url: Option<String>,
+    #[rustler(rename = "type")]
+    pub chat_type: ChatType,
+    pub title: String,
+    pub username: Option<String>,
+}
+
+#[derive(NifMap)]
+pub struct InitData {
+    pub auth_date: u64,
+    pub can_send_after: Option<u32>,
+    pub chat: Option<Chat>,
+    pub chat_type: Option<ChatType>,
+    pub chat_instance: Option<i64>,
+    pub hash: String,
+    pub query_id: Option<String>,
+    pub receiver: Option<User>,
+    pub start_param: Option<String>,
+    pub user: Option<User>,
+    pub signature: Option<String>,
+}
+
+impl From<crate::ChatType> for ChatType {
+    fn from(chat_type: crate::ChatType) -> Self {
+        match chat_type {
+            crate::ChatType::Sender => Self::Sender,
+            crate::ChatType::Private => Self::Private,
+            crate::ChatType::Group => Self::Group,
+            crate::ChatType::Supergroup => Self::Supergroup,
+            crate::ChatType::Channel => Self::Channel,
+        }
+    }
+}
+
+impl From<crate::User> for User {
+    fn from(user: crate::User) -> Self {
+        Self {
+            added_to_attachment_menu: user.added_to_attachment_menu,
+            allows_write_to_pm: user.allows_write_to_pm,
+            first_name: user.first_name,
+            id: user.id,
+            is_bot: user.is_bot,
+            is_premium: user.is_premium,
+            last_name: user.last_name,
+            language_code: user.language_code,
+            photo_url: user.photo_url,
+            username: user.username,
+        }
+    }
+}
+
+impl From<crate::Chat> for Chat {
+    fn from(chat: crate::Chat) -> Self {
+        Self {
+            id: chat.id,
+            photo_url: chat.photo_url,
+            chat_type: chat.chat_type.into(),
+            title: chat.title,
+            username: chat.username,
+        }
+    }
+}
+
+impl From<crate::InitData> for InitData {
+    fn from(data: crate::InitData) -> Self {
+        Self {
+            auth_date: data.auth_date,
+            can_send_after: data.can_send_after,
+            chat: data.chat.map(Into::into),
+            chat_type: data.chat_type.map(Into::into),
+            chat_instance: data.chat_instance,
+            hash: data.hash,
+            query_id: data.query_id,
+            receiver: data.receiver.map(Into::into),
+            start_param: data.start_param,
+            user: data.user.map(Into::into),
+            signature: data.signature,
+        }
+    }
+}
+
+#[allow(clippy::needless_pass_by_value)]
+fn to_atom(env: Env<'_>, error: InitDataError) -> Atom {
+    Atom::from_str(env, error.kind()).expect("error kinds are valid atoms")
+}
+
+/// Validates init data using the bot token, see [`crate::validate`]. `expires_in` is in seconds, `nil` for
+/// [`crate::Expiration::Default`] and `0` for [`crate::Expiration::Never`].
+///
+/// # Errors
+///
+/// Returns `{:error, kind}` with the [`InitDataError::kind`] of the error.
+#[rustler::nif]
+pub fn validate(env: Env<'_>, init_data: &str, token: &str, expires_in: Option<u64>) -> Result<InitData, Atom> {
+    crate::validate(init_data, token, crate::Expiration::from_seconds(expires_in))
+        .map(Into::into)
+        .map_err(|error| to_atom(env, error))
+}
+
+/// Parses init data without validating it, see [`crate::parse`].
+///
+/// # Errors
+///
+/// Returns `{:error, kind}` with the [`InitDataError::kind`] of the error.
+#[rustler::nif]
+pub fn parse(env: Env<'_>, init_data: &str) -> Result<InitData, Atom> {
+    crate::parse(init_data)
+        .map(Into::into)
+        .map_err(|error| to_atom(env, error))
+}
+
+rustler::init!("Elixir.InitData");
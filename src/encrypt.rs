@@ -0,0 +1,217 @@
+//! Init data encrypted at rest, for cookies and session stores.
+//!
+//! The `hash` of init data is a bearer credential: anyone reading it from a store can replay the init data
+//! until it expires. [`seal`] encrypts the raw init data with XChaCha20-Poly1305 under a random nonce, and
+//! [`open`] decrypts it and rejects blobs that were modified or sealed with another key.
+//!
+//! A blob is the unpadded URL-safe base64 of a version byte, the 24 bytes nonce, the ciphertext and the tag,
+//! so it fits in a cookie as is.
+//!
+//! ```
+//! use init_data_rs::encrypt::{open, seal, SealingKey};
+//!
+//! let key = SealingKey::generate();
+//! let blob = seal("query_id=123&auth_date=1662771648&hash=...", &key).unwrap();
+//!
+//! assert_eq!(open(&blob, &key).unwrap(), "query_id=123&auth_date=1662771648&hash=...");
+//! ```
+
+use std::fmt;
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD as base64_engine;
+use base64::Engine as _;
+use chacha20poly1305::aead::{Aead, Generate, Payload};
+use chacha20poly1305::{KeyInit, XChaCha20Poly1305, XNonce};
+
+use crate::error::InitDataError;
+#[cfg(feature = "json")]
+use crate::model::InitData;
+#[cfg(feature = "json")]
+use crate::scheme::ValidationScheme;
+#[cfg(feature = "json")]
+use crate::stored::StoredInitData;
+
+/// Version of the blob format, the first byte of every blob.
+const VERSION: u8 = 1;
+const NONCE_LEN: usize = 24;
+/// Associated data of raw init data, so that a blob can't be opened as another kind of content.
+const RAW: &[u8] = b"init-data-rs/raw";
+/// Associated data of a storage envelope, see [`InitData::to_stored_bytes`].
+#[cfg(feature = "json")]
+const STORED: &[u8] = b"init-data-rs/stored";
+
+/// A 256-bit key for [`seal`] and [`open`].
+///
+/// It is redacted from `Debug` output. With the `zeroize` feature it is wiped from memory on drop.
+#[derive(Clone, PartialEq, Eq)]
+pub struct SealingKey([u8; 32]);
+
+impl SealingKey {
+    /// Uses `key`, e.g. read from a secret manager.
+    #[must_use]
+    pub fn new(key: [u8; 32]) -> Self {
+        Self(key)
+    }
+
+    /// Creates a random key from the OS random number generator.
+    #[must_use]
+    pub fn generate() -> Self {
+        Self(<[u8; 32]>::generate())
+    }
+
+    /// Returns the raw key bytes.
+    #[must_use]
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+
+    fn cipher(&self) -> XChaCha20Poly1305 {
+        XChaCha20Poly1305::new(&self.0.into())
+    }
+}
+
+impl fmt::Debug for SealingKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SealingKey(***)")
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl zeroize::Zeroize for SealingKey {
+    fn zeroize(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl Drop for SealingKey {
+    fn drop(&mut self) {
+        zeroize::Zeroize::zeroize(self);
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl zeroize::ZeroizeOnDrop for SealingKey {}
+
+/// Encrypts raw `init_data` with `key`.
+///
+/// # Errors
+///
+/// Returns [`InitDataError::Internal`] if encryption fails, which only happens for inputs of several gigabytes.
+pub fn seal(init_data: &str, key: &SealingKey) -> Result<String, InitDataError> {
+    seal_bytes(init_data.as_bytes(), RAW, key)
+}
+
+/// Decrypts a blob sealed by [`seal`] with `key`.
+///
+/// # Errors
+///
+/// Returns [`InitDataError::UnexpectedFormat`] if the blob is malformed, was modified or sealed with another key.
+pub fn open(blob: &str, key: &SealingKey) -> Result<String, InitDataError> {
+    String::from_utf8(open_bytes(blob, RAW, key)?)
+        .map_err(|_| InitDataError::UnexpectedFormat("sealed init data is not UTF-8".to_string()))
+}
+
+/// Encrypts validated init data with `key`, in the envelope of [`InitData::to_stored_bytes`].
+///
+/// # Errors
+///
+/// See [`seal`] and [`InitData::to_stored_bytes`].
+#[cfg(feature = "json")]
+pub fn seal_data(
+    data: &InitData,
+    scheme: ValidationScheme,
+    validated_at: u64,
+    key: &SealingKey,
+) -> Result<String, InitDataError> {
+    seal_bytes(&data.to_stored_bytes(scheme, validated_at)?, STORED, key)
+}
+
+/// Decrypts init data sealed by [`seal_data`] with `key`.
+///
+/// # Errors
+///
+/// See [`open`] and [`InitData::from_stored_bytes`].
+#[cfg(feature = "json")]
+pub fn open_data(blob: &str, key: &SealingKey) -> Result<StoredInitData, InitDataError> {
+    InitData::from_stored_bytes(&open_bytes(blob, STORED, key)?)
+}
+
+fn seal_bytes(plaintext: &[u8], aad: &[u8], key: &SealingKey) -> Result<String, InitDataError> {
+    let nonce = XNonce::generate();
+    let ciphertext = key
+        .cipher()
+        .encrypt(&nonce, Payload { msg: plaintext, aad })
+        .map_err(|_| InitDataError::Internal("init data is too large to seal".to_string()))?;
+
+    let mut blob = Vec::with_capacity(1 + NONCE_LEN + ciphertext.len());
+    blob.push(VERSION);
+    blob.extend_from_slice(&nonce);
+    blob.extend_from_slice(&ciphertext);
+
+    Ok(base64_engine.encode(blob))
+}
+
+fn open_bytes(blob: &str, aad: &[u8], key: &SealingKey) -> Result<Vec<u8>, InitDataError> {
+    let malformed = || InitDataError::UnexpectedFormat("sealed init data is malformed or was modified".to_string());
+
+    let blob = base64_engine.decode(blob).map_err(|_| malformed())?;
+    let Some((&version, rest)) = blob.split_first() else {
+        return Err(malformed());
+    };
+    if version != VERSION {
+        return Err(InitDataError::UnexpectedFormat(format!(
+            "sealed init data has unsupported version {version}"
+        )));
+    }
+    if rest.len() < NONCE_LEN {
+        return Err(malformed());
+    }
+    let (nonce, ciphertext) = rest.split_at(NONCE_LEN);
+    let nonce = XNonce::try_from(nonce).map_err(|_| malformed())?;
+
+    key.cipher()
+        .decrypt(&nonce, Payload { msg: ciphertext, aad })
+        .map_err(|_| malformed())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INIT_DATA: &str =
+        "query_id=AAHdF6IQAAAAAN0XohDhrOrc&auth_date=1662771648&hash=c501b71e775f74ce10e377dea85a7ea24ecd640b223ea86dfe453e0eaed2e2b2";
+
+    #[test]
+    fn test_seal_open() {
+        let key = SealingKey::new([7; 32]);
+        let blob = seal(INIT_DATA, &key).unwrap();
+
+        assert!(!blob.contains("c501b71e"));
+        assert_ne!(seal(INIT_DATA, &key).unwrap(), blob, "nonces are random");
+        assert_eq!(open(&blob, &key).unwrap(), INIT_DATA);
+
+        assert!(open(&blob, &SealingKey::new([8; 32])).is_err());
+        let mut tampered = base64_engine.decode(&blob).unwrap();
+        *tampered.last_mut().unwrap() ^= 1;
+        assert!(open(&base64_engine.encode(tampered), &key).is_err());
+        assert!(open("", &key).is_err());
+        assert!(open("AQ", &key).is_err());
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_seal_data() {
+        let key = SealingKey::generate();
+        let data = crate::parse(INIT_DATA).unwrap();
+        let blob = seal_data(&data, ValidationScheme::BotTokenHmac, 1_662_771_700, &key).unwrap();
+
+        let stored = open_data(&blob, &key).unwrap();
+        assert_eq!(stored.data.query_id, data.query_id);
+        assert_eq!(stored.validated_at, 1_662_771_700);
+
+        // Raw and model blobs can't be mixed up
+        assert!(open(&blob, &key).is_err());
+        assert!(open_data(&seal(INIT_DATA, &key).unwrap(), &key).is_err());
+    }
+}
@@ -1,6 +1,6 @@
 use thiserror::Error;
 
-#[derive(Error, Debug)]
+#[derive(Error, Debug, Clone)]
 pub enum InitDataError {
     #[error("auth_date is missing")]
     AuthDateMissing,
@@ -25,4 +25,65 @@ pub enum InitDataError {
 
     #[error("signature is invalid: {0}")]
     SignatureInvalid(String),
+
+    #[error("too many failed validations, try again later")]
+    Throttled,
+
+    #[error("init data is too large: {0}")]
+    PayloadTooLarge(String),
+
+    #[error("invalid configuration: {0}")]
+    Config(String),
+
+    #[error("no init data was received before the deadline")]
+    DeadlineExceeded,
+
+    #[error("user is missing")]
+    UserMissing,
+
+    #[error("chat is missing")]
+    ChatMissing,
+
+    #[error("client is not allowed: {0}")]
+    ClientNotAllowed(String),
+
+    #[error("start_param is invalid: {0}")]
+    StartParamInvalid(String),
+
+    #[error("auth_date is not a unix time in seconds: {value:?}")]
+    AuthDateInvalid { value: String },
+
+    #[error("user is not allowed: {0}")]
+    UserDenied(String),
+
+    #[error("chat is not allowed: {0}")]
+    ChatNotAllowed(String),
+}
+
+impl InitDataError {
+    /// Stable `snake_case` name of the variant, e.g. as a metrics label.
+    #[must_use]
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::AuthDateMissing => "auth_date_missing",
+            Self::HashMissing => "hash_missing",
+            Self::HashInvalid => "hash_invalid",
+            Self::UnexpectedFormat(_) => "unexpected_format",
+            Self::Expired => "expired",
+            Self::Internal(_) => "internal",
+            Self::SignatureMissing => "signature_missing",
+            Self::SignatureInvalid(_) => "signature_invalid",
+            Self::Throttled => "throttled",
+            Self::PayloadTooLarge(_) => "payload_too_large",
+            Self::Config(_) => "config",
+            Self::DeadlineExceeded => "deadline_exceeded",
+            Self::UserMissing => "user_missing",
+            Self::ChatMissing => "chat_missing",
+            Self::ClientNotAllowed(_) => "client_not_allowed",
+            Self::StartParamInvalid(_) => "start_param_invalid",
+            Self::AuthDateInvalid { .. } => "auth_date_invalid",
+            Self::UserDenied(_) => "user_denied",
+            Self::ChatNotAllowed(_) => "chat_not_allowed",
+        }
+    }
 }
@@ -0,0 +1,237 @@
+//! How long init data stays valid after its `auth_date`.
+
+use std::time::Duration;
+
+#[cfg(feature = "model")]
+use crate::model::{ChatType, InitData};
+
+/// How long init data stays valid after its `auth_date`.
+///
+/// The validation functions take `impl Into<Expiration>`, a `Duration` converting into
+/// [`Expiration::After`]. They used to take `expires_in: Option<u64>` seconds, which
+/// [`Expiration::from_expires_in`] converts while migrating.
+///
+/// # Example
+/// ```
+/// use std::time::Duration;
+/// use init_data_rs::{verify, Expiration};
+///
+/// let init_data = "query_id=123&auth_date=1662771648&hash=...";
+/// let result = verify(init_data, "BOT_TOKEN", Expiration::After(Duration::from_secs(3600)));
+/// assert_eq!(Expiration::from(Duration::from_secs(60)), Expiration::After(Duration::from_secs(60)));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Expiration {
+    /// Init data never expires, only its hash or signature is checked.
+    Never,
+    /// Init data expires once it is older than the duration, rounded down to whole seconds.
+    After(Duration),
+    /// Init data expires after [`Expiration::DEFAULT_TTL`].
+    #[default]
+    Default,
+}
+
+impl Expiration {
+    /// Lifetime of init data when none is given: 24 hours.
+    pub const DEFAULT_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+    /// How long init data stays valid, `None` if it never expires.
+    #[must_use]
+    pub fn ttl(self) -> Option<Duration> {
+        match self {
+            Self::Never => None,
+            Self::After(ttl) => Some(ttl),
+            Self::Default => Some(Self::DEFAULT_TTL),
+        }
+    }
+
+    /// Whether init data issued at `auth_date` has expired at `now`, both Unix times in seconds.
+    #[must_use]
+    pub fn is_expired(self, auth_date: u64, now: u64) -> bool {
+        self.overshoot(auth_date, now).is_some()
+    }
+
+    /// How long ago init data issued at `auth_date` expired at `now`, `None` if it hasn't.
+    #[must_use]
+    pub fn overshoot(self, auth_date: u64, now: u64) -> Option<Duration> {
+        let expires_at = auth_date.saturating_add(self.ttl()?.as_secs());
+
+        (expires_at < now).then(|| Duration::from_secs(now - expires_at))
+    }
+
+    /// Converts the former `expires_in` seconds: `None` is [`Expiration::Default`] and `Some(0)`, which used to
+    /// mean no expiration, is [`Expiration::Never`].
+    #[deprecated(note = "pass `Expiration::Default`, `Expiration::Never` or `Expiration::After` instead")]
+    #[must_use]
+    pub fn from_expires_in(expires_in: Option<u64>) -> Self {
+        Self::from_seconds(expires_in)
+    }
+
+    /// The expiration of the bindings, which take seconds since they have no [`Expiration`] to pass: `None` is
+    /// [`Expiration::Default`] and `Some(0)` is [`Expiration::Never`].
+    pub(crate) fn from_seconds(seconds: Option<u64>) -> Self {
+        match seconds {
+            None => Self::Default,
+            Some(0) => Self::Never,
+            Some(seconds) => Self::After(Duration::from_secs(seconds)),
+        }
+    }
+}
+
+impl From<Duration> for Expiration {
+    fn from(ttl: Duration) -> Self {
+        Self::After(ttl)
+    }
+}
+
+/// Whether init data accepted within a grace period had expired, see [`crate::validate_graceful`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Freshness {
+    /// The init data has not expired.
+    Fresh,
+    /// The init data expired `overshoot` ago, but within the grace period.
+    Stale {
+        /// Time elapsed since the init data expired.
+        overshoot: Duration,
+    },
+}
+
+impl Freshness {
+    /// Whether the init data had expired, e.g. to only allow read-only access.
+    #[must_use]
+    pub fn is_stale(self) -> bool {
+        matches!(self, Self::Stale { .. })
+    }
+}
+
+/// Expiration that differs by the chat the Mini App was opened from, e.g. 10 minutes for groups and
+/// channels but 24 hours for private chats.
+///
+/// The chat type is read from `chat_type`, or from the `chat` of attachment menu launches. Init data
+/// without either gets the default expiration.
+///
+/// # Example
+/// ```
+/// use std::time::Duration;
+/// use init_data_rs::{ChatType, Expiration, ExpirationPolicy, Validator};
+///
+/// let short = Expiration::After(Duration::from_secs(600));
+/// let policy = ExpirationPolicy::new(Expiration::Default)
+///     .chat_type(ChatType::Group, short)
+///     .chat_type(ChatType::Supergroup, short)
+///     .chat_type(ChatType::Channel, short);
+/// let validator = Validator::new("BOT_TOKEN").expiration_policy(policy);
+/// ```
+#[cfg(feature = "model")]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ExpirationPolicy {
+    default: Expiration,
+    chat_types: Vec<(ChatType, Expiration)>,
+}
+
+#[cfg(feature = "model")]
+impl ExpirationPolicy {
+    /// Creates a policy applying `default` to every chat type.
+    #[must_use]
+    pub fn new(default: Expiration) -> Self {
+        Self {
+            default,
+            chat_types: Vec::new(),
+        }
+    }
+
+    /// Applies `expiration` to init data opened from a chat of `chat_type`, replacing an earlier one.
+    #[must_use]
+    pub fn chat_type(mut self, chat_type: ChatType, expiration: Expiration) -> Self {
+        self.chat_types.retain(|(other, _)| *other != chat_type);
+        self.chat_types.push((chat_type, expiration));
+        self
+    }
+
+    /// The expiration that applies to `data`.
+    #[must_use]
+    pub fn expiration_for(&self, data: &InitData) -> Expiration {
+        let chat_type = data
+            .chat_type
+            .as_ref()
+            .or(data.chat.as_ref().map(|chat| &chat.chat_type));
+
+        chat_type
+            .and_then(|chat_type| self.chat_types.iter().find(|(other, _)| other == chat_type))
+            .map_or(self.default, |(_, expiration)| *expiration)
+    }
+
+    /// The expiration to check before parsing: the default if it applies to every chat type, otherwise
+    /// none, the actual one being checked by [`ExpirationPolicy::is_expired`] once the chat type is known.
+    pub(crate) fn before_parsing(&self) -> Expiration {
+        if self.chat_types.is_empty() {
+            self.default
+        } else {
+            Expiration::Never
+        }
+    }
+
+    /// Whether `data` has expired at `now` under this policy.
+    pub(crate) fn is_expired(&self, data: &InitData, now: u64) -> bool {
+        !self.chat_types.is_empty() && self.expiration_for(data).is_expired(data.auth_date, now)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_expiration_from_seconds() {
+        assert_eq!(Expiration::from_seconds(None), Expiration::Default);
+        assert_eq!(Expiration::from_seconds(Some(0)), Expiration::Never);
+        assert_eq!(
+            Expiration::from_seconds(Some(60)),
+            Expiration::After(Duration::from_secs(60))
+        );
+        assert_eq!(Expiration::from_expires_in(Some(0)), Expiration::Never);
+    }
+
+    #[test]
+    fn test_expiration_is_expired() {
+        assert!(!Expiration::Never.is_expired(0, u64::MAX));
+        assert!(!Expiration::Default.is_expired(1000, 1000 + 86400));
+        assert!(Expiration::Default.is_expired(1000, 1000 + 86401));
+        assert!(!Expiration::After(Duration::from_secs(30)).is_expired(1000, 1030));
+        assert!(Expiration::After(Duration::from_secs(30)).is_expired(1000, 1031));
+        assert!(!Expiration::After(Duration::MAX).is_expired(u64::MAX, u64::MAX));
+        assert_eq!(
+            Expiration::After(Duration::from_secs(30)).overshoot(1000, 1045),
+            Some(Duration::from_secs(15))
+        );
+    }
+
+    #[cfg(feature = "model")]
+    #[test]
+    fn test_expiration_policy() {
+        let short = Expiration::After(Duration::from_secs(600));
+        let policy = ExpirationPolicy::new(Expiration::Never).chat_type(ChatType::Group, short);
+        let data = |fields: &str| {
+            crate::parse(&format!(
+                "{fields}auth_date=1000&hash=c501b71e775f74ce10e377dea85a7ea24ecd640b223ea86dfe453e0eaed2e2b2"
+            ))
+            .unwrap()
+        };
+
+        assert_eq!(policy.expiration_for(&data("chat_type=group&")), short);
+        assert_eq!(
+            policy.expiration_for(&data(
+                "chat=%7B%22id%22%3A-1%2C%22type%22%3A%22group%22%2C%22title%22%3A%22T%22%7D&"
+            )),
+            short
+        );
+        assert_eq!(policy.expiration_for(&data("chat_type=private&")), Expiration::Never);
+        assert_eq!(policy.expiration_for(&data("")), Expiration::Never);
+
+        assert!(policy.is_expired(&data("chat_type=group&"), 1601));
+        assert!(!policy.is_expired(&data("chat_type=sender&"), 1601));
+        assert_eq!(policy.before_parsing(), Expiration::Never);
+        assert_eq!(ExpirationPolicy::new(short).before_parsing(), short);
+    }
+}
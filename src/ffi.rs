@@ -0,0 +1,395 @@
+//! Stable C ABI for linking the library from other languages (PHP FFI, Go via cgo, C/C++).
+//!
+//! Enabled with the `ffi` feature. The crate is built as an `rlib`, so ask for the shared library
+//! explicitly; the matching header is generated with `cbindgen` into `include/init_data.h`:
+//!
+//! ```sh
+//! cargo rustc --lib --release --features ffi --crate-type cdylib
+//! cbindgen --config cbindgen.toml --output include/init_data.h
+//! ```
+//!
+//! All functions take NUL-terminated UTF-8 strings and report the outcome as an
+//! [`InitDataErrorCode`]. Strings returned through out-parameters are owned by the caller and
+//! must be released with [`init_data_string_free`].
+
+use std::ffi::{c_char, c_int, CStr, CString};
+
+use crate::{Expiration, InitDataError};
+
+/// `expires_in` selecting the default expiration of 24 hours, [`Expiration::Default`]. Any negative value does.
+pub const INIT_DATA_EXPIRATION_DEFAULT: i64 = -1;
+
+/// `expires_in` disabling the expiration check, [`Expiration::Never`].
+pub const INIT_DATA_EXPIRATION_NEVER: i64 = 0;
+
+/// Outcome of an FFI call.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InitDataErrorCode {
+    Ok = 0,
+    AuthDateMissing = 1,
+    HashMissing = 2,
+    HashInvalid = 3,
+    UnexpectedFormat = 4,
+    Expired = 5,
+    Internal = 6,
+    SignatureMissing = 7,
+    SignatureInvalid = 8,
+    Throttled = 9,
+    PayloadTooLarge = 10,
+    Config = 11,
+    DeadlineExceeded = 12,
+    UserMissing = 13,
+    ChatMissing = 14,
+    ClientNotAllowed = 15,
+    StartParamInvalid = 16,
+    AuthDateInvalid = 17,
+    UserDenied = 18,
+    ChatNotAllowed = 19,
+    /// A pointer argument was null or a string argument was not valid UTF-8.
+    InvalidArgument = 100,
+}
+
+impl From<&InitDataError> for InitDataErrorCode {
+    fn from(error: &InitDataError) -> Self {
+        match error {
+            InitDataError::AuthDateMissing => Self::AuthDateMissing,
+            InitDataError::HashMissing => Self::HashMissing,
+            InitDataError::HashInvalid => Self::HashInvalid,
+            InitDataError::UnexpectedFormat(_) => Self::UnexpectedFormat,
+            InitDataError::Expired => Self::Expired,
+            InitDataError::Internal(_) => Self::Internal,
+            InitDataError::SignatureMissing => Self::SignatureMissing,
+            InitDataError::SignatureInvalid(_) => Self::SignatureInvalid,
+            InitDataError::Throttled => Self::Throttled,
+            InitDataError::PayloadTooLarge(_) => Self::PayloadTooLarge,
+            InitDataError::Config(_) => Self::Config,
+            InitDataError::DeadlineExceeded => Self::DeadlineExceeded,
+            InitDataError::UserMissing => Self::UserMissing,
+            InitDataError::ChatMissing => Self::ChatMissing,
+            InitDataError::ClientNotAllowed(_) => Self::ClientNotAllowed,
+            InitDataError::StartParamInvalid(_) => Self::StartParamInvalid,
+            InitDataError::AuthDateInvalid { .. } => Self::AuthDateInvalid,
+            InitDataError::UserDenied(_) => Self::UserDenied,
+            InitDataError::ChatNotAllowed(_) => Self::ChatNotAllowed,
+        }
+    }
+}
+
+impl TryFrom<c_int> for InitDataErrorCode {
+    type Error = c_int;
+
+    /// Reads an error code passed by C, which may be any `int`, returning it back if it is unknown.
+    fn try_from(code: c_int) -> Result<Self, Self::Error> {
+        Ok(match code {
+            0 => Self::Ok,
+            1 => Self::AuthDateMissing,
+            2 => Self::HashMissing,
+            3 => Self::HashInvalid,
+            4 => Self::UnexpectedFormat,
+            5 => Self::Expired,
+            6 => Self::Internal,
+            7 => Self::SignatureMissing,
+            8 => Self::SignatureInvalid,
+            9 => Self::Throttled,
+            10 => Self::PayloadTooLarge,
+            11 => Self::Config,
+            12 => Self::DeadlineExceeded,
+            13 => Self::UserMissing,
+            14 => Self::ChatMissing,
+            15 => Self::ClientNotAllowed,
+            16 => Self::StartParamInvalid,
+            17 => Self::AuthDateInvalid,
+            18 => Self::UserDenied,
+            19 => Self::ChatNotAllowed,
+            100 => Self::InvalidArgument,
+            _ => return Err(code),
+        })
+    }
+}
+
+/// Converts the C expiration argument into an [`Expiration`]: [`INIT_DATA_EXPIRATION_DEFAULT`] (any negative
+/// value), [`INIT_DATA_EXPIRATION_NEVER`] or a number of seconds.
+fn expiration_from_c(expires_in: i64) -> Expiration {
+    Expiration::from_seconds(u64::try_from(expires_in).ok())
+}
+
+/// # Safety
+///
+/// `value` must be null or point to a valid NUL-terminated string.
+unsafe fn str_from_c<'a>(value: *const c_char) -> Result<&'a str, InitDataErrorCode> {
+    if value.is_null() {
+        return Err(InitDataErrorCode::InvalidArgument);
+    }
+
+    CStr::from_ptr(value)
+        .to_str()
+        .map_err(|_| InitDataErrorCode::InvalidArgument)
+}
+
+/// # Safety
+///
+/// `out` must be null or valid for writes.
+unsafe fn write_string(out: *mut *mut c_char, value: String) -> InitDataErrorCode {
+    if out.is_null() {
+        return InitDataErrorCode::InvalidArgument;
+    }
+
+    match CString::new(value) {
+        Ok(value) => {
+            *out = value.into_raw();
+            InitDataErrorCode::Ok
+        }
+        Err(_) => InitDataErrorCode::Internal,
+    }
+}
+
+fn to_json(data: &crate::InitData) -> Result<String, InitDataErrorCode> {
+    serde_json::to_string(data).map_err(|_| InitDataErrorCode::Internal)
+}
+
+/// Validates init data using the bot token.
+///
+/// `expires_in` is how long the init data stays valid in seconds, [`INIT_DATA_EXPIRATION_DEFAULT`] for
+/// the default of 24 hours or [`INIT_DATA_EXPIRATION_NEVER`] to skip the expiration check.
+///
+/// # Safety
+///
+/// `init_data` and `token` must be null or point to valid NUL-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn init_data_validate(
+    init_data: *const c_char,
+    token: *const c_char,
+    expires_in: i64,
+) -> InitDataErrorCode {
+    let (init_data, token) = match (str_from_c(init_data), str_from_c(token)) {
+        (Ok(init_data), Ok(token)) => (init_data, token),
+        (Err(code), _) | (_, Err(code)) => return code,
+    };
+
+    match crate::validate(init_data, token, expiration_from_c(expires_in)) {
+        Ok(_) => InitDataErrorCode::Ok,
+        Err(error) => (&error).into(),
+    }
+}
+
+/// Validates init data for third-party use with the bot id.
+///
+/// `expires_in` follows the same convention as in [`init_data_validate`].
+///
+/// # Safety
+///
+/// `init_data` must be null or point to a valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn init_data_validate_third_party(
+    init_data: *const c_char,
+    bot_id: i64,
+    expires_in: i64,
+) -> InitDataErrorCode {
+    let init_data = match str_from_c(init_data) {
+        Ok(init_data) => init_data,
+        Err(code) => return code,
+    };
+
+    match crate::validate_third_party(init_data, bot_id, expiration_from_c(expires_in)) {
+        Ok(_) => InitDataErrorCode::Ok,
+        Err(error) => (&error).into(),
+    }
+}
+
+/// Validates init data using the bot token and writes the parsed data as JSON to `out_json`.
+///
+/// On success `*out_json` must be released with [`init_data_string_free`]; on failure it is left
+/// untouched.
+///
+/// # Safety
+///
+/// `init_data` and `token` must be null or point to valid NUL-terminated strings, `out_json` must
+/// be null or valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn init_data_validate_json(
+    init_data: *const c_char,
+    token: *const c_char,
+    expires_in: i64,
+    out_json: *mut *mut c_char,
+) -> InitDataErrorCode {
+    let (init_data, token) = match (str_from_c(init_data), str_from_c(token)) {
+        (Ok(init_data), Ok(token)) => (init_data, token),
+        (Err(code), _) | (_, Err(code)) => return code,
+    };
+
+    match crate::validate(init_data, token, expiration_from_c(expires_in)) {
+        Ok(data) => match to_json(&data) {
+            Ok(json) => write_string(out_json, json),
+            Err(code) => code,
+        },
+        Err(error) => (&error).into(),
+    }
+}
+
+/// Parses init data without validating it and writes the result as JSON to `out_json`.
+///
+/// On success `*out_json` must be released with [`init_data_string_free`]; on failure it is left
+/// untouched.
+///
+/// # Safety
+///
+/// `init_data` must be null or point to a valid NUL-terminated string, `out_json` must be null or
+/// valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn init_data_parse_json(
+    init_data: *const c_char,
+    out_json: *mut *mut c_char,
+) -> InitDataErrorCode {
+    let init_data = match str_from_c(init_data) {
+        Ok(init_data) => init_data,
+        Err(code) => return code,
+    };
+
+    match crate::parse(init_data) {
+        Ok(data) => match to_json(&data) {
+            Ok(json) => write_string(out_json, json),
+            Err(code) => code,
+        },
+        Err(error) => (&error).into(),
+    }
+}
+
+/// Computes the hash of init data using the bot token and writes it as hex to `out_hash`.
+///
+/// On success `*out_hash` must be released with [`init_data_string_free`]; on failure it is left
+/// untouched.
+///
+/// # Safety
+///
+/// `init_data` and `token` must be null or point to valid NUL-terminated strings, `out_hash` must
+/// be null or valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn init_data_sign(
+    init_data: *const c_char,
+    token: *const c_char,
+    out_hash: *mut *mut c_char,
+) -> InitDataErrorCode {
+    let (init_data, token) = match (str_from_c(init_data), str_from_c(token)) {
+        (Ok(init_data), Ok(token)) => (init_data, token),
+        (Err(code), _) | (_, Err(code)) => return code,
+    };
+
+    match crate::sign(init_data, token) {
+        Ok(hash) => write_string(out_hash, hash),
+        Err(error) => (&error).into(),
+    }
+}
+
+/// Releases a string previously returned by this library. Passing null is a no-op.
+///
+/// # Safety
+///
+/// `value` must be null or a pointer returned by this library that has not been freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn init_data_string_free(value: *mut c_char) {
+    if !value.is_null() {
+        drop(CString::from_raw(value));
+    }
+}
+
+/// Returns a static, NUL-terminated description of an error code, or of an unknown one. The result must not be
+/// freed.
+///
+/// `code` is an `int` rather than an [`InitDataErrorCode`], as C allows any value in an enum.
+#[no_mangle]
+pub extern "C" fn init_data_error_message(code: c_int) -> *const c_char {
+    let Ok(code) = InitDataErrorCode::try_from(code) else {
+        return c"unknown error code".as_ptr();
+    };
+    let message: &'static CStr = match code {
+        InitDataErrorCode::Ok => c"ok",
+        InitDataErrorCode::AuthDateMissing => c"auth_date is missing",
+        InitDataErrorCode::HashMissing => c"hash is missing",
+        InitDataErrorCode::HashInvalid => c"hash is invalid",
+        InitDataErrorCode::UnexpectedFormat => c"init data has unexpected format",
+        InitDataErrorCode::Expired => c"init data is expired",
+        InitDataErrorCode::Internal => c"internal library's error occurred",
+        InitDataErrorCode::SignatureMissing => c"signature is missing",
+        InitDataErrorCode::SignatureInvalid => c"signature is invalid",
+        InitDataErrorCode::Throttled => c"too many failed validations",
+        InitDataErrorCode::PayloadTooLarge => c"init data is too large",
+        InitDataErrorCode::Config => c"invalid configuration",
+        InitDataErrorCode::DeadlineExceeded => c"deadline exceeded",
+        InitDataErrorCode::UserMissing => c"user is missing",
+        InitDataErrorCode::ChatMissing => c"chat is missing",
+        InitDataErrorCode::ClientNotAllowed => c"client is not allowed",
+        InitDataErrorCode::StartParamInvalid => c"start_param is invalid",
+        InitDataErrorCode::AuthDateInvalid => c"auth_date is invalid",
+        InitDataErrorCode::UserDenied => c"user is not allowed",
+        InitDataErrorCode::ChatNotAllowed => c"chat is not allowed",
+        InitDataErrorCode::InvalidArgument => c"invalid argument",
+    };
+
+    message.as_ptr()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ptr;
+
+    const BOT_TOKEN: &str = "5768337691:AAH5YkoiEuPk8-FZa32hStHTqXiLPtAEhx8";
+    const VALID_INIT_DATA: &str = "query_id=AAHdF6IQAAAAAN0XohDhrOrc&user=%7B%22id%22%3A279058397%2C%22first_name%22%3A%22Vladislav%22%2C%22last_name%22%3A%22Kibenko%22%2C%22username%22%3A%22vdkfrost%22%2C%22language_code%22%3A%22ru%22%2C%22is_premium%22%3Atrue%7D&auth_date=1662771648&hash=c501b71e775f74ce10e377dea85a7ea24ecd640b223ea86dfe453e0eaed2e2b2";
+
+    #[test]
+    fn test_ffi_validate() {
+        let init_data = CString::new(VALID_INIT_DATA).unwrap();
+        let token = CString::new(BOT_TOKEN).unwrap();
+
+        let code = unsafe { init_data_validate(init_data.as_ptr(), token.as_ptr(), 0) };
+        assert_eq!(code, InitDataErrorCode::Ok);
+
+        let code = unsafe { init_data_validate(init_data.as_ptr(), token.as_ptr(), -1) };
+        assert_eq!(code, InitDataErrorCode::Expired);
+
+        let code = unsafe { init_data_validate(ptr::null(), token.as_ptr(), 0) };
+        assert_eq!(code, InitDataErrorCode::InvalidArgument);
+    }
+
+    #[test]
+    fn test_ffi_parse_json() {
+        let init_data = CString::new(VALID_INIT_DATA).unwrap();
+        let mut json: *mut c_char = ptr::null_mut();
+
+        let code = unsafe { init_data_parse_json(init_data.as_ptr(), &raw mut json) };
+        assert_eq!(code, InitDataErrorCode::Ok);
+
+        let value: serde_json::Value = serde_json::from_str(unsafe { CStr::from_ptr(json) }.to_str().unwrap()).unwrap();
+        assert_eq!(value["auth_date"], 1662771648);
+        assert_eq!(value["user"]["id"], 279058397);
+
+        unsafe { init_data_string_free(json) };
+    }
+
+    #[test]
+    fn test_ffi_error_message() {
+        let message = |code| {
+            unsafe { CStr::from_ptr(init_data_error_message(code)) }
+                .to_str()
+                .unwrap()
+        };
+
+        assert_eq!(message(InitDataErrorCode::HashInvalid as c_int), "hash is invalid");
+        assert_eq!(message(InitDataErrorCode::InvalidArgument as c_int), "invalid argument");
+        assert_eq!(message(20), "unknown error code");
+        assert_eq!(message(-1), "unknown error code");
+    }
+
+    #[test]
+    fn test_error_code_from_c_int() {
+        for code in [
+            InitDataErrorCode::Ok,
+            InitDataErrorCode::ChatNotAllowed,
+            InitDataErrorCode::InvalidArgument,
+        ] {
+            assert_eq!(InitDataErrorCode::try_from(code as c_int), Ok(code));
+        }
+        assert_eq!(InitDataErrorCode::try_from(99), Err(99));
+    }
+}
@@ -0,0 +1,116 @@
+//! Anonymized fingerprints of init data for log correlation and rate limiting.
+
+use std::fmt;
+
+use crate::crypto::Hmac;
+
+/// Produces stable, PII-free digests of init data.
+///
+/// Fingerprints are keyed with a salt, so they can't be reversed by hashing candidate payloads or user ids
+/// without knowing it. Use the same salt across instances to correlate logs between them.
+///
+/// # Example
+/// ```
+/// use init_data_rs::Fingerprinter;
+///
+/// let fingerprinter = Fingerprinter::new(b"per-deployment secret salt");
+/// let request_key = fingerprinter.fingerprint("query_id=123&auth_date=1662771648&hash=...");
+/// let user_key = fingerprinter.user_fingerprint(279058397);
+/// assert_eq!(request_key.len(), 32);
+/// assert_ne!(request_key, user_key);
+/// ```
+#[derive(Clone)]
+pub struct Fingerprinter {
+    hmac: Hmac,
+}
+
+impl Fingerprinter {
+    /// Creates a fingerprinter keyed with `salt`.
+    ///
+    /// # Panics
+    ///
+    /// Never: HMAC accepts keys of any length.
+    pub fn new(salt: impl AsRef<[u8]>) -> Self {
+        Self {
+            hmac: Hmac::new(salt.as_ref()).expect("HMAC accepts keys of any length"),
+        }
+    }
+
+    /// Digest of the raw init data, identical for replays of the same payload.
+    #[must_use]
+    pub fn fingerprint(&self, init_data: &str) -> String {
+        self.digest(b"payload", init_data.as_bytes())
+    }
+
+    /// Digest of a user id, identical for every payload of that user.
+    #[must_use]
+    pub fn user_fingerprint(&self, user_id: i64) -> String {
+        self.digest(b"user", &user_id.to_be_bytes())
+    }
+
+    /// Replaces a user or chat id by a salted pseudonym, identical for every payload with that id.
+    ///
+    /// The pseudonym is below 2^53, so that it stays exact in JavaScript, and negative for negative ids, so that
+    /// group and channel ids still stand out.
+    #[must_use]
+    pub fn pseudonymous_id(&self, id: i64) -> i64 {
+        let digest = self.mac(b"id", &id.to_be_bytes());
+        let mut bytes = [0; 8];
+        bytes.copy_from_slice(&digest[..8]);
+        let pseudonym = i64::from_be_bytes(bytes) & ((1 << 53) - 1);
+        if id < 0 {
+            -pseudonym
+        } else {
+            pseudonym
+        }
+    }
+
+    /// Truncated to 128 bits, which is plenty to tell payloads apart and keeps log lines short.
+    fn digest(&self, domain: &[u8], data: &[u8]) -> String {
+        hex::encode(&self.mac(domain, data)[..16])
+    }
+
+    fn mac(&self, domain: &[u8], data: &[u8]) -> [u8; 32] {
+        let mut hmac = self.hmac.clone();
+        hmac.update(domain);
+        hmac.update(b"\0");
+        hmac.update(data);
+
+        hmac.finalize().expect("HMAC-SHA256 of a message in memory can't fail")
+    }
+}
+
+impl fmt::Debug for Fingerprinter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Fingerprinter(***)")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fingerprint_is_stable_and_salted() {
+        let a = Fingerprinter::new("salt-a");
+        let b = Fingerprinter::new("salt-b");
+
+        assert_eq!(a.fingerprint("auth_date=1&hash=x"), a.fingerprint("auth_date=1&hash=x"));
+        assert_ne!(a.fingerprint("auth_date=1&hash=x"), a.fingerprint("auth_date=2&hash=x"));
+        assert_ne!(a.fingerprint("auth_date=1&hash=x"), b.fingerprint("auth_date=1&hash=x"));
+        assert_eq!(a.user_fingerprint(42), a.user_fingerprint(42));
+        assert_ne!(a.user_fingerprint(42), b.user_fingerprint(42));
+        assert!(!a.user_fingerprint(279058397).contains("279058397"));
+    }
+
+    #[test]
+    fn test_pseudonymous_id() {
+        let a = Fingerprinter::new("salt-a");
+
+        assert_eq!(a.pseudonymous_id(42), a.pseudonymous_id(42));
+        assert_ne!(a.pseudonymous_id(42), a.pseudonymous_id(43));
+        assert_ne!(a.pseudonymous_id(42), Fingerprinter::new("salt-b").pseudonymous_id(42));
+        assert!((0..1 << 53).contains(&a.pseudonymous_id(279058397)));
+        assert!(a.pseudonymous_id(-1_001_234_567_890) < 0);
+    }
+}
@@ -0,0 +1,137 @@
+//! Dart/Flutter API exposed through `flutter_rust_bridge`.
+//!
+//! Enabled with the `flutter` feature. The Dart bindings are generated from this module by the
+//! bridge code generator using `flutter_rust_bridge.yaml`, and load the library built with
+//! `--crate-type cdylib` (`staticlib` for iOS):
+//!
+//! ```sh
+//! flutter_rust_bridge_codegen generate
+//! cargo rustc --lib --release --features flutter --crate-type cdylib
+//! ```
+//!
+//! The model types are mirrored rather than duplicated, so Dart sees exactly the same fields as
+//! the Rust API.
+
+pub use flutter_rust_bridge::frb;
+
+pub use crate::{Chat, ChatType, InitData, InitDataError, User};
+
+#[frb(mirror(ChatType))]
+pub enum _ChatType {
+    Sender,
+    Private,
+    Group,
+    Supergroup,
+    Channel,
+}
+
+#[frb(mirror(User))]
+pub struct _User {
+    pub added_to_attachment_menu: Option<bool>,
+    pub allows_write_to_pm: Option<bool>,
+    pub first_name: String,
+    pub id: i64,
+    pub is_bot: Option<bool>,
+    pub is_premium: Option<bool>,
+    pub last_name: Option<String>,
+    pub language_code: Option<String>,
+    pub photo_url: Option<String>,
+    pub username: Option<String>,
+}
+
+#[frb(mirror(Chat))]
+pub struct _Chat {
+    pub id: i64,
+    pub photo_url: Option<String>,
+    pub chat_type: ChatType,
+    pub title: String,
+    pub username: Option<String>,
+}
+
+#[frb(mirror(InitData))]
+pub struct _InitData {
+    pub auth_date: u64,
+    pub can_send_after: Option<u32>,
+    pub chat: Option<Chat>,
+    pub chat_type: Option<ChatType>,
+    pub chat_instance: Option<i64>,
+    pub hash: String,
+    pub query_id: Option<String>,
+    pub receiver: Option<User>,
+    pub start_param: Option<String>,
+    pub user: Option<User>,
+    pub signature: Option<String>,
+}
+
+#[frb(mirror(InitDataError))]
+pub enum _InitDataError {
+    AuthDateMissing,
+    HashMissing,
+    HashInvalid,
+    UnexpectedFormat(String),
+    Expired,
+    Internal(String),
+    SignatureMissing,
+    SignatureInvalid(String),
+    Throttled,
+    PayloadTooLarge(String),
+    Config(String),
+    DeadlineExceeded,
+    UserMissing,
+    ChatMissing,
+    ClientNotAllowed(String),
+    StartParamInvalid(String),
+    AuthDateInvalid { value: String },
+    UserDenied(String),
+    ChatNotAllowed(String),
+}
+
+/// Validates init data using the bot token, see [`crate::validate`]. `expires_in` is in seconds, `null` for
+/// [`crate::Expiration::Default`] and `0` for [`crate::Expiration::Never`].
+///
+/// # Errors
+///
+/// See [`crate::validate`].
+#[frb(sync)]
+#[allow(clippy::needless_pass_by_value)]
+pub fn validate(init_data: String, token: String, expires_in: Option<u64>) -> Result<InitData, InitDataError> {
+    crate::validate(&init_data, &token, crate::Expiration::from_seconds(expires_in))
+}
+
+/// Validates init data for third-party use, see [`crate::validate_third_party`]. `expires_in` is the same as
+/// in [`validate`].
+///
+/// # Errors
+///
+/// See [`crate::validate_third_party`].
+#[frb(sync)]
+#[allow(clippy::needless_pass_by_value)]
+pub fn validate_third_party(
+    init_data: String,
+    bot_id: i64,
+    expires_in: Option<u64>,
+) -> Result<InitData, InitDataError> {
+    crate::validate_third_party(&init_data, bot_id, crate::Expiration::from_seconds(expires_in))
+}
+
+/// Parses init data without validating it, see [`crate::parse`].
+///
+/// # Errors
+///
+/// See [`crate::parse`].
+#[frb(sync)]
+#[allow(clippy::needless_pass_by_value)]
+pub fn parse(init_data: String) -> Result<InitData, InitDataError> {
+    crate::parse(&init_data)
+}
+
+/// Computes the hash of init data using the bot token, see [`crate::sign`].
+///
+/// # Errors
+///
+/// See [`crate::sign`].
+#[frb(sync)]
+#[allow(clippy::needless_pass_by_value)]
+pub fn sign(init_data: String, token: String) -> Result<String, InitDataError> {
+    crate::sign(&init_data, &token)
+}
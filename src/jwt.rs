@@ -0,0 +1,181 @@
+//! Session tokens (HS256 JWTs) issued in exchange for validated init data.
+//!
+//! Mini Apps usually validate init data once and then authenticate further requests with a session
+//! token. The token is signed with a key of the service, the bot token is not needed to verify it.
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD as base64_engine;
+use base64::Engine as _;
+
+use crate::clock::{Clock, SystemClock};
+use crate::crypto::Hmac;
+use crate::error::InitDataError;
+use crate::model::InitData;
+use crate::scheme::Verified;
+use crate::session::SessionClaims;
+
+/// `{"alg":"HS256","typ":"JWT"}`
+const HEADER: &str = "eyJhbGciOiJIUzI1NiIsInR5cCI6IkpXVCJ9";
+
+/// Issues a session token for validated init data, valid for `ttl` seconds from now.
+///
+/// # Example
+/// ```
+/// use init_data_rs::{issue_jwt, validate_auto, verify_jwt, Expiration};
+///
+/// let init_data = "user=%7B%22id%22%3A279058397%7D&auth_date=1662771648&hash=...";
+/// if let Ok(verified) = validate_auto(init_data, "BOT_TOKEN", Expiration::Default) {
+///     let token = issue_jwt(&verified, b"service key", 3600).unwrap();
+///     let claims = verify_jwt(&token, b"service key").unwrap();
+/// }
+/// ```
+///
+/// # Errors
+///
+/// Returns [`InitDataError::UnexpectedFormat`] if the init data has no user or `key` is empty.
+///
+/// # Panics
+///
+/// See [`crate::validate`].
+pub fn issue_jwt(verified: &Verified<InitData>, key: &[u8], ttl: u64) -> Result<String, InitDataError> {
+    issue_jwt_with_clock(verified, key, ttl, &SystemClock)
+}
+
+/// Same as [`issue_jwt`], but reads the current time from `clock`.
+///
+/// # Errors
+///
+/// See [`issue_jwt`].
+pub fn issue_jwt_with_clock(
+    verified: &Verified<InitData>,
+    key: &[u8],
+    ttl: u64,
+    clock: &impl Clock,
+) -> Result<String, InitDataError> {
+    let claims = SessionClaims::new_with_clock(verified, ttl, clock)?.to_json()?;
+
+    let mut token = format!("{HEADER}.{}", base64_engine.encode(claims));
+    let mac = mac(key, &token)?.finalize()?;
+    token.push('.');
+    token.push_str(&base64_engine.encode(mac));
+
+    Ok(token)
+}
+
+/// Checks the signature and the expiration of a session token issued by [`issue_jwt`] and decodes its claims.
+///
+/// Only HS256 is accepted, whatever algorithm the token header claims.
+///
+/// # Errors
+///
+/// Returns [`InitDataError::UnexpectedFormat`] if the token is malformed or `key` is empty,
+/// [`InitDataError::HashInvalid`] if it wasn't signed with `key` and [`InitDataError::Expired`]
+/// once it has expired.
+///
+/// # Panics
+///
+/// See [`crate::validate`].
+pub fn verify_jwt(token: &str, key: &[u8]) -> Result<SessionClaims, InitDataError> {
+    verify_jwt_with_clock(token, key, &SystemClock)
+}
+
+/// Same as [`verify_jwt`], but reads the current time from `clock`.
+///
+/// # Errors
+///
+/// See [`verify_jwt`].
+pub fn verify_jwt_with_clock(token: &str, key: &[u8], clock: &impl Clock) -> Result<SessionClaims, InitDataError> {
+    let malformed = || InitDataError::UnexpectedFormat("token is malformed".to_string());
+
+    let (signed, signature) = token.rsplit_once('.').ok_or_else(malformed)?;
+    let (header, claims) = signed.split_once('.').ok_or_else(malformed)?;
+
+    let header: serde_json::Value = base64_engine
+        .decode(header)
+        .ok()
+        .and_then(|header| serde_json::from_slice(&header).ok())
+        .ok_or_else(malformed)?;
+    if header.get("alg").and_then(serde_json::Value::as_str) != Some("HS256") {
+        return Err(InitDataError::UnexpectedFormat(
+            "token is not signed with HS256".to_string(),
+        ));
+    }
+
+    let mut signature_bytes = [0u8; 32];
+    let signature_len = base64_engine
+        .decode_slice(signature, &mut signature_bytes)
+        .map_err(|_| malformed())?;
+    if !mac(key, signed)?.verify(&signature_bytes[..signature_len])? {
+        return Err(InitDataError::HashInvalid);
+    }
+
+    let claims = base64_engine.decode(claims).map_err(|_| malformed())?;
+
+    SessionClaims::from_json(&claims, clock)
+}
+
+fn mac(key: &[u8], signed: &str) -> Result<Hmac, InitDataError> {
+    if key.is_empty() {
+        return Err(InitDataError::UnexpectedFormat("token key is empty".to_string()));
+    }
+
+    let mut mac = Hmac::new(key)?;
+    mac.update(signed.as_bytes());
+
+    Ok(mac)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse;
+    use crate::scheme::ValidationScheme;
+
+    const KEY: &[u8] = b"service key";
+
+    fn verified() -> Verified<InitData> {
+        let data = parse(
+            "user=%7B%22id%22%3A279058397%2C%22first_name%22%3A%22Vladislav%22%2C%22username%22%3A%22vdkfrost%22%2C%22is_premium%22%3Atrue%7D&auth_date=1662771648&hash=c501b71e775f74ce10e377dea85a7ea24ecd640b223ea86dfe453e0eaed2e2b2",
+        )
+        .unwrap();
+        Verified::new(data, ValidationScheme::BotTokenHmac)
+    }
+
+    #[test]
+    fn test_jwt_roundtrip() {
+        let token = issue_jwt_with_clock(&verified(), KEY, 60, &|| 1000).unwrap();
+        assert!(token.starts_with(HEADER));
+
+        let claims = verify_jwt_with_clock(&token, KEY, &|| 1060).unwrap();
+        assert_eq!(claims.user_id().unwrap(), 279058397);
+        assert_eq!(claims.username.as_deref(), Some("vdkfrost"));
+        assert!(claims.premium);
+        assert_eq!(claims.scheme, ValidationScheme::BotTokenHmac);
+        assert_eq!((claims.iat, claims.exp), (1000, 1060));
+
+        assert!(matches!(
+            verify_jwt_with_clock(&token, KEY, &|| 1061),
+            Err(InitDataError::Expired)
+        ));
+        assert!(matches!(
+            verify_jwt_with_clock(&token, b"other key", &|| 1000),
+            Err(InitDataError::HashInvalid)
+        ));
+    }
+
+    #[test]
+    fn test_jwt_rejects_other_algorithms() {
+        let token = issue_jwt_with_clock(&verified(), KEY, 60, &|| 1000).unwrap();
+        let (_, rest) = token.split_once('.').unwrap();
+        let (claims, _) = rest.split_once('.').unwrap();
+        let none = format!("{}.{claims}.", base64_engine.encode(r#"{"alg":"none","typ":"JWT"}"#));
+
+        assert!(matches!(
+            verify_jwt_with_clock(&none, KEY, &|| 1000),
+            Err(InitDataError::UnexpectedFormat(_))
+        ));
+        assert!(matches!(
+            verify_jwt_with_clock("a.b", KEY, &|| 1000),
+            Err(InitDataError::UnexpectedFormat(_))
+        ));
+    }
+}
@@ -0,0 +1,116 @@
+//! [`AsyncMacProvider`] backed by AWS KMS.
+
+use aws_sdk_kms::error::DisplayErrorContext;
+use aws_sdk_kms::operation::verify_mac::VerifyMacError;
+use aws_sdk_kms::primitives::Blob;
+use aws_sdk_kms::types::MacAlgorithmSpec;
+use aws_sdk_kms::Client;
+
+use crate::error::InitDataError;
+use crate::mac_provider::AsyncMacProvider;
+
+/// Computes the HMAC with an `HMAC_256` KMS key through `GenerateMac` and `VerifyMac`.
+///
+/// The key material must be the [`crate::BotSecret`] of the bot, imported into a key created with origin
+/// `EXTERNAL`, e.g. [`crate::BotSecret::as_bytes`] computed once on a trusted machine. The service then never
+/// sees the bot token and never holds it in memory.
+///
+/// # Example
+/// ```no_run
+/// use std::time::Duration;
+///
+/// use init_data_rs::{verify_with_async_provider, Expiration, KmsMacProvider};
+///
+/// # async fn run(client: aws_sdk_kms::Client, init_data: &str) -> Result<(), init_data_rs::InitDataError> {
+/// // `client` comes from `aws_sdk_kms::Client::new(&aws_config::load_from_env().await)`
+/// let provider = KmsMacProvider::new(client, "alias/telegram-bot");
+///
+/// verify_with_async_provider(init_data, &provider, Expiration::After(Duration::from_secs(3600))).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct KmsMacProvider {
+    client: Client,
+    key_id: String,
+}
+
+impl KmsMacProvider {
+    /// Uses the KMS key `key_id`, an id, ARN or alias, through `client`.
+    pub fn new(client: Client, key_id: impl Into<String>) -> Self {
+        Self {
+            client,
+            key_id: key_id.into(),
+        }
+    }
+
+    /// The KMS key in use.
+    #[must_use]
+    pub fn key_id(&self) -> &str {
+        &self.key_id
+    }
+}
+
+impl AsyncMacProvider for KmsMacProvider {
+    async fn generate_mac(&self, message: &[u8]) -> Result<[u8; 32], InitDataError> {
+        let output = self
+            .client
+            .generate_mac()
+            .key_id(&self.key_id)
+            .mac_algorithm(MacAlgorithmSpec::HmacSha256)
+            .message(Blob::new(message))
+            .send()
+            .await
+            .map_err(|err| InitDataError::Internal(format!("KMS GenerateMac failed: {}", DisplayErrorContext(err))))?;
+
+        mac_from_blob(output.mac())
+    }
+
+    async fn verify_mac(&self, message: &[u8], mac: &[u8; 32]) -> Result<bool, InitDataError> {
+        let result = self
+            .client
+            .verify_mac()
+            .key_id(&self.key_id)
+            .mac_algorithm(MacAlgorithmSpec::HmacSha256)
+            .message(Blob::new(message))
+            .mac(Blob::new(mac.as_slice()))
+            .send()
+            .await;
+
+        match result {
+            Ok(output) => Ok(output.mac_valid()),
+            // KMS reports a mismatch as an error rather than `MacValid: false`
+            Err(err)
+                if err
+                    .as_service_error()
+                    .is_some_and(VerifyMacError::is_kms_invalid_mac_exception) =>
+            {
+                Ok(false)
+            }
+            Err(err) => Err(InitDataError::Internal(format!(
+                "KMS VerifyMac failed: {}",
+                DisplayErrorContext(err)
+            ))),
+        }
+    }
+}
+
+fn mac_from_blob(mac: Option<&Blob>) -> Result<[u8; 32], InitDataError> {
+    mac.and_then(|mac| <[u8; 32]>::try_from(mac.as_ref()).ok())
+        .ok_or_else(|| InitDataError::Internal("KMS returned no HMAC-SHA256, is the key HMAC_256?".to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mac_from_blob() {
+        assert_eq!(mac_from_blob(Some(&Blob::new([7; 32].as_slice()))).unwrap(), [7; 32]);
+        assert!(matches!(
+            mac_from_blob(Some(&Blob::new([7; 64].as_slice()))),
+            Err(InitDataError::Internal(_))
+        ));
+        assert!(mac_from_blob(None).is_err());
+    }
+}
@@ -0,0 +1,387 @@
+//! Launch parameters Telegram appends to the Mini App URL, e.g.
+//! `#tgWebAppData=...&tgWebAppVersion=8.0&tgWebAppPlatform=ios`.
+//!
+//! Frontends can forward them as a whole instead of only the init data.
+//! See: <https://docs.telegram-mini-apps.com/platform/launch-parameters>
+
+use std::convert::Infallible;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::error::InitDataError;
+use crate::query;
+
+/// The Telegram client the Mini App was launched from, `tgWebAppPlatform`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Platform {
+    Android,
+    Ios,
+    Macos,
+    /// Telegram Desktop.
+    Tdesktop,
+    /// Telegram Web K.
+    Web,
+    /// Telegram Web A.
+    Weba,
+    /// Any other client, with the name it reported.
+    Unknown(String),
+}
+
+impl Platform {
+    /// The name Telegram uses for the platform.
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Android => "android",
+            Self::Ios => "ios",
+            Self::Macos => "macos",
+            Self::Tdesktop => "tdesktop",
+            Self::Web => "web",
+            Self::Weba => "weba",
+            Self::Unknown(name) => name,
+        }
+    }
+}
+
+impl FromStr for Platform {
+    type Err = Infallible;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Ok(match name {
+            "android" => Self::Android,
+            "ios" => Self::Ios,
+            "macos" => Self::Macos,
+            "tdesktop" => Self::Tdesktop,
+            "web" => Self::Web,
+            "weba" => Self::Weba,
+            name => Self::Unknown(name.to_string()),
+        })
+    }
+}
+
+impl fmt::Display for Platform {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Parsed launch parameters, see the module documentation.
+///
+/// # Example
+/// ```
+/// use init_data_rs::{LaunchParams, Platform};
+///
+/// let params = LaunchParams::parse("#tgWebAppData=auth_date%3D1662771648%26hash%3D...&tgWebAppVersion=8.0&tgWebAppPlatform=ios").unwrap();
+///
+/// assert_eq!(params.platform, Platform::Ios);
+/// assert_eq!(params.init_data.as_deref(), Some("auth_date=1662771648&hash=..."));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LaunchParams {
+    /// Raw init data, `tgWebAppData`, to be validated like any init data.
+    pub init_data: Option<String>,
+    /// Version of the Mini Apps API supported by the client, `tgWebAppVersion`.
+    pub version: String,
+    /// The client the Mini App was launched from, `tgWebAppPlatform`.
+    pub platform: Platform,
+    /// Theme parameters as JSON, `tgWebAppThemeParams`.
+    pub theme_params: Option<String>,
+    /// Value of the `startapp` link parameter, `tgWebAppStartParam`.
+    pub start_param: Option<String>,
+    /// Whether the Mini App was launched in inline mode, `tgWebAppBotInline`.
+    pub bot_inline: bool,
+}
+
+impl LaunchParams {
+    /// Parses launch parameters from the URL fragment or query, with or without the leading `#` or `?`.
+    ///
+    /// Nothing is validated here: validate [`LaunchParams::init_data`] before trusting any field.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`InitDataError::UnexpectedFormat`] if `tgWebAppVersion` or `tgWebAppPlatform` is missing.
+    pub fn parse(launch_params: &str) -> Result<Self, InitDataError> {
+        let launch_params = launch_params.trim_start_matches(['#', '?']);
+
+        let mut init_data = None;
+        let mut version = None;
+        let mut platform = None;
+        let mut theme_params = None;
+        let mut start_param = None;
+        let mut bot_inline = false;
+        for (key, value) in form_urlencoded::parse(launch_params.as_bytes()) {
+            match key.as_ref() {
+                "tgWebAppData" => init_data = Some(value.into_owned()),
+                "tgWebAppVersion" => version = Some(value.into_owned()),
+                "tgWebAppPlatform" => platform = value.parse().ok(),
+                "tgWebAppThemeParams" => theme_params = Some(value.into_owned()),
+                "tgWebAppStartParam" => start_param = Some(value.into_owned()),
+                "tgWebAppBotInline" => bot_inline = value == "1",
+                _ => {}
+            }
+        }
+
+        let missing = |name: &str| InitDataError::UnexpectedFormat(format!("{name} is missing"));
+        Ok(Self {
+            init_data,
+            version: version.ok_or_else(|| missing("tgWebAppVersion"))?,
+            platform: platform.ok_or_else(|| missing("tgWebAppPlatform"))?,
+            theme_params,
+            start_param,
+            bot_inline,
+        })
+    }
+}
+
+/// Requirements on the Telegram client, checked against its launch parameters by
+/// [`crate::Validator::validate_launch_params`].
+///
+/// # Example
+/// ```
+/// use init_data_rs::{ClientPolicy, LaunchParams, Platform};
+///
+/// let policy = ClientPolicy::new().platforms([Platform::Android, Platform::Ios]).min_version(7, 10);
+///
+/// let params = LaunchParams::parse("tgWebAppVersion=8.0&tgWebAppPlatform=ios").unwrap();
+/// assert!(policy.check(&params).is_ok());
+/// let params = LaunchParams::parse("tgWebAppVersion=8.0&tgWebAppPlatform=tdesktop").unwrap();
+/// assert!(policy.check(&params).is_err());
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ClientPolicy {
+    platforms: Option<Vec<Platform>>,
+    min_version: Option<(u32, u32)>,
+}
+
+impl ClientPolicy {
+    /// Creates a policy accepting every client.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only accepts clients launched from one of `platforms`.
+    #[must_use]
+    pub fn platforms(mut self, platforms: impl IntoIterator<Item = Platform>) -> Self {
+        self.platforms = Some(platforms.into_iter().collect());
+        self
+    }
+
+    /// Only accepts clients supporting at least version `major.minor` of the Mini Apps API.
+    #[must_use]
+    pub fn min_version(mut self, major: u32, minor: u32) -> Self {
+        self.min_version = Some((major, minor));
+        self
+    }
+
+    /// Checks the client that sent `params`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`InitDataError::ClientNotAllowed`] if the platform isn't allowed, or if the version is older
+    /// than the minimum or not a version at all.
+    pub fn check(&self, params: &LaunchParams) -> Result<(), InitDataError> {
+        if let Some(platforms) = &self.platforms {
+            if !platforms.contains(&params.platform) {
+                return Err(InitDataError::ClientNotAllowed(format!(
+                    "platform {} is not allowed",
+                    params.platform
+                )));
+            }
+        }
+
+        if let Some((major, minor)) = self.min_version {
+            if parse_version(&params.version).is_none_or(|version| version < (major, minor)) {
+                return Err(InitDataError::ClientNotAllowed(format!(
+                    "version {} is older than {major}.{minor}",
+                    params.version
+                )));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Parses a `major.minor` version, the minor version defaulting to 0.
+fn parse_version(version: &str) -> Option<(u32, u32)> {
+    let mut parts = version.split('.');
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts.next().map_or(Some(0), |minor| minor.parse().ok())?;
+
+    Some((major, minor))
+}
+
+/// Pulls raw init data out of the parameter `param` (e.g. `initData` or `init_data`) of a URL query string,
+/// for GET endpoints receiving it as `?initData=...`. Nothing is validated here.
+///
+/// The init data must have been percent-encoded once, e.g. with `encodeURIComponent` or `URLSearchParams`.
+/// Init data encoded twice is decoded twice.
+///
+/// # Arguments
+/// * `url_query` - Query string of the request URL, with or without the leading `?`
+/// * `param` - Name of the parameter holding the init data
+///
+/// # Errors
+///
+/// Returns [`InitDataError::UnexpectedFormat`] if the parameter is missing or empty.
+///
+/// # Example
+/// ```
+/// use init_data_rs::extract_from_query;
+///
+/// let init_data = extract_from_query("?page=2&initData=query_id%3D123%26auth_date%3D1662771648%26hash%3D...", "initData");
+/// assert_eq!(init_data.unwrap(), "query_id=123&auth_date=1662771648&hash=...");
+///
+/// // encoded twice
+/// let init_data = extract_from_query("initData=query_id%253D123%2526auth_date%253D1662771648%2526hash%253D...", "initData");
+/// assert_eq!(init_data.unwrap(), "query_id=123&auth_date=1662771648&hash=...");
+/// ```
+pub fn extract_from_query(url_query: &str, param: &str) -> Result<String, InitDataError> {
+    let url_query = url_query.trim_start_matches('?');
+
+    let init_data = form_urlencoded::parse(url_query.as_bytes())
+        .filter(|(key, _)| key == param)
+        .last()
+        .map(|(_, value)| value.into_owned())
+        .filter(|value| !value.is_empty())
+        .ok_or_else(|| InitDataError::UnexpectedFormat(format!("{param} is missing")))?;
+
+    // Once decoded, init data is a query string. Still encoded, it has no `=` left, only `%3D`.
+    if init_data.contains('=') || !init_data.to_ascii_uppercase().contains("%3D") {
+        return Ok(init_data);
+    }
+
+    // The remaining encoding came from `encodeURIComponent`, which never writes `+` for a space
+    let decoded: Vec<u8> = query::decode(&init_data.replace('+', "%2B")).collect();
+    String::from_utf8(decoded).map_err(|_| InitDataError::UnexpectedFormat(format!("{param} is not UTF-8")))
+}
+
+/// Authorization scheme of init data sent as `Authorization: tma <init data>`.
+pub const AUTHORIZATION_SCHEME: &str = "tma";
+
+/// Pulls raw init data out of an `Authorization` header value, `tma <init data>`. The scheme is matched
+/// case-insensitively and surrounding whitespace is ignored. Nothing is validated here.
+///
+/// # Errors
+///
+/// Returns [`InitDataError::UnexpectedFormat`] if the scheme is not `tma` or the init data is empty.
+///
+/// # Example
+/// ```
+/// use init_data_rs::extract_from_authorization_header;
+///
+/// let init_data = extract_from_authorization_header("tma query_id=123&auth_date=1662771648&hash=...");
+/// assert_eq!(init_data.unwrap(), "query_id=123&auth_date=1662771648&hash=...");
+/// assert!(extract_from_authorization_header("Bearer eyJhbGciOi...").is_err());
+/// ```
+pub fn extract_from_authorization_header(header: &str) -> Result<&str, InitDataError> {
+    let header = header.trim();
+    let (scheme, init_data) = header.split_once(char::is_whitespace).unwrap_or((header, ""));
+
+    if !scheme.eq_ignore_ascii_case(AUTHORIZATION_SCHEME) {
+        return Err(InitDataError::UnexpectedFormat(
+            "authorization scheme is not tma".to_string(),
+        ));
+    }
+
+    let init_data = init_data.trim_start();
+    if init_data.is_empty() {
+        return Err(InitDataError::UnexpectedFormat(
+            "authorization header is empty".to_string(),
+        ));
+    }
+
+    Ok(init_data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_platform_from_str() {
+        assert_eq!("tdesktop".parse(), Ok(Platform::Tdesktop));
+        assert_eq!("weba".parse(), Ok(Platform::Weba));
+        assert_eq!(
+            "unigram".parse::<Platform>().unwrap(),
+            Platform::Unknown("unigram".to_string())
+        );
+        assert_eq!(Platform::Unknown("unigram".to_string()).to_string(), "unigram");
+    }
+
+    #[test]
+    fn test_launch_params_parse() {
+        let params = LaunchParams::parse(
+            "?tgWebAppData=query_id%3DAAH%26user%3D%257B%2522id%2522%253A1%257D%26auth_date%3D1%26hash%3Dabc&tgWebAppVersion=7.10&tgWebAppPlatform=android&tgWebAppThemeParams=%7B%22bg_color%22%3A%22%23ffffff%22%7D&tgWebAppStartParam=ref_1&tgWebAppBotInline=1",
+        )
+        .unwrap();
+
+        assert_eq!(
+            params.init_data.as_deref(),
+            Some("query_id=AAH&user=%7B%22id%22%3A1%7D&auth_date=1&hash=abc")
+        );
+        assert_eq!(params.version, "7.10");
+        assert_eq!(params.platform, Platform::Android);
+        assert_eq!(params.theme_params.as_deref(), Some(r##"{"bg_color":"#ffffff"}"##));
+        assert_eq!(params.start_param.as_deref(), Some("ref_1"));
+        assert!(params.bot_inline);
+
+        assert!(matches!(
+            LaunchParams::parse("tgWebAppVersion=8.0"),
+            Err(InitDataError::UnexpectedFormat(_))
+        ));
+    }
+
+    #[test]
+    fn test_extract_from_query() {
+        let init_data = "query_id=AAH&user=%7B%22id%22%3A1%7D&start_param=a+b&auth_date=1&hash=abc";
+        let once: String = form_urlencoded::byte_serialize(init_data.as_bytes()).collect();
+        let twice: String = form_urlencoded::byte_serialize(once.as_bytes()).collect();
+
+        assert_eq!(
+            extract_from_query(&format!("?init_data={once}"), "init_data").unwrap(),
+            init_data
+        );
+        assert_eq!(
+            extract_from_query(&format!("a=1&initData={twice}"), "initData").unwrap(),
+            init_data
+        );
+
+        assert!(matches!(
+            extract_from_query("initData=", "initData"),
+            Err(InitDataError::UnexpectedFormat(_))
+        ));
+        assert!(extract_from_query(&format!("initData={once}"), "init_data").is_err());
+    }
+
+    #[test]
+    fn test_client_policy() {
+        let params = |version: &str, platform: &str| {
+            LaunchParams::parse(&format!("tgWebAppVersion={version}&tgWebAppPlatform={platform}")).unwrap()
+        };
+        let policy = ClientPolicy::new()
+            .platforms([Platform::Android, Platform::Ios])
+            .min_version(7, 10);
+
+        assert!(policy.check(&params("7.10", "android")).is_ok());
+        assert!(policy.check(&params("8", "ios")).is_ok());
+        assert!(matches!(
+            policy.check(&params("7.9", "ios")),
+            Err(InitDataError::ClientNotAllowed(_))
+        ));
+        assert!(policy.check(&params("latest", "ios")).is_err());
+        assert!(policy.check(&params("8.0", "weba")).is_err());
+        assert!(ClientPolicy::new().check(&params("6.0", "weba")).is_ok());
+    }
+
+    #[test]
+    fn test_extract_from_authorization_header() {
+        assert_eq!(extract_from_authorization_header("tma a=1&b=2").unwrap(), "a=1&b=2");
+        assert_eq!(extract_from_authorization_header("  TMA   a=1\r\n").unwrap(), "a=1");
+        assert!(extract_from_authorization_header("tma").is_err());
+        assert!(extract_from_authorization_header("tma   ").is_err());
+        assert!(extract_from_authorization_header("tmax a=1").is_err());
+        assert!(extract_from_authorization_header("a=1").is_err());
+    }
+}
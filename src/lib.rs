@@ -2,16 +2,208 @@
 // We ignore this warning because the only literals we use
 // are telegram ids, which are not meant to be read
 #![allow(clippy::unreadable_literal)]
+
+#[cfg(not(any(feature = "json", feature = "mini-parse", feature = "verify-only")))]
+compile_error!(
+    "either the `json` or the `mini-parse` feature must be enabled to parse init data, or `verify-only` for just `verify` and `sign`"
+);
+
+#[cfg(feature = "analytics")]
+pub mod analytics;
+mod body;
+mod buffers;
+mod canonical;
+#[cfg(feature = "model")]
+mod chat_policy;
+mod check_order;
+mod clock;
+#[cfg(feature = "compact-serde")]
+mod compact;
+mod compat;
+#[cfg(feature = "component")]
+mod component;
+#[cfg(feature = "model")]
+mod config;
+pub mod conformance;
+#[cfg(feature = "json")]
+mod cookie;
+mod crypto;
+#[cfg(feature = "model")]
+pub mod deep_link;
+#[cfg(feature = "diagnostics")]
+mod diagnostics;
+#[cfg(feature = "diesel")]
+mod diesel_types;
+#[cfg(feature = "rustler")]
+pub mod elixir;
+#[cfg(feature = "encrypt")]
+pub mod encrypt;
 mod error;
+mod expiration;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+mod fingerprint;
+#[cfg(feature = "flutter")]
+pub mod flutter;
+#[cfg(feature = "jwt")]
+mod jwt;
+#[cfg(feature = "kms")]
+mod kms;
+mod launch_params;
+#[cfg(feature = "model")]
+pub mod login_widget;
+mod mac_provider;
+#[cfg(feature = "model")]
+mod metrics_sink;
+#[cfg(feature = "mini-parse")]
+mod mini_json;
+#[cfg(feature = "model")]
 mod model;
+#[cfg(feature = "napi")]
+pub mod node;
+#[cfg(feature = "model")]
+mod nonce;
+#[cfg(feature = "model")]
 mod parse;
+mod parsed_query;
+#[cfg(feature = "paseto")]
+mod paseto;
+#[cfg(feature = "model")]
+pub mod prelude;
+#[cfg(feature = "model")]
+mod privacy;
+#[cfg(feature = "json")]
+mod problem;
+mod query;
+mod raw;
+#[cfg(feature = "model")]
+mod receipt;
+#[cfg(feature = "model")]
+mod rejection_cache;
+mod sanitize;
+#[cfg(feature = "model")]
+mod scheme;
+mod secret;
+#[cfg(any(feature = "jwt", feature = "paseto"))]
+mod session;
 mod sign;
+#[cfg(feature = "sqlx")]
+mod sqlx_types;
+#[cfg(feature = "abi_stable")]
+pub mod stable_abi;
+#[cfg(feature = "model")]
+mod start_param;
+#[cfg(feature = "json")]
+mod stored;
+#[cfg(feature = "model")]
+mod telegram_json;
+#[cfg(any(feature = "tracing", feature = "log"))]
+mod telemetry;
+#[cfg(feature = "testing")]
+pub mod testing;
+#[cfg(feature = "model")]
 mod third_party_validation;
+#[cfg(feature = "model")]
+mod throttle;
+#[cfg(feature = "model")]
+mod user_gate;
 mod validation;
+#[cfg(feature = "model")]
+mod validation_cache;
+#[cfg(feature = "model")]
+mod validator;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+#[cfg(feature = "model")]
+mod websocket;
 
+#[cfg(feature = "json")]
+pub use body::extract_from_json;
+pub use body::{extract_from_form, extract_from_multipart};
+pub use buffers::ValidationBuffers;
+pub use canonical::{cache_key, canonicalize};
+#[cfg(feature = "model")]
+pub use chat_policy::ChatPolicy;
+pub use check_order::CheckOrder;
+pub use clock::{Clock, SystemClock};
+pub use compat::Compat;
+#[cfg(feature = "model")]
+pub use config::{TokenSource, ValidatorConfig};
+#[cfg(feature = "json")]
+pub use cookie::{SameSite, SessionCookie};
+pub use crypto::{CryptoBackend, CRYPTO_BACKEND};
+#[cfg(feature = "diagnostics")]
+pub use diagnostics::FailedPayload;
 pub use error::InitDataError;
+#[cfg(feature = "model")]
+pub use expiration::ExpirationPolicy;
+pub use expiration::{Expiration, Freshness};
+pub use fingerprint::Fingerprinter;
+#[cfg(feature = "jwt")]
+pub use jwt::{issue_jwt, issue_jwt_with_clock, verify_jwt, verify_jwt_with_clock};
+#[cfg(feature = "kms")]
+pub use kms::KmsMacProvider;
+pub use launch_params::{
+    extract_from_authorization_header, extract_from_query, ClientPolicy, LaunchParams, Platform, AUTHORIZATION_SCHEME,
+};
+pub use mac_provider::{
+    verify_with_async_provider, verify_with_async_provider_with_clock, verify_with_provider,
+    verify_with_provider_with_clock, AsyncMacProvider, MacProvider,
+};
+#[cfg(feature = "metrics")]
+pub use metrics_sink::MetricsFacade;
+#[cfg(feature = "model")]
+pub use metrics_sink::MetricsSink;
+#[cfg(feature = "model")]
 pub use model::*;
-pub use parse::parse;
-pub use sign::sign;
-pub use third_party_validation::validate_third_party;
-pub use validation::validate;
+#[cfg(feature = "model")]
+pub use nonce::NonceBinder;
+#[cfg(feature = "model")]
+pub use parse::{parse, parse_lossy, parse_with_options, FieldError, ParseOptions, PartialInitData};
+pub use parsed_query::ParsedQuery;
+#[cfg(feature = "paseto")]
+pub use paseto::{
+    issue_paseto_local, issue_paseto_local_with_clock, issue_paseto_public, issue_paseto_public_with_clock,
+    verify_paseto_local, verify_paseto_local_with_clock, verify_paseto_public, verify_paseto_public_with_clock,
+};
+#[cfg(feature = "model")]
+pub use privacy::PrivacyMode;
+#[cfg(feature = "json")]
+pub use problem::ProblemDetails;
+pub use raw::{reencode_preserving_order, RawInitData};
+#[cfg(feature = "model")]
+pub use receipt::Receipt;
+#[cfg(feature = "model")]
+pub use rejection_cache::RejectionCacheStats;
+pub use sanitize::strip_secrets;
+#[cfg(feature = "model")]
+pub use scheme::{validate_auto, validate_scheme, validate_scheme_with_clock, Unverified, ValidationScheme, Verified};
+pub use secret::{BotSecret, BotToken, TokenProvider};
+#[cfg(any(feature = "jwt", feature = "paseto"))]
+pub use session::SessionClaims;
+pub use sign::{sign, sign_with_compat, verify_hash};
+#[cfg(feature = "model")]
+pub use start_param::{is_valid_start_param, StartParamPolicy, MAX_START_PARAM_LEN};
+#[cfg(feature = "json")]
+pub use stored::{StoredInitData, STORED_FORMAT_VERSION};
+#[cfg(feature = "model")]
+pub use third_party_validation::{
+    validate_third_party, validate_third_party_in, validate_third_party_with_clock, verify_signature, Environment,
+};
+#[cfg(feature = "governor")]
+pub use throttle::GovernorThrottle;
+#[cfg(feature = "model")]
+pub use throttle::{ThrottleHook, TokenBucket};
+#[cfg(feature = "model")]
+pub use user_gate::{AsyncUserGate, GateDecision, UserGate};
+#[cfg(feature = "model")]
+pub use validation::{validate, validate_graceful, validate_graceful_with_clock, validate_with_clock, ValidateExt};
+pub use validation::{verify, verify_with_clock, verify_with_compat, verify_with_order, verify_with_order_with_clock};
+#[cfg(feature = "moka")]
+pub use validation_cache::MokaValidationCache;
+#[cfg(feature = "model")]
+pub use validation_cache::{MemoryValidationCache, PayloadHash, ValidationCache};
+#[cfg(feature = "model")]
+pub use validator::{TokenHandle, TokenSlot, Validator};
+#[cfg(feature = "model")]
+pub use websocket::{init_data_from_protocols, tma_protocol, FirstMessageAuth};
@@ -0,0 +1,368 @@
+//! Validation of data received from the Telegram Login Widget.
+//!
+//! The widget hands the user over either as query parameters of the redirect URL or as a JSON object
+//! passed to the `data-onauth` callback. Unlike Mini App init data, the secret key is the SHA-256 of the
+//! bot token. See: <https://core.telegram.org/widgets/login#checking-authorization>
+
+use std::borrow::Cow;
+
+use serde::{Deserialize, Serialize};
+
+use crate::clock::{Clock, SystemClock};
+use crate::crypto::{self, Hmac};
+use crate::error::InitDataError;
+use crate::expiration::Expiration;
+use crate::secret::TokenProvider;
+use crate::sign::decode_hash;
+
+/// Decoded `key=value` fields of the widget data.
+pub(crate) type Fields<'a> = Vec<(Cow<'a, str>, Cow<'a, str>)>;
+
+/// A user authorized through the Login Widget.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LoginData {
+    /// Unique identifier of the user.
+    pub id: i64,
+    /// First name of the user.
+    pub first_name: String,
+    /// Optional. Last name of the user.
+    pub last_name: Option<String>,
+    /// Optional. Username of the user.
+    pub username: Option<String>,
+    /// Optional. URL of the user's profile photo.
+    pub photo_url: Option<String>,
+    /// Unix time when the user authorized.
+    pub auth_date: u64,
+    /// A hash of all other fields, which the bot server can use to check their validity.
+    pub hash: String,
+}
+
+/// Validates the query string of a Login Widget redirect, e.g. `id=1&first_name=John&auth_date=...&hash=...`.
+///
+/// # Arguments
+/// * `query` - Query string of the redirect URL, without the leading `?`
+/// * `token` - Bot token used for validation, see [`crate::TokenProvider`]
+/// * `expiration` - How long the login data stays valid, see [`crate::Expiration`]
+///
+/// # Example
+/// ```
+/// use init_data_rs::{login_widget, Expiration};
+///
+/// let query = "id=42&first_name=John&auth_date=1662771648&hash=...";
+/// let result = login_widget::validate(query, "BOT_TOKEN", Expiration::Default);
+/// ```
+///
+/// # Errors
+///
+/// Returns an `Err` if the hash is missing or doesn't match, a field is missing or malformed, or the
+/// data is expired.
+///
+/// # Panics
+///
+/// See [`crate::validate`].
+pub fn validate(
+    query: &str,
+    token: &(impl TokenProvider + ?Sized),
+    expiration: impl Into<Expiration>,
+) -> Result<LoginData, InitDataError> {
+    validate_with_clock(query, token, expiration, &SystemClock)
+}
+
+/// Same as [`validate`], but reads the current time from `clock`.
+///
+/// # Errors
+///
+/// See [`validate`].
+pub fn validate_with_clock(
+    query: &str,
+    token: &(impl TokenProvider + ?Sized),
+    expiration: impl Into<Expiration>,
+    clock: &impl Clock,
+) -> Result<LoginData, InitDataError> {
+    let fields = form_urlencoded::parse(query.as_bytes()).collect();
+
+    check(fields, token, expiration.into(), clock)
+}
+
+/// Validates the JSON object passed to the widget's `data-onauth` callback.
+///
+/// # Errors
+///
+/// See [`validate`].
+///
+/// # Panics
+///
+/// See [`crate::validate`].
+#[cfg(feature = "json")]
+pub fn validate_json(
+    json: &str,
+    token: &(impl TokenProvider + ?Sized),
+    expiration: impl Into<Expiration>,
+) -> Result<LoginData, InitDataError> {
+    validate_json_with_clock(json, token, expiration, &SystemClock)
+}
+
+/// Same as [`validate_json`], but reads the current time from `clock`.
+///
+/// # Errors
+///
+/// See [`validate`].
+#[cfg(feature = "json")]
+pub fn validate_json_with_clock(
+    json: &str,
+    token: &(impl TokenProvider + ?Sized),
+    expiration: impl Into<Expiration>,
+    clock: &impl Clock,
+) -> Result<LoginData, InitDataError> {
+    let object: serde_json::Map<String, serde_json::Value> =
+        serde_json::from_str(json).map_err(|err| InitDataError::UnexpectedFormat(err.to_string()))?;
+
+    check(json_fields(object)?, token, expiration.into(), clock)
+}
+
+/// Validates the `tgAuthResult` payload Telegram appends to the redirect URL after a web login, e.g.
+/// `#tgAuthResult=eyJpZCI6...`.
+///
+/// # Arguments
+/// * `auth_result` - The URL fragment, with or without the leading `#tgAuthResult=`, or the bare base64 value
+/// * `token` - Bot token used for validation, see [`crate::TokenProvider`]
+/// * `expiration` - How long the login data stays valid, see [`crate::Expiration`]
+///
+/// # Errors
+///
+/// Returns [`InitDataError::UnexpectedFormat`] if the payload is not base64 encoded JSON, otherwise see
+/// [`validate`].
+///
+/// # Panics
+///
+/// See [`crate::validate`].
+#[cfg(feature = "json")]
+pub fn validate_auth_result(
+    auth_result: &str,
+    token: &(impl TokenProvider + ?Sized),
+    expiration: impl Into<Expiration>,
+) -> Result<LoginData, InitDataError> {
+    validate_auth_result_with_clock(auth_result, token, expiration, &SystemClock)
+}
+
+/// Same as [`validate_auth_result`], but reads the current time from `clock`.
+///
+/// # Errors
+///
+/// See [`validate_auth_result`].
+#[cfg(feature = "json")]
+pub fn validate_auth_result_with_clock(
+    auth_result: &str,
+    token: &(impl TokenProvider + ?Sized),
+    expiration: impl Into<Expiration>,
+    clock: &impl Clock,
+) -> Result<LoginData, InitDataError> {
+    use base64::alphabet::URL_SAFE;
+    use base64::engine::{DecodePaddingMode, GeneralPurpose, GeneralPurposeConfig};
+    use base64::Engine as _;
+
+    const ENGINE: GeneralPurpose = GeneralPurpose::new(
+        &URL_SAFE,
+        GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent),
+    );
+
+    let auth_result = auth_result.trim_start_matches('#');
+    let auth_result = auth_result.strip_prefix("tgAuthResult=").unwrap_or(auth_result);
+    // Accept the standard alphabet as well, some clients re-encode the fragment
+    let auth_result = auth_result.replace('+', "-").replace('/', "_");
+
+    let json = ENGINE
+        .decode(auth_result)
+        .map_err(|_| InitDataError::UnexpectedFormat("tgAuthResult is not valid base64".to_string()))?;
+    let json = String::from_utf8(json)
+        .map_err(|_| InitDataError::UnexpectedFormat("tgAuthResult is not valid UTF-8".to_string()))?;
+
+    validate_json_with_clock(&json, token, expiration, clock)
+}
+
+/// Turns a JSON object into the fields of the data-check-string, numbers and booleans as written.
+#[cfg(feature = "json")]
+pub(crate) fn json_fields(
+    object: serde_json::Map<String, serde_json::Value>,
+) -> Result<Fields<'static>, InitDataError> {
+    object
+        .into_iter()
+        .filter(|(_, value)| !value.is_null())
+        .map(|(key, value)| {
+            let value = match value {
+                serde_json::Value::String(value) => value,
+                serde_json::Value::Number(value) => value.to_string(),
+                serde_json::Value::Bool(value) => value.to_string(),
+                _ => return Err(InitDataError::UnexpectedFormat(format!("`{key}` is not a scalar"))),
+            };
+            Ok((Cow::Owned(key), Cow::Owned(value)))
+        })
+        .collect()
+}
+
+/// Checks the hash and the expiration of the widget fields and builds the [`LoginData`].
+pub(crate) fn check(
+    mut fields: Fields<'_>,
+    token: &(impl TokenProvider + ?Sized),
+    expiration: Expiration,
+    clock: &impl Clock,
+) -> Result<LoginData, InitDataError> {
+    let token = token.expose_token();
+    if token.is_empty() {
+        return Err(InitDataError::UnexpectedFormat("token is empty".to_string()));
+    }
+
+    // Later occurrences of a key win: reversing first makes the stable sort keep them in front for `dedup_by`.
+    fields.reverse();
+    fields.sort_by(|a, b| a.0.cmp(&b.0));
+    fields.dedup_by(|a, b| a.0 == b.0);
+
+    let hash_index = fields
+        .iter()
+        .position(|(key, _)| key == "hash")
+        .ok_or(InitDataError::HashMissing)?;
+    let (_, hash) = fields.remove(hash_index);
+    let hash_bytes = decode_hash(&hash).ok_or(InitDataError::HashInvalid)?;
+
+    let mut hmac = Hmac::new(&crypto::sha256(token))?;
+    for (index, (key, value)) in fields.iter().enumerate() {
+        if index > 0 {
+            hmac.update(b"\n");
+        }
+        hmac.update(key.as_bytes());
+        hmac.update(b"=");
+        hmac.update(value.as_bytes());
+    }
+    if !hmac.verify(&hash_bytes)? {
+        return Err(InitDataError::HashInvalid);
+    }
+
+    let field = |name: &str| {
+        fields
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.to_string())
+    };
+
+    let auth_date = number(field("auth_date"), "auth_date")?.ok_or(InitDataError::AuthDateMissing)?;
+    if expiration != Expiration::Never && expiration.is_expired(auth_date, clock.now()) {
+        return Err(InitDataError::Expired);
+    }
+
+    Ok(LoginData {
+        id: number(field("id"), "id")?.ok_or_else(|| InitDataError::UnexpectedFormat("`id` is missing".to_string()))?,
+        first_name: field("first_name")
+            .ok_or_else(|| InitDataError::UnexpectedFormat("`first_name` is missing".to_string()))?,
+        last_name: field("last_name"),
+        username: field("username"),
+        photo_url: field("photo_url"),
+        auth_date,
+        hash: hash.into_owned(),
+    })
+}
+
+fn number<T: std::str::FromStr>(value: Option<String>, name: &str) -> Result<Option<T>, InitDataError> {
+    value
+        .map(|value| value.parse())
+        .transpose()
+        .map_err(|_| InitDataError::UnexpectedFormat(format!("`{name}` is not a number")))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use hmac::{Hmac, KeyInit, Mac};
+    use sha2::{Digest, Sha256};
+
+    use super::*;
+
+    const BOT_TOKEN: &str = "5768337691:AAH5YkoiEuPk8-FZa32hStHTqXiLPtAEhx8";
+
+    fn signed_query(fields: &str) -> String {
+        let mut pairs: Vec<_> = fields.split('&').map(|pair| pair.replace('+', " ")).collect();
+        pairs.sort();
+
+        let mut hmac: Hmac<Sha256> = Hmac::new_from_slice(&Sha256::digest(BOT_TOKEN)).unwrap();
+        hmac.update(pairs.join("\n").as_bytes());
+
+        format!("{fields}&hash={}", hex::encode(hmac.finalize().as_bytes()))
+    }
+
+    #[test]
+    fn test_login_widget_validate() {
+        let query = signed_query("id=279058397&first_name=Vladislav&username=vdkfrost&auth_date=1662771648");
+
+        let data = validate_with_clock(&query, BOT_TOKEN, Duration::from_secs(60), &|| 1662771648 + 30).unwrap();
+        assert_eq!(data.id, 279058397);
+        assert_eq!(data.first_name, "Vladislav");
+        assert_eq!(data.username.as_deref(), Some("vdkfrost"));
+        assert_eq!(data.last_name, None);
+
+        assert!(matches!(
+            validate_with_clock(&query, BOT_TOKEN, Duration::from_secs(60), &|| 1662771648 + 61),
+            Err(InitDataError::Expired)
+        ));
+        assert!(matches!(
+            validate(&query.replace("Vladislav", "Mallory"), BOT_TOKEN, Expiration::Never),
+            Err(InitDataError::HashInvalid)
+        ));
+        assert!(matches!(
+            validate("id=1&first_name=a&auth_date=1", BOT_TOKEN, Expiration::Never),
+            Err(InitDataError::HashMissing)
+        ));
+    }
+
+    #[test]
+    fn test_login_widget_mini_app_secret_is_rejected() {
+        // A Mini App hash is keyed differently and must not pass as a Login Widget hash
+        let init_data = "auth_date=1662771648&id=1&first_name=a";
+        let hash = crate::sign(init_data, BOT_TOKEN).unwrap();
+
+        assert!(matches!(
+            validate(&format!("{init_data}&hash={hash}"), BOT_TOKEN, Expiration::Never),
+            Err(InitDataError::HashInvalid)
+        ));
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_login_widget_validate_json() {
+        let query = signed_query("id=279058397&first_name=Vladislav+K&auth_date=1662771648");
+        let hash = query.rsplit_once("hash=").unwrap().1;
+        let json = format!(r#"{{"id":279058397,"first_name":"Vladislav K","auth_date":1662771648,"hash":"{hash}"}}"#);
+
+        let data = validate_json(&json, BOT_TOKEN, Expiration::Never).unwrap();
+        assert_eq!(data.first_name, "Vladislav K");
+        assert!(matches!(
+            validate_json(&json.replace("279058397", "1"), BOT_TOKEN, Expiration::Never),
+            Err(InitDataError::HashInvalid)
+        ));
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_login_widget_validate_auth_result() {
+        use base64::Engine as _;
+
+        let query = signed_query("id=279058397&first_name=Vladislav&auth_date=1662771648");
+        let hash = query.rsplit_once("hash=").unwrap().1;
+        let json = format!(r#"{{"id":279058397,"first_name":"Vladislav","auth_date":1662771648,"hash":"{hash}"}}"#);
+        let encoded = base64::engine::general_purpose::URL_SAFE.encode(&json);
+
+        for auth_result in [
+            format!("#tgAuthResult={encoded}"),
+            format!("tgAuthResult={}", encoded.trim_end_matches('=')),
+            encoded.clone(),
+        ] {
+            let data =
+                validate_auth_result_with_clock(&auth_result, BOT_TOKEN, Expiration::Default, &|| 1662771648).unwrap();
+            assert_eq!(data.id, 279058397);
+        }
+
+        assert!(matches!(
+            validate_auth_result("#tgAuthResult=!!!", BOT_TOKEN, Expiration::Default),
+            Err(InitDataError::UnexpectedFormat(_))
+        ));
+    }
+}
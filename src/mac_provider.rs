@@ -0,0 +1,239 @@
+//! Verification with the HMAC computed outside the process, e.g. by AWS KMS or an HSM.
+//!
+//! The key of the HMAC over the data-check-string is [`BotSecret`], `HMAC_SHA256("WebAppData", token)`.
+//! Importing that secret into a key store and verifying through a [`MacProvider`] or an [`AsyncMacProvider`]
+//! keeps both the token and the secret out of process memory.
+
+use std::future::Future;
+
+use crate::buffers::ValidationBuffers;
+use crate::check_order::CheckOrder;
+use crate::clock::{Clock, SystemClock};
+use crate::crypto::{self, Hmac};
+use crate::error::InitDataError;
+use crate::expiration::Expiration;
+use crate::secret::BotSecret;
+use crate::sign;
+use crate::validation::Precheck;
+
+/// Computes and checks HMAC-SHA256 with the bot secret as key, blocking until done.
+///
+/// [`BotSecret`] is the local implementation. Implement it over PKCS#11 or a similar interface to keep the
+/// secret in an HSM.
+pub trait MacProvider {
+    /// Returns the HMAC-SHA256 of `message`.
+    ///
+    /// # Errors
+    ///
+    /// Whatever error the provider reports, e.g. [`InitDataError::Internal`] if the key store fails.
+    fn generate_mac(&self, message: &[u8]) -> Result<[u8; 32], InitDataError>;
+
+    /// Whether `mac` is the HMAC-SHA256 of `message`, compared in constant time.
+    ///
+    /// The default implementation compares `mac` with [`MacProvider::generate_mac`].
+    ///
+    /// # Errors
+    ///
+    /// See [`MacProvider::generate_mac`].
+    fn verify_mac(&self, message: &[u8], mac: &[u8; 32]) -> Result<bool, InitDataError> {
+        Ok(crypto::constant_time_eq(&self.generate_mac(message)?, mac))
+    }
+}
+
+impl MacProvider for BotSecret {
+    fn generate_mac(&self, message: &[u8]) -> Result<[u8; 32], InitDataError> {
+        let mut hmac = Hmac::new(self.as_bytes())?;
+        hmac.update(message);
+        hmac.finalize()
+    }
+
+    fn verify_mac(&self, message: &[u8], mac: &[u8; 32]) -> Result<bool, InitDataError> {
+        let mut hmac = Hmac::new(self.as_bytes())?;
+        hmac.update(message);
+        hmac.verify(mac)
+    }
+}
+
+impl<T: MacProvider + ?Sized> MacProvider for &T {
+    fn generate_mac(&self, message: &[u8]) -> Result<[u8; 32], InitDataError> {
+        (**self).generate_mac(message)
+    }
+
+    fn verify_mac(&self, message: &[u8], mac: &[u8; 32]) -> Result<bool, InitDataError> {
+        (**self).verify_mac(message, mac)
+    }
+}
+
+/// Same as [`MacProvider`], for providers behind a network call such as [`crate::KmsMacProvider`].
+pub trait AsyncMacProvider: Sync {
+    /// Returns the HMAC-SHA256 of `message`.
+    ///
+    /// # Errors
+    ///
+    /// Whatever error the provider reports, e.g. [`InitDataError::Internal`] if the service can't be reached.
+    fn generate_mac(&self, message: &[u8]) -> impl Future<Output = Result<[u8; 32], InitDataError>> + Send;
+
+    /// Whether `mac` is the HMAC-SHA256 of `message`, compared in constant time.
+    ///
+    /// The default implementation compares `mac` with [`AsyncMacProvider::generate_mac`].
+    ///
+    /// # Errors
+    ///
+    /// See [`AsyncMacProvider::generate_mac`].
+    fn verify_mac(&self, message: &[u8], mac: &[u8; 32]) -> impl Future<Output = Result<bool, InitDataError>> + Send {
+        async move { Ok(crypto::constant_time_eq(&self.generate_mac(message).await?, mac)) }
+    }
+}
+
+/// Same as [`crate::verify`], but checks the hash through `provider` instead of a bot token.
+///
+/// # Arguments
+/// * `init_data` - Raw init data string from Telegram Mini App
+/// * `provider` - Computes the HMAC with the bot secret, see [`MacProvider`]
+/// * `expiration` - How long the init data stays valid, see [`crate::Expiration`]
+///
+/// # Example
+/// ```
+/// use init_data_rs::{sign, verify_with_provider, BotSecret, Expiration};
+///
+/// let secret = BotSecret::from_token("12345:BOT_TOKEN").unwrap();
+/// let base_data = "query_id=123&auth_date=1662771648";
+/// let init_data = format!("{base_data}&hash={}", sign(base_data, "12345:BOT_TOKEN").unwrap());
+///
+/// assert!(verify_with_provider(&init_data, &secret, Expiration::Never).is_ok());
+/// ```
+///
+/// # Errors
+///
+/// See [`crate::verify`], and the errors of the provider.
+///
+/// # Panics
+///
+/// See [`crate::validate`].
+pub fn verify_with_provider(
+    init_data: &str,
+    provider: &(impl MacProvider + ?Sized),
+    expiration: impl Into<Expiration>,
+) -> Result<(), InitDataError> {
+    verify_with_provider_with_clock(init_data, provider, expiration, &SystemClock)
+}
+
+/// Same as [`verify_with_provider`], but reads the current time from `clock`.
+///
+/// # Errors
+///
+/// See [`verify_with_provider`].
+pub fn verify_with_provider_with_clock(
+    init_data: &str,
+    provider: &(impl MacProvider + ?Sized),
+    expiration: impl Into<Expiration>,
+    clock: &impl Clock,
+) -> Result<(), InitDataError> {
+    let expiration = expiration.into();
+    let precheck = Precheck::new(init_data);
+    precheck.check_before_hmac(CheckOrder::default(), expiration, clock)?;
+    let message = sign::data_check_string(init_data, &mut ValidationBuffers::default());
+    let hash_matches = provider.verify_mac(&message, &precheck.hash())?;
+
+    precheck.finish(hash_matches, expiration, clock)
+}
+
+/// Same as [`verify_with_provider`], for an [`AsyncMacProvider`].
+///
+/// # Errors
+///
+/// See [`verify_with_provider`].
+///
+/// # Panics
+///
+/// See [`crate::validate`].
+pub async fn verify_with_async_provider(
+    init_data: &str,
+    provider: &(impl AsyncMacProvider + ?Sized),
+    expiration: impl Into<Expiration>,
+) -> Result<(), InitDataError> {
+    verify_with_async_provider_with_clock(init_data, provider, expiration, &SystemClock).await
+}
+
+/// Same as [`verify_with_async_provider`], but reads the current time from `clock`.
+///
+/// # Errors
+///
+/// See [`verify_with_provider`].
+pub async fn verify_with_async_provider_with_clock(
+    init_data: &str,
+    provider: &(impl AsyncMacProvider + ?Sized),
+    expiration: impl Into<Expiration>,
+    clock: &impl Clock,
+) -> Result<(), InitDataError> {
+    let expiration = expiration.into();
+    let precheck = Precheck::new(init_data);
+    precheck.check_before_hmac(CheckOrder::default(), expiration, clock)?;
+    let message = sign::data_check_string(init_data, &mut ValidationBuffers::default());
+    let hash_matches = provider.verify_mac(&message, &precheck.hash()).await?;
+
+    precheck.finish(hash_matches, expiration, clock)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::pin::pin;
+    use std::task::{Context, Poll, Waker};
+    use std::time::Duration;
+
+    use super::*;
+
+    const BOT_TOKEN: &str = "12345:YOUR_BOT_TOKEN";
+    const BASE_DATA: &str = "query_id=AAHdF6IQAAAAAN0XohDhrOrc&auth_date=1662771648";
+
+    /// An async provider answering right away, like a remote one with a local copy of the secret.
+    struct Remote(BotSecret);
+
+    impl AsyncMacProvider for Remote {
+        async fn generate_mac(&self, message: &[u8]) -> Result<[u8; 32], InitDataError> {
+            MacProvider::generate_mac(&self.0, message)
+        }
+    }
+
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let mut future = pin!(future);
+        match future.as_mut().poll(&mut Context::from_waker(Waker::noop())) {
+            Poll::Ready(output) => output,
+            Poll::Pending => panic!("the test provider never waits"),
+        }
+    }
+
+    #[test]
+    fn test_verify_with_provider() {
+        let secret = BotSecret::from_token(BOT_TOKEN).unwrap();
+        let init_data = format!("{BASE_DATA}&hash={}", sign::sign(BASE_DATA, BOT_TOKEN).unwrap());
+        let forged = format!("{BASE_DATA}&hash={}", "0".repeat(64));
+
+        assert!(verify_with_provider(&init_data, &secret, Expiration::Never).is_ok());
+        assert!(matches!(
+            verify_with_provider(&forged, &secret, Expiration::Never),
+            Err(InitDataError::HashInvalid)
+        ));
+        assert!(matches!(
+            verify_with_provider_with_clock(&init_data, &secret, Duration::from_secs(60), &|| { 1_662_771_648 + 61 }),
+            Err(InitDataError::Expired)
+        ));
+        assert!(matches!(
+            verify_with_provider(BASE_DATA, &secret, Expiration::Never),
+            Err(InitDataError::HashMissing)
+        ));
+    }
+
+    #[test]
+    fn test_verify_with_async_provider() {
+        let remote = Remote(BotSecret::from_token(BOT_TOKEN).unwrap());
+        let init_data = format!("{BASE_DATA}&hash={}", sign::sign(BASE_DATA, BOT_TOKEN).unwrap());
+        let forged = format!("{BASE_DATA}&hash={}", "0".repeat(64));
+
+        assert!(block_on(verify_with_async_provider(&init_data, &remote, Expiration::Never)).is_ok());
+        assert!(matches!(
+            block_on(verify_with_async_provider(&forged, &remote, Expiration::Never)),
+            Err(InitDataError::HashInvalid)
+        ));
+    }
+}
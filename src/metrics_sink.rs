@@ -0,0 +1,49 @@
+//! Metrics of validation outcomes.
+//!
+//! A [`MetricsSink`] set with [`crate::Validator::with_metrics`] is told about every validation the
+//! validator runs, so dashboards don't require wrapping every call site.
+
+use std::time::Duration;
+
+use crate::error::InitDataError;
+use crate::scheme::ValidationScheme;
+
+/// Receives the outcome and the latency of validations.
+pub trait MetricsSink: Send + Sync {
+    /// Records a successful validation with `scheme` that took `elapsed`.
+    fn record_success(&self, scheme: ValidationScheme, elapsed: Duration);
+
+    /// Records a validation with `scheme` that failed with `error` after `elapsed`.
+    fn record_failure(&self, scheme: ValidationScheme, error: &InitDataError, elapsed: Duration);
+}
+
+/// [`MetricsSink`] reporting to the recorder installed for the [`metrics`](https://docs.rs/metrics) crate.
+///
+/// * `init_data_validations_total` counter, labelled with `outcome` (`success` or `failure`), `scheme`
+///   (`hash` or `signature`) and, for failures, `error` (see [`InitDataError::kind`])
+/// * `init_data_validation_duration_seconds` histogram, labelled with `scheme`
+#[cfg(feature = "metrics")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MetricsFacade;
+
+#[cfg(feature = "metrics")]
+impl MetricsSink for MetricsFacade {
+    fn record_success(&self, scheme: ValidationScheme, elapsed: Duration) {
+        metrics::counter!("init_data_validations_total", "outcome" => "success", "scheme" => scheme.as_str())
+            .increment(1);
+        metrics::histogram!("init_data_validation_duration_seconds", "scheme" => scheme.as_str())
+            .record(elapsed.as_secs_f64());
+    }
+
+    fn record_failure(&self, scheme: ValidationScheme, error: &InitDataError, elapsed: Duration) {
+        metrics::counter!(
+            "init_data_validations_total",
+            "outcome" => "failure",
+            "scheme" => scheme.as_str(),
+            "error" => error.kind()
+        )
+        .increment(1);
+        metrics::histogram!("init_data_validation_duration_seconds", "scheme" => scheme.as_str())
+            .record(elapsed.as_secs_f64());
+    }
+}
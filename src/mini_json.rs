@@ -0,0 +1,277 @@
+//! Minimal JSON reader used by the `mini-parse` feature instead of `serde_json`.
+//!
+//! It only supports what init data contains: flat objects with string, integer, boolean and null
+//! values. Nested objects and arrays of unknown fields are skipped.
+
+/// Streaming reader over a JSON document.
+pub(crate) struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+type Result<T> = std::result::Result<T, String>;
+
+impl<'a> Reader<'a> {
+    pub(crate) fn new(json: &'a str) -> Self {
+        Self {
+            bytes: json.as_bytes(),
+            pos: 0,
+        }
+    }
+
+    /// Fails unless only whitespace is left.
+    pub(crate) fn finish(mut self) -> Result<()> {
+        self.skip_whitespace();
+        if self.pos == self.bytes.len() {
+            Ok(())
+        } else {
+            Err(self.error("trailing characters"))
+        }
+    }
+
+    fn error(&self, message: &str) -> String {
+        format!("{message} at position {}", self.pos)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.bytes.get(self.pos), Some(b' ' | b'\t' | b'\n' | b'\r')) {
+            self.pos += 1;
+        }
+    }
+
+    fn peek(&mut self) -> Option<u8> {
+        self.skip_whitespace();
+        self.bytes.get(self.pos).copied()
+    }
+
+    fn expect(&mut self, byte: u8) -> Result<()> {
+        if self.peek() == Some(byte) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected `{}`", char::from(byte))))
+        }
+    }
+
+    fn literal(&mut self, literal: &[u8]) -> bool {
+        self.skip_whitespace();
+        if self.bytes[self.pos..].starts_with(literal) {
+            self.pos += literal.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Reads an object, calling `field` with every key; `field` must consume the value.
+    pub(crate) fn object(&mut self, mut field: impl FnMut(&str, &mut Self) -> Result<()>) -> Result<()> {
+        self.expect(b'{')?;
+        if self.peek() == Some(b'}') {
+            self.pos += 1;
+            return Ok(());
+        }
+
+        loop {
+            let key = self.string()?;
+            self.expect(b':')?;
+            field(&key, self)?;
+
+            match self.peek() {
+                Some(b',') => self.pos += 1,
+                Some(b'}') => {
+                    self.pos += 1;
+                    return Ok(());
+                }
+                _ => return Err(self.error("expected `,` or `}`")),
+            }
+        }
+    }
+
+    /// Reads `null` as `None`, anything else with `read`.
+    pub(crate) fn optional<T>(&mut self, read: impl FnOnce(&mut Self) -> Result<T>) -> Result<Option<T>> {
+        if self.literal(b"null") {
+            Ok(None)
+        } else {
+            read(self).map(Some)
+        }
+    }
+
+    pub(crate) fn bool(&mut self) -> Result<bool> {
+        if self.literal(b"true") {
+            Ok(true)
+        } else if self.literal(b"false") {
+            Ok(false)
+        } else {
+            Err(self.error("expected a boolean"))
+        }
+    }
+
+    pub(crate) fn i64(&mut self) -> Result<i64> {
+        self.skip_whitespace();
+        let start = self.pos;
+        if self.bytes.get(self.pos) == Some(&b'-') {
+            self.pos += 1;
+        }
+        while self.bytes.get(self.pos).is_some_and(u8::is_ascii_digit) {
+            self.pos += 1;
+        }
+
+        std::str::from_utf8(&self.bytes[start..self.pos])
+            .ok()
+            .and_then(|number| number.parse().ok())
+            .ok_or_else(|| self.error("expected an integer"))
+    }
+
+    pub(crate) fn string(&mut self) -> Result<String> {
+        self.expect(b'"')?;
+        let mut out = Vec::new();
+
+        loop {
+            let Some(&byte) = self.bytes.get(self.pos) else {
+                return Err(self.error("unterminated string"));
+            };
+            self.pos += 1;
+
+            match byte {
+                b'"' => break,
+                b'\\' => {
+                    let Some(&escape) = self.bytes.get(self.pos) else {
+                        return Err(self.error("unterminated string"));
+                    };
+                    self.pos += 1;
+
+                    let unescaped = match escape {
+                        b'"' => '"',
+                        b'\\' => '\\',
+                        b'/' => '/',
+                        b'b' => '\u{8}',
+                        b'f' => '\u{c}',
+                        b'n' => '\n',
+                        b'r' => '\r',
+                        b't' => '\t',
+                        b'u' => self.unicode_escape()?,
+                        _ => return Err(self.error("invalid escape")),
+                    };
+                    out.extend_from_slice(unescaped.encode_utf8(&mut [0; 4]).as_bytes());
+                }
+                0x00..=0x1f => return Err(self.error("control character in string")),
+                byte => out.push(byte),
+            }
+        }
+
+        String::from_utf8(out).map_err(|_| self.error("invalid UTF-8 in string"))
+    }
+
+    fn hex4(&mut self) -> Result<u32> {
+        let digits = self
+            .bytes
+            .get(self.pos..self.pos + 4)
+            .and_then(|digits| std::str::from_utf8(digits).ok())
+            .and_then(|digits| u32::from_str_radix(digits, 16).ok())
+            .ok_or_else(|| self.error("invalid unicode escape"))?;
+        self.pos += 4;
+        Ok(digits)
+    }
+
+    fn unicode_escape(&mut self) -> Result<char> {
+        let high = self.hex4()?;
+        let code = if (0xD800..0xDC00).contains(&high) {
+            if !self.bytes[self.pos..].starts_with(b"\\u") {
+                return Err(self.error("unpaired surrogate"));
+            }
+            self.pos += 2;
+            let low = self.hex4()?;
+            if !(0xDC00..0xE000).contains(&low) {
+                return Err(self.error("unpaired surrogate"));
+            }
+            0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)
+        } else {
+            high
+        };
+
+        char::from_u32(code).ok_or_else(|| self.error("invalid unicode escape"))
+    }
+
+    /// Skips any value, including nested objects and arrays.
+    pub(crate) fn skip(&mut self) -> Result<()> {
+        match self.peek() {
+            Some(b'"') => self.string().map(drop),
+            Some(b'{') => self.object(|_, reader| reader.skip()),
+            Some(b'[') => {
+                self.pos += 1;
+                if self.peek() == Some(b']') {
+                    self.pos += 1;
+                    return Ok(());
+                }
+                loop {
+                    self.skip()?;
+                    match self.peek() {
+                        Some(b',') => self.pos += 1,
+                        Some(b']') => {
+                            self.pos += 1;
+                            return Ok(());
+                        }
+                        _ => return Err(self.error("expected `,` or `]`")),
+                    }
+                }
+            }
+            Some(b't' | b'f') => self.bool().map(drop),
+            Some(b'n') if self.literal(b"null") => Ok(()),
+            Some(b'-' | b'0'..=b'9') => {
+                while self
+                    .bytes
+                    .get(self.pos)
+                    .is_some_and(|byte| matches!(byte, b'-' | b'+' | b'.' | b'e' | b'E' | b'0'..=b'9'))
+                {
+                    self.pos += 1;
+                }
+                Ok(())
+            }
+            _ => Err(self.error("expected a value")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reader_object() {
+        let mut reader = Reader::new(
+            r#" {"id": -12, "name": "a\"é😀\/", "ok": true, "none": null, "nested": {"x": [1, {"y": 2.5e3}]}} "#,
+        );
+        let mut fields = Vec::new();
+
+        reader
+            .object(|key, reader| {
+                let value = match key {
+                    "id" => reader.i64()?.to_string(),
+                    "name" => reader.string()?,
+                    "ok" => reader.bool()?.to_string(),
+                    "none" => format!("{:?}", reader.optional(Reader::bool)?),
+                    _ => reader.skip().map(|()| "skipped".to_string())?,
+                };
+                fields.push(format!("{key}={value}"));
+                Ok(())
+            })
+            .unwrap();
+        reader.finish().unwrap();
+
+        assert_eq!(
+            fields,
+            ["id=-12", "name=a\"é😀/", "ok=true", "none=None", "nested=skipped"]
+        );
+    }
+
+    #[test]
+    fn test_reader_errors() {
+        assert!(Reader::new(r#"{"id": }"#)
+            .object(|_, reader| reader.i64().map(drop))
+            .is_err());
+        assert!(Reader::new(r#"{"name": "abc"#)
+            .object(|_, reader| reader.string().map(drop))
+            .is_err());
+        assert!(Reader::new(r#"{"a": 1} x"#).finish().is_err());
+    }
+}
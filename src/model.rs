@@ -1,7 +1,19 @@
-use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::time::Duration;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+use serde::{Deserialize, Deserializer, Serialize};
+
+/// Encoded by name in JSON, and by the index of the variant in compact formats like postcard: new variants
+/// go last.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(
+    feature = "diesel",
+    derive(diesel::expression::AsExpression, diesel::deserialize::FromSqlRow)
+)]
+#[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Text))]
 #[serde(rename_all = "lowercase")]
+#[cfg_attr(feature = "borsh", derive(borsh::BorshSerialize, borsh::BorshDeserialize))]
 pub enum ChatType {
     Sender,
     Private,
@@ -10,23 +22,81 @@ pub enum ChatType {
     Channel,
 }
 
+impl ChatType {
+    /// The name Telegram uses for the chat type, e.g. `supergroup`.
+    #[must_use]
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Sender => "sender",
+            Self::Private => "private",
+            Self::Group => "group",
+            Self::Supergroup => "supergroup",
+            Self::Channel => "channel",
+        }
+    }
+}
+
+impl fmt::Display for ChatType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for ChatType {
+    type Err = crate::InitDataError;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "sender" => Ok(Self::Sender),
+            "private" => Ok(Self::Private),
+            "group" => Ok(Self::Group),
+            "supergroup" => Ok(Self::Supergroup),
+            "channel" => Ok(Self::Channel),
+            _ => Err(crate::InitDataError::UnexpectedFormat(format!(
+                "unknown chat type: {name}"
+            ))),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(
+    feature = "diesel",
+    derive(diesel::expression::AsExpression, diesel::deserialize::FromSqlRow)
+)]
+#[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Jsonb))]
+#[cfg_attr(feature = "borsh", derive(borsh::BorshSerialize, borsh::BorshDeserialize))]
 pub struct User {
+    #[serde(alias = "addedToAttachmentMenu")]
     pub added_to_attachment_menu: Option<bool>,
+    #[serde(alias = "allowsWriteToPm")]
     pub allows_write_to_pm: Option<bool>,
+    #[serde(alias = "firstName")]
     pub first_name: String,
     pub id: i64,
+    #[serde(alias = "isBot")]
     pub is_bot: Option<bool>,
+    #[serde(alias = "isPremium")]
     pub is_premium: Option<bool>,
+    #[serde(alias = "lastName")]
     pub last_name: Option<String>,
+    #[serde(alias = "languageCode")]
     pub language_code: Option<String>,
+    #[serde(alias = "photoUrl", default, deserialize_with = "deserialize_url")]
     pub photo_url: Option<String>,
     pub username: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(
+    feature = "diesel",
+    derive(diesel::expression::AsExpression, diesel::deserialize::FromSqlRow)
+)]
+#[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Jsonb))]
+#[cfg_attr(feature = "borsh", derive(borsh::BorshSerialize, borsh::BorshDeserialize))]
 pub struct Chat {
     pub id: i64,
+    #[serde(alias = "photoUrl", default, deserialize_with = "deserialize_url")]
     pub photo_url: Option<String>,
     #[serde(rename = "type")]
     pub chat_type: ChatType,
@@ -34,28 +104,164 @@ pub struct Chat {
     pub username: Option<String>,
 }
 
+impl User {
+    /// Encodes the user as JSON exactly like Telegram does in init data: fields in Telegram's order, absent ones
+    /// skipped, and forward slashes escaped. Fields the model doesn't know are lost, see
+    /// [`InitData::to_query_string`].
+    #[must_use]
+    pub fn to_telegram_json(&self) -> String {
+        crate::telegram_json::user(self)
+    }
+
+    /// `https://t.me/<username>`, `None` if the user has no username.
+    #[must_use]
+    pub fn username_link(&self) -> Option<String> {
+        username_link(self.username.as_deref())
+    }
+
+    /// `tg://user?id=<id>`, which opens the profile in Telegram apps even without a username, as long as the
+    /// user's privacy settings allow it.
+    #[must_use]
+    pub fn id_link(&self) -> String {
+        format!("tg://user?id={}", self.id)
+    }
+
+    /// A link to the profile of the user: [`User::username_link`] when they have a username, which also works
+    /// in browsers, and [`User::id_link`] otherwise.
+    ///
+    /// # Example
+    /// ```
+    /// use init_data_rs::parse;
+    ///
+    /// let data = parse("user=%7B%22id%22%3A42%2C%22first_name%22%3A%22Ada%22%7D&auth_date=1&hash=c501b71e775f74ce10e377dea85a7ea24ecd640b223ea86dfe453e0eaed2e2b2").unwrap();
+    /// let user = data.user.unwrap();
+    ///
+    /// assert_eq!(user.profile_link(), "tg://user?id=42");
+    /// assert_eq!(user.mention_html(), r#"<a href="tg://user?id=42">Ada</a>"#);
+    /// ```
+    #[must_use]
+    pub fn profile_link(&self) -> String {
+        self.username_link().unwrap_or_else(|| self.id_link())
+    }
+
+    /// First and last name, separated by a space.
+    #[must_use]
+    pub fn full_name(&self) -> String {
+        match self.last_name.as_deref() {
+            Some(last_name) if !last_name.is_empty() => format!("{} {last_name}", self.first_name),
+            _ => self.first_name.clone(),
+        }
+    }
+
+    /// An inline mention for messages sent with `parse_mode` `HTML`: the [`User::full_name`], HTML escaped,
+    /// linked to [`User::id_link`].
+    #[must_use]
+    pub fn mention_html(&self) -> String {
+        let mut html = format!("<a href=\"{}\">", self.id_link());
+        for char in self.full_name().chars() {
+            match char {
+                '<' => html.push_str("&lt;"),
+                '>' => html.push_str("&gt;"),
+                '&' => html.push_str("&amp;"),
+                '"' => html.push_str("&quot;"),
+                char => html.push(char),
+            }
+        }
+        html.push_str("</a>");
+        html
+    }
+}
+
+impl Chat {
+    /// Encodes the chat as JSON exactly like Telegram does in init data, see [`User::to_telegram_json`].
+    #[must_use]
+    pub fn to_telegram_json(&self) -> String {
+        crate::telegram_json::chat(self)
+    }
+
+    /// `https://t.me/<username>` for public groups and channels, `None` for private ones.
+    #[must_use]
+    pub fn username_link(&self) -> Option<String> {
+        username_link(self.username.as_deref())
+    }
+}
+
+/// `https://t.me/<username>`, unless the username is missing or couldn't have been issued by Telegram.
+fn username_link(username: Option<&str>) -> Option<String> {
+    let username = username?.trim_start_matches('@');
+    let valid = !username.is_empty()
+        && username
+            .bytes()
+            .all(|byte| byte.is_ascii_alphanumeric() || byte == b'_');
+
+    valid.then(|| format!("https://t.me/{username}"))
+}
+
+/// How the Mini App was launched, inferred by [`InitData::launch_context`] from the fields that are present.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LaunchContext {
+    /// From the attachment menu: `chat` is set in groups, supergroups and channels, `receiver` in private chats.
+    AttachmentMenu,
+    /// From a direct link or an inline keyboard button: `chat_type` and `chat_instance` identify the chat.
+    DirectLink,
+    /// From the menu button or the main Mini App in the side menu: only `query_id` is set, so the app can
+    /// answer through `answerWebAppQuery`.
+    SideMenu,
+    /// From a keyboard button or inline mode: nothing identifies where the app was opened.
+    KeyboardButton,
+}
+
+/// Who the user opened the Mini App with from the attachment menu, see [`InitData::counterpart`].
+#[derive(Debug, Clone, Copy)]
+pub enum Counterpart<'a> {
+    /// The chat partner in a private chat.
+    User(&'a User),
+    /// The group, supergroup or channel.
+    Chat(&'a Chat),
+}
+
+impl Counterpart<'_> {
+    /// Telegram id of the user or chat.
+    #[must_use]
+    pub fn id(&self) -> i64 {
+        match self {
+            Self::User(user) => user.id,
+            Self::Chat(chat) => chat.id,
+        }
+    }
+}
+
 /// This object contains data that is transferred to the Mini App when it is opened. It is empty if the Mini App was launched from a keyboard button or from inline mode.
 /// See: <https://core.telegram.org/bots/webapps#webappinitdata>
+///
+/// Serializes with Telegram's `snake_case` keys, but also deserializes from the `camelCase` keys (`authDate`,
+/// `queryId`, `firstName`, ...) some client SDKs re-serialize init data with.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "borsh", derive(borsh::BorshSerialize, borsh::BorshDeserialize))]
 pub struct InitData {
     /// Unix time when the form was opened.
+    #[serde(alias = "authDate")]
     pub auth_date: u64,
     /// Optional.
     /// Time in seconds, after which a message can be sent via the answerWebAppQuery method.
+    #[serde(alias = "canSendAfter")]
     pub can_send_after: Option<u32>,
     /// Optional.
     /// An object containing data about the chat where the bot was launched via the attachment menu. Returned for supergroups, channels and group chats – only for Mini Apps launched via the attachment menu.
     pub chat: Option<Chat>,
     /// Optional.
     /// Type of the chat from which the Mini App was opened. Can be either “sender” for a private chat with the user opening the link, “private”, “group”, “supergroup”, or “channel”. Returned only for Mini Apps launched from direct links.
+    #[serde(alias = "chatType")]
     pub chat_type: Option<ChatType>,
     /// Optional.
     /// Global identifier, uniquely corresponding to the chat from which the Mini App was opened. Returned only for Mini Apps launched from a direct link.
+    #[serde(alias = "chatInstance")]
     pub chat_instance: Option<i64>,
     /// A hash of all passed parameters, which the bot server can use to check their validity.
     pub hash: String,
     /// Optional.
     /// A unique identifier for the Mini App session, required for sending messages via the answerWebAppQuery method.
+    #[serde(alias = "queryId")]
     pub query_id: Option<String>,
     /// Optional.
     /// An object containing data about the chat partner of the current user in the chat where the bot was launched via the attachment menu. Returned only for private chats and only for Mini Apps launched via the attachment menu.
@@ -63,6 +269,7 @@ pub struct InitData {
     /// Optional.
     /// The value of the startattach parameter, passed via link. Only returned for Mini Apps when launched from the attachment menu via link.
     /// The value of the `start_param` parameter will also be passed in the GET-parameter tgWebAppStartParam, so the Mini App can load the correct interface right away.
+    #[serde(alias = "startParam")]
     pub start_param: Option<String>,
     /// Optional. An object containing data about the current user.
     pub user: Option<User>,
@@ -70,3 +277,526 @@ pub struct InitData {
     /// This field is only for third-party validation, shall be optional?
     pub signature: Option<String>,
 }
+
+impl InitData {
+    /// Time elapsed since `auth_date`, zero if `auth_date` is in the future.
+    ///
+    /// # Arguments
+    /// * `now` - Current Unix time in seconds, e.g. from [`crate::Clock::now`]
+    #[must_use]
+    pub fn age(&self, now: u64) -> Duration {
+        Duration::from_secs(now.saturating_sub(self.auth_date))
+    }
+
+    /// Whether the init data has expired at `now` under `expiration`, like the expiration argument of
+    /// [`crate::validate`] checks it.
+    ///
+    /// Only the timestamp is checked, it doesn't replace validation.
+    ///
+    /// # Arguments
+    /// * `expiration` - How long the init data stays valid, see [`crate::Expiration`]
+    /// * `now` - Current Unix time in seconds, e.g. from [`crate::Clock::now`]
+    #[must_use]
+    pub fn is_expired(&self, expiration: crate::Expiration, now: u64) -> bool {
+        expiration.is_expired(self.auth_date, now)
+    }
+
+    /// The `hash` as raw bytes, for storing or comparing it without the hex encoding.
+    ///
+    /// # Errors
+    ///
+    /// `InitDataError::HashInvalid` if the hash is not 64 hex characters, which [`crate::parse`] already rejects.
+    pub fn hash_bytes(&self) -> Result<[u8; 32], crate::InitDataError> {
+        crate::sign::decode_hash(&self.hash).ok_or(crate::InitDataError::HashInvalid)
+    }
+
+    /// The Ed25519 `signature` as raw bytes, `None` if there is none.
+    ///
+    /// # Errors
+    ///
+    /// `InitDataError::SignatureInvalid` if the signature is not a base64url encoded Ed25519 signature.
+    pub fn signature_bytes(&self) -> Result<Option<[u8; 64]>, crate::InitDataError> {
+        self.signature
+            .as_deref()
+            .map(crate::third_party_validation::decode_signature)
+            .transpose()
+    }
+
+    /// Moves the user out of the init data, e.g. when only the user is persisted.
+    ///
+    /// # Errors
+    ///
+    /// `InitDataError::UserMissing` if there is no `user`.
+    pub fn into_user(self) -> Result<User, crate::InitDataError> {
+        self.user.ok_or(crate::InitDataError::UserMissing)
+    }
+
+    /// Moves the chat out of the init data, only set when launched from the attachment menu.
+    ///
+    /// # Errors
+    ///
+    /// `InitDataError::ChatMissing` if there is no `chat`.
+    pub fn into_chat(self) -> Result<Chat, crate::InitDataError> {
+        self.chat.ok_or(crate::InitDataError::ChatMissing)
+    }
+
+    /// Infers how the Mini App was launched, see [`LaunchContext`] for the fields each launch sets.
+    ///
+    /// The first match wins: attachment menu, then direct link, then side menu.
+    #[must_use]
+    pub fn launch_context(&self) -> LaunchContext {
+        if self.chat.is_some() || self.receiver.is_some() {
+            LaunchContext::AttachmentMenu
+        } else if self.chat_type.is_some() || self.chat_instance.is_some() {
+            LaunchContext::DirectLink
+        } else if self.query_id.is_some() {
+            LaunchContext::SideMenu
+        } else {
+            LaunchContext::KeyboardButton
+        }
+    }
+
+    /// Whether the Mini App was launched from the attachment menu, which sets `chat` or `receiver`.
+    #[must_use]
+    pub fn is_attachment_menu_launch(&self) -> bool {
+        self.launch_context() == LaunchContext::AttachmentMenu
+    }
+
+    /// Whether the Mini App was launched from a direct link or an inline keyboard button, which set `chat_type`
+    /// and `chat_instance`.
+    #[must_use]
+    pub fn is_direct_link_launch(&self) -> bool {
+        self.launch_context() == LaunchContext::DirectLink
+    }
+
+    /// The type of the chat the Mini App was opened from: `chat_type`, else the type of the attachment menu
+    /// `chat`, else [`ChatType::Private`] for attachment menu launches with a `receiver`.
+    #[must_use]
+    pub fn effective_chat_type(&self) -> Option<ChatType> {
+        self.chat_type
+            .clone()
+            .or_else(|| self.chat.as_ref().map(|chat| chat.chat_type.clone()))
+            .or_else(|| self.receiver.as_ref().map(|_| ChatType::Private))
+    }
+
+    /// Whether the Mini App was opened from a private chat, with the bot (`sender`) or with another user.
+    #[must_use]
+    pub fn is_private_chat(&self) -> bool {
+        matches!(self.effective_chat_type(), Some(ChatType::Sender | ChatType::Private))
+    }
+
+    /// Whether the Mini App was opened from a group, supergroup or channel.
+    #[must_use]
+    pub fn is_group_chat(&self) -> bool {
+        matches!(
+            self.effective_chat_type(),
+            Some(ChatType::Group | ChatType::Supergroup | ChatType::Channel)
+        )
+    }
+
+    /// Whether the bot can reply through `answerWebAppQuery`, which needs a `query_id`.
+    #[must_use]
+    pub fn can_answer_query(&self) -> bool {
+        self.query_id.is_some()
+    }
+
+    /// The chat partner for private attachment menu launches, otherwise the chat, `None` outside the
+    /// attachment menu.
+    #[must_use]
+    pub fn counterpart(&self) -> Option<Counterpart<'_>> {
+        self.receiver
+            .as_ref()
+            .map(Counterpart::User)
+            .or(self.chat.as_ref().map(Counterpart::Chat))
+    }
+
+    /// Builds init data from its parameters as found in the query string, nested objects as JSON strings.
+    ///
+    /// Nothing is validated, see [`crate::parse`].
+    ///
+    /// # Errors
+    ///
+    /// See [`crate::parse`].
+    pub fn try_from_map(map: &BTreeMap<String, String>) -> Result<Self, crate::InitDataError> {
+        let init_data = form_urlencoded::Serializer::new(String::new())
+            .extend_pairs(map)
+            .finish();
+
+        crate::parse(&init_data)
+    }
+
+    /// Returns the parameters of the init data as found in the query string, nested objects encoded like
+    /// Telegram does, see [`User::to_telegram_json`].
+    #[must_use]
+    pub fn to_map(&self) -> BTreeMap<String, String> {
+        let mut map = BTreeMap::new();
+        map.insert("auth_date".to_string(), self.auth_date.to_string());
+        map.insert("hash".to_string(), self.hash.clone());
+        let optional = [
+            ("can_send_after", self.can_send_after.map(|value| value.to_string())),
+            ("chat", self.chat.as_ref().map(Chat::to_telegram_json)),
+            ("chat_type", self.chat_type.as_ref().map(ToString::to_string)),
+            ("chat_instance", self.chat_instance.map(|value| value.to_string())),
+            ("query_id", self.query_id.clone()),
+            ("receiver", self.receiver.as_ref().map(User::to_telegram_json)),
+            ("start_param", self.start_param.clone()),
+            ("user", self.user.as_ref().map(User::to_telegram_json)),
+            ("signature", self.signature.clone()),
+        ];
+        for (key, value) in optional {
+            if let Some(value) = value {
+                map.insert(key.to_string(), value);
+            }
+        }
+
+        map
+    }
+
+    /// Encodes the init data back into a query string, nested objects encoded like Telegram does, see
+    /// [`User::to_telegram_json`].
+    ///
+    /// The `hash` and `signature` of parsed init data still verify as long as the model kept every byte they
+    /// cover, which it doesn't when:
+    /// * the init data carries parameters, or `user`, `receiver` and `chat` fields, the model doesn't know, which
+    ///   are dropped;
+    /// * it was parsed with [`crate::ParseOptions::empty_strings_as_none`], or a `photo_url` escaped twice by the
+    ///   client was normalized;
+    /// * fields were blanked or replaced afterwards, e.g. by [`crate::PrivacyMode`] or [`InitData::anonymized`].
+    ///
+    /// Forward the raw string instead to keep the init data exactly as received, see [`crate::RawInitData`].
+    ///
+    /// # Example
+    /// ```
+    /// use init_data_rs::{parse, sign, verify, Expiration};
+    ///
+    /// let params = "user=%7B%22id%22%3A1%2C%22first_name%22%3A%22A%22%2C%22photo_url%22%3A%22https%3A%5C%2F%5C%2Ft.me%5C%2Fa.svg%22%7D&auth_date=1662771648";
+    /// let init_data = format!("{params}&hash={}", sign(params, "123:BOT_TOKEN").unwrap());
+    ///
+    /// let reencoded = parse(&init_data).unwrap().to_query_string();
+    /// assert!(verify(&reencoded, "123:BOT_TOKEN", Expiration::Never).is_ok());
+    /// ```
+    #[must_use]
+    pub fn to_query_string(&self) -> String {
+        form_urlencoded::Serializer::new(String::new())
+            .extend_pairs(self.to_map())
+            .finish()
+    }
+}
+
+/// Compact summary for log lines, without names, usernames, photos or other personal data:
+/// `user=279058397 chat_type=private auth_date=1662771648 premium=true`.
+impl fmt::Display for InitData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(user) = &self.user {
+            write!(f, "user={} ", user.id)?;
+        }
+        if let Some(chat) = &self.chat {
+            write!(f, "chat={} ", chat.id)?;
+        }
+        if let Some(chat_type) = &self.chat_type {
+            write!(f, "chat_type={chat_type} ")?;
+        }
+        write!(f, "auth_date={}", self.auth_date)?;
+        if let Some(premium) = self.user.as_ref().and_then(|user| user.is_premium) {
+            write!(f, " premium={premium}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Turns the `\/` escapes some clients leave in URLs, e.g. `https:\/\/t.me\/...` from JSON escaped twice, back
+/// into slashes. A URL never contains a backslash otherwise.
+pub(crate) fn normalize_url(url: String) -> String {
+    if url.contains("\\/") {
+        url.replace("\\/", "/")
+    } else {
+        url
+    }
+}
+
+fn deserialize_url<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<String>, D::Error> {
+    Ok(Option::<String>::deserialize(deserializer)?.map(normalize_url))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse, Expiration};
+
+    #[test]
+    fn test_init_data_age() {
+        let data =
+            parse("auth_date=1000&hash=c501b71e775f74ce10e377dea85a7ea24ecd640b223ea86dfe453e0eaed2e2b2").unwrap();
+
+        assert_eq!(data.age(1030), Duration::from_secs(30));
+        assert_eq!(data.age(900), Duration::ZERO);
+        let minute = Expiration::After(Duration::from_secs(60));
+        assert!(!data.is_expired(minute, 1060));
+        assert!(data.is_expired(minute, 1061));
+        assert!(!data.is_expired(Expiration::Never, u64::MAX));
+        assert!(data.is_expired(Expiration::Default, 1000 + 86401));
+
+        // The largest auth_date parses, and adding the ttl saturates instead of wrapping
+        let data = parse(&format!(
+            "auth_date={}&hash=c501b71e775f74ce10e377dea85a7ea24ecd640b223ea86dfe453e0eaed2e2b2",
+            u64::MAX
+        ))
+        .unwrap();
+        assert!(!data.is_expired(Expiration::After(Duration::MAX), u64::MAX));
+        assert_eq!(data.age(0), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_init_data_hash_and_signature_bytes() {
+        let mut data = parse("auth_date=1&signature=zL-ucjNyREiHDE8aihFwpfR9aggP2xiAo3NSpfe-p7IbCisNlDKlo7Kb6G4D0Ao2mBrSgEk4maLSdv6MLIlADQ&hash=c501b71e775f74ce10e377dea85a7ea24ecd640b223ea86dfe453e0eaed2e2b2").unwrap();
+
+        assert_eq!(data.hash_bytes().unwrap()[..4], [0xc5, 0x01, 0xb7, 0x1e]);
+        assert_eq!(data.signature_bytes().unwrap().unwrap()[..4], [0xcc, 0xbf, 0xae, 0x72]);
+
+        data.signature = Some("abc".to_string());
+        assert!(matches!(
+            data.signature_bytes(),
+            Err(crate::InitDataError::SignatureInvalid(_))
+        ));
+        data.signature = None;
+        assert_eq!(data.signature_bytes().unwrap(), None);
+        data.hash = "abc".to_string();
+        assert!(matches!(data.hash_bytes(), Err(crate::InitDataError::HashInvalid)));
+    }
+
+    #[test]
+    fn test_init_data_into_user_and_chat() {
+        let data = crate::parse(
+            "user=%7B%22id%22%3A1%2C%22first_name%22%3A%22Ada%22%7D&auth_date=1&hash=c501b71e775f74ce10e377dea85a7ea24ecd640b223ea86dfe453e0eaed2e2b2",
+        )
+        .unwrap();
+
+        assert!(matches!(
+            data.clone().into_chat(),
+            Err(crate::InitDataError::ChatMissing)
+        ));
+        assert_eq!(data.into_user().unwrap().first_name, "Ada");
+    }
+
+    #[test]
+    fn test_init_data_launch_context() {
+        let context = |fields: &str| {
+            parse(&format!(
+                "{fields}auth_date=1&hash=c501b71e775f74ce10e377dea85a7ea24ecd640b223ea86dfe453e0eaed2e2b2"
+            ))
+            .unwrap()
+            .launch_context()
+        };
+
+        assert_eq!(
+            context("receiver=%7B%22id%22%3A1%2C%22first_name%22%3A%22A%22%7D&chat_type=sender&"),
+            LaunchContext::AttachmentMenu
+        );
+        assert_eq!(
+            context("chat=%7B%22id%22%3A-1%2C%22type%22%3A%22group%22%2C%22title%22%3A%22T%22%7D&"),
+            LaunchContext::AttachmentMenu
+        );
+        assert_eq!(
+            context("query_id=AAHdF6IQAAAAAN0XohDhrOrc&chat_type=group&chat_instance=-42&"),
+            LaunchContext::DirectLink
+        );
+        assert_eq!(context("query_id=AAHdF6IQAAAAAN0XohDhrOrc&"), LaunchContext::SideMenu);
+        assert_eq!(context(""), LaunchContext::KeyboardButton);
+    }
+
+    #[test]
+    fn test_init_data_attachment_menu_helpers() {
+        let data = |fields: &str| {
+            parse(&format!(
+                "{fields}auth_date=1&hash=c501b71e775f74ce10e377dea85a7ea24ecd640b223ea86dfe453e0eaed2e2b2"
+            ))
+            .unwrap()
+        };
+
+        let private = data("receiver=%7B%22id%22%3A7%2C%22first_name%22%3A%22A%22%7D&");
+        assert!(private.is_attachment_menu_launch());
+        assert!(private.is_private_chat());
+        assert_eq!(private.effective_chat_type(), Some(ChatType::Private));
+        assert!(matches!(private.counterpart(), Some(Counterpart::User(user)) if user.id == 7));
+
+        let group = data("chat=%7B%22id%22%3A-1%2C%22type%22%3A%22supergroup%22%2C%22title%22%3A%22T%22%7D&");
+        assert!(group.is_attachment_menu_launch());
+        assert!(group.is_group_chat());
+        assert_eq!(group.counterpart().map(|counterpart| counterpart.id()), Some(-1));
+
+        let direct = data("query_id=AAHdF6IQAAAAAN0XohDhrOrc&chat_type=sender&chat_instance=-42&");
+        assert!(direct.is_direct_link_launch());
+        assert!(!direct.is_attachment_menu_launch());
+        assert!(direct.is_private_chat());
+        assert!(direct.can_answer_query());
+        assert!(direct.counterpart().is_none());
+
+        let keyboard = data("");
+        assert!(!keyboard.is_private_chat() && !keyboard.is_group_chat());
+        assert_eq!(keyboard.effective_chat_type(), None);
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_init_data_camel_case_keys() {
+        let data: InitData = serde_json::from_str(
+            r#"{"authDate":1,"hash":"abc","queryId":"q","startParam":"ref","chatType":"group","chatInstance":-42,
+                "user":{"id":1,"firstName":"Ada","languageCode":"en","isPremium":true}}"#,
+        )
+        .unwrap();
+
+        assert_eq!(data.auth_date, 1);
+        assert_eq!(data.query_id.as_deref(), Some("q"));
+        assert_eq!(data.start_param.as_deref(), Some("ref"));
+        assert_eq!(data.chat_type, Some(ChatType::Group));
+        assert_eq!(data.chat_instance, Some(-42));
+        let user = data.user.unwrap();
+        assert_eq!((user.first_name.as_str(), user.is_premium), ("Ada", Some(true)));
+        assert_eq!(user.language_code.as_deref(), Some("en"));
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_to_query_string_roundtrip() {
+        let sign = |params: &str| format!("{params}&hash={}", crate::sign(params, "123:BOT_TOKEN").unwrap());
+        let verify = |init_data: &str| crate::verify(init_data, "123:BOT_TOKEN", Expiration::Never);
+
+        let known = sign("auth_date=1&user=%7B%22id%22%3A1%2C%22first_name%22%3A%22A%22%7D");
+        assert!(verify(&parse(&known).unwrap().to_query_string()).is_ok());
+
+        // `added_to_bot` isn't in the model: it is dropped, and the hash no longer matches
+        let unknown =
+            sign("auth_date=1&user=%7B%22id%22%3A1%2C%22first_name%22%3A%22A%22%2C%22added_to_bot%22%3Atrue%7D");
+        assert!(verify(&unknown).is_ok());
+        let reencoded = parse(&unknown).unwrap().to_query_string();
+        assert!(!reencoded.contains("added_to_bot"));
+        assert!(matches!(verify(&reencoded), Err(crate::InitDataError::HashInvalid)));
+    }
+
+    #[cfg(feature = "borsh")]
+    #[test]
+    fn test_borsh_roundtrip() {
+        let init_data = "user=%7B%22id%22%3A1%2C%22first_name%22%3A%22A%22%2C%22is_premium%22%3Atrue%7D&chat=%7B%22id%22%3A-1001%2C%22title%22%3A%22Dev%22%2C%22type%22%3A%22channel%22%7D&chat_type=channel&start_param=ref&auth_date=1&hash=c501b71e775f74ce10e377dea85a7ea24ecd640b223ea86dfe453e0eaed2e2b2";
+        let data = crate::parse(init_data).unwrap();
+
+        let bytes = borsh::to_vec(&data).unwrap();
+        let decoded: InitData = borsh::from_slice(&bytes).unwrap();
+
+        assert_eq!(decoded.to_map(), data.to_map());
+        assert_eq!(borsh::to_vec(&ChatType::Channel).unwrap(), [4]);
+        assert!(borsh::from_slice::<InitData>(&bytes[..bytes.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn test_photo_url_unescaped() {
+        let parse_with = |key: &str, json: &str| {
+            parse(
+                &form_urlencoded::Serializer::new(String::new())
+                    .append_pair(key, json)
+                    .append_pair("auth_date", "1")
+                    .append_pair(
+                        "hash",
+                        "c501b71e775f74ce10e377dea85a7ea24ecd640b223ea86dfe453e0eaed2e2b2",
+                    )
+                    .finish(),
+            )
+            .unwrap()
+        };
+
+        // Captured from a Telegram client
+        let data = parse_with(
+            "user",
+            r#"{"id":279058397,"first_name":"Vladislav","photo_url":"https:\/\/t.me\/i\/userpic\/320\/4FPEE4tmP3ATHa57u6MqTDih13LTOiMoKoLDRG4PnSA.svg"}"#,
+        );
+        assert_eq!(
+            data.user.unwrap().photo_url.as_deref(),
+            Some("https://t.me/i/userpic/320/4FPEE4tmP3ATHa57u6MqTDih13LTOiMoKoLDRG4PnSA.svg")
+        );
+
+        // Escaped twice by a client
+        let data = parse_with(
+            "user",
+            r#"{"id":1,"first_name":"A","photo_url":"https:\\/\\/t.me\\/a.svg"}"#,
+        );
+        assert_eq!(data.user.unwrap().photo_url.as_deref(), Some("https://t.me/a.svg"));
+        let data = parse_with(
+            "chat",
+            r#"{"id":-1,"title":"T","type":"group","photo_url":"https:\\/\\/t.me\\/c.svg"}"#,
+        );
+        assert_eq!(data.chat.unwrap().photo_url.as_deref(), Some("https://t.me/c.svg"));
+    }
+
+    #[test]
+    fn test_user_links() {
+        let data = parse("user=%7B%22id%22%3A279058397%2C%22first_name%22%3A%22Vladislav%22%2C%22last_name%22%3A%22%3Cb%3EK%26K%3C%2Fb%3E%22%2C%22username%22%3A%22vdkfrost%22%7D&chat=%7B%22id%22%3A-1%2C%22title%22%3A%22T%22%2C%22type%22%3A%22group%22%2C%22username%22%3A%22a%2Fb%22%7D&auth_date=1&hash=c501b71e775f74ce10e377dea85a7ea24ecd640b223ea86dfe453e0eaed2e2b2").unwrap();
+        let mut user = data.user.unwrap();
+
+        assert_eq!(user.profile_link(), "https://t.me/vdkfrost");
+        assert_eq!(user.id_link(), "tg://user?id=279058397");
+        assert_eq!(user.full_name(), "Vladislav <b>K&K</b>");
+        assert_eq!(
+            user.mention_html(),
+            r#"<a href="tg://user?id=279058397">Vladislav &lt;b&gt;K&amp;K&lt;/b&gt;</a>"#
+        );
+
+        user.username = None;
+        assert_eq!(user.username_link(), None);
+        assert_eq!(user.profile_link(), "tg://user?id=279058397");
+        user.username = Some(String::new());
+        assert_eq!(user.profile_link(), "tg://user?id=279058397");
+
+        // Not something Telegram issues, so no link is made from it
+        assert_eq!(data.chat.unwrap().username_link(), None);
+    }
+
+    #[test]
+    fn test_chat_type_from_str() {
+        for chat_type in [
+            ChatType::Sender,
+            ChatType::Private,
+            ChatType::Group,
+            ChatType::Supergroup,
+            ChatType::Channel,
+        ] {
+            assert_eq!(chat_type.as_str().parse::<ChatType>().unwrap(), chat_type);
+        }
+        assert!("Group".parse::<ChatType>().is_err());
+    }
+
+    #[test]
+    fn test_init_data_display_is_redacted() {
+        let data = parse("user=%7B%22id%22%3A279058397%2C%22first_name%22%3A%22Vladislav%22%2C%22username%22%3A%22vdkfrost%22%2C%22is_premium%22%3Atrue%7D&chat_type=private&query_id=AAHdF6IQAAAAAN0XohDhrOrc&auth_date=1662771648&hash=c501b71e775f74ce10e377dea85a7ea24ecd640b223ea86dfe453e0eaed2e2b2").unwrap();
+
+        assert_eq!(
+            data.to_string(),
+            "user=279058397 chat_type=private auth_date=1662771648 premium=true"
+        );
+        assert_eq!(
+            parse("auth_date=1&hash=c501b71e775f74ce10e377dea85a7ea24ecd640b223ea86dfe453e0eaed2e2b2")
+                .unwrap()
+                .to_string(),
+            "auth_date=1"
+        );
+    }
+
+    #[test]
+    fn test_init_data_map_roundtrip() {
+        let data = parse("query_id=AAHdF6IQAAAAAN0XohDhrOrc&user=%7B%22id%22%3A279058397%2C%22first_name%22%3A%22Vladislav%22%2C%22is_premium%22%3Atrue%7D&chat_type=sender&auth_date=1662771648&hash=c501b71e775f74ce10e377dea85a7ea24ecd640b223ea86dfe453e0eaed2e2b2").unwrap();
+
+        let map = data.to_map();
+        assert_eq!(
+            map.keys().collect::<Vec<_>>(),
+            ["auth_date", "chat_type", "hash", "query_id", "user"]
+        );
+        assert_eq!(
+            map["user"],
+            r#"{"id":279058397,"first_name":"Vladislav","is_premium":true}"#
+        );
+
+        let data = InitData::try_from_map(&map).unwrap();
+        assert_eq!(data.user.unwrap().id, 279058397);
+        assert_eq!(data.query_id.as_deref(), Some("AAHdF6IQAAAAAN0XohDhrOrc"));
+
+        assert!(InitData::try_from_map(&BTreeMap::new()).is_err());
+    }
+}
@@ -0,0 +1,259 @@
+//! Node.js native addon built with `napi-rs`.
+//!
+//! Enabled with the `napi` feature. Every function is exported in a synchronous flavour and an
+//! `*Async` flavour that runs the validation on the libuv thread pool and returns a `Promise`. The addon is
+//! the shared library built with `--crate-type cdylib`, renamed to `.node`:
+//!
+//! ```sh
+//! cargo rustc --lib --release --features napi --crate-type cdylib
+//! cp target/release/libinit_data_rs.so init-data-rs.node
+//! ```
+//!
+//! Following Node conventions, object properties are exposed in camelCase. 64-bit integers
+//! (`authDate`, ids and `chatInstance`) are returned as `bigint`s so they never lose precision.
+//!
+//! `expiresIn` is in seconds: `undefined` for [`crate::Expiration::Default`] and `0` for
+//! [`crate::Expiration::Never`].
+
+use napi::bindgen_prelude::{AsyncTask, BigInt};
+use napi::{Env, Error, Result, Task};
+use napi_derive::napi;
+
+use crate::InitDataError;
+
+#[napi(string_enum = "lowercase")]
+pub enum ChatType {
+    Sender,
+    Private,
+    Group,
+    Supergroup,
+    Channel,
+}
+
+#[napi(object)]
+pub struct User {
+    pub added_to_attachment_menu: Option<bool>,
+    pub allows_write_to_pm: Option<bool>,
+    pub first_name: String,
+    pub id: BigInt,
+    pub is_bot: Option<bool>,
+    pub is_premium: Option<bool>,
+    pub last_name: Option<String>,
+    pub language_code: Option<String>,
+    pub photo_url: Option<String>,
+    pub username: Option<String>,
+}
+
+#[napi(object)]
+pub struct Chat {
+    pub id: BigInt,
+    pub photo_url: Option<String>,
+    #[napi(js_name = "type")]
+    pub chat_type: ChatType,
+    pub title: String,
+    pub username: Option<String>,
+}
+
+#[napi(object)]
+pub struct InitData {
+    pub auth_date: BigInt,
+    pub can_send_after: Option<u32>,
+    pub chat: Option<Chat>,
+    pub chat_type: Option<ChatType>,
+    pub chat_instance: Option<BigInt>,
+    pub hash: String,
+    pub query_id: Option<String>,
+    pub receiver: Option<User>,
+    pub start_param: Option<String>,
+    pub user: Option<User>,
+    pub signature: Option<String>,
+}
+
+impl From<crate::ChatType> for ChatType {
+    fn from(chat_type: crate::ChatType) -> Self {
+        match chat_type {
+            crate::ChatType::Sender => Self::Sender,
+            crate::ChatType::Private => Self::Private,
+            crate::ChatType::Group => Self::Group,
+            crate::ChatType::Supergroup => Self::Supergroup,
+            crate::ChatType::Channel => Self::Channel,
+        }
+    }
+}
+
+impl From<crate::User> for User {
+    fn from(user: crate::User) -> Self {
+        Self {
+            added_to_attachment_menu: user.added_to_attachment_menu,
+            allows_write_to_pm: user.allows_write_to_pm,
+            first_name: user.first_name,
+            id: user.id.into(),
+            is_bot: user.is_bot,
+            is_premium: user.is_premium,
+            last_name: user.last_name,
+            language_code: user.language_code,
+            photo_url: user.photo_url,
+            username: user.username,
+        }
+    }
+}
+
+impl From<crate::Chat> for Chat {
+    fn from(chat: crate::Chat) -> Self {
+        Self {
+            id: chat.id.into(),
+            photo_url: chat.photo_url,
+            chat_type: chat.chat_type.into(),
+            title: chat.title,
+            username: chat.username,
+        }
+    }
+}
+
+impl From<crate::InitData> for InitData {
+    fn from(data: crate::InitData) -> Self {
+        Self {
+            auth_date: data.auth_date.into(),
+            can_send_after: data.can_send_after,
+            chat: data.chat.map(Into::into),
+            chat_type: data.chat_type.map(Into::into),
+            chat_instance: data.chat_instance.map(Into::into),
+            hash: data.hash,
+            query_id: data.query_id,
+            receiver: data.receiver.map(Into::into),
+            start_param: data.start_param,
+            user: data.user.map(Into::into),
+            signature: data.signature,
+        }
+    }
+}
+
+#[allow(clippy::needless_pass_by_value)]
+fn to_napi_error(error: InitDataError) -> Error {
+    Error::from_reason(error.to_string())
+}
+
+/// Validates init data using the bot token, see [`crate::validate`].
+///
+/// # Errors
+///
+/// Throws a JavaScript `Error` carrying the message of the underlying [`InitDataError`].
+#[napi]
+#[allow(clippy::needless_pass_by_value)]
+pub fn validate(init_data: String, token: String, expires_in: Option<u32>) -> Result<InitData> {
+    crate::validate(
+        &init_data,
+        &token,
+        crate::Expiration::from_seconds(expires_in.map(u64::from)),
+    )
+    .map(Into::into)
+    .map_err(to_napi_error)
+}
+
+/// Validates init data for third-party use, see [`crate::validate_third_party`].
+///
+/// # Errors
+///
+/// Throws a JavaScript `Error` carrying the message of the underlying [`InitDataError`].
+#[napi]
+#[allow(clippy::needless_pass_by_value)]
+pub fn validate_third_party(init_data: String, bot_id: i64, expires_in: Option<u32>) -> Result<InitData> {
+    crate::validate_third_party(
+        &init_data,
+        bot_id,
+        crate::Expiration::from_seconds(expires_in.map(u64::from)),
+    )
+    .map(Into::into)
+    .map_err(to_napi_error)
+}
+
+/// Parses init data without validating it, see [`crate::parse`].
+///
+/// # Errors
+///
+/// Throws a JavaScript `Error` carrying the message of the underlying [`InitDataError`].
+#[napi]
+#[allow(clippy::needless_pass_by_value)]
+pub fn parse(init_data: String) -> Result<InitData> {
+    crate::parse(&init_data).map(Into::into).map_err(to_napi_error)
+}
+
+/// Computes the hash of init data using the bot token, see [`crate::sign`].
+///
+/// # Errors
+///
+/// Throws a JavaScript `Error` carrying the message of the underlying [`InitDataError`].
+#[napi]
+#[allow(clippy::needless_pass_by_value)]
+pub fn sign(init_data: String, token: String) -> Result<String> {
+    crate::sign(&init_data, &token).map_err(to_napi_error)
+}
+
+/// Background validation job backing the `*Async` exports.
+pub enum ValidateTask {
+    BotToken {
+        init_data: String,
+        token: String,
+        expires_in: Option<u32>,
+    },
+    ThirdParty {
+        init_data: String,
+        bot_id: i64,
+        expires_in: Option<u32>,
+    },
+}
+
+impl Task for ValidateTask {
+    type Output = crate::InitData;
+    type JsValue = InitData;
+
+    fn compute(&mut self) -> Result<Self::Output> {
+        match self {
+            Self::BotToken {
+                init_data,
+                token,
+                expires_in,
+            } => crate::validate(
+                init_data,
+                token,
+                crate::Expiration::from_seconds(expires_in.map(u64::from)),
+            ),
+            Self::ThirdParty {
+                init_data,
+                bot_id,
+                expires_in,
+            } => crate::validate_third_party(
+                init_data,
+                *bot_id,
+                crate::Expiration::from_seconds(expires_in.map(u64::from)),
+            ),
+        }
+        .map_err(to_napi_error)
+    }
+
+    fn resolve(&mut self, _env: Env, output: Self::Output) -> Result<Self::JsValue> {
+        Ok(output.into())
+    }
+}
+
+/// Same as [`validate`], but runs on the libuv thread pool and returns a `Promise`.
+#[must_use]
+#[napi(ts_return_type = "Promise<InitData>")]
+pub fn validate_async(init_data: String, token: String, expires_in: Option<u32>) -> AsyncTask<ValidateTask> {
+    AsyncTask::new(ValidateTask::BotToken {
+        init_data,
+        token,
+        expires_in,
+    })
+}
+
+/// Same as [`validate_third_party`], but runs on the libuv thread pool and returns a `Promise`.
+#[must_use]
+#[napi(ts_return_type = "Promise<InitData>")]
+pub fn validate_third_party_async(init_data: String, bot_id: i64, expires_in: Option<u32>) -> AsyncTask<ValidateTask> {
+    AsyncTask::new(ValidateTask::ThirdParty {
+        init_data,
+        bot_id,
+        expires_in,
+    })
+}
@@ -0,0 +1,133 @@
+//! Binding init data to the session that opened the Mini App, through a nonce in `start_param`.
+//!
+//! Init data stays valid for hours and can be forwarded: anyone holding it can replay it from their own session.
+//! For sensitive flows, the service puts a nonce derived from the caller's session into the deep link, and
+//! accepts the init data only from that session, where the `start_param` matches.
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD as base64_engine;
+use base64::Engine as _;
+
+use crate::crypto::{self, Hmac};
+use crate::error::InitDataError;
+use crate::model::InitData;
+
+/// Bytes of the HMAC kept in a nonce, 32 characters once encoded.
+const NONCE_LEN: usize = 24;
+
+/// Derives and checks `start_param` nonces bound to a session, see the module docs.
+///
+/// A nonce is the truncated HMAC of the session id keyed with a server secret: it can't be forged for another
+/// session without the secret, and no state needs to be stored.
+///
+/// # Example
+/// ```
+/// use init_data_rs::{deep_link, NonceBinder};
+///
+/// let binder = NonceBinder::new(b"server secret");
+/// let link = deep_link::builder("my_bot", "pay")
+///     .start_param(binder.bind_nonce("session-42"))
+///     .build()
+///     .unwrap();
+///
+/// // Once the init data of the Mini App is validated
+/// # let start_param = binder.bind_nonce("session-42");
+/// assert!(binder.verify(Some(&start_param), "session-42").is_ok());
+/// assert!(binder.verify(Some(&start_param), "session-7").is_err());
+/// ```
+#[derive(Clone)]
+pub struct NonceBinder {
+    hmac: Hmac,
+}
+
+impl NonceBinder {
+    /// Creates a binder keyed with `key`, a secret of the service that must not be the bot token.
+    ///
+    /// # Panics
+    ///
+    /// Never: HMAC accepts keys of any length.
+    pub fn new(key: impl AsRef<[u8]>) -> Self {
+        Self {
+            hmac: Hmac::new(key.as_ref()).expect("HMAC accepts keys of any length"),
+        }
+    }
+
+    /// Returns the nonce of `session_id` to pass as `start_param`, a valid one per
+    /// [`crate::is_valid_start_param`].
+    #[must_use]
+    pub fn bind_nonce(&self, session_id: &str) -> String {
+        base64_engine.encode(self.nonce(session_id))
+    }
+
+    /// Checks that `start_param`, of validated init data, is the nonce of `session_id`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`InitDataError::StartParamInvalid`] if `start_param` is missing or is not the nonce of
+    /// `session_id`.
+    pub fn verify(&self, start_param: Option<&str>, session_id: &str) -> Result<(), InitDataError> {
+        let start_param =
+            start_param.ok_or_else(|| InitDataError::StartParamInvalid("start_param is missing".to_string()))?;
+        let matches = base64_engine
+            .decode(start_param)
+            .is_ok_and(|nonce| crypto::constant_time_eq(&nonce, &self.nonce(session_id)));
+
+        if !matches {
+            return Err(InitDataError::StartParamInvalid(
+                "not the nonce of the session".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Same as [`NonceBinder::verify`], with the `start_param` of `data`.
+    ///
+    /// # Errors
+    ///
+    /// See [`NonceBinder::verify`].
+    pub fn verify_data(&self, data: &InitData, session_id: &str) -> Result<(), InitDataError> {
+        self.verify(data.start_param.as_deref(), session_id)
+    }
+
+    fn nonce(&self, session_id: &str) -> [u8; NONCE_LEN] {
+        let mut hmac = self.hmac.clone();
+        hmac.update(b"start_param-nonce\0");
+        hmac.update(session_id.as_bytes());
+
+        let mac = hmac.finalize().expect("HMAC-SHA256 of a message in memory can't fail");
+        let mut nonce = [0; NONCE_LEN];
+        nonce.copy_from_slice(&mac[..NONCE_LEN]);
+        nonce
+    }
+}
+
+impl std::fmt::Debug for NonceBinder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("NonceBinder(***)")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::is_valid_start_param;
+
+    #[test]
+    fn test_nonce_binder() {
+        let binder = NonceBinder::new("secret");
+        let nonce = binder.bind_nonce("session-42");
+
+        assert_eq!(nonce.len(), 32);
+        assert!(is_valid_start_param(&nonce));
+        assert_eq!(binder.bind_nonce("session-42"), nonce);
+        assert_ne!(NonceBinder::new("other secret").bind_nonce("session-42"), nonce);
+
+        assert!(binder.verify(Some(&nonce), "session-42").is_ok());
+        for start_param in [None, Some("ref_1"), Some(&nonce[..31]), Some("!")] {
+            assert!(matches!(
+                binder.verify(start_param, "session-42"),
+                Err(InitDataError::StartParamInvalid(_))
+            ));
+        }
+        assert!(binder.verify(Some(&nonce), "session-7").is_err());
+    }
+}
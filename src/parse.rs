@@ -1,12 +1,44 @@
-use serde_json::Value;
-use std::collections::BTreeMap;
-use url::form_urlencoded;
+#[cfg(not(feature = "mini-parse"))]
+use serde::de::IgnoredAny;
 
 use crate::error::InitDataError;
-use crate::model::InitData;
+#[cfg(feature = "mini-parse")]
+use crate::mini_json::Reader;
+#[cfg(feature = "mini-parse")]
+use crate::model::normalize_url;
+use crate::model::{Chat, ChatType, InitData, User};
+use crate::query;
+use crate::validation::extract_auth_date;
 
+#[cfg(not(feature = "mini-parse"))]
 const STRING_PROPS: [&str; 1] = ["start_param"];
 
+/// Limits enforced on init data before it is deserialized, and how the fields are normalized after.
+///
+/// The `user`, `receiver` and `chat` values are attacker-controlled JSON, so oversized or deeply
+/// nested values are rejected with [`InitDataError::PayloadTooLarge`] up front.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseOptions {
+    /// Maximum decoded length of a single parameter value in bytes, 16 KiB by default.
+    pub max_value_len: usize,
+    /// Maximum nesting depth of JSON objects and arrays within a value, 16 by default.
+    pub max_depth: usize,
+    /// Turns empty `last_name`, `username` and `photo_url` strings into `None`, off by default.
+    ///
+    /// Telegram sends `"last_name":""` for users without a last name, which is otherwise kept as `Some("")`.
+    pub empty_strings_as_none: bool,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self {
+            max_value_len: 16 * 1024,
+            max_depth: 16,
+            empty_strings_as_none: false,
+        }
+    }
+}
+
 /// Parse converts passed init data presented as query string to `InitData` object.
 ///
 /// # Errors
@@ -18,14 +50,28 @@ const STRING_PROPS: [&str; 1] = ["start_param"];
 /// - hash is invalid
 /// - init data has unexpected format
 /// - signature is invalid
+/// - a value exceeds the default [`ParseOptions`] limits
 /// - the library has an internal error while hmac-ing the string. this should never happen
 ///
-/// # Panics
-///
-/// This function will panic if the hash field is missing from the parameters after
-/// the initial existence check. This should never happen in normal usage as the
-/// function returns an error before reaching the unwrap call.
 pub fn parse(init_data: &str) -> Result<InitData, InitDataError> {
+    parse_with_options(init_data, &ParseOptions::default())
+}
+
+/// Same as [`parse`], but with custom size and depth limits.
+///
+/// # Errors
+///
+/// See [`parse`].
+pub fn parse_with_options(init_data: &str, options: &ParseOptions) -> Result<InitData, InitDataError> {
+    parse_into(init_data, options, &mut Vec::with_capacity(init_data.len() + 64))
+}
+
+/// Same as [`parse_with_options`], but decodes into a reusable `buffer`.
+pub(crate) fn parse_into(
+    init_data: &str,
+    options: &ParseOptions,
+    buffer: &mut Vec<u8>,
+) -> Result<InitData, InitDataError> {
     if init_data.is_empty() {
         return Err(InitDataError::UnexpectedFormat("init_data is empty".to_string()));
     }
@@ -36,55 +82,496 @@ pub fn parse(init_data: &str) -> Result<InitData, InitDataError> {
         ));
     }
 
-    let pairs = form_urlencoded::parse(init_data.as_bytes());
-    let mut params: BTreeMap<String, String> = BTreeMap::new();
+    check_limits(init_data, options)?;
 
-    for (key, value) in pairs {
-        params.insert(key.to_string(), value.into_owned());
+    buffer.clear();
+    let mut data = deserialize(init_data, buffer)?;
+
+    if options.empty_strings_as_none {
+        for user in [&mut data.user, &mut data.receiver].into_iter().flatten() {
+            empty_as_none(&mut user.last_name);
+            empty_as_none(&mut user.username);
+            empty_as_none(&mut user.photo_url);
+        }
+        if let Some(chat) = &mut data.chat {
+            empty_as_none(&mut chat.username);
+            empty_as_none(&mut chat.photo_url);
+        }
     }
 
-    if !params.contains_key("auth_date") {
-        return Err(InitDataError::AuthDateMissing);
+    Ok(data)
+}
+
+fn empty_as_none(value: &mut Option<String>) {
+    if value.as_deref() == Some("") {
+        *value = None;
     }
+}
+
+/// Init data as far as [`parse_lossy`] could read it.
+///
+/// Every field is optional, `auth_date` and `hash` included: a field is `None` when it is missing or malformed.
+#[derive(Debug, Clone, Default)]
+pub struct PartialInitData {
+    /// See [`InitData::auth_date`].
+    pub auth_date: Option<u64>,
+    /// See [`InitData::can_send_after`].
+    pub can_send_after: Option<u32>,
+    /// See [`InitData::chat`].
+    pub chat: Option<Chat>,
+    /// See [`InitData::chat_type`].
+    pub chat_type: Option<ChatType>,
+    /// See [`InitData::chat_instance`].
+    pub chat_instance: Option<i64>,
+    /// See [`InitData::hash`].
+    pub hash: Option<String>,
+    /// See [`InitData::query_id`].
+    pub query_id: Option<String>,
+    /// See [`InitData::receiver`].
+    pub receiver: Option<User>,
+    /// See [`InitData::start_param`].
+    pub start_param: Option<String>,
+    /// See [`InitData::user`].
+    pub user: Option<User>,
+    /// See [`InitData::signature`].
+    pub signature: Option<String>,
+}
 
-    if !params.contains_key("hash") {
-        return Err(InitDataError::HashMissing);
+impl PartialInitData {
+    /// Returns the init data if its required `auth_date` and `hash` could be read, dropping nothing else.
+    #[must_use]
+    pub fn into_init_data(self) -> Option<InitData> {
+        Some(InitData {
+            auth_date: self.auth_date?,
+            can_send_after: self.can_send_after,
+            chat: self.chat,
+            chat_type: self.chat_type,
+            chat_instance: self.chat_instance,
+            hash: self.hash?,
+            query_id: self.query_id,
+            receiver: self.receiver,
+            start_param: self.start_param,
+            user: self.user,
+            signature: self.signature,
+        })
     }
+}
+
+/// A parameter [`parse_lossy`] could not read.
+#[derive(Debug, Clone)]
+pub struct FieldError {
+    /// Name of the parameter, e.g. `user`.
+    pub field: &'static str,
+    /// Why it could not be read, the error [`parse`] would have returned for it.
+    pub error: InitDataError,
+}
 
-    // Validate hash format (should be a 64-character hex string)
-    let hash = params.get("hash").unwrap(); // Safe to unwrap since we checked existence above
-    if hash.len() != 64 || !hash.chars().all(|c| c.is_ascii_hexdigit()) {
-        return Err(InitDataError::HashInvalid);
+impl std::fmt::Display for FieldError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "`{}`: {}", self.field, self.error)
     }
+}
 
-    if let Some(signature) = params.get("signature") {
-        // Basic signature format validation (should be base64 URL-safe or hex)
-        // Allow base64 URL-safe characters (A-Z, a-z, 0-9, -, _) and standard base64 characters (+, /, =)
-        if !signature
-            .chars()
-            .all(|c| c.is_ascii_alphanumeric() || c == '+' || c == '/' || c == '=' || c == '-' || c == '_')
-        {
-            return Err(InitDataError::SignatureInvalid("Invalid signature format".to_string()));
+/// Reads every field of `init_data` it can and reports the others, instead of failing on the first one.
+///
+/// Meant for logging and support tooling inspecting broken init data: a malformed `user` still leaves the
+/// `auth_date`, `chat` and `query_id` readable. Missing `auth_date` and `hash` are reported, unknown parameters
+/// are ignored and values exceeding the default [`ParseOptions`] limits are reported as
+/// [`InitDataError::PayloadTooLarge`]. Nothing is validated, see [`crate::validate`].
+///
+/// # Example
+/// ```
+/// use init_data_rs::parse_lossy;
+///
+/// let (data, errors) = parse_lossy("auth_date=1662771648&user=%7B%22id%22%3A1%7D&query_id=AAHdF6IQ");
+///
+/// assert_eq!(data.auth_date, Some(1_662_771_648));
+/// assert_eq!(data.query_id.as_deref(), Some("AAHdF6IQ"));
+/// assert!(data.user.is_none());
+/// assert_eq!(errors.iter().map(|err| err.field).collect::<Vec<_>>(), ["hash", "user"]);
+/// ```
+#[must_use]
+pub fn parse_lossy(init_data: &str) -> (PartialInitData, Vec<FieldError>) {
+    let mut raw: [Option<&str>; FIELDS.len()] = [None; FIELDS.len()];
+    for (raw_key, raw_value) in query::raw_pairs(init_data) {
+        let key = decoded(raw_key);
+        // Later occurrences of a key win, like in `parse`
+        if let Some(index) = FIELDS.iter().position(|field| *field == key) {
+            raw[index] = Some(raw_value);
         }
     }
+    let [auth_date, can_send_after, chat, chat_type, chat_instance, hash, query_id, receiver, start_param, user, signature] =
+        raw;
+
+    let mut errors = Vec::new();
+    if auth_date.is_none() {
+        errors.push(FieldError {
+            field: "auth_date",
+            error: InitDataError::AuthDateMissing,
+        });
+    }
+    if hash.is_none() {
+        errors.push(FieldError {
+            field: "hash",
+            error: InitDataError::HashMissing,
+        });
+    }
+
+    let data = PartialInitData {
+        auth_date: lossy_field(&mut errors, "auth_date", auth_date, |_| extract_auth_date(init_data)),
+        can_send_after: lossy_field(&mut errors, "can_send_after", can_send_after, |value| {
+            number("can_send_after", value)
+        }),
+        chat: lossy_field(&mut errors, "chat", chat, |value| chat_object("chat", value)),
+        chat_type: lossy_field(&mut errors, "chat_type", chat_type, |value| decoded(value).parse()),
+        chat_instance: lossy_field(&mut errors, "chat_instance", chat_instance, |value| {
+            number("chat_instance", value)
+        }),
+        hash: lossy_field(&mut errors, "hash", hash, |value| {
+            is_valid_hash(value)
+                .then(|| decoded(value))
+                .ok_or(InitDataError::HashInvalid)
+        }),
+        query_id: lossy_field(&mut errors, "query_id", query_id, |value| Ok(decoded(value))),
+        receiver: lossy_field(&mut errors, "receiver", receiver, |value| {
+            user_object("receiver", value)
+        }),
+        start_param: lossy_field(&mut errors, "start_param", start_param, |value| Ok(decoded(value))),
+        user: lossy_field(&mut errors, "user", user, |value| user_object("user", value)),
+        signature: lossy_field(&mut errors, "signature", signature, |value| {
+            is_valid_signature(value)
+                .then(|| decoded(value))
+                .ok_or_else(|| InitDataError::SignatureInvalid("Invalid signature format".to_string()))
+        }),
+    };
 
-    let json_pairs: Vec<String> = params
-        .iter()
-        .map(|(k, v)| {
-            if !STRING_PROPS.contains(&k.as_str()) && serde_json::from_str::<Value>(v).is_ok() {
-                format!("\"{k}\":{v}")
-            } else {
-                format!("\"{k}\":\"{}\"", v.replace('\"', "\\\""))
+    // Reported in the order of the parameters in `FIELDS`, not the order they were read in
+    errors.sort_by_key(|err| FIELDS.iter().position(|field| *field == err.field));
+    (data, errors)
+}
+
+/// Reads the raw value of `field` with `read`, pushing the error to `errors` if it fails.
+fn lossy_field<T>(
+    errors: &mut Vec<FieldError>,
+    field: &'static str,
+    raw_value: Option<&str>,
+    read: impl FnOnce(&str) -> Result<T, InitDataError>,
+) -> Option<T> {
+    let raw_value = raw_value?;
+    match check_value(field, raw_value, &ParseOptions::default()).and_then(|()| read(raw_value)) {
+        Ok(value) => Some(value),
+        Err(error) => {
+            errors.push(FieldError { field, error });
+            None
+        }
+    }
+}
+
+/// Rejects values longer or more deeply nested than `options` allow, without allocating.
+fn check_limits(init_data: &str, options: &ParseOptions) -> Result<(), InitDataError> {
+    query::raw_pairs(init_data).try_for_each(|(raw_key, raw_value)| check_value(raw_key, raw_value, options))
+}
+
+/// Same as [`check_limits`], for a single parameter.
+fn check_value(raw_key: &str, raw_value: &str, options: &ParseOptions) -> Result<(), InitDataError> {
+    let too_large = |limit: &str| {
+        let key = String::from_utf8_lossy(&query::decode(raw_key).collect::<Vec<_>>()).into_owned();
+        InitDataError::PayloadTooLarge(format!("`{key}` exceeds the {limit}"))
+    };
+
+    // Decoding never makes a value longer, so only long raw values need to be measured
+    if raw_value.len() > options.max_value_len && query::decode(raw_value).count() > options.max_value_len {
+        return Err(too_large(&format!("maximum length of {} bytes", options.max_value_len)));
+    }
+
+    let (mut depth, mut in_string, mut escaped) = (0usize, false, false);
+    for byte in query::decode(raw_value) {
+        match byte {
+            _ if escaped => escaped = false,
+            b'\\' if in_string => escaped = true,
+            b'"' => in_string = !in_string,
+            b'{' | b'[' if !in_string => {
+                depth += 1;
+                if depth > options.max_depth {
+                    return Err(too_large(&format!("maximum depth of {}", options.max_depth)));
+                }
             }
-        })
-        .collect();
+            b'}' | b']' if !in_string => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+fn is_valid_hash(raw_value: &str) -> bool {
+    let mut len = 0;
+    let is_hex = query::decode(raw_value)
+        .inspect(|_| len += 1)
+        .all(|c| c.is_ascii_hexdigit());
+    is_hex && len == 64
+}
+
+fn is_valid_signature(raw_value: &str) -> bool {
+    // Basic signature format validation (should be base64 URL-safe or hex)
+    // Allow base64 URL-safe characters (A-Z, a-z, 0-9, -, _) and standard base64 characters (+, /, =)
+    query::decode(raw_value).all(|c| c.is_ascii_alphanumeric() || matches!(c, b'+' | b'/' | b'=' | b'-' | b'_'))
+}
+
+/// Reports missing or malformed required parameters, before any field is deserialized.
+fn check_required(has_auth_date: bool, hash_valid: Option<bool>, signature_valid: bool) -> Result<(), InitDataError> {
+    if !has_auth_date {
+        return Err(InitDataError::AuthDateMissing);
+    }
+
+    match hash_valid {
+        None => return Err(InitDataError::HashMissing),
+        Some(false) => return Err(InitDataError::HashInvalid),
+        Some(true) => {}
+    }
+
+    if !signature_valid {
+        return Err(InitDataError::SignatureInvalid("Invalid signature format".to_string()));
+    }
+
+    Ok(())
+}
+
+#[cfg(not(feature = "mini-parse"))]
+fn deserialize(init_data: &str, json: &mut Vec<u8>) -> Result<InitData, InitDataError> {
+    // The whole query string is decoded into a single JSON object buffer, which is then deserialized in place.
+    // Decoding never makes a component longer, so the buffer only grows when string values need escaping.
+    let mut has_auth_date = false;
+    let mut hash_valid = None;
+    let mut signature_valid = true;
+
+    // Later occurrences of a key win: reversing first makes the stable sort keep them in front for `dedup_by`.
+    // Sorting keeps thousands of parameters from taking quadratic time.
+    let mut pairs: Vec<(&str, &str)> = query::raw_pairs(init_data).collect();
+    pairs.reverse();
+    pairs.sort_by(|a, b| query::decode(a.0).cmp(query::decode(b.0)));
+    pairs.dedup_by(|a, b| query::decoded_eq(a.0, b.0));
+
+    json.push(b'{');
+    for (raw_key, raw_value) in pairs {
+        if json.len() > 1 {
+            json.push(b',');
+        }
+
+        let key_start = json.len() + 1;
+        query::write_json_string(json, query::decode(raw_key));
+        let key = &json[key_start..json.len() - 1];
+        let is_string_prop = STRING_PROPS.iter().any(|prop| prop.as_bytes() == key);
+
+        match key {
+            b"auth_date" => has_auth_date = true,
+            b"hash" => hash_valid = Some(is_valid_hash(raw_value)),
+            b"signature" => signature_valid = is_valid_signature(raw_value),
+            _ => {}
+        }
+
+        json.push(b':');
+        let value_start = json.len();
+        json.extend(query::decode(raw_value));
+        if is_string_prop || serde_json::from_slice::<IgnoredAny>(&json[value_start..]).is_err() {
+            json.truncate(value_start);
+            query::write_json_string(json, query::decode(raw_value));
+        }
+    }
+    json.push(b'}');
+
+    check_required(has_auth_date, hash_valid, signature_valid)?;
+    // Checked up front so that a negative or overflowing value gets a clearer error than serde's
+    extract_auth_date(init_data)?;
+
+    let result = match std::str::from_utf8(json) {
+        Ok(json) => serde_json::from_str::<InitData>(json),
+        // Invalid UTF-8 sequences are replaced rather than rejected, like `form_urlencoded` does
+        Err(_) => serde_json::from_str::<InitData>(&String::from_utf8_lossy(json)),
+    };
+
+    result.map_err(|err| InitDataError::UnexpectedFormat(err.to_string()))
+}
+
+/// Fields of [`InitData`], in the order [`parse_lossy`] and the `mini-parse` deserializer store their raw values.
+const FIELDS: [&str; 11] = [
+    "auth_date",
+    "can_send_after",
+    "chat",
+    "chat_type",
+    "chat_instance",
+    "hash",
+    "query_id",
+    "receiver",
+    "start_param",
+    "user",
+    "signature",
+];
+
+#[cfg(feature = "mini-parse")]
+fn deserialize(init_data: &str, key: &mut Vec<u8>) -> Result<InitData, InitDataError> {
+    let mut raw: [Option<&str>; FIELDS.len()] = [None; FIELDS.len()];
+    for (raw_key, raw_value) in query::raw_pairs(init_data) {
+        key.clear();
+        key.extend(query::decode(raw_key));
+        // Later occurrences of a key overwrite earlier ones
+        if let Some(index) = FIELDS.iter().position(|field| field.as_bytes() == key.as_slice()) {
+            raw[index] = Some(raw_value);
+        }
+    }
+    let [auth_date, can_send_after, chat, chat_type, chat_instance, hash, query_id, receiver, start_param, user, signature] =
+        raw;
+
+    check_required(
+        auth_date.is_some(),
+        hash.map(is_valid_hash),
+        signature.is_none_or(is_valid_signature),
+    )?;
+
+    Ok(InitData {
+        auth_date: extract_auth_date(init_data)?,
+        can_send_after: can_send_after
+            .map(|value| number("can_send_after", value))
+            .transpose()?,
+        chat: chat.map(|value| object("chat", value, read_chat)).transpose()?,
+        chat_type: chat_type.map(|value| self::chat_type(&decoded(value))).transpose()?,
+        chat_instance: chat_instance.map(|value| number("chat_instance", value)).transpose()?,
+        hash: decoded(hash.unwrap_or_default()),
+        query_id: query_id.map(decoded),
+        receiver: receiver.map(|value| object("receiver", value, read_user)).transpose()?,
+        start_param: start_param.map(decoded),
+        user: user.map(|value| object("user", value, read_user)).transpose()?,
+        signature: signature.map(decoded),
+    })
+}
+
+/// Decodes a raw query component, replacing invalid UTF-8 sequences like `form_urlencoded` does.
+fn decoded(raw_value: &str) -> String {
+    let bytes: Vec<u8> = query::decode(raw_value).collect();
+    match String::from_utf8(bytes) {
+        Ok(value) => value,
+        Err(err) => String::from_utf8_lossy(err.as_bytes()).into_owned(),
+    }
+}
+
+fn number<T: std::str::FromStr>(field: &str, raw_value: &str) -> Result<T, InitDataError> {
+    let value = decoded(raw_value);
+    value
+        .parse()
+        .map_err(|_| InitDataError::UnexpectedFormat(format!("invalid number `{value}` for field `{field}`")))
+}
+
+#[cfg(feature = "mini-parse")]
+fn object<T>(field: &str, raw_value: &str, read: fn(&mut Reader) -> Result<T, String>) -> Result<T, InitDataError> {
+    let json = decoded(raw_value);
+    let mut reader = Reader::new(&json);
+    read(&mut reader)
+        .and_then(|value| reader.finish().map(|()| value))
+        .map_err(|err| InitDataError::UnexpectedFormat(format!("invalid field `{field}`: {err}")))
+}
+
+/// Reads the `user` or `receiver` JSON object in `raw_value`, like [`parse`] does.
+#[cfg(not(feature = "mini-parse"))]
+fn user_object(field: &str, raw_value: &str) -> Result<User, InitDataError> {
+    json_object(field, raw_value)
+}
+
+/// Reads the `chat` JSON object in `raw_value`, like [`parse`] does.
+#[cfg(not(feature = "mini-parse"))]
+fn chat_object(field: &str, raw_value: &str) -> Result<Chat, InitDataError> {
+    json_object(field, raw_value)
+}
 
-    let json_str = format!("{{{}}}", json_pairs.join(","));
+#[cfg(not(feature = "mini-parse"))]
+fn json_object<T: serde::de::DeserializeOwned>(field: &str, raw_value: &str) -> Result<T, InitDataError> {
+    serde_json::from_str(&decoded(raw_value))
+        .map_err(|err| InitDataError::UnexpectedFormat(format!("invalid field `{field}`: {err}")))
+}
 
-    let result =
-        serde_json::from_str::<InitData>(&json_str).map_err(|err| InitDataError::UnexpectedFormat(err.to_string()))?;
+#[cfg(feature = "mini-parse")]
+fn user_object(field: &str, raw_value: &str) -> Result<User, InitDataError> {
+    object(field, raw_value, read_user)
+}
 
-    Ok(result)
+#[cfg(feature = "mini-parse")]
+fn chat_object(field: &str, raw_value: &str) -> Result<Chat, InitDataError> {
+    object(field, raw_value, read_chat)
+}
+
+#[cfg(feature = "mini-parse")]
+fn chat_type(value: &str) -> Result<ChatType, InitDataError> {
+    match value {
+        "sender" => Ok(ChatType::Sender),
+        "private" => Ok(ChatType::Private),
+        "group" => Ok(ChatType::Group),
+        "supergroup" => Ok(ChatType::Supergroup),
+        "channel" => Ok(ChatType::Channel),
+        _ => Err(InitDataError::UnexpectedFormat(format!("unknown chat type `{value}`"))),
+    }
+}
+
+#[cfg(feature = "mini-parse")]
+fn read_user(reader: &mut Reader) -> Result<User, String> {
+    let (mut first_name, mut id) = (None, None);
+    let mut user = User {
+        added_to_attachment_menu: None,
+        allows_write_to_pm: None,
+        first_name: String::new(),
+        id: 0,
+        is_bot: None,
+        is_premium: None,
+        last_name: None,
+        language_code: None,
+        photo_url: None,
+        username: None,
+    };
+
+    reader.object(|key, reader| {
+        match key {
+            "added_to_attachment_menu" => user.added_to_attachment_menu = reader.optional(Reader::bool)?,
+            "allows_write_to_pm" => user.allows_write_to_pm = reader.optional(Reader::bool)?,
+            "first_name" => first_name = Some(reader.string()?),
+            "id" => id = Some(reader.i64()?),
+            "is_bot" => user.is_bot = reader.optional(Reader::bool)?,
+            "is_premium" => user.is_premium = reader.optional(Reader::bool)?,
+            "last_name" => user.last_name = reader.optional(Reader::string)?,
+            "language_code" => user.language_code = reader.optional(Reader::string)?,
+            "photo_url" => user.photo_url = reader.optional(Reader::string)?.map(normalize_url),
+            "username" => user.username = reader.optional(Reader::string)?,
+            _ => reader.skip()?,
+        }
+        Ok(())
+    })?;
+
+    user.first_name = first_name.ok_or("missing field `first_name`")?;
+    user.id = id.ok_or("missing field `id`")?;
+    Ok(user)
+}
+
+#[cfg(feature = "mini-parse")]
+fn read_chat(reader: &mut Reader) -> Result<Chat, String> {
+    let (mut id, mut chat_type, mut title) = (None, None, None);
+    let (mut photo_url, mut username) = (None, None);
+
+    reader.object(|key, reader| {
+        match key {
+            "id" => id = Some(reader.i64()?),
+            "photo_url" => photo_url = reader.optional(Reader::string)?.map(normalize_url),
+            "type" => chat_type = Some(self::chat_type(&reader.string()?).map_err(|err| err.to_string())?),
+            "title" => title = Some(reader.string()?),
+            "username" => username = reader.optional(Reader::string)?,
+            _ => reader.skip()?,
+        }
+        Ok(())
+    })?;
+
+    Ok(Chat {
+        id: id.ok_or("missing field `id`")?,
+        photo_url,
+        chat_type: chat_type.ok_or("missing field `type`")?,
+        title: title.ok_or("missing field `title`")?,
+        username,
+    })
 }
 
 #[cfg(test)]
@@ -147,6 +634,74 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_receiver_and_chat_type() {
+        let init_data = "receiver=%7B%22id%22%3A42%2C%22first_name%22%3A%22Bob%22%2C%22is_bot%22%3Afalse%2C%22extra%22%3A%5B1%2C%7B%7D%5D%7D&chat_type=private&chat_type=group&can_send_after=10&auth_date=1748683232&hash=c8fdc0e1608154171a77ef4ce838d114b0229d891ee55ac1ee566f14551433e8";
+        let result = parse(init_data).unwrap();
+
+        let receiver = result.receiver.unwrap();
+        assert_eq!(
+            (receiver.id, receiver.first_name.as_str(), receiver.is_bot),
+            (42, "Bob", Some(false))
+        );
+        assert!(matches!(result.chat_type, Some(ChatType::Group)));
+        assert_eq!(result.can_send_after, Some(10));
+    }
+
+    #[test]
+    fn test_parse_limits() {
+        let nested = format!(
+            "user={}&auth_date=1748683232&hash=c8fdc0e1608154171a77ef4ce838d114b0229d891ee55ac1ee566f14551433e8",
+            "%5B".repeat(20)
+        );
+        assert!(matches!(parse(&nested), Err(InitDataError::PayloadTooLarge(_))));
+
+        let options = ParseOptions {
+            max_value_len: 64,
+            ..ParseOptions::default()
+        };
+        let long = "start_param=abcdefghijklmnopqrstuvwxyzabcdefghijklmnopqrstuvwxyzabcdefghijklm&auth_date=1748683232&hash=c8fdc0e1608154171a77ef4ce838d114b0229d891ee55ac1ee566f14551433e8";
+        assert!(matches!(
+            parse_with_options(long, &options),
+            Err(InitDataError::PayloadTooLarge(_))
+        ));
+        // Percent-encoded values are measured after decoding
+        let encoded = long.replace("abcdefghijklm&", "%61%62%63%64%65%66%67%68%69%6A%6B%6C&");
+        assert!(parse_with_options(&encoded, &options).is_ok());
+
+        // Brackets inside JSON strings don't count towards the depth
+        let options = ParseOptions {
+            max_depth: 1,
+            ..ParseOptions::default()
+        };
+        let init_data = "user=%7B%22id%22%3A1%2C%22first_name%22%3A%22%5B%5B%5C%22%7B%22%7D&auth_date=1748683232&hash=c8fdc0e1608154171a77ef4ce838d114b0229d891ee55ac1ee566f14551433e8";
+        assert_eq!(
+            parse_with_options(init_data, &options)
+                .unwrap()
+                .user
+                .unwrap()
+                .first_name,
+            "[[\"{"
+        );
+    }
+
+    #[test]
+    fn test_parse_empty_strings_as_none() {
+        let init_data = "user=%7B%22id%22%3A1%2C%22first_name%22%3A%22A%22%2C%22last_name%22%3A%22%22%2C%22username%22%3A%22%22%2C%22photo_url%22%3A%22u%22%7D&auth_date=1748683232&hash=c8fdc0e1608154171a77ef4ce838d114b0229d891ee55ac1ee566f14551433e8";
+
+        let user = parse(init_data).unwrap().user.unwrap();
+        assert_eq!(user.last_name.as_deref(), Some(""));
+
+        let options = ParseOptions {
+            empty_strings_as_none: true,
+            ..ParseOptions::default()
+        };
+        let user = parse_with_options(init_data, &options).unwrap().user.unwrap();
+        assert_eq!(user.last_name, None);
+        assert_eq!(user.username, None);
+        assert_eq!(user.photo_url.as_deref(), Some("u"));
+    }
+
     #[test]
     fn test_parse_start_param() {
         let init_data = "start_param=test123&auth_date=1748683232&signature=abc&hash=c8fdc0e1608154171a77ef4ce838d114b0229d891ee55ac1ee566f14551433e8";
@@ -154,6 +709,15 @@ mod tests {
         assert_eq!(result.start_param, Some("test123".to_string()));
     }
 
+    #[test]
+    fn test_parse_many_parameters() {
+        // Unknown and duplicate parameters are dropped by sorting, in O(n log n); a quadratic pass took seconds here
+        let mut init_data = (0..50_000).map(|i| format!("p{i}=x")).collect::<Vec<_>>().join("&");
+        init_data.push_str("&query_id=first&auth_date=1662771648&query_id=last&hash=c8fdc0e1608154171a77ef4ce838d114b0229d891ee55ac1ee566f14551433e8");
+
+        assert_eq!(parse(&init_data).unwrap().query_id.as_deref(), Some("last"));
+    }
+
     #[test]
     fn test_parse_missing_auth_date() {
         let init_data = "hash=c8fdc0e1608154171a77ef4ce838d114b0229d891ee55ac1ee566f14551433e8";
@@ -186,6 +750,48 @@ mod tests {
     fn test_parse_invalid_auth_date_format() {
         let init_data = "auth_date=not_a_number&hash=c8fdc0e1608154171a77ef4ce838d114b0229d891ee55ac1ee566f14551433e8";
         let result = parse(init_data);
-        assert!(matches!(result, Err(InitDataError::UnexpectedFormat(_))));
+        assert!(matches!(result, Err(InitDataError::AuthDateInvalid { value }) if value == "not_a_number"));
+
+        for auth_date in ["-1662771648", "%2B1662771648", "1662771648.5", "18446744073709551616"] {
+            let init_data =
+                format!("auth_date={auth_date}&hash=c8fdc0e1608154171a77ef4ce838d114b0229d891ee55ac1ee566f14551433e8");
+            assert!(matches!(parse(&init_data), Err(InitDataError::AuthDateInvalid { .. })));
+        }
+    }
+
+    #[test]
+    fn test_parse_lossy() {
+        let (data, errors) = parse_lossy(PARSE_TEST_INIT_DATA);
+        assert!(errors.is_empty());
+        let data = data.into_init_data().unwrap();
+        assert_eq!(data.user.unwrap().id, 6_601_562_775);
+        assert_eq!(data.chat_type, Some(ChatType::Sender));
+
+        let broken = PARSE_TEST_INIT_DATA
+            .replace("user=%7B%22id%22", "user=%7B%22ID%22")
+            .replace("chat_type=sender", "chat_type=forum")
+            .replace("auth_date=1748683232", "auth_date=yesterday");
+        let (data, errors) = parse_lossy(&broken);
+        assert!(data.user.is_none() && data.chat_type.is_none() && data.auth_date.is_none());
+        assert_eq!(data.chat_instance, Some(-8_599_080_687_359_297_588));
+        assert!(data.hash.is_some() && data.signature.is_some());
+        assert_eq!(
+            errors.iter().map(|err| err.field).collect::<Vec<_>>(),
+            ["auth_date", "chat_type", "user"]
+        );
+        assert!(matches!(errors[0].error, InitDataError::AuthDateInvalid { .. }));
+        assert!(errors[2].to_string().starts_with("`user`: "));
+        assert!(data.into_init_data().is_none());
+
+        let (data, errors) = parse_lossy(&format!("user={}&hash=nope", "%5B".repeat(20)));
+        assert!(data.user.is_none() && data.hash.is_none());
+        assert!(matches!(
+            errors.iter().map(|err| &err.error).collect::<Vec<_>>()[..],
+            [
+                InitDataError::AuthDateMissing,
+                InitDataError::HashInvalid,
+                InitDataError::PayloadTooLarge(_)
+            ]
+        ));
     }
 }
@@ -0,0 +1,195 @@
+//! Decoded parameters of init data, for inspecting them before validation.
+
+use std::borrow::Cow;
+
+use crate::clock::{Clock, SystemClock};
+use crate::error::InitDataError;
+use crate::expiration::Expiration;
+#[cfg(feature = "model")]
+use crate::model::InitData;
+use crate::secret::TokenProvider;
+
+/// The decoded key/value pairs of init data, in their original order, next to the raw string.
+///
+/// Middlewares that route on a parameter (e.g. `start_param`) can read it here and hand the same value
+/// on to validation. The checks still run on the raw string, so the results are exactly the ones of
+/// [`crate::sign`], [`crate::verify`], [`crate::validate`] and [`crate::parse`].
+///
+/// # Example
+/// ```
+/// use init_data_rs::{Expiration, ParsedQuery};
+///
+/// let query = ParsedQuery::new("query_id=123&start_param=ref%3Dabc&auth_date=1662771648&hash=...");
+/// assert_eq!(query.get("start_param"), Some("ref=abc"));
+///
+/// if query.get("start_param").is_some_and(|start_param| start_param.starts_with("ref=")) {
+///     let result = query.verify("BOT_TOKEN", Expiration::Default);
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ParsedQuery<'a> {
+    raw: &'a str,
+    pairs: Vec<(Cow<'a, str>, Cow<'a, str>)>,
+}
+
+impl<'a> ParsedQuery<'a> {
+    /// Decodes the parameters of `init_data`, nothing is validated.
+    #[must_use]
+    pub fn new(init_data: &'a str) -> Self {
+        Self {
+            raw: init_data,
+            pairs: form_urlencoded::parse(init_data.as_bytes()).collect(),
+        }
+    }
+
+    /// The raw init data.
+    #[must_use]
+    pub fn as_str(&self) -> &'a str {
+        self.raw
+    }
+
+    /// The decoded value of `key`, the last one if it occurs several times, like validation does.
+    #[must_use]
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.pairs.iter().rev().find(|(k, _)| k == key).map(|(_, v)| v.as_ref())
+    }
+
+    /// The decoded key/value pairs in their original order, duplicates included.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.pairs.iter().map(|(k, v)| (k.as_ref(), v.as_ref()))
+    }
+
+    /// Number of pairs, duplicates included.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.pairs.len()
+    }
+
+    /// Whether there are no pairs at all.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.pairs.is_empty()
+    }
+
+    /// Same as [`crate::sign`].
+    ///
+    /// # Errors
+    ///
+    /// See [`crate::sign`].
+    pub fn sign(&self, token: &(impl TokenProvider + ?Sized)) -> Result<String, InitDataError> {
+        crate::sign(self.raw, token)
+    }
+
+    /// Same as [`crate::verify`].
+    ///
+    /// # Errors
+    ///
+    /// See [`crate::verify`].
+    pub fn verify(
+        &self,
+        token: &(impl TokenProvider + ?Sized),
+        expiration: impl Into<Expiration>,
+    ) -> Result<(), InitDataError> {
+        self.verify_with_clock(token, expiration, &SystemClock)
+    }
+
+    /// Same as [`crate::verify_with_clock`].
+    ///
+    /// # Errors
+    ///
+    /// See [`crate::verify`].
+    pub fn verify_with_clock(
+        &self,
+        token: &(impl TokenProvider + ?Sized),
+        expiration: impl Into<Expiration>,
+        clock: &impl Clock,
+    ) -> Result<(), InitDataError> {
+        crate::verify_with_clock(self.raw, token, expiration, clock)
+    }
+
+    /// Same as [`crate::validate`].
+    ///
+    /// # Errors
+    ///
+    /// See [`crate::validate`].
+    #[cfg(feature = "model")]
+    pub fn validate(
+        &self,
+        token: &(impl TokenProvider + ?Sized),
+        expiration: impl Into<Expiration>,
+    ) -> Result<InitData, InitDataError> {
+        self.validate_with_clock(token, expiration, &SystemClock)
+    }
+
+    /// Same as [`crate::validate_with_clock`].
+    ///
+    /// # Errors
+    ///
+    /// See [`crate::validate`].
+    #[cfg(feature = "model")]
+    pub fn validate_with_clock(
+        &self,
+        token: &(impl TokenProvider + ?Sized),
+        expiration: impl Into<Expiration>,
+        clock: &impl Clock,
+    ) -> Result<InitData, InitDataError> {
+        crate::validate_with_clock(self.raw, token, expiration, clock)
+    }
+
+    /// Same as [`crate::parse`].
+    ///
+    /// # Errors
+    ///
+    /// See [`crate::parse`].
+    #[cfg(feature = "model")]
+    pub fn parse(&self) -> Result<InitData, InitDataError> {
+        crate::parse(self.raw)
+    }
+}
+
+impl<'a> From<&'a str> for ParsedQuery<'a> {
+    fn from(init_data: &'a str) -> Self {
+        Self::new(init_data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    const BOT_TOKEN: &str = "5768337691:AAH5YkoiEuPk8-FZa32hStHTqXiLPtAEhx8";
+
+    #[test]
+    fn test_parsed_query_accessors() {
+        let query = ParsedQuery::new("query_id=a&start_param=x%20y&&query_id=b+c&auth_date=1");
+
+        assert_eq!(query.len(), 4);
+        assert_eq!(query.get("query_id"), Some("b c"));
+        assert_eq!(query.get("start_param"), Some("x y"));
+        assert_eq!(query.get("hash"), None);
+        assert_eq!(
+            query.iter().map(|(k, _)| k).collect::<Vec<_>>(),
+            ["query_id", "start_param", "query_id", "auth_date"]
+        );
+        assert!(ParsedQuery::new("").is_empty());
+    }
+
+    #[test]
+    fn test_parsed_query_verify() {
+        let base_data = "query_id=test&auth_date=1000";
+        let hash = crate::sign(base_data, BOT_TOKEN).unwrap();
+        let init_data = format!("{base_data}&hash={hash}");
+        let query = ParsedQuery::from(init_data.as_str());
+
+        assert_eq!(query.sign(BOT_TOKEN).unwrap(), hash);
+        assert!(query
+            .verify_with_clock(BOT_TOKEN, Duration::from_secs(60), &|| 1010)
+            .is_ok());
+        assert!(matches!(
+            query.verify_with_clock(BOT_TOKEN, Duration::from_secs(60), &|| 2000),
+            Err(InitDataError::Expired)
+        ));
+    }
+}
@@ -0,0 +1,225 @@
+//! PASETO v4 session tokens issued in exchange for validated init data.
+//!
+//! `v4.local` tokens are encrypted with a symmetric key shared by the services, `v4.public` tokens are
+//! signed with an Ed25519 key, so services holding only the public key can verify them. Both carry the
+//! same [`SessionClaims`] as the JWTs of the `jwt` feature, `iat` and `exp` being Unix timestamps.
+
+use ed25519_dalek::SigningKey;
+use pasetors::keys::{AsymmetricPublicKey, AsymmetricSecretKey, SymmetricKey};
+use pasetors::token::UntrustedToken;
+use pasetors::version4::{LocalToken, PublicToken, V4};
+use pasetors::{Local, Public};
+
+use crate::clock::{Clock, SystemClock};
+use crate::error::InitDataError;
+use crate::model::InitData;
+use crate::scheme::Verified;
+use crate::session::SessionClaims;
+
+/// Issues a `v4.local` session token for validated init data, valid for `ttl` seconds from now.
+///
+/// # Example
+/// ```
+/// use init_data_rs::{issue_paseto_local, validate_auto, verify_paseto_local, Expiration};
+///
+/// let key = [7u8; 32];
+/// let init_data = "user=%7B%22id%22%3A279058397%7D&auth_date=1662771648&hash=...";
+/// if let Ok(verified) = validate_auto(init_data, "BOT_TOKEN", Expiration::Default) {
+///     let token = issue_paseto_local(&verified, &key, 3600).unwrap();
+///     let claims = verify_paseto_local(&token, &key).unwrap();
+/// }
+/// ```
+///
+/// # Errors
+///
+/// Returns [`InitDataError::UnexpectedFormat`] if the init data has no user and
+/// [`InitDataError::Internal`] if no randomness is available for the nonce.
+///
+/// # Panics
+///
+/// See [`crate::validate`].
+pub fn issue_paseto_local(verified: &Verified<InitData>, key: &[u8; 32], ttl: u64) -> Result<String, InitDataError> {
+    issue_paseto_local_with_clock(verified, key, ttl, &SystemClock)
+}
+
+/// Same as [`issue_paseto_local`], but reads the current time from `clock`.
+///
+/// # Errors
+///
+/// See [`issue_paseto_local`].
+pub fn issue_paseto_local_with_clock(
+    verified: &Verified<InitData>,
+    key: &[u8; 32],
+    ttl: u64,
+    clock: &impl Clock,
+) -> Result<String, InitDataError> {
+    let claims = SessionClaims::new_with_clock(verified, ttl, clock)?.to_json()?;
+    let key = SymmetricKey::<V4>::from(key).map_err(internal)?;
+
+    LocalToken::encrypt(&key, &claims, None, None).map_err(internal)
+}
+
+/// Decrypts a `v4.local` session token issued by [`issue_paseto_local`] and checks its expiration.
+///
+/// # Errors
+///
+/// Returns [`InitDataError::UnexpectedFormat`] if the token is malformed, [`InitDataError::HashInvalid`]
+/// if it wasn't encrypted with `key` and [`InitDataError::Expired`] once it has expired.
+///
+/// # Panics
+///
+/// See [`crate::validate`].
+pub fn verify_paseto_local(token: &str, key: &[u8; 32]) -> Result<SessionClaims, InitDataError> {
+    verify_paseto_local_with_clock(token, key, &SystemClock)
+}
+
+/// Same as [`verify_paseto_local`], but reads the current time from `clock`.
+///
+/// # Errors
+///
+/// See [`verify_paseto_local`].
+pub fn verify_paseto_local_with_clock(
+    token: &str,
+    key: &[u8; 32],
+    clock: &impl Clock,
+) -> Result<SessionClaims, InitDataError> {
+    let token = UntrustedToken::<Local, V4>::try_from(token).map_err(|_| malformed())?;
+    let key = SymmetricKey::<V4>::from(key).map_err(internal)?;
+    let token = LocalToken::decrypt(&key, &token, None, None).map_err(|_| InitDataError::HashInvalid)?;
+
+    SessionClaims::from_json(token.payload().as_bytes(), clock)
+}
+
+/// Issues a `v4.public` session token for validated init data, valid for `ttl` seconds from now.
+///
+/// # Arguments
+/// * `verified` - Validated init data
+/// * `secret_key` - Ed25519 secret key (seed) of the issuing service
+/// * `ttl` - Lifetime of the token in seconds
+///
+/// # Errors
+///
+/// Returns [`InitDataError::UnexpectedFormat`] if the init data has no user.
+///
+/// # Panics
+///
+/// See [`crate::validate`].
+pub fn issue_paseto_public(
+    verified: &Verified<InitData>,
+    secret_key: &[u8; 32],
+    ttl: u64,
+) -> Result<String, InitDataError> {
+    issue_paseto_public_with_clock(verified, secret_key, ttl, &SystemClock)
+}
+
+/// Same as [`issue_paseto_public`], but reads the current time from `clock`.
+///
+/// # Errors
+///
+/// See [`issue_paseto_public`].
+pub fn issue_paseto_public_with_clock(
+    verified: &Verified<InitData>,
+    secret_key: &[u8; 32],
+    ttl: u64,
+    clock: &impl Clock,
+) -> Result<String, InitDataError> {
+    let claims = SessionClaims::new_with_clock(verified, ttl, clock)?.to_json()?;
+    let secret_key =
+        AsymmetricSecretKey::<V4>::from(&SigningKey::from_bytes(secret_key).to_keypair_bytes()).map_err(internal)?;
+
+    PublicToken::sign(&secret_key, &claims, None, None).map_err(internal)
+}
+
+/// Checks the signature of a `v4.public` session token issued by [`issue_paseto_public`] and its expiration.
+///
+/// # Errors
+///
+/// Returns [`InitDataError::UnexpectedFormat`] if the token is malformed, [`InitDataError::HashInvalid`]
+/// if it wasn't signed with the secret key of `public_key` and [`InitDataError::Expired`] once it has expired.
+///
+/// # Panics
+///
+/// See [`crate::validate`].
+pub fn verify_paseto_public(token: &str, public_key: &[u8; 32]) -> Result<SessionClaims, InitDataError> {
+    verify_paseto_public_with_clock(token, public_key, &SystemClock)
+}
+
+/// Same as [`verify_paseto_public`], but reads the current time from `clock`.
+///
+/// # Errors
+///
+/// See [`verify_paseto_public`].
+pub fn verify_paseto_public_with_clock(
+    token: &str,
+    public_key: &[u8; 32],
+    clock: &impl Clock,
+) -> Result<SessionClaims, InitDataError> {
+    let token = UntrustedToken::<Public, V4>::try_from(token).map_err(|_| malformed())?;
+    let public_key = AsymmetricPublicKey::<V4>::from(public_key).map_err(internal)?;
+    let token = PublicToken::verify(&public_key, &token, None, None).map_err(|_| InitDataError::HashInvalid)?;
+
+    SessionClaims::from_json(token.payload().as_bytes(), clock)
+}
+
+fn malformed() -> InitDataError {
+    InitDataError::UnexpectedFormat("token is malformed".to_string())
+}
+
+#[allow(clippy::needless_pass_by_value)]
+fn internal(error: pasetors::errors::Error) -> InitDataError {
+    InitDataError::Internal(error.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse;
+    use crate::scheme::ValidationScheme;
+
+    fn verified() -> Verified<InitData> {
+        let data = parse("user=%7B%22id%22%3A279058397%2C%22first_name%22%3A%22Vladislav%22%2C%22username%22%3A%22vdkfrost%22%7D&auth_date=1662771648&hash=c501b71e775f74ce10e377dea85a7ea24ecd640b223ea86dfe453e0eaed2e2b2").unwrap();
+        Verified::new(data, ValidationScheme::Ed25519ThirdParty)
+    }
+
+    #[test]
+    fn test_paseto_local() {
+        let key = [7u8; 32];
+        let token = issue_paseto_local_with_clock(&verified(), &key, 60, &|| 1000).unwrap();
+        assert!(token.starts_with("v4.local."));
+
+        let claims = verify_paseto_local_with_clock(&token, &key, &|| 1060).unwrap();
+        assert_eq!(claims.user_id().unwrap(), 279058397);
+        assert_eq!(claims.scheme, ValidationScheme::Ed25519ThirdParty);
+        assert!(!claims.premium);
+
+        assert!(matches!(
+            verify_paseto_local_with_clock(&token, &key, &|| 1061),
+            Err(InitDataError::Expired)
+        ));
+        assert!(matches!(
+            verify_paseto_local_with_clock(&token, &[8u8; 32], &|| 1000),
+            Err(InitDataError::HashInvalid)
+        ));
+        assert!(matches!(
+            verify_paseto_local_with_clock("v4.public.abc", &key, &|| 1000),
+            Err(InitDataError::UnexpectedFormat(_))
+        ));
+    }
+
+    #[test]
+    fn test_paseto_public() {
+        let secret_key = [9u8; 32];
+        let public_key = SigningKey::from_bytes(&secret_key).verifying_key().to_bytes();
+        let token = issue_paseto_public_with_clock(&verified(), &secret_key, 60, &|| 1000).unwrap();
+        assert!(token.starts_with("v4.public."));
+
+        let claims = verify_paseto_public_with_clock(&token, &public_key, &|| 1000).unwrap();
+        assert_eq!(claims.username.as_deref(), Some("vdkfrost"));
+
+        let other_key = SigningKey::from_bytes(&[10u8; 32]).verifying_key().to_bytes();
+        assert!(matches!(
+            verify_paseto_public_with_clock(&token, &other_key, &|| 1000),
+            Err(InitDataError::HashInvalid)
+        ));
+    }
+}
@@ -0,0 +1,24 @@
+//! The commonly used types and traits in one import.
+//!
+//! ```
+//! use init_data_rs::prelude::*;
+//!
+//! fn user_id(validator: &Validator, init_data: &str) -> Result<Option<i64>, InitDataError> {
+//!     Ok(validator.validate(init_data)?.user.map(|user| user.id))
+//! }
+//! ```
+
+pub use crate::clock::{Clock, SystemClock};
+pub use crate::error::InitDataError;
+pub use crate::expiration::Expiration;
+pub use crate::launch_params::{LaunchParams, Platform};
+pub use crate::metrics_sink::MetricsSink;
+pub use crate::model::{Chat, ChatType, Counterpart, InitData, LaunchContext, User};
+pub use crate::parse::ParseOptions;
+pub use crate::scheme::{Unverified, ValidationScheme, Verified};
+pub use crate::secret::{BotToken, TokenProvider};
+pub use crate::third_party_validation::Environment;
+pub use crate::throttle::ThrottleHook;
+pub use crate::validation::ValidateExt;
+pub use crate::validation_cache::ValidationCache;
+pub use crate::validator::Validator;
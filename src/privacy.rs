@@ -0,0 +1,180 @@
+//! Data minimization of validated init data.
+
+use crate::fingerprint::Fingerprinter;
+use crate::model::{InitData, User};
+
+/// Personal data blanked from init data once it is validated, see [`crate::Validator::privacy_mode`].
+///
+/// Services that only need the user id then never hold names or profile pictures, in memory, caches or logs.
+/// Blanked init data no longer matches its hash, so once anything is blanked it can't be re-encoded with
+/// [`InitData::to_query_string`] and verified again afterwards.
+///
+/// The default mode keeps everything.
+///
+/// # Example
+/// ```
+/// use init_data_rs::{PrivacyMode, Validator};
+///
+/// let validator = Validator::new("BOT_TOKEN").privacy_mode(PrivacyMode::strict());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PrivacyMode {
+    /// Blanks `first_name` and removes `last_name` of users, and blanks the `title` of the chat.
+    pub names: bool,
+    /// Removes the `username` of users and of the chat.
+    pub username: bool,
+    /// Removes the `photo_url` of users and of the chat.
+    pub photo_url: bool,
+}
+
+impl PrivacyMode {
+    /// Blanks every personal field: names, usernames and profile pictures.
+    #[must_use]
+    pub fn strict() -> Self {
+        Self {
+            names: true,
+            username: true,
+            photo_url: true,
+        }
+    }
+
+    /// Whether the mode blanks anything.
+    #[must_use]
+    pub fn is_enabled(self) -> bool {
+        self.names || self.username || self.photo_url
+    }
+
+    /// Blanks the personal fields of `data` selected by the mode.
+    pub fn apply(self, data: &mut InitData) {
+        if !self.is_enabled() {
+            return;
+        }
+
+        for user in [&mut data.user, &mut data.receiver].into_iter().flatten() {
+            self.apply_to_user(user);
+        }
+        if let Some(chat) = &mut data.chat {
+            if self.names {
+                chat.title.clear();
+            }
+            if self.username {
+                chat.username = None;
+            }
+            if self.photo_url {
+                chat.photo_url = None;
+            }
+        }
+    }
+
+    fn apply_to_user(self, user: &mut User) {
+        if self.names {
+            user.first_name.clear();
+            user.last_name = None;
+        }
+        if self.username {
+            user.username = None;
+        }
+        if self.photo_url {
+            user.photo_url = None;
+        }
+    }
+}
+
+impl InitData {
+    /// Returns a copy fit for analytics pipelines and long-term storage, without personal data or credentials.
+    ///
+    /// User, receiver and chat ids and `chat_instance` are replaced by pseudonyms keyed with `salt`, see
+    /// [`Fingerprinter::pseudonymous_id`], so that the same user keeps the same id across payloads. Names,
+    /// usernames and profile pictures are removed like with [`PrivacyMode::strict`], and so are `query_id`,
+    /// `hash`, `signature` and the raw init data. Everything else, e.g. `auth_date`, `language_code` and
+    /// `start_param`, is kept.
+    ///
+    /// Keep `salt` secret and stable: without it ids can't be brute-forced back, and changing it breaks the
+    /// correlation with earlier records.
+    ///
+    /// # Example
+    /// ```
+    /// use init_data_rs::parse;
+    ///
+    /// let data = parse("user=%7B%22id%22%3A42%2C%22first_name%22%3A%22Ada%22%7D&auth_date=1&hash=c501b71e775f74ce10e377dea85a7ea24ecd640b223ea86dfe453e0eaed2e2b2").unwrap();
+    /// let anonymized = data.anonymized(b"per-deployment secret salt");
+    /// let user = anonymized.user.unwrap();
+    ///
+    /// assert_ne!(user.id, 42);
+    /// assert!(user.first_name.is_empty());
+    /// assert!(anonymized.hash.is_empty());
+    /// ```
+    #[must_use]
+    pub fn anonymized(&self, salt: impl AsRef<[u8]>) -> InitData {
+        let fingerprinter = Fingerprinter::new(salt);
+        let mut data = self.clone();
+        PrivacyMode::strict().apply(&mut data);
+
+        for user in [&mut data.user, &mut data.receiver].into_iter().flatten() {
+            user.id = fingerprinter.pseudonymous_id(user.id);
+        }
+        if let Some(chat) = &mut data.chat {
+            chat.id = fingerprinter.pseudonymous_id(chat.id);
+        }
+        data.chat_instance = data.chat_instance.map(|id| fingerprinter.pseudonymous_id(id));
+        data.query_id = None;
+        data.hash.clear();
+        data.signature = None;
+
+        data
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse;
+
+    #[test]
+    fn test_privacy_mode() {
+        let init_data = "user=%7B%22id%22%3A42%2C%22first_name%22%3A%22Ada%22%2C%22last_name%22%3A%22Lovelace%22%2C%22username%22%3A%22ada%22%2C%22photo_url%22%3A%22https%3A%2F%2Ft.me%2Fi%2Fuserpic%2F320%2Fa.svg%22%7D&chat=%7B%22id%22%3A-1%2C%22type%22%3A%22group%22%2C%22title%22%3A%22Lovelace%20family%22%7D&auth_date=1662771648&hash=c501b71e775f74ce10e377dea85a7ea24ecd640b223ea86dfe453e0eaed2e2b2";
+
+        let mut data = parse(init_data).unwrap();
+        PrivacyMode::default().apply(&mut data);
+        assert_eq!(data.to_map(), parse(init_data).unwrap().to_map());
+
+        PrivacyMode {
+            username: true,
+            ..PrivacyMode::default()
+        }
+        .apply(&mut data);
+        let user = data.user.as_ref().unwrap();
+        assert_eq!((user.first_name.as_str(), user.username.as_deref()), ("Ada", None));
+        assert!(user.photo_url.is_some());
+
+        PrivacyMode::strict().apply(&mut data);
+        let user = data.user.unwrap();
+        assert_eq!(user.id, 42);
+        assert!(user.first_name.is_empty() && user.last_name.is_none() && user.photo_url.is_none());
+        assert!(data.chat.unwrap().title.is_empty());
+    }
+
+    #[test]
+    fn test_anonymized() {
+        let init_data = "query_id=AAHdF6IQ&user=%7B%22id%22%3A42%2C%22first_name%22%3A%22Ada%22%2C%22language_code%22%3A%22en%22%7D&chat=%7B%22id%22%3A-100%2C%22type%22%3A%22group%22%2C%22title%22%3A%22Lovelace%20family%22%7D&chat_instance=-8599080687359297588&start_param=ref_1&auth_date=1662771648&signature=abc&hash=c501b71e775f74ce10e377dea85a7ea24ecd640b223ea86dfe453e0eaed2e2b2";
+        let data = parse(init_data).unwrap();
+
+        let anonymized = data.anonymized("salt");
+        let (user, chat) = (anonymized.user.as_ref().unwrap(), anonymized.chat.as_ref().unwrap());
+        assert_eq!(user.id, Fingerprinter::new("salt").pseudonymous_id(42));
+        assert_eq!(user.language_code.as_deref(), Some("en"));
+        assert!(user.first_name.is_empty() && chat.title.is_empty());
+        assert!(chat.id < 0 && chat.id != -100);
+        assert_ne!(anonymized.chat_instance, data.chat_instance);
+        assert!(anonymized.query_id.is_none() && anonymized.signature.is_none());
+        assert!(anonymized.hash.is_empty());
+        assert_eq!(
+            (anonymized.auth_date, anonymized.start_param.as_deref()),
+            (1_662_771_648, Some("ref_1"))
+        );
+
+        assert_eq!(data.anonymized("salt").user.unwrap().id, user.id);
+        assert_ne!(data.anonymized("pepper").user.unwrap().id, user.id);
+    }
+}
@@ -0,0 +1,126 @@
+//! RFC 7807 problem details for validation failures.
+
+use serde::Serialize;
+
+use crate::error::InitDataError;
+
+/// Prefix of the `type` URI when none is set, followed by the error code.
+const DEFAULT_TYPE_BASE: &str = "urn:init-data-rs:error:";
+
+/// Body of an `application/problem+json` response (RFC 7807) describing why init data was rejected.
+///
+/// The `detail` never contains the payload or the reason of [`InitDataError`] variants carrying one, so the
+/// body can be returned to untrusted clients. The stable [`InitDataError::kind`] is added as a `code` member.
+///
+/// # Example
+/// ```
+/// use init_data_rs::{InitDataError, ProblemDetails};
+///
+/// let problem = ProblemDetails::new(&InitDataError::Expired).type_base("https://api.example.com/problems/");
+/// assert_eq!(problem.status, 401);
+/// assert_eq!(problem.type_uri, "https://api.example.com/problems/expired");
+///
+/// // response with `Content-Type: application/problem+json`
+/// let body = problem.to_json();
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ProblemDetails {
+    /// URI identifying the problem type, the error code appended to the type base.
+    #[serde(rename = "type")]
+    pub type_uri: String,
+    /// Short summary of the problem type.
+    pub title: &'static str,
+    /// HTTP status code of the response.
+    pub status: u16,
+    /// Explanation of the problem, without anything taken from the request.
+    pub detail: &'static str,
+    /// Stable error code, see [`InitDataError::kind`].
+    pub code: &'static str,
+}
+
+impl ProblemDetails {
+    /// Media type of the response body.
+    pub const CONTENT_TYPE: &'static str = "application/problem+json";
+
+    /// Describes `error`, with a `urn:init-data-rs:error:<code>` type URI.
+    #[must_use]
+    pub fn new(error: &InitDataError) -> Self {
+        let code = error.kind();
+        let (status, title, detail) = match error {
+            InitDataError::AuthDateMissing => (401, "Invalid init data", "auth_date is missing"),
+            InitDataError::HashMissing => (401, "Invalid init data", "hash is missing"),
+            InitDataError::HashInvalid => (401, "Invalid init data", "hash is invalid"),
+            InitDataError::UnexpectedFormat(_) => (401, "Invalid init data", "init data has unexpected format"),
+            InitDataError::Expired => (401, "Expired init data", "init data is expired"),
+            InitDataError::SignatureMissing => (401, "Invalid init data", "signature is missing"),
+            InitDataError::SignatureInvalid(_) => (401, "Invalid init data", "signature is invalid"),
+            InitDataError::Throttled => (429, "Too many requests", "too many failed validations, try again later"),
+            InitDataError::PayloadTooLarge(_) => (413, "Init data too large", "init data is too large"),
+            InitDataError::DeadlineExceeded => {
+                (408, "Request timeout", "no init data was received before the deadline")
+            }
+            InitDataError::UserMissing => (403, "User required", "init data carries no user"),
+            InitDataError::ChatMissing => (403, "Chat required", "init data carries no chat"),
+            InitDataError::ClientNotAllowed(_) => (403, "Client not supported", "the Telegram client is not supported"),
+            InitDataError::StartParamInvalid(_) => (400, "Invalid start parameter", "the start_param is not accepted"),
+            InitDataError::AuthDateInvalid { .. } => (401, "Invalid init data", "auth_date is not a unix time"),
+            InitDataError::UserDenied(_) => (403, "User not allowed", "the user is not allowed"),
+            InitDataError::ChatNotAllowed(_) => (403, "Chat not allowed", "the Mini App can't be used in this chat"),
+            InitDataError::Internal(_) | InitDataError::Config(_) => {
+                (500, "Internal error", "init data could not be validated")
+            }
+        };
+
+        Self {
+            type_uri: format!("{DEFAULT_TYPE_BASE}{code}"),
+            title,
+            status,
+            detail,
+            code,
+        }
+    }
+
+    /// Sets the prefix of the `type` URI, e.g. `https://api.example.com/problems/` for
+    /// `https://api.example.com/problems/expired`.
+    #[must_use]
+    pub fn type_base(mut self, base: &str) -> Self {
+        self.type_uri = format!("{base}{}", self.code);
+        self
+    }
+
+    /// Serializes the problem as the JSON response body.
+    #[must_use]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+}
+
+impl From<&InitDataError> for ProblemDetails {
+    fn from(error: &InitDataError) -> Self {
+        Self::new(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_problem_details_json() {
+        let problem = ProblemDetails::new(&InitDataError::HashInvalid);
+        assert_eq!(
+            problem.to_json(),
+            r#"{"type":"urn:init-data-rs:error:hash_invalid","title":"Invalid init data","status":401,"detail":"hash is invalid","code":"hash_invalid"}"#
+        );
+    }
+
+    #[test]
+    fn test_problem_details_redacts_detail() {
+        let problem = ProblemDetails::from(&InitDataError::UnexpectedFormat("user=%7B%22id%22".to_string()));
+        assert_eq!(problem.detail, "init data has unexpected format");
+        assert!(!problem.to_json().contains("user="));
+
+        assert_eq!(ProblemDetails::new(&InitDataError::Throttled).status, 429);
+        assert_eq!(ProblemDetails::new(&InitDataError::Config("token".into())).status, 500);
+    }
+}
@@ -0,0 +1,135 @@
+//! Allocation-free helpers for `application/x-www-form-urlencoded` query strings.
+
+/// Splits a query string into raw, still percent-encoded, key/value pairs.
+///
+/// Empty segments are skipped and a segment without `=` yields an empty value,
+/// the same way `form_urlencoded::parse` does.
+pub(crate) fn raw_pairs(query: &str) -> impl Iterator<Item = (&str, &str)> + Clone {
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| pair.split_once('=').unwrap_or((pair, "")))
+}
+
+/// Percent-decodes a raw query component, turning `+` into a space.
+/// Invalid escape sequences are kept as they are.
+pub(crate) fn decode(raw: &str) -> Decode<'_> {
+    decode_with(raw, true)
+}
+
+/// Same as [`decode`], but keeps `+` as is unless `plus_as_space`, see [`crate::Compat::plus_as_space`].
+pub(crate) fn decode_with(raw: &str, plus_as_space: bool) -> Decode<'_> {
+    Decode {
+        bytes: raw.as_bytes(),
+        plus_as_space,
+    }
+}
+
+/// Iterator over the decoded bytes of a query component, see [`decode`].
+pub(crate) struct Decode<'a> {
+    bytes: &'a [u8],
+    plus_as_space: bool,
+}
+
+impl Iterator for Decode<'_> {
+    type Item = u8;
+
+    fn next(&mut self) -> Option<u8> {
+        let (&byte, rest) = self.bytes.split_first()?;
+        self.bytes = rest;
+
+        match byte {
+            b'+' if self.plus_as_space => Some(b' '),
+            b'%' => match rest {
+                [high, low, rest @ ..] => match (hex_value(*high), hex_value(*low)) {
+                    (Some(high), Some(low)) => {
+                        self.bytes = rest;
+                        Some(high << 4 | low)
+                    }
+                    _ => Some(b'%'),
+                },
+                _ => Some(b'%'),
+            },
+            byte => Some(byte),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.bytes.len().div_ceil(3), Some(self.bytes.len()))
+    }
+}
+
+fn hex_value(byte: u8) -> Option<u8> {
+    match byte {
+        b'0'..=b'9' => Some(byte - b'0'),
+        b'a'..=b'f' => Some(byte - b'a' + 10),
+        b'A'..=b'F' => Some(byte - b'A' + 10),
+        _ => None,
+    }
+}
+
+/// Compares the decoded forms of two raw query components without allocating.
+pub(crate) fn decoded_eq(left: &str, right: &str) -> bool {
+    left == right || decode(left).eq(decode(right))
+}
+
+/// Appends `bytes` to `out` as a JSON string literal, quotes included.
+#[cfg_attr(any(feature = "mini-parse", not(feature = "model")), allow(dead_code))]
+pub(crate) fn write_json_string(out: &mut Vec<u8>, bytes: impl Iterator<Item = u8>) {
+    const HEX: &[u8; 16] = b"0123456789abcdef";
+
+    out.push(b'"');
+    for byte in bytes {
+        match byte {
+            b'"' => out.extend_from_slice(b"\\\""),
+            b'\\' => out.extend_from_slice(b"\\\\"),
+            0x00..=0x1f => out.extend_from_slice(&[
+                b'\\',
+                b'u',
+                b'0',
+                b'0',
+                HEX[usize::from(byte >> 4)],
+                HEX[usize::from(byte & 0xf)],
+            ]),
+            byte => out.push(byte),
+        }
+    }
+    out.push(b'"');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode_to_string(raw: &str) -> String {
+        String::from_utf8(decode(raw).collect()).unwrap()
+    }
+
+    #[test]
+    fn test_raw_pairs() {
+        let pairs: Vec<_> = raw_pairs("a=1&&b&c=x=y&").collect();
+        assert_eq!(pairs, [("a", "1"), ("b", ""), ("c", "x=y")]);
+    }
+
+    #[test]
+    fn test_decode() {
+        assert_eq!(decode_to_string("a+b%20c"), "a b c");
+        assert_eq!(decode_to_string("%7B%22id%22%3A1%7D"), "{\"id\":1}");
+        assert_eq!(decode_to_string("%D0%BF%D1%80"), "пр");
+        assert_eq!(decode_to_string("100%"), "100%");
+        assert_eq!(decode_to_string("%zz%4"), "%zz%4");
+    }
+
+    #[test]
+    fn test_decoded_eq() {
+        assert!(decoded_eq("auth_date", "auth%5Fdate"));
+        assert!(!decoded_eq("auth_date", "hash"));
+    }
+
+    #[test]
+    fn test_write_json_string() {
+        let mut out = Vec::new();
+        write_json_string(&mut out, "a\"b\\c\n".bytes());
+        assert_eq!(out, b"\"a\\\"b\\\\c\\u000a\"");
+    }
+}
@@ -0,0 +1,118 @@
+//! Raw init data kept as received, for gateways forwarding it upstream after inspection.
+
+use std::borrow::Cow;
+
+use crate::query::{decode, decoded_eq, raw_pairs};
+
+/// The key/value pairs of init data, still percent-encoded, in their original order.
+///
+/// Unlike [`crate::ParsedQuery`], nothing is decoded up front and [`RawInitData::encode`] gives back the payload
+/// as received, so a gateway can inspect it and forward it without re-sorting or re-encoding parameters, which
+/// would risk breaking the hash for the upstream service.
+///
+/// # Example
+/// ```
+/// use init_data_rs::RawInitData;
+///
+/// let init_data = "user=%7B%22id%22%3A1%7D&query_id=a+b&auth_date=1662771648&hash=...";
+/// let raw = RawInitData::new(init_data);
+///
+/// assert_eq!(raw.get("query_id").as_deref(), Some("a b"));
+/// assert_eq!(raw.encode(), init_data);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RawInitData<'a> {
+    segments: Vec<&'a str>,
+}
+
+impl<'a> RawInitData<'a> {
+    /// Splits `init_data` into its raw pairs, nothing is decoded or validated.
+    #[must_use]
+    pub fn new(init_data: &'a str) -> Self {
+        Self {
+            segments: init_data.split('&').filter(|segment| !segment.is_empty()).collect(),
+        }
+    }
+
+    /// The raw key/value pairs in their original order, duplicates included.
+    pub fn iter(&self) -> impl Iterator<Item = (&'a str, &'a str)> + '_ {
+        self.segments.iter().flat_map(|segment| raw_pairs(segment))
+    }
+
+    /// The decoded value of `key`, the last one if it occurs several times, like validation does.
+    #[must_use]
+    pub fn get(&self, key: &str) -> Option<Cow<'a, str>> {
+        let (_, value) = self.iter().filter(|(k, _)| decoded_eq(k, key)).last()?;
+        let decoded: Vec<u8> = decode(value).collect();
+
+        Some(if decoded.as_slice() == value.as_bytes() {
+            Cow::Borrowed(value)
+        } else {
+            Cow::Owned(String::from_utf8_lossy(&decoded).into_owned())
+        })
+    }
+
+    /// Re-emits the payload with its pairs in their original order and encoding.
+    ///
+    /// The only modification is that empty segments (`&&`, or a leading or trailing `&`) are dropped, which
+    /// neither the hash nor the signature covers.
+    #[must_use]
+    pub fn encode(&self) -> String {
+        self.segments.join("&")
+    }
+
+    /// Value of an `Authorization` header forwarding the init data as received, `tma <init data>`, which
+    /// [`crate::extract_from_authorization_header`] reads back, see [`RawInitData::encode`].
+    #[must_use]
+    pub fn to_authorization_header(&self) -> String {
+        format!("{} {}", crate::AUTHORIZATION_SCHEME, self.encode())
+    }
+}
+
+impl<'a> From<&'a str> for RawInitData<'a> {
+    fn from(init_data: &'a str) -> Self {
+        Self::new(init_data)
+    }
+}
+
+/// Re-emits raw init data for forwarding, see [`RawInitData::encode`].
+///
+/// # Example
+/// ```
+/// use init_data_rs::reencode_preserving_order;
+///
+/// assert_eq!(
+///     reencode_preserving_order("user=%7B%7D&&auth_date=1662771648&hash=abc&"),
+///     "user=%7B%7D&auth_date=1662771648&hash=abc"
+/// );
+/// ```
+#[must_use]
+pub fn reencode_preserving_order(init_data: &str) -> String {
+    RawInitData::new(init_data).encode()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    const BOT_TOKEN: &str = "5768337691:AAH5YkoiEuPk8-FZa32hStHTqXiLPtAEhx8";
+
+    #[test]
+    fn test_reencode_preserving_order() {
+        let base_data = "user=%7B%22id%22%3A1%7D&query_id=a+b%2Bc&start_param&auth_date=1000";
+        let init_data = format!("&{base_data}&&hash={}&", crate::sign(base_data, BOT_TOKEN).unwrap());
+        let reencoded = reencode_preserving_order(&init_data);
+
+        assert_eq!(reencoded, init_data.trim_matches('&').replace("&&", "&"));
+        assert!(crate::verify_with_clock(&reencoded, BOT_TOKEN, Duration::from_secs(60), &|| 1010).is_ok());
+
+        let raw = RawInitData::new(&init_data);
+        assert_eq!(raw.get("query_id").as_deref(), Some("a b+c"));
+        assert!(matches!(raw.get("auth_date"), Some(Cow::Borrowed("1000"))));
+        assert_eq!(raw.get("start_param").as_deref(), Some(""));
+        assert_eq!(raw.iter().count(), 5);
+        assert_eq!(raw.to_authorization_header(), format!("tma {reencoded}"));
+    }
+}
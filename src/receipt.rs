@@ -0,0 +1,224 @@
+//! Signed receipts vouching that init data was validated.
+//!
+//! The edge service validates init data once and hands internal services a short-lived receipt,
+//! signed with a key shared between services, instead of the raw init data and the bot token.
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD as base64_engine;
+use base64::Engine as _;
+
+use crate::clock::{Clock, SystemClock};
+use crate::crypto::Hmac;
+use crate::error::InitDataError;
+use crate::model::InitData;
+use crate::scheme::ValidationScheme;
+
+const VERSION: &str = "v1";
+
+/// Proof that init data passed validation, see the module documentation.
+///
+/// # Example
+/// ```
+/// use init_data_rs::{Receipt, ValidationScheme};
+///
+/// let receipt = Receipt {
+///     user_id: Some(279058397),
+///     auth_date: 1662771648,
+///     scheme: ValidationScheme::BotTokenHmac,
+///     expires_at: u64::MAX,
+/// };
+/// let token = receipt.sign(b"service key").unwrap();
+///
+/// assert_eq!(Receipt::verify(&token, b"service key").unwrap(), receipt);
+/// assert!(Receipt::verify(&token, b"other key").is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Receipt {
+    /// Id of the user the init data was issued for, if it contained one.
+    pub user_id: Option<i64>,
+    /// `auth_date` of the validated init data.
+    pub auth_date: u64,
+    /// How the init data was validated.
+    pub scheme: ValidationScheme,
+    /// Unix time after which the receipt is no longer accepted.
+    pub expires_at: u64,
+}
+
+impl Receipt {
+    /// Creates a receipt for validated init data, valid for `ttl` seconds from now.
+    ///
+    /// # Panics
+    ///
+    /// See [`crate::validate`].
+    #[must_use]
+    pub fn new(data: &InitData, scheme: ValidationScheme, ttl: u64) -> Self {
+        Self::new_with_clock(data, scheme, ttl, &SystemClock)
+    }
+
+    /// Same as [`Receipt::new`], but reads the current time from `clock`.
+    pub fn new_with_clock(data: &InitData, scheme: ValidationScheme, ttl: u64, clock: &impl Clock) -> Self {
+        Self {
+            user_id: data.user.as_ref().map(|user| user.id),
+            auth_date: data.auth_date,
+            scheme,
+            expires_at: clock.now().saturating_add(ttl),
+        }
+    }
+
+    /// Encodes and signs the receipt as `v1.<payload>.<mac>`, URL-safe and without padding.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`InitDataError::UnexpectedFormat`] if `key` is empty.
+    pub fn sign(&self, key: &[u8]) -> Result<String, InitDataError> {
+        let user_id = self.user_id.map(|id| id.to_string()).unwrap_or_default();
+        let payload = format!("{user_id}:{}:{}:{}", self.auth_date, self.scheme, self.expires_at);
+
+        let mut token = format!("{VERSION}.{}", base64_engine.encode(payload));
+        let mac = mac(key, &token)?.finalize()?;
+        token.push('.');
+        token.push_str(&base64_engine.encode(mac));
+
+        Ok(token)
+    }
+
+    /// Checks the signature and the expiration of a receipt and decodes it.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`InitDataError::UnexpectedFormat`] if the receipt is malformed or `key` is empty,
+    /// [`InitDataError::HashInvalid`] if it wasn't signed with `key` and [`InitDataError::Expired`]
+    /// once it has expired.
+    ///
+    /// # Panics
+    ///
+    /// See [`crate::validate`].
+    pub fn verify(receipt: &str, key: &[u8]) -> Result<Self, InitDataError> {
+        Self::verify_with_clock(receipt, key, &SystemClock)
+    }
+
+    /// Same as [`Receipt::verify`], but reads the current time from `clock`.
+    ///
+    /// # Errors
+    ///
+    /// See [`Receipt::verify`].
+    pub fn verify_with_clock(receipt: &str, key: &[u8], clock: &impl Clock) -> Result<Self, InitDataError> {
+        let malformed = || InitDataError::UnexpectedFormat("receipt is malformed".to_string());
+
+        let (signed, signature) = receipt.rsplit_once('.').ok_or_else(malformed)?;
+        let (version, payload) = signed.split_once('.').ok_or_else(malformed)?;
+        if version != VERSION {
+            return Err(malformed());
+        }
+
+        let mut signature_bytes = [0u8; 32];
+        let signature_len = base64_engine
+            .decode_slice(signature, &mut signature_bytes)
+            .map_err(|_| malformed())?;
+        if !mac(key, signed)?.verify(&signature_bytes[..signature_len])? {
+            return Err(InitDataError::HashInvalid);
+        }
+
+        let payload = base64_engine.decode(payload).map_err(|_| malformed())?;
+        let payload = String::from_utf8(payload).map_err(|_| malformed())?;
+        let mut fields = payload.split(':');
+        let (Some(user_id), Some(auth_date), Some(scheme), Some(expires_at), None) = (
+            fields.next(),
+            fields.next(),
+            fields.next(),
+            fields.next(),
+            fields.next(),
+        ) else {
+            return Err(malformed());
+        };
+
+        let receipt = Self {
+            user_id: if user_id.is_empty() {
+                None
+            } else {
+                Some(user_id.parse().map_err(|_| malformed())?)
+            },
+            auth_date: auth_date.parse().map_err(|_| malformed())?,
+            scheme: ValidationScheme::from_name(scheme).ok_or_else(malformed)?,
+            expires_at: expires_at.parse().map_err(|_| malformed())?,
+        };
+
+        if receipt.expires_at < clock.now() {
+            return Err(InitDataError::Expired);
+        }
+
+        Ok(receipt)
+    }
+}
+
+fn mac(key: &[u8], signed: &str) -> Result<Hmac, InitDataError> {
+    if key.is_empty() {
+        return Err(InitDataError::UnexpectedFormat("receipt key is empty".to_string()));
+    }
+
+    let mut mac = Hmac::new(key)?;
+    mac.update(signed.as_bytes());
+
+    Ok(mac)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse;
+
+    const KEY: &[u8] = b"service key";
+    const INIT_DATA: &str = "user=%7B%22id%22%3A279058397%2C%22first_name%22%3A%22Vladislav%22%7D&auth_date=1662771648&hash=c501b71e775f74ce10e377dea85a7ea24ecd640b223ea86dfe453e0eaed2e2b2";
+
+    #[test]
+    fn test_receipt_roundtrip() {
+        let data = parse(INIT_DATA).unwrap();
+        let receipt = Receipt::new_with_clock(&data, ValidationScheme::BotTokenHmac, 60, &|| 1_000);
+        assert_eq!(receipt.user_id, Some(279058397));
+        assert_eq!(receipt.expires_at, 1_060);
+
+        let token = receipt.sign(KEY).unwrap();
+        assert_eq!(Receipt::verify_with_clock(&token, KEY, &|| 1_060).unwrap(), receipt);
+        assert!(matches!(
+            Receipt::verify_with_clock(&token, KEY, &|| 1_061),
+            Err(InitDataError::Expired)
+        ));
+    }
+
+    #[test]
+    fn test_receipt_rejects_tampering() {
+        let receipt = Receipt {
+            user_id: None,
+            auth_date: 1,
+            scheme: ValidationScheme::Ed25519ThirdParty,
+            expires_at: 100,
+        };
+        let token = receipt.sign(KEY).unwrap();
+        let clock = || 0;
+
+        assert_eq!(Receipt::verify_with_clock(&token, KEY, &clock).unwrap(), receipt);
+        assert!(matches!(
+            Receipt::verify_with_clock(&token, b"other key", &clock),
+            Err(InitDataError::HashInvalid)
+        ));
+
+        let forged = Receipt {
+            expires_at: u64::MAX,
+            ..receipt
+        }
+        .sign(b"other key")
+        .unwrap();
+        let (_, forged_payload) = forged.split_once('.').unwrap();
+        let (forged_payload, _) = forged_payload.split_once('.').unwrap();
+        let (_, signature) = token.rsplit_once('.').unwrap();
+        assert!(matches!(
+            Receipt::verify_with_clock(&format!("v1.{forged_payload}.{signature}"), KEY, &clock),
+            Err(InitDataError::HashInvalid)
+        ));
+
+        assert!(matches!(
+            Receipt::verify_with_clock("garbage", KEY, &clock),
+            Err(InitDataError::UnexpectedFormat(_))
+        ));
+        assert!(matches!(receipt.sign(b""), Err(InitDataError::UnexpectedFormat(_))));
+    }
+}
@@ -0,0 +1,164 @@
+//! Bounded cache of recently rejected init data.
+//!
+//! Forged init data tends to be replayed many times in a row. Remembering the fingerprints of
+//! rejected payloads lets the [`crate::Validator`] answer repeated attempts without running the
+//! HMAC again.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+use crate::crypto;
+use crate::error::InitDataError;
+
+type Fingerprint = [u8; 32];
+
+/// Hit/miss counters of a rejection cache.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RejectionCacheStats {
+    /// Lookups answered from the cache.
+    pub hits: u64,
+    /// Lookups that had to run the full validation.
+    pub misses: u64,
+    /// Number of payloads currently cached.
+    pub len: usize,
+}
+
+impl RejectionCacheStats {
+    /// Share of lookups answered from the cache, between `0.0` and `1.0`.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn hit_rate(&self) -> f64 {
+        let total = self.hits + self.misses;
+        if total == 0 {
+            0.0
+        } else {
+            self.hits as f64 / total as f64
+        }
+    }
+}
+
+#[derive(Default)]
+struct Entries {
+    order: VecDeque<Fingerprint>,
+    errors: HashMap<Fingerprint, InitDataError>,
+}
+
+pub(crate) struct RejectionCache {
+    capacity: usize,
+    entries: Mutex<Entries>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl RejectionCache {
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: Mutex::default(),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    pub(crate) fn fingerprint(init_data: &str) -> Fingerprint {
+        crypto::sha256(init_data.as_bytes())
+    }
+
+    /// Returns the error the payload was rejected with, if it is cached.
+    pub(crate) fn get(&self, fingerprint: &Fingerprint) -> Option<InitDataError> {
+        let error = self
+            .entries
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .errors
+            .get(fingerprint)
+            .cloned();
+
+        let counter = if error.is_some() { &self.hits } else { &self.misses };
+        counter.fetch_add(1, Ordering::Relaxed);
+
+        error
+    }
+
+    /// Remembers a rejection, evicting the oldest one when the cache is full.
+    /// Internal errors are not cached, since they do not depend on the payload.
+    pub(crate) fn insert(&self, fingerprint: Fingerprint, error: &InitDataError) {
+        if self.capacity == 0 || matches!(error, InitDataError::Internal(_)) {
+            return;
+        }
+
+        let mut entries = self.entries.lock().unwrap_or_else(std::sync::PoisonError::into_inner);
+        if entries.errors.insert(fingerprint, error.clone()).is_some() {
+            return;
+        }
+
+        entries.order.push_back(fingerprint);
+        if entries.order.len() > self.capacity {
+            if let Some(oldest) = entries.order.pop_front() {
+                entries.errors.remove(&oldest);
+            }
+        }
+    }
+
+    /// Forgets every rejection, e.g. once they may no longer hold.
+    pub(crate) fn clear(&self) {
+        let mut entries = self.entries.lock().unwrap_or_else(std::sync::PoisonError::into_inner);
+        entries.order.clear();
+        entries.errors.clear();
+    }
+
+    pub(crate) fn stats(&self) -> RejectionCacheStats {
+        RejectionCacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            len: self
+                .entries
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner)
+                .order
+                .len(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rejection_cache_evicts_oldest() {
+        let cache = RejectionCache::new(2);
+        let (a, b, c) = (
+            RejectionCache::fingerprint("a"),
+            RejectionCache::fingerprint("b"),
+            RejectionCache::fingerprint("c"),
+        );
+
+        cache.insert(a, &InitDataError::HashInvalid);
+        cache.insert(b, &InitDataError::Expired);
+        cache.insert(c, &InitDataError::HashMissing);
+
+        assert!(cache.get(&a).is_none());
+        assert!(matches!(cache.get(&b), Some(InitDataError::Expired)));
+        assert!(matches!(cache.get(&c), Some(InitDataError::HashMissing)));
+        assert_eq!(
+            cache.stats(),
+            RejectionCacheStats {
+                hits: 2,
+                misses: 1,
+                len: 2
+            }
+        );
+    }
+
+    #[test]
+    fn test_rejection_cache_skips_internal_errors() {
+        let cache = RejectionCache::new(2);
+        let fingerprint = RejectionCache::fingerprint("a");
+
+        cache.insert(fingerprint, &InitDataError::Internal("boom".to_string()));
+
+        assert!(cache.get(&fingerprint).is_none());
+    }
+}
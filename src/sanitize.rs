@@ -0,0 +1,101 @@
+//! Sanitizing raw init data before it ends up in logs.
+
+use crate::query::{decode, decoded_eq, raw_pairs};
+
+/// Placeholder written in place of the `hash` and `signature` values.
+const MASK: &str = "REDACTED";
+
+/// Masks the `hash` and `signature` of raw init data so the payload can be logged for debugging.
+///
+/// Anyone holding the hash of a fresh payload can replay it, so it must be treated like a credential.
+/// The other parameters are kept in their original order and encoding.
+///
+/// # Arguments
+/// * `init_data` - Raw init data string from Telegram Mini App
+/// * `max_user_len` - When set, the decoded `user` object is truncated to that many bytes, followed by `…`
+///
+/// # Returns
+/// The sanitized query string. It no longer validates.
+///
+/// # Example
+/// ```
+/// use init_data_rs::strip_secrets;
+///
+/// let init_data = "query_id=123&user=%7B%22id%22%3A279058397%7D&auth_date=1662771648&hash=abcdef";
+/// assert_eq!(
+///     strip_secrets(init_data, Some(8)),
+///     "query_id=123&user=%7B%22id%22%3A27%E2%80%A6&auth_date=1662771648&hash=REDACTED"
+/// );
+/// ```
+#[must_use]
+pub fn strip_secrets(init_data: &str, max_user_len: Option<usize>) -> String {
+    let mut sanitized = String::with_capacity(init_data.len());
+
+    for (key, value) in raw_pairs(init_data) {
+        if !sanitized.is_empty() {
+            sanitized.push('&');
+        }
+        sanitized.push_str(key);
+        sanitized.push('=');
+
+        if decoded_eq(key, "hash") || decoded_eq(key, "signature") {
+            sanitized.push_str(MASK);
+        } else if let Some(user) = max_user_len
+            .filter(|_| decoded_eq(key, "user"))
+            .and_then(|max| truncate(value, max))
+        {
+            sanitized.extend(form_urlencoded::byte_serialize(user.as_bytes()));
+        } else {
+            sanitized.push_str(value);
+        }
+    }
+
+    sanitized
+}
+
+/// Returns the decoded `value` cut to at most `max` bytes on a char boundary, or `None` if it already fits.
+fn truncate(value: &str, max: usize) -> Option<String> {
+    let mut decoded = String::from_utf8_lossy(&decode(value).collect::<Vec<_>>()).into_owned();
+    if decoded.len() <= max {
+        return None;
+    }
+
+    let end = (0..=max)
+        .rev()
+        .find(|&index| decoded.is_char_boundary(index))
+        .unwrap_or(0);
+    decoded.truncate(end);
+    decoded.push('…');
+
+    Some(decoded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INIT_DATA: &str = "query_id=AAHdF6IQAAAAAN0XohDhrOrc&user=%7B%22id%22%3A279058397%2C%22first_name%22%3A%22Vladislav%22%7D&auth_date=1662771648&signature=6fbdaab833d39f54518bd5c3eb3f511d&hash=c501b71e775f74ce10e377dea85a7ea24ecd640b223ea86dfe453e0eaed2e2b2";
+
+    #[test]
+    fn test_strip_secrets() {
+        let sanitized = strip_secrets(INIT_DATA, None);
+        assert_eq!(
+            sanitized,
+            "query_id=AAHdF6IQAAAAAN0XohDhrOrc&user=%7B%22id%22%3A279058397%2C%22first_name%22%3A%22Vladislav%22%7D&auth_date=1662771648&signature=REDACTED&hash=REDACTED"
+        );
+    }
+
+    #[test]
+    fn test_strip_secrets_truncates_user() {
+        let sanitized = strip_secrets(INIT_DATA, Some(15));
+        assert!(sanitized.contains("&user=%7B%22id%22%3A279058397%E2%80%A6&"));
+
+        assert_eq!(strip_secrets(INIT_DATA, Some(1000)), strip_secrets(INIT_DATA, None));
+    }
+
+    #[test]
+    fn test_strip_secrets_truncates_on_char_boundary() {
+        let sanitized = strip_secrets("user=%C3%A9%C3%A9", Some(3));
+        assert_eq!(sanitized, "user=%C3%A9%E2%80%A6");
+    }
+}
@@ -0,0 +1,308 @@
+//! The ways Telegram lets init data be checked.
+//!
+//! Init data can carry both a `hash`, checked with the bot token, and a `signature`, checked with
+//! Telegram's public key by third parties. Only the fields covered by the scheme that was actually
+//! checked are trustworthy, so the functions here make the expected scheme explicit and record it
+//! in the [`Verified`] result.
+
+use std::fmt;
+use std::ops::Deref;
+
+use crate::clock::{Clock, SystemClock};
+use crate::crypto::{CryptoBackend, CRYPTO_BACKEND};
+use crate::error::InitDataError;
+use crate::expiration::Expiration;
+use crate::model::InitData;
+use crate::query;
+use crate::secret::TokenProvider;
+use crate::third_party_validation::validate_third_party_with_clock;
+use crate::validation::validate_with_clock;
+
+/// How init data was authenticated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ValidationScheme {
+    /// The `hash` parameter, an HMAC keyed with the bot token.
+    BotTokenHmac,
+    /// The `signature` parameter, an Ed25519 signature by Telegram for third parties.
+    Ed25519ThirdParty,
+}
+
+impl ValidationScheme {
+    /// The name of the parameter the scheme checks, `hash` or `signature`.
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::BotTokenHmac => "hash",
+            Self::Ed25519ThirdParty => "signature",
+        }
+    }
+
+    /// The crypto backend that checks the scheme, see [`crate::CRYPTO_BACKEND`].
+    #[must_use]
+    pub fn backend(self) -> CryptoBackend {
+        CRYPTO_BACKEND
+    }
+
+    pub(crate) fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "hash" => Some(Self::BotTokenHmac),
+            "signature" => Some(Self::Ed25519ThirdParty),
+            _ => None,
+        }
+    }
+
+    /// The scheme `validate_auto` picks for `init_data`: the bot token HMAC if a `hash` is present,
+    /// the third-party signature otherwise.
+    pub(crate) fn detect(init_data: &str) -> Result<Self, InitDataError> {
+        let has = |name| query::raw_pairs(init_data).any(|(key, _)| query::decoded_eq(key, name));
+
+        if has("hash") {
+            Ok(Self::BotTokenHmac)
+        } else if has("signature") {
+            Ok(Self::Ed25519ThirdParty)
+        } else {
+            Err(InitDataError::HashMissing)
+        }
+    }
+}
+
+impl fmt::Display for ValidationScheme {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Serializes a [`ValidationScheme`] by its name, for `#[serde(with = "crate::scheme::scheme_name")]`.
+#[cfg(feature = "json")]
+pub(crate) mod scheme_name {
+    use serde::{de::Error as _, Deserialize, Deserializer, Serializer};
+
+    use super::ValidationScheme;
+
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub(crate) fn serialize<S: Serializer>(scheme: &ValidationScheme, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(scheme.as_str())
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<ValidationScheme, D::Error> {
+        let name = <&str>::deserialize(deserializer)?;
+        ValidationScheme::from_name(name).ok_or_else(|| D::Error::custom(format!("unknown scheme `{name}`")))
+    }
+}
+
+/// A value that passed validation, along with the scheme it was validated with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Verified<T> {
+    value: T,
+    scheme: ValidationScheme,
+}
+
+impl<T> Verified<T> {
+    pub(crate) fn new(value: T, scheme: ValidationScheme) -> Self {
+        Self { value, scheme }
+    }
+
+    /// The scheme the value was validated with.
+    pub fn scheme(&self) -> ValidationScheme {
+        self.scheme
+    }
+
+    /// The crypto backend that verified the value, e.g. to assert that it is FIPS validated.
+    pub fn backend(&self) -> CryptoBackend {
+        self.scheme.backend()
+    }
+
+    /// Returns the validated value.
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<T> Deref for Verified<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+/// A value parsed without any cryptographic check, e.g. for logging or debugging.
+///
+/// Unlike [`Verified`], it doesn't dereference to the value: reaching into it takes an explicit
+/// [`Unverified::as_unverified`] or [`Unverified::into_unverified`].
+///
+/// # Example
+/// ```
+/// use init_data_rs::{InitData, Unverified};
+///
+/// let data = Unverified::<InitData>::try_from("auth_date=1662771648&hash=c501b71e775f74ce10e377dea85a7ea24ecd640b223ea86dfe453e0eaed2e2b2").unwrap();
+/// assert_eq!(data.as_unverified().auth_date, 1662771648);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Unverified<T> {
+    value: T,
+}
+
+impl<T> Unverified<T> {
+    /// The value, which nothing vouches for.
+    pub fn as_unverified(&self) -> &T {
+        &self.value
+    }
+
+    /// Returns the value, which nothing vouches for.
+    pub fn into_unverified(self) -> T {
+        self.value
+    }
+}
+
+impl TryFrom<&str> for Unverified<InitData> {
+    type Error = InitDataError;
+
+    /// Parses init data without validating it, see [`crate::parse`].
+    fn try_from(init_data: &str) -> Result<Self, Self::Error> {
+        crate::parse(init_data).map(|value| Self { value })
+    }
+}
+
+/// Reads the bot id, the part of the token before the colon.
+pub(crate) fn bot_id(token: &(impl TokenProvider + ?Sized)) -> Result<i64, InitDataError> {
+    let token = token.expose_token();
+    let id = token.split(|&byte| byte == b':').next().unwrap_or_default();
+
+    std::str::from_utf8(id)
+        .ok()
+        .and_then(|id| id.parse().ok())
+        .ok_or_else(|| InitDataError::UnexpectedFormat("token doesn't start with a bot id".to_string()))
+}
+
+/// Validates init data with the given scheme only.
+///
+/// For [`ValidationScheme::Ed25519ThirdParty`] the bot id is taken from `token`, and the data is checked
+/// against Telegram's production key, see [`crate::validate_third_party`].
+///
+/// # Example
+/// ```
+/// use init_data_rs::{validate_scheme, Expiration, ValidationScheme};
+///
+/// let init_data = "query_id=123&auth_date=1662771648&hash=...";
+/// let result = validate_scheme(init_data, ValidationScheme::BotTokenHmac, "BOT_TOKEN", Expiration::Default);
+/// ```
+///
+/// # Errors
+///
+/// See [`crate::validate`] and [`crate::validate_third_party`].
+///
+/// # Panics
+///
+/// See [`crate::validate`].
+pub fn validate_scheme(
+    init_data: &str,
+    scheme: ValidationScheme,
+    token: &(impl TokenProvider + ?Sized),
+    expiration: impl Into<Expiration>,
+) -> Result<Verified<InitData>, InitDataError> {
+    validate_scheme_with_clock(init_data, scheme, token, expiration, &SystemClock)
+}
+
+/// Same as [`validate_scheme`], but reads the current time from `clock`.
+///
+/// # Errors
+///
+/// See [`validate_scheme`].
+pub fn validate_scheme_with_clock(
+    init_data: &str,
+    scheme: ValidationScheme,
+    token: &(impl TokenProvider + ?Sized),
+    expiration: impl Into<Expiration>,
+    clock: &impl Clock,
+) -> Result<Verified<InitData>, InitDataError> {
+    let data = match scheme {
+        ValidationScheme::BotTokenHmac => validate_with_clock(init_data, token, expiration, clock)?,
+        ValidationScheme::Ed25519ThirdParty => {
+            validate_third_party_with_clock(init_data, bot_id(token)?, expiration, clock)?
+        }
+    };
+
+    Ok(Verified::new(data, scheme))
+}
+
+/// Validates init data with the bot token HMAC if it carries a `hash`, or with the third-party
+/// signature otherwise. Check [`Verified::scheme`] before trusting scheme-specific guarantees.
+///
+/// # Errors
+///
+/// Returns [`InitDataError::HashMissing`] if the init data carries neither, see [`validate_scheme`]
+/// for the other errors.
+///
+/// # Panics
+///
+/// See [`crate::validate`].
+pub fn validate_auto(
+    init_data: &str,
+    token: &(impl TokenProvider + ?Sized),
+    expiration: impl Into<Expiration>,
+) -> Result<Verified<InitData>, InitDataError> {
+    validate_scheme(init_data, ValidationScheme::detect(init_data)?, token, expiration)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BOT_TOKEN: &str = "5768337691:AAH5YkoiEuPk8-FZa32hStHTqXiLPtAEhx8";
+    const VALID_INIT_DATA: &str = "query_id=AAHdF6IQAAAAAN0XohDhrOrc&user=%7B%22id%22%3A279058397%2C%22first_name%22%3A%22Vladislav%22%2C%22last_name%22%3A%22Kibenko%22%2C%22username%22%3A%22vdkfrost%22%2C%22language_code%22%3A%22ru%22%2C%22is_premium%22%3Atrue%7D&auth_date=1662771648&hash=c501b71e775f74ce10e377dea85a7ea24ecd640b223ea86dfe453e0eaed2e2b2";
+
+    #[test]
+    fn test_validate_scheme_is_explicit() {
+        let verified = validate_scheme(
+            VALID_INIT_DATA,
+            ValidationScheme::BotTokenHmac,
+            BOT_TOKEN,
+            Expiration::Never,
+        )
+        .unwrap();
+        assert_eq!(verified.scheme(), ValidationScheme::BotTokenHmac);
+        assert_eq!(verified.backend(), crate::CRYPTO_BACKEND);
+        assert_eq!(verified.auth_date, 1662771648);
+
+        // The payload has a valid hash but no signature: it must not pass as third-party validated
+        assert!(matches!(
+            validate_scheme(
+                VALID_INIT_DATA,
+                ValidationScheme::Ed25519ThirdParty,
+                BOT_TOKEN,
+                Expiration::Never
+            ),
+            Err(InitDataError::SignatureMissing)
+        ));
+    }
+
+    #[test]
+    fn test_validate_auto() {
+        let verified = validate_auto(VALID_INIT_DATA, BOT_TOKEN, Expiration::Never).unwrap();
+        assert_eq!(verified.scheme(), ValidationScheme::BotTokenHmac);
+
+        assert_eq!(
+            ValidationScheme::detect("auth_date=1&signature=abc").unwrap(),
+            ValidationScheme::Ed25519ThirdParty
+        );
+        assert!(matches!(
+            validate_auto("auth_date=1", BOT_TOKEN, Expiration::Default),
+            Err(InitDataError::HashMissing)
+        ));
+    }
+
+    #[test]
+    fn test_bot_id() {
+        assert_eq!(bot_id(BOT_TOKEN).unwrap(), 5768337691);
+        assert!(bot_id("not a token").is_err());
+    }
+
+    #[test]
+    fn test_unverified_try_from() {
+        let data = Unverified::<InitData>::try_from(VALID_INIT_DATA).unwrap();
+        assert_eq!(data.into_unverified().user.unwrap().id, 279058397);
+
+        assert!(Unverified::<InitData>::try_from("auth_date=1").is_err());
+    }
+}
@@ -0,0 +1,259 @@
+//! Bot token and the HMAC key derived from it.
+
+use std::fmt;
+
+use crate::crypto::Hmac;
+use crate::error::InitDataError;
+
+/// Anything a bot token can be read from.
+///
+/// Every function taking a token accepts `&str`, `String`, [`BotToken`] and, with the `secrecy` feature,
+/// `secrecy::SecretString` and `secrecy::SecretSlice<u8>`, so secrets can stay wrapped along the call chain.
+pub trait TokenProvider {
+    /// Returns the raw token bytes.
+    fn expose_token(&self) -> &[u8];
+}
+
+impl TokenProvider for str {
+    fn expose_token(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+impl TokenProvider for String {
+    fn expose_token(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+impl TokenProvider for [u8] {
+    fn expose_token(&self) -> &[u8] {
+        self
+    }
+}
+
+impl TokenProvider for Vec<u8> {
+    fn expose_token(&self) -> &[u8] {
+        self
+    }
+}
+
+impl TokenProvider for BotToken {
+    fn expose_token(&self) -> &[u8] {
+        self.0.as_bytes()
+    }
+}
+
+impl<T: TokenProvider + ?Sized> TokenProvider for &T {
+    fn expose_token(&self) -> &[u8] {
+        (**self).expose_token()
+    }
+}
+
+#[cfg(feature = "secrecy")]
+impl TokenProvider for secrecy::SecretString {
+    fn expose_token(&self) -> &[u8] {
+        secrecy::ExposeSecret::expose_secret(self).as_bytes()
+    }
+}
+
+#[cfg(feature = "secrecy")]
+impl TokenProvider for secrecy::SecretSlice<u8> {
+    fn expose_token(&self) -> &[u8] {
+        secrecy::ExposeSecret::expose_secret(self)
+    }
+}
+
+/// A Telegram bot token.
+///
+/// The token is redacted from `Debug` output. With the `zeroize` feature it is wiped from memory on drop.
+#[derive(Clone, PartialEq, Eq)]
+pub struct BotToken(String);
+
+impl BotToken {
+    /// Wraps a bot token as issued by `@BotFather`.
+    pub fn new(token: impl Into<String>) -> Self {
+        Self(token.into())
+    }
+
+    /// Returns the token itself.
+    #[must_use]
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+
+    /// Derives the key init data hashes are signed with.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`InitDataError::UnexpectedFormat`] if the token is empty.
+    pub fn secret(&self) -> Result<BotSecret, InitDataError> {
+        BotSecret::from_token(self)
+    }
+}
+
+impl From<String> for BotToken {
+    fn from(token: String) -> Self {
+        Self(token)
+    }
+}
+
+impl From<&str> for BotToken {
+    fn from(token: &str) -> Self {
+        Self(token.to_string())
+    }
+}
+
+#[cfg(feature = "secrecy")]
+impl From<secrecy::SecretString> for BotToken {
+    fn from(token: secrecy::SecretString) -> Self {
+        Self(secrecy::ExposeSecret::expose_secret(&token).to_string())
+    }
+}
+
+#[cfg(feature = "secrecy")]
+impl TryFrom<secrecy::SecretSlice<u8>> for BotToken {
+    type Error = InitDataError;
+
+    fn try_from(token: secrecy::SecretSlice<u8>) -> Result<Self, Self::Error> {
+        std::str::from_utf8(secrecy::ExposeSecret::expose_secret(&token))
+            .map(|token| Self(token.to_string()))
+            .map_err(|_| InitDataError::UnexpectedFormat("token is not valid UTF-8".to_string()))
+    }
+}
+
+impl fmt::Debug for BotToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("BotToken(***)")
+    }
+}
+
+/// The secret key derived from a bot token, `HMAC_SHA256("WebAppData", token)`.
+///
+/// It is redacted from `Debug` output. With the `zeroize` feature it is wiped from memory on drop.
+#[derive(Clone, PartialEq, Eq)]
+pub struct BotSecret([u8; 32]);
+
+impl BotSecret {
+    /// Derives the secret key from a bot token.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`InitDataError::UnexpectedFormat`] if the token is empty.
+    pub fn from_token(token: &(impl TokenProvider + ?Sized)) -> Result<Self, InitDataError> {
+        let token = token.expose_token();
+        if token.is_empty() {
+            return Err(InitDataError::UnexpectedFormat("token is empty".to_string()));
+        }
+
+        // More : https://core.telegram.org/bots/webapps#validating-data-received-via-the-mini-app
+        let mut hmac = Hmac::new(b"WebAppData")?;
+        hmac.update(token);
+
+        // Finalizing in place avoids leaving a copy of the key in a temporary
+        let mut secret = Self([0; 32]);
+        hmac.finalize_into(&mut secret.0)?;
+
+        Ok(secret)
+    }
+
+    /// Returns the raw key bytes.
+    #[must_use]
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+}
+
+impl fmt::Debug for BotSecret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("BotSecret(***)")
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl zeroize::Zeroize for BotToken {
+    fn zeroize(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl Drop for BotToken {
+    fn drop(&mut self) {
+        zeroize::Zeroize::zeroize(self);
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl zeroize::ZeroizeOnDrop for BotToken {}
+
+#[cfg(feature = "zeroize")]
+impl zeroize::Zeroize for BotSecret {
+    fn zeroize(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl Drop for BotSecret {
+    fn drop(&mut self) {
+        zeroize::Zeroize::zeroize(self);
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl zeroize::ZeroizeOnDrop for BotSecret {}
+
+#[cfg(test)]
+mod tests {
+    use hmac::{Hmac, KeyInit, Mac};
+    use sha2::Sha256;
+
+    use super::*;
+
+    const BOT_TOKEN: &str = "5768337691:AAH5YkoiEuPk8-FZa32hStHTqXiLPtAEhx8";
+
+    #[test]
+    fn test_bot_secret_from_token() {
+        let mut hmac: Hmac<Sha256> = Hmac::new_from_slice(b"WebAppData").unwrap();
+        hmac.update(BOT_TOKEN.as_bytes());
+
+        let secret = BotToken::new(BOT_TOKEN).secret().unwrap();
+        assert_eq!(secret.as_bytes()[..], hmac.finalize().as_bytes()[..]);
+        assert!(matches!(
+            BotSecret::from_token(""),
+            Err(InitDataError::UnexpectedFormat(_))
+        ));
+    }
+
+    #[test]
+    fn test_debug_is_redacted() {
+        let token = BotToken::new(BOT_TOKEN);
+        assert_eq!(format!("{token:?}"), "BotToken(***)");
+        assert_eq!(format!("{:?}", token.secret().unwrap()), "BotSecret(***)");
+    }
+
+    #[cfg(feature = "secrecy")]
+    #[test]
+    fn test_secrecy_tokens() {
+        let expected = BotSecret::from_token(BOT_TOKEN).unwrap();
+
+        let token = secrecy::SecretString::from(BOT_TOKEN);
+        assert_eq!(BotSecret::from_token(&token).unwrap(), expected);
+        assert_eq!(BotToken::from(token).secret().unwrap(), expected);
+
+        let token = secrecy::SecretSlice::from(BOT_TOKEN.as_bytes().to_vec());
+        assert_eq!(BotSecret::from_token(&token).unwrap(), expected);
+        assert_eq!(BotToken::try_from(token).unwrap().secret().unwrap(), expected);
+    }
+
+    #[cfg(feature = "zeroize")]
+    #[test]
+    fn test_zeroize() {
+        use zeroize::Zeroize;
+
+        let mut secret = BotSecret::from_token(BOT_TOKEN).unwrap();
+        secret.zeroize();
+        assert_eq!(secret.as_bytes(), &[0; 32]);
+    }
+}
@@ -0,0 +1,80 @@
+//! Claims of the session tokens issued in exchange for validated init data, shared by the `jwt` and
+//! `paseto` features.
+
+use serde::{Deserialize, Serialize};
+
+use crate::clock::Clock;
+use crate::error::InitDataError;
+use crate::model::InitData;
+use crate::scheme::{ValidationScheme, Verified};
+
+/// Claims of a session token issued with the `jwt` or the `paseto` feature.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionClaims {
+    /// Id of the user, as a string like registered JWT subjects.
+    pub sub: String,
+    /// Username of the user, if they have one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    /// Whether the user has Telegram Premium.
+    #[serde(default)]
+    pub premium: bool,
+    /// How the init data was validated.
+    #[serde(with = "crate::scheme::scheme_name")]
+    pub scheme: ValidationScheme,
+    /// Unix time the token was issued at.
+    pub iat: u64,
+    /// Unix time after which the token is no longer accepted.
+    pub exp: u64,
+}
+
+impl SessionClaims {
+    /// Creates the claims for validated init data, valid for `ttl` seconds from now.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`InitDataError::UnexpectedFormat`] if the init data has no user.
+    pub fn new_with_clock(verified: &Verified<InitData>, ttl: u64, clock: &impl Clock) -> Result<Self, InitDataError> {
+        let user = verified
+            .user
+            .as_ref()
+            .ok_or_else(|| InitDataError::UnexpectedFormat("init data has no user".to_string()))?;
+
+        let now = clock.now();
+        Ok(Self {
+            sub: user.id.to_string(),
+            username: user.username.clone(),
+            premium: user.is_premium.unwrap_or(false),
+            scheme: verified.scheme(),
+            iat: now,
+            exp: now.saturating_add(ttl),
+        })
+    }
+
+    /// Id of the user as a number.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`InitDataError::UnexpectedFormat`] if `sub` is not a user id.
+    pub fn user_id(&self) -> Result<i64, InitDataError> {
+        self.sub
+            .parse()
+            .map_err(|_| InitDataError::UnexpectedFormat("`sub` is not a user id".to_string()))
+    }
+
+    pub(crate) fn to_json(&self) -> Result<Vec<u8>, InitDataError> {
+        serde_json::to_vec(self).map_err(|error| InitDataError::Internal(error.to_string()))
+    }
+
+    /// Decodes verified claims and checks their expiration.
+    pub(crate) fn from_json(claims: &[u8], clock: &impl Clock) -> Result<Self, InitDataError> {
+        let claims: Self = serde_json::from_slice(claims)
+            .map_err(|_| InitDataError::UnexpectedFormat("token is malformed".to_string()))?;
+
+        if claims.exp < clock.now() {
+            return Err(InitDataError::Expired);
+        }
+
+        Ok(claims)
+    }
+}
@@ -1,58 +1,205 @@
-use hmac::{Hmac, KeyInit, Mac};
-use sha2::Sha256;
-use std::collections::BTreeMap;
-use url::form_urlencoded;
+use std::ops::Range;
 
+use crate::buffers::ValidationBuffers;
+use crate::compat::Compat;
+use crate::crypto::Hmac;
 use crate::error::InitDataError;
+use crate::query;
+use crate::secret::{BotSecret, TokenProvider};
 
 /// Sign creates hash for init data using bot token.
 ///
 /// # Errors
 ///
 /// See `init_data_rs::parse` for possible errors
-pub fn sign(init_data: &str, token: &str) -> Result<String, InitDataError> {
+pub fn sign(init_data: &str, token: &(impl TokenProvider + ?Sized)) -> Result<String, InitDataError> {
+    sign_with_compat(init_data, token, Compat::default())
+}
+
+/// Same as [`sign`], but computes the data-check-string like another implementation would, see [`Compat`].
+///
+/// # Errors
+///
+/// See [`sign`].
+pub fn sign_with_compat(
+    init_data: &str,
+    token: &(impl TokenProvider + ?Sized),
+    compat: Compat,
+) -> Result<String, InitDataError> {
     if init_data.is_empty() {
         return Err(InitDataError::UnexpectedFormat("init_data is empty".to_string()));
     }
 
-    if token.is_empty() {
-        return Err(InitDataError::UnexpectedFormat("token is empty".to_string()));
+    let secret = BotSecret::from_token(token)?;
+    let hmac = data_check_hmac(init_data, &secret, &mut ValidationBuffers::default(), compat)?;
+
+    Ok(encode_hex(&hmac.finalize()?))
+}
+
+/// Checks a hex `provided_hash` against the hash of `base_data` for the bot token, in constant time.
+///
+/// This is only the cryptographic core of [`crate::verify`], for stacks that parse init data themselves:
+/// neither the expiration nor the shape of the payload is checked. A `hash` parameter in `base_data` is
+/// ignored, like in [`sign`].
+///
+/// # Arguments
+/// * `base_data` - Raw init data string, with or without its `hash`
+/// * `provided_hash` - Hex encoded hash to check
+/// * `token` - Bot token used for validation, see [`crate::TokenProvider`]
+///
+/// # Returns
+/// Whether `provided_hash` is the hash of `base_data`. A hash that is not 64 hex characters never matches.
+///
+/// # Example
+/// ```
+/// use init_data_rs::{sign, verify_hash};
+///
+/// let base_data = "query_id=123&auth_date=1662771648";
+/// let hash = sign(base_data, "12345:BOT_TOKEN").unwrap();
+/// assert!(verify_hash(base_data, &hash, "12345:BOT_TOKEN").unwrap());
+/// assert!(!verify_hash(base_data, &hash, "12345:OTHER_TOKEN").unwrap());
+/// ```
+///
+/// # Errors
+///
+/// `InitDataError::UnexpectedFormat` if `base_data` is empty, or the errors of the token provider.
+pub fn verify_hash(
+    base_data: &str,
+    provided_hash: &str,
+    token: &(impl TokenProvider + ?Sized),
+) -> Result<bool, InitDataError> {
+    if base_data.is_empty() {
+        return Err(InitDataError::UnexpectedFormat("init_data is empty".to_string()));
     }
 
-    let pairs = form_urlencoded::parse(init_data.as_bytes());
-    let mut params: BTreeMap<String, String> = BTreeMap::new();
+    let secret = BotSecret::from_token(token)?;
+    let hash = decode_hash(provided_hash);
+    let matches = check_hash(
+        base_data,
+        &secret,
+        &hash.unwrap_or([0; 32]),
+        &mut ValidationBuffers::default(),
+        Compat::default(),
+    )
+    .is_ok();
 
-    for (key, value) in pairs {
-        if key != "hash" {
-            params.insert(key.to_string(), value.into_owned());
-        }
+    Ok(hash.is_some() && matches)
+}
+
+/// Checks `hash`, the raw bytes of a hex hash, against the hash of `init_data` in constant time.
+pub(crate) fn check_hash(
+    init_data: &str,
+    secret: &BotSecret,
+    hash: &[u8; 32],
+    buffers: &mut ValidationBuffers,
+    compat: Compat,
+) -> Result<(), InitDataError> {
+    if data_check_hmac(init_data, secret, buffers, compat)?.verify(hash)? {
+        Ok(())
+    } else {
+        Err(InitDataError::HashInvalid)
     }
+}
+
+/// Decodes a 64 characters long hex hash.
+pub(crate) fn decode_hash(hash: &str) -> Option<[u8; 32]> {
+    let mut bytes = [0u8; 32];
+
+    #[cfg(feature = "faster-hex")]
+    let decoded = hash.len() == 64 && faster_hex::hex_decode(hash.as_bytes(), &mut bytes).is_ok();
+    #[cfg(not(feature = "faster-hex"))]
+    let decoded = hex::decode_to_slice(hash, &mut bytes).is_ok();
+
+    decoded.then_some(bytes)
+}
 
-    let data_check_string = params
-        .iter()
-        .map(|(k, v)| format!("{k}={v}"))
-        .collect::<Vec<_>>()
-        .join("\n");
+fn encode_hex(bytes: &[u8]) -> String {
+    #[cfg(feature = "faster-hex")]
+    return faster_hex::hex_string(bytes);
+    #[cfg(not(feature = "faster-hex"))]
+    return hex::encode(bytes);
+}
 
-    // More : https://core.telegram.org/bots/webapps#validating-data-received-via-the-mini-app
+/// Appends the decoded form of a raw query component to `out`, replacing invalid UTF-8 sequences
+/// like `form_urlencoded` does, and returns where it was written.
+fn push_decoded(out: &mut Vec<u8>, raw: &str, plus_as_space: bool) -> Range<usize> {
+    let start = out.len();
+    out.extend(query::decode_with(raw, plus_as_space));
 
-    let mut hmac: Hmac<Sha256> = hmac::Hmac::new_from_slice("WebAppData".as_bytes())
-        .map_err(|error| InitDataError::Internal(error.to_string()))?;
+    if std::str::from_utf8(&out[start..]).is_err() {
+        let lossy = String::from_utf8_lossy(&out[start..]).into_owned();
+        out.truncate(start);
+        out.extend_from_slice(lossy.as_bytes());
+    }
 
-    hmac.update(token.as_bytes());
+    start..out.len()
+}
 
-    let secret_key = hmac.finalize();
+/// Feeds the data-check-string of `init_data` into an HMAC keyed with the bot secret.
+fn data_check_hmac(
+    init_data: &str,
+    secret: &BotSecret,
+    buffers: &mut ValidationBuffers,
+    compat: Compat,
+) -> Result<Hmac, InitDataError> {
+    collect_params(init_data, buffers, compat);
+    let mut hmac = Hmac::new(secret.as_bytes())?;
 
-    let mut hmac: Hmac<Sha256> = hmac::Hmac::new_from_slice(secret_key.as_bytes())
-        .map_err(|error| InitDataError::Internal(error.to_string()))?;
+    // The data-check-string is streamed into the HMAC instead of being assembled first
+    write_data_check_string(buffers, |chunk| hmac.update(chunk));
 
-    hmac.update(data_check_string.as_bytes());
+    Ok(hmac)
+}
 
-    Ok(hex::encode(hmac.finalize().as_bytes()))
+/// Assembles the data-check-string of `init_data`, for a [`crate::MacProvider`] that needs the whole message.
+pub(crate) fn data_check_string(init_data: &str, buffers: &mut ValidationBuffers) -> Vec<u8> {
+    collect_params(init_data, buffers, Compat::default());
+    let mut message = Vec::with_capacity(buffers.decoded.len() + buffers.params.len() * 2);
+    write_data_check_string(buffers, |chunk| message.extend_from_slice(chunk));
+    message
+}
+
+/// Decodes the parameters of `init_data` into `buffers`, without `hash`, sorted and deduplicated by key.
+fn collect_params(init_data: &str, buffers: &mut ValidationBuffers, compat: Compat) {
+    let ValidationBuffers { decoded, params, .. } = buffers;
+    decoded.clear();
+    params.clear();
+
+    for (raw_key, raw_value) in query::raw_pairs(init_data) {
+        let key = push_decoded(decoded, raw_key, compat.plus_as_space);
+        if decoded[key.clone()] == *b"hash" || (compat.exclude_signature && decoded[key.clone()] == *b"signature") {
+            decoded.truncate(key.start);
+            continue;
+        }
+        let value = push_decoded(decoded, raw_value, compat.plus_as_space);
+        params.push((key, value));
+    }
+
+    // Later occurrences of a key win: reversing first makes the stable sort keep them in front for `dedup_by`.
+    params.reverse();
+    params.sort_by(|a, b| decoded[a.0.clone()].cmp(&decoded[b.0.clone()]));
+    params.dedup_by(|a, b| decoded[a.0.clone()] == decoded[b.0.clone()]);
+}
+
+/// Passes the data-check-string of the parameters collected in `buffers` to `write`, piece by piece.
+fn write_data_check_string(buffers: &ValidationBuffers, mut write: impl FnMut(&[u8])) {
+    let ValidationBuffers { decoded, params, .. } = buffers;
+
+    for (index, (key, value)) in params.iter().enumerate() {
+        if index > 0 {
+            write(b"\n");
+        }
+        write(&decoded[key.clone()]);
+        write(b"=");
+        write(&decoded[value.clone()]);
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use hmac::{Hmac, KeyInit, Mac};
+    use sha2::Sha256;
+
     use super::*;
 
     const BOT_TOKEN: &str = "12345:YOUR_BOT_TOKEN";
@@ -127,4 +274,119 @@ mod tests {
 
         assert_eq!(hash1, hash2);
     }
+
+    #[test]
+    fn test_sign_matches_data_check_string() {
+        let init_data = "query_id=test123&user=%7B%22id%22%3A1%7D&auth_date=1662771648&hash=abc";
+
+        let mut hmac: Hmac<Sha256> = Hmac::new_from_slice(b"WebAppData").unwrap();
+        hmac.update(BOT_TOKEN.as_bytes());
+        let mut hmac: Hmac<Sha256> = Hmac::new_from_slice(hmac.finalize().as_bytes()).unwrap();
+        hmac.update(b"auth_date=1662771648\nquery_id=test123\nuser={\"id\":1}");
+
+        assert_eq!(
+            sign(init_data, BOT_TOKEN).unwrap(),
+            hex::encode(hmac.finalize().as_bytes())
+        );
+    }
+
+    #[test]
+    fn test_check_hash() {
+        let init_data = "auth_date=1662771648&query_id=test123";
+        let hash = decode_hash(&sign(init_data, BOT_TOKEN).unwrap()).unwrap();
+
+        let mut buffers = ValidationBuffers::default();
+        let secret = BotSecret::from_token(BOT_TOKEN).unwrap();
+        assert!(check_hash(init_data, &secret, &hash, &mut buffers, Compat::default()).is_ok());
+
+        let secret = BotSecret::from_token("token2").unwrap();
+        assert!(matches!(
+            check_hash(init_data, &secret, &hash, &mut buffers, Compat::default()),
+            Err(InitDataError::HashInvalid)
+        ));
+    }
+
+    #[test]
+    fn test_verify_hash() {
+        let init_data = "auth_date=1662771648&query_id=test123";
+        let hash = sign(init_data, BOT_TOKEN).unwrap();
+
+        assert!(verify_hash(init_data, &hash, BOT_TOKEN).unwrap());
+        assert!(verify_hash(&format!("{init_data}&hash={hash}"), &hash, BOT_TOKEN).unwrap());
+        assert!(verify_hash(init_data, &hash.to_uppercase(), BOT_TOKEN).unwrap());
+        assert!(!verify_hash("auth_date=1662771649&query_id=test123", &hash, BOT_TOKEN).unwrap());
+        assert!(!verify_hash(init_data, "not hex", BOT_TOKEN).unwrap());
+        assert!(matches!(
+            verify_hash("", &hash, BOT_TOKEN),
+            Err(InitDataError::UnexpectedFormat(_))
+        ));
+    }
+
+    #[test]
+    fn test_decode_hash() {
+        assert!(decode_hash("c501b71e775f74ce10e377dea85a7ea24ecd640b223ea86dfe453e0eaed2e2b2").is_some());
+        assert!(decode_hash("C501B71E775F74CE10E377DEA85A7EA24ECD640B223EA86DFE453E0EAED2E2B2").is_some());
+        assert!(decode_hash("c501b71e").is_none());
+        assert!(decode_hash("g501b71e775f74ce10e377dea85a7ea24ecd640b223ea86dfe453e0eaed2e2b2").is_none());
+    }
+
+    #[test]
+    fn test_sign_matches_form_urlencoded() {
+        let init_data = "b=%FF%FEx&a=%E2%82&c=plain+text&a%3D=%7B%7D";
+
+        let mut params: Vec<_> = form_urlencoded::parse(init_data.as_bytes()).collect();
+        params.sort_by(|a, b| a.0.cmp(&b.0));
+        let data_check_string = params
+            .iter()
+            .map(|(key, value)| format!("{key}={value}"))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let mut hmac: Hmac<Sha256> = Hmac::new_from_slice(b"WebAppData").unwrap();
+        hmac.update(BOT_TOKEN.as_bytes());
+        let mut hmac: Hmac<Sha256> = Hmac::new_from_slice(hmac.finalize().as_bytes()).unwrap();
+        hmac.update(data_check_string.as_bytes());
+
+        assert_eq!(
+            sign(init_data, BOT_TOKEN).unwrap(),
+            hex::encode(hmac.finalize().as_bytes())
+        );
+    }
+
+    #[test]
+    fn test_sign_duplicate_keys_last_wins() {
+        let hash1 = sign("auth_date=1&query_id=a&query_id=b", BOT_TOKEN).unwrap();
+        let hash2 = sign("auth_date=1&query_id=b", BOT_TOKEN).unwrap();
+
+        assert_eq!(hash1, hash2);
+    }
+
+    #[test]
+    fn test_sign_with_compat() {
+        let keep_plus = Compat {
+            plus_as_space: false,
+            ..Compat::default()
+        };
+        assert_eq!(
+            sign_with_compat("auth_date=1&query_id=a+b", BOT_TOKEN, keep_plus).unwrap(),
+            sign("auth_date=1&query_id=a%2Bb", BOT_TOKEN).unwrap()
+        );
+        assert_eq!(
+            sign_with_compat("auth_date=1&query_id=a+b", BOT_TOKEN, Compat::default()).unwrap(),
+            sign("auth_date=1&query_id=a%20b", BOT_TOKEN).unwrap()
+        );
+
+        let exclude_signature = Compat {
+            exclude_signature: true,
+            ..Compat::default()
+        };
+        assert_eq!(
+            sign_with_compat("auth_date=1&signature=abc", BOT_TOKEN, exclude_signature).unwrap(),
+            sign("auth_date=1", BOT_TOKEN).unwrap()
+        );
+        assert_ne!(
+            sign("auth_date=1&signature=abc", BOT_TOKEN).unwrap(),
+            sign("auth_date=1", BOT_TOKEN).unwrap()
+        );
+    }
 }
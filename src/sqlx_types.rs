@@ -0,0 +1,95 @@
+//! [`sqlx`](https://docs.rs/sqlx) types for storing the model in Postgres.
+//!
+//! [`User`] and [`Chat`] are stored as `JSONB` (or `JSON`), in the same shape Telegram sends them, and
+//! [`ChatType`] as `TEXT`, e.g. `group`.
+
+use sqlx::encode::IsNull;
+use sqlx::error::BoxDynError;
+use sqlx::postgres::{PgArgumentBuffer, PgTypeInfo, PgValueRef, Postgres};
+use sqlx::types::Json;
+use sqlx::{Decode, Encode, Type};
+
+use crate::model::{Chat, ChatType, User};
+
+macro_rules! json_type {
+    ($ty:ty) => {
+        impl Type<Postgres> for $ty {
+            fn type_info() -> PgTypeInfo {
+                <Json<Self> as Type<Postgres>>::type_info()
+            }
+
+            fn compatible(ty: &PgTypeInfo) -> bool {
+                <Json<Self> as Type<Postgres>>::compatible(ty)
+            }
+        }
+
+        impl Encode<'_, Postgres> for $ty {
+            fn encode_by_ref(&self, buf: &mut PgArgumentBuffer) -> Result<IsNull, BoxDynError> {
+                <Json<&Self> as Encode<'_, Postgres>>::encode_by_ref(&Json(self), buf)
+            }
+        }
+
+        impl<'r> Decode<'r, Postgres> for $ty {
+            fn decode(value: PgValueRef<'r>) -> Result<Self, BoxDynError> {
+                <Json<Self> as Decode<'r, Postgres>>::decode(value).map(|json| json.0)
+            }
+        }
+    };
+}
+
+json_type!(User);
+json_type!(Chat);
+
+impl Type<Postgres> for ChatType {
+    fn type_info() -> PgTypeInfo {
+        <str as Type<Postgres>>::type_info()
+    }
+
+    fn compatible(ty: &PgTypeInfo) -> bool {
+        <str as Type<Postgres>>::compatible(ty)
+    }
+}
+
+impl Encode<'_, Postgres> for ChatType {
+    fn encode_by_ref(&self, buf: &mut PgArgumentBuffer) -> Result<IsNull, BoxDynError> {
+        <&str as Encode<'_, Postgres>>::encode(self.as_str(), buf)
+    }
+}
+
+impl<'r> Decode<'r, Postgres> for ChatType {
+    fn decode(value: PgValueRef<'r>) -> Result<Self, BoxDynError> {
+        Ok(<&str as Decode<'r, Postgres>>::decode(value)?.parse()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode(value: &impl for<'q> Encode<'q, Postgres>) -> Vec<u8> {
+        let mut buf = PgArgumentBuffer::default();
+        assert!(matches!(value.encode_by_ref(&mut buf), Ok(IsNull::No)));
+        buf.to_vec()
+    }
+
+    #[test]
+    fn test_sqlx_types() {
+        assert_eq!(<User as Type<Postgres>>::type_info(), PgTypeInfo::with_name("JSONB"));
+        assert!(<Chat as Type<Postgres>>::compatible(&PgTypeInfo::with_name("JSON")));
+        assert_eq!(<ChatType as Type<Postgres>>::type_info(), PgTypeInfo::with_name("TEXT"));
+    }
+
+    #[test]
+    fn test_sqlx_encode() {
+        let chat: Chat =
+            serde_json::from_str(r#"{"id":-1,"type":"supergroup","title":"Chat","photo_url":null}"#).unwrap();
+
+        // JSONB version byte, then the JSON
+        let encoded = encode(&chat);
+        assert_eq!(encoded[0], 1);
+        let json: serde_json::Value = serde_json::from_slice(&encoded[1..]).unwrap();
+        assert_eq!(json["type"], "supergroup");
+
+        assert_eq!(encode(&ChatType::Supergroup), b"supergroup");
+    }
+}
@@ -0,0 +1,264 @@
+//! Rust-to-Rust API across a stable ABI, for plugin hosts loading the crate as a dynamic library.
+//!
+//! Enabled with the `abi_stable` feature. The library exports an [`InitDataModRef`] root module through
+//! [`abi_stable`], which checks the layout of every type when the library is loaded, so a host and a library
+//! built with different compilers or crate versions fail to load instead of corrupting memory. Build the plugin
+//! with `cargo rustc --lib --release --features abi_stable --crate-type cdylib`, then load it from the host:
+//!
+//! ```no_run
+//! use abi_stable::library::RootModule;
+//! use abi_stable::std_types::RStr;
+//! use init_data_rs::stable_abi::InitDataModRef;
+//!
+//! let module = InitDataModRef::load_from_directory("plugins/".as_ref()).unwrap();
+//! let data = module.validate()(RStr::from("query_id=..."), RStr::from("BOT_TOKEN"), -1);
+//! ```
+//!
+//! Expirations are in seconds like in the C ABI: `-1` (any negative value) for [`crate::Expiration::Default`] and
+//! `0` for [`crate::Expiration::Never`].
+
+// Lints on the code generated by the `StableAbi` derive
+#![allow(clippy::must_use_candidate, clippy::expl_impl_clone_on_copy)]
+
+use abi_stable::library::RootModule;
+use abi_stable::prefix_type::PrefixTypeTrait;
+use abi_stable::sabi_types::VersionStrings;
+use abi_stable::std_types::{ROption, RResult, RStr, RString};
+use abi_stable::{declare_root_module_statics, export_root_module, package_version_strings, StableAbi};
+
+use crate::{Chat, ChatType, Expiration, InitData, InitDataError, User};
+
+/// FFI-safe [`ChatType`].
+#[repr(u8)]
+#[derive(StableAbi, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AbiChatType {
+    Sender,
+    Private,
+    Group,
+    Supergroup,
+    Channel,
+}
+
+/// FFI-safe [`User`].
+#[repr(C)]
+#[derive(StableAbi, Debug, Clone, PartialEq, Eq)]
+pub struct AbiUser {
+    pub added_to_attachment_menu: ROption<bool>,
+    pub allows_write_to_pm: ROption<bool>,
+    pub first_name: RString,
+    pub id: i64,
+    pub is_bot: ROption<bool>,
+    pub is_premium: ROption<bool>,
+    pub last_name: ROption<RString>,
+    pub language_code: ROption<RString>,
+    pub photo_url: ROption<RString>,
+    pub username: ROption<RString>,
+}
+
+/// FFI-safe [`Chat`].
+#[repr(C)]
+#[derive(StableAbi, Debug, Clone, PartialEq, Eq)]
+pub struct AbiChat {
+    pub id: i64,
+    pub photo_url: ROption<RString>,
+    pub chat_type: AbiChatType,
+    pub title: RString,
+    pub username: ROption<RString>,
+}
+
+/// FFI-safe [`InitData`].
+#[repr(C)]
+#[derive(StableAbi, Debug, Clone, PartialEq, Eq)]
+pub struct AbiInitData {
+    pub auth_date: u64,
+    pub can_send_after: ROption<u32>,
+    pub chat: ROption<AbiChat>,
+    pub chat_type: ROption<AbiChatType>,
+    pub chat_instance: ROption<i64>,
+    pub hash: RString,
+    pub query_id: ROption<RString>,
+    pub receiver: ROption<AbiUser>,
+    pub start_param: ROption<RString>,
+    pub user: ROption<AbiUser>,
+    pub signature: ROption<RString>,
+}
+
+/// FFI-safe [`InitDataError`]: its stable [`InitDataError::kind`] and its message.
+#[repr(C)]
+#[derive(StableAbi, Debug, Clone, PartialEq, Eq)]
+pub struct AbiError {
+    pub kind: RString,
+    pub message: RString,
+}
+
+/// The functions exported by the library.
+#[repr(C)]
+#[derive(StableAbi)]
+#[sabi(kind(Prefix(prefix_ref = InitDataModRef)))]
+#[sabi(missing_field(panic))]
+pub struct InitDataMod {
+    /// Same as [`crate::validate`]: init data, bot token and expiration in seconds.
+    pub validate: extern "C" fn(RStr<'_>, RStr<'_>, i64) -> RResult<AbiInitData, AbiError>,
+    /// Same as [`crate::verify`]: init data, bot token and expiration in seconds.
+    pub verify: extern "C" fn(RStr<'_>, RStr<'_>, i64) -> RResult<(), AbiError>,
+    /// Same as [`crate::parse`].
+    pub parse: extern "C" fn(RStr<'_>) -> RResult<AbiInitData, AbiError>,
+    /// Same as [`crate::sign`]: init data without `hash` and bot token.
+    #[sabi(last_prefix_field)]
+    pub sign: extern "C" fn(RStr<'_>, RStr<'_>) -> RResult<RString, AbiError>,
+}
+
+impl RootModule for InitDataModRef {
+    declare_root_module_statics! {InitDataModRef}
+
+    const BASE_NAME: &'static str = "init_data_rs";
+    const NAME: &'static str = "init_data_rs";
+    const VERSION_STRINGS: VersionStrings = package_version_strings!();
+}
+
+/// The root module of the library, looked up by [`RootModule::load_from_directory`].
+#[export_root_module]
+#[must_use]
+pub fn root_module() -> InitDataModRef {
+    InitDataMod {
+        validate,
+        verify,
+        parse,
+        sign,
+    }
+    .leak_into_prefix()
+}
+
+extern "C" fn validate(init_data: RStr<'_>, token: RStr<'_>, expires_in: i64) -> RResult<AbiInitData, AbiError> {
+    crate::validate(init_data.as_str(), token.as_str(), expiration(expires_in))
+        .map(AbiInitData::from)
+        .map_err(AbiError::from)
+        .into()
+}
+
+extern "C" fn verify(init_data: RStr<'_>, token: RStr<'_>, expires_in: i64) -> RResult<(), AbiError> {
+    crate::verify(init_data.as_str(), token.as_str(), expiration(expires_in))
+        .map_err(AbiError::from)
+        .into()
+}
+
+extern "C" fn parse(init_data: RStr<'_>) -> RResult<AbiInitData, AbiError> {
+    crate::parse(init_data.as_str())
+        .map(AbiInitData::from)
+        .map_err(AbiError::from)
+        .into()
+}
+
+extern "C" fn sign(init_data: RStr<'_>, token: RStr<'_>) -> RResult<RString, AbiError> {
+    crate::sign(init_data.as_str(), token.as_str())
+        .map(RString::from)
+        .map_err(AbiError::from)
+        .into()
+}
+
+fn expiration(expires_in: i64) -> Expiration {
+    Expiration::from_seconds(u64::try_from(expires_in).ok())
+}
+
+fn string(value: Option<String>) -> ROption<RString> {
+    value.map(RString::from).into()
+}
+
+impl From<ChatType> for AbiChatType {
+    fn from(chat_type: ChatType) -> Self {
+        match chat_type {
+            ChatType::Sender => Self::Sender,
+            ChatType::Private => Self::Private,
+            ChatType::Group => Self::Group,
+            ChatType::Supergroup => Self::Supergroup,
+            ChatType::Channel => Self::Channel,
+        }
+    }
+}
+
+impl From<User> for AbiUser {
+    fn from(user: User) -> Self {
+        Self {
+            added_to_attachment_menu: user.added_to_attachment_menu.into(),
+            allows_write_to_pm: user.allows_write_to_pm.into(),
+            first_name: user.first_name.into(),
+            id: user.id,
+            is_bot: user.is_bot.into(),
+            is_premium: user.is_premium.into(),
+            last_name: string(user.last_name),
+            language_code: string(user.language_code),
+            photo_url: string(user.photo_url),
+            username: string(user.username),
+        }
+    }
+}
+
+impl From<Chat> for AbiChat {
+    fn from(chat: Chat) -> Self {
+        Self {
+            id: chat.id,
+            photo_url: string(chat.photo_url),
+            chat_type: chat.chat_type.into(),
+            title: chat.title.into(),
+            username: string(chat.username),
+        }
+    }
+}
+
+impl From<InitData> for AbiInitData {
+    fn from(data: InitData) -> Self {
+        Self {
+            auth_date: data.auth_date,
+            can_send_after: data.can_send_after.into(),
+            chat: data.chat.map(AbiChat::from).into(),
+            chat_type: data.chat_type.map(AbiChatType::from).into(),
+            chat_instance: data.chat_instance.into(),
+            hash: data.hash.into(),
+            query_id: string(data.query_id),
+            receiver: data.receiver.map(AbiUser::from).into(),
+            start_param: string(data.start_param),
+            user: data.user.map(AbiUser::from).into(),
+            signature: string(data.signature),
+        }
+    }
+}
+
+impl From<InitDataError> for AbiError {
+    fn from(error: InitDataError) -> Self {
+        Self {
+            kind: error.kind().into(),
+            message: error.to_string().into(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BOT_TOKEN: &str = "5768337691:AAH5YkoiEuPk8-FZa32hStHTqXiLPtAEhx8";
+    const VALID_INIT_DATA: &str = "query_id=AAHdF6IQAAAAAN0XohDhrOrc&user=%7B%22id%22%3A279058397%2C%22first_name%22%3A%22Vladislav%22%2C%22last_name%22%3A%22Kibenko%22%2C%22username%22%3A%22vdkfrost%22%2C%22language_code%22%3A%22ru%22%2C%22is_premium%22%3Atrue%7D&auth_date=1662771648&hash=c501b71e775f74ce10e377dea85a7ea24ecd640b223ea86dfe453e0eaed2e2b2";
+
+    #[test]
+    fn test_root_module() {
+        let module = root_module();
+
+        let data = module.validate()(VALID_INIT_DATA.into(), BOT_TOKEN.into(), 0).unwrap();
+        let user = data.user.unwrap();
+        assert_eq!(user.id, 279_058_397);
+        assert_eq!(user.username.as_ref().map(RString::as_str), ROption::RSome("vdkfrost"));
+
+        let error = module.verify()(VALID_INIT_DATA.into(), "123:OTHER".into(), 0).unwrap_err();
+        assert_eq!(error.kind.as_str(), InitDataError::HashInvalid.kind());
+        assert_eq!(
+            module.validate()(VALID_INIT_DATA.into(), BOT_TOKEN.into(), -1)
+                .unwrap_err()
+                .kind,
+            "expired"
+        );
+
+        let hash = module.sign()("query_id=AAHdF6IQ&auth_date=1000".into(), BOT_TOKEN.into()).unwrap();
+        assert_eq!(hash.len(), 64);
+        assert!(module.parse()("auth_date=1000".into()).is_err());
+    }
+}
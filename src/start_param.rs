@@ -0,0 +1,162 @@
+//! Checks on the `start_param` of deep links.
+
+use std::fmt;
+use std::sync::Arc;
+
+use crate::error::InitDataError;
+
+/// Longest `start_param` Telegram accepts in a deep link.
+pub const MAX_START_PARAM_LEN: usize = 512;
+
+/// Whether `start_param` could have come from a Telegram deep link: at most [`MAX_START_PARAM_LEN`]
+/// characters out of `A-Z`, `a-z`, `0-9`, `_` and `-`.
+#[must_use]
+pub fn is_valid_start_param(start_param: &str) -> bool {
+    start_param.len() <= MAX_START_PARAM_LEN
+        && start_param
+            .bytes()
+            .all(|byte| byte.is_ascii_alphanumeric() || byte == b'_' || byte == b'-')
+}
+
+type Check = dyn Fn(&str) -> bool + Send + Sync;
+
+/// Requirements on the `start_param` of init data, checked by [`crate::Validator::start_param_policy`] after
+/// the hash, so that forged or corrupted deep links are rejected before reaching business logic.
+///
+/// The default policy accepts any `start_param`.
+///
+/// # Example
+/// ```
+/// use init_data_rs::StartParamPolicy;
+///
+/// let policy = StartParamPolicy::telegram().check(|start_param| start_param.starts_with("ref_"));
+///
+/// assert!(policy.validate(Some("ref_42")).is_ok());
+/// assert!(policy.validate(Some("promo")).is_err());
+/// assert!(policy.validate(Some("ref_<script>")).is_err());
+/// assert!(policy.validate(None).is_ok());
+/// ```
+#[derive(Clone, Default)]
+pub struct StartParamPolicy {
+    telegram: bool,
+    required: bool,
+    check: Option<Arc<Check>>,
+}
+
+impl StartParamPolicy {
+    /// Creates a policy accepting any `start_param`.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a policy enforcing Telegram's constraints, see [`is_valid_start_param`].
+    #[must_use]
+    pub fn telegram() -> Self {
+        Self {
+            telegram: true,
+            ..Self::default()
+        }
+    }
+
+    /// Rejects init data without a `start_param`.
+    #[must_use]
+    pub fn required(mut self) -> Self {
+        self.required = true;
+        self
+    }
+
+    /// Only accepts a `start_param` for which `check` returns `true`, e.g. one matching a regex. Replaces an
+    /// earlier check, and runs after Telegram's constraints if those are enforced.
+    #[must_use]
+    pub fn check(mut self, check: impl Fn(&str) -> bool + Send + Sync + 'static) -> Self {
+        self.check = Some(Arc::new(check));
+        self
+    }
+
+    /// Checks `start_param`, `None` if the init data has none.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`InitDataError::StartParamInvalid`] if `start_param` is missing but required, breaks Telegram's
+    /// constraints while they are enforced, or is rejected by the check.
+    pub fn validate(&self, start_param: Option<&str>) -> Result<(), InitDataError> {
+        let Some(start_param) = start_param else {
+            return if self.required {
+                Err(InitDataError::StartParamInvalid("start_param is missing".to_string()))
+            } else {
+                Ok(())
+            };
+        };
+
+        if self.telegram && start_param.len() > MAX_START_PARAM_LEN {
+            return Err(InitDataError::StartParamInvalid(format!(
+                "longer than {MAX_START_PARAM_LEN} characters"
+            )));
+        }
+        if self.telegram && !is_valid_start_param(start_param) {
+            return Err(InitDataError::StartParamInvalid(
+                "contains characters other than A-Z, a-z, 0-9, _ and -".to_string(),
+            ));
+        }
+        if self.check.as_ref().is_some_and(|check| !check(start_param)) {
+            return Err(InitDataError::StartParamInvalid("rejected by the policy".to_string()));
+        }
+
+        Ok(())
+    }
+}
+
+impl StartParamPolicy {
+    /// Identifies the policy in the keys of the validation cache. Clones share the check, and the fingerprint.
+    pub(crate) fn fingerprint(&self) -> String {
+        format!(
+            "start_param:{}:{}:{:?}",
+            self.telegram,
+            self.required,
+            self.check.as_ref().map(|check| Arc::as_ptr(check).cast::<()>())
+        )
+    }
+}
+
+impl fmt::Debug for StartParamPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StartParamPolicy")
+            .field("telegram", &self.telegram)
+            .field("required", &self.required)
+            .field("check", &self.check.is_some())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_valid_start_param() {
+        assert!(is_valid_start_param("ref_42-abc"));
+        assert!(is_valid_start_param(""));
+        assert!(is_valid_start_param(&"a".repeat(512)));
+        assert!(!is_valid_start_param(&"a".repeat(513)));
+        assert!(!is_valid_start_param("a b"));
+        assert!(!is_valid_start_param("a=b"));
+        assert!(!is_valid_start_param("é"));
+    }
+
+    #[test]
+    fn test_start_param_policy() {
+        assert!(StartParamPolicy::new().validate(Some("a b")).is_ok());
+        assert!(StartParamPolicy::new().validate(None).is_ok());
+        assert!(matches!(
+            StartParamPolicy::new().required().validate(None),
+            Err(InitDataError::StartParamInvalid(_))
+        ));
+        assert!(StartParamPolicy::telegram().validate(Some("a b")).is_err());
+        assert!(StartParamPolicy::telegram().validate(Some(&"a".repeat(513))).is_err());
+
+        let policy = StartParamPolicy::telegram().check(|start_param| start_param.starts_with("ref_"));
+        assert!(policy.validate(Some("ref_1")).is_ok());
+        assert!(policy.validate(Some("other")).is_err());
+    }
+}
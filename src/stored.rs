@@ -0,0 +1,135 @@
+//! Versioned envelope for persisting validated init data, e.g. in a session store.
+//!
+//! The envelope is a JSON object, `{"v":1,"scheme":"hash","validated_at":1662771648,"data":{...}}`. The format
+//! version lets later releases keep reading what earlier ones wrote when the model changes.
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::InitDataError;
+use crate::model::InitData;
+use crate::scheme::ValidationScheme;
+
+/// Version of the envelope written by [`InitData::to_stored_bytes`].
+pub const STORED_FORMAT_VERSION: u32 = 1;
+
+/// Init data read back by [`InitData::from_stored_bytes`], along with how and when it was validated.
+///
+/// Nothing is verified when reading it back, so only store it where clients can't write.
+#[derive(Debug, Clone)]
+pub struct StoredInitData {
+    /// The init data.
+    pub data: InitData,
+    /// The scheme the init data was validated with.
+    pub scheme: ValidationScheme,
+    /// Unix time the init data was validated at.
+    pub validated_at: u64,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Envelope<D> {
+    v: u32,
+    #[serde(with = "crate::scheme::scheme_name")]
+    scheme: ValidationScheme,
+    validated_at: u64,
+    data: D,
+}
+
+/// Only the version, read first so that a newer envelope is reported as such rather than as malformed.
+#[derive(Deserialize)]
+struct Version {
+    v: u32,
+}
+
+impl InitData {
+    /// Wraps the init data in a versioned envelope for storage, see [`InitData::from_stored_bytes`].
+    ///
+    /// # Arguments
+    /// * `scheme` - The scheme the init data was validated with, e.g. [`crate::Verified::scheme`]
+    /// * `validated_at` - Unix time the init data was validated at
+    ///
+    /// # Example
+    /// ```
+    /// use init_data_rs::{parse, InitData, ValidationScheme};
+    ///
+    /// let data = parse("auth_date=1662771648&hash=c501b71e775f74ce10e377dea85a7ea24ecd640b223ea86dfe453e0eaed2e2b2").unwrap();
+    /// let bytes = data.to_stored_bytes(ValidationScheme::BotTokenHmac, 1662771700).unwrap();
+    ///
+    /// let stored = InitData::from_stored_bytes(&bytes).unwrap();
+    /// assert_eq!(stored.data.auth_date, 1662771648);
+    /// assert_eq!(stored.validated_at, 1662771700);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns [`InitDataError::Internal`] if the init data can't be serialized.
+    pub fn to_stored_bytes(&self, scheme: ValidationScheme, validated_at: u64) -> Result<Vec<u8>, InitDataError> {
+        serde_json::to_vec(&Envelope {
+            v: STORED_FORMAT_VERSION,
+            scheme,
+            validated_at,
+            data: self,
+        })
+        .map_err(|error| InitDataError::Internal(error.to_string()))
+    }
+
+    /// Reads init data stored by [`InitData::to_stored_bytes`] of this or an earlier release.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`InitDataError::UnexpectedFormat`] if `bytes` is not an envelope or was written by a newer
+    /// release.
+    pub fn from_stored_bytes(bytes: &[u8]) -> Result<StoredInitData, InitDataError> {
+        let Version { v } = serde_json::from_slice(bytes)
+            .map_err(|error| InitDataError::UnexpectedFormat(format!("stored init data is malformed: {error}")))?;
+        if v != STORED_FORMAT_VERSION {
+            return Err(InitDataError::UnexpectedFormat(format!(
+                "stored init data has unsupported format version {v}"
+            )));
+        }
+
+        let envelope: Envelope<InitData> = serde_json::from_slice(bytes)
+            .map_err(|error| InitDataError::UnexpectedFormat(format!("stored init data is malformed: {error}")))?;
+
+        Ok(StoredInitData {
+            data: envelope.data,
+            scheme: envelope.scheme,
+            validated_at: envelope.validated_at,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stored_bytes_roundtrip() {
+        let data = crate::parse(
+            "user=%7B%22id%22%3A1%2C%22first_name%22%3A%22A%22%7D&auth_date=1&signature=abc&hash=c501b71e775f74ce10e377dea85a7ea24ecd640b223ea86dfe453e0eaed2e2b2",
+        )
+        .unwrap();
+
+        let bytes = data.to_stored_bytes(ValidationScheme::Ed25519ThirdParty, 2).unwrap();
+        let stored = InitData::from_stored_bytes(&bytes).unwrap();
+
+        assert_eq!(stored.data.user.unwrap().id, 1);
+        assert_eq!(stored.data.hash, data.hash);
+        assert_eq!(stored.scheme, ValidationScheme::Ed25519ThirdParty);
+        assert_eq!(stored.validated_at, 2);
+    }
+
+    #[test]
+    fn test_stored_bytes_compatibility() {
+        // Written by the first release of the format, with a field a later model may have dropped
+        let v1 = br#"{"v":1,"scheme":"hash","validated_at":2,"data":{"auth_date":1,"hash":"abc","query_id":"q","user":{"id":1,"first_name":"A","retired":true}}}"#;
+        let stored = InitData::from_stored_bytes(v1).unwrap();
+        assert_eq!(stored.data.query_id.as_deref(), Some("q"));
+
+        let v2 = br#"{"v":2,"scheme":"hash","validated_at":2,"data":{}}"#;
+        assert!(matches!(
+            InitData::from_stored_bytes(v2),
+            Err(InitDataError::UnexpectedFormat(message)) if message.contains("version 2")
+        ));
+        assert!(InitData::from_stored_bytes(b"{\"auth_date\":1}").is_err());
+    }
+}
@@ -0,0 +1,258 @@
+//! Structured telemetry of the validations a [`crate::Validator`] runs.
+//!
+//! Both backends, `tracing` and `log`, report the same [`ValidationEvent`]. Attribute names follow the
+//! OpenTelemetry semantic conventions where one exists (`enduser.id`) and use a `tma.` namespace otherwise.
+
+#[cfg(feature = "tracing")]
+use tracing::{field::Empty, Span};
+
+use crate::clock::{Clock, SystemClock};
+use crate::error::InitDataError;
+use crate::model::InitData;
+use crate::scheme::ValidationScheme;
+
+/// Target of the spans and log records.
+const TARGET: &str = "init_data_rs";
+
+/// Outcome of one validation, the single source of the attributes every backend reports.
+pub(crate) struct ValidationEvent {
+    #[cfg_attr(not(feature = "log"), allow(dead_code))]
+    scheme: ValidationScheme,
+    user_id: Option<i64>,
+    auth_age_ms: Option<u64>,
+    error_code: Option<&'static str>,
+}
+
+impl ValidationEvent {
+    pub(crate) fn new(scheme: ValidationScheme, result: &Result<InitData, InitDataError>) -> Self {
+        match result {
+            Ok(data) => Self {
+                scheme,
+                user_id: data.user.as_ref().map(|user| user.id),
+                auth_age_ms: Some(SystemClock.now().saturating_sub(data.auth_date).saturating_mul(1000)),
+                error_code: None,
+            },
+            Err(error) => Self {
+                scheme,
+                user_id: None,
+                auth_age_ms: None,
+                error_code: Some(error.kind()),
+            },
+        }
+    }
+
+    /// Records the attributes on a span opened with [`validation_span`].
+    #[cfg(feature = "tracing")]
+    pub(crate) fn record(&self, span: &Span) {
+        if let Some(user_id) = self.user_id {
+            span.record("enduser.id", user_id);
+        }
+        if let Some(auth_age_ms) = self.auth_age_ms {
+            span.record("tma.auth_age_ms", auth_age_ms);
+        }
+        if let Some(error_code) = self.error_code {
+            span.record("tma.error_code", error_code);
+            span.record("otel.status_code", "ERROR");
+        }
+    }
+
+    /// Emits the event as a log record: `debug` for successes, `info` for failures, which clients can
+    /// trigger at will.
+    #[cfg(feature = "log")]
+    pub(crate) fn log(&self) {
+        let scheme = self.scheme.as_str();
+
+        match self.error_code {
+            None => log::debug!(
+                target: TARGET,
+                "tma.scheme" = scheme,
+                "enduser.id" = self.user_id,
+                "tma.auth_age_ms" = self.auth_age_ms;
+                "init data validated"
+            ),
+            Some(error_code) => log::info!(
+                target: TARGET,
+                "tma.scheme" = scheme,
+                "tma.error_code" = error_code;
+                "init data rejected: {error_code}"
+            ),
+        }
+    }
+}
+
+/// Opens the `tma.validate` span a validation with `scheme` runs in.
+#[cfg(feature = "tracing")]
+pub(crate) fn validation_span(scheme: ValidationScheme) -> Span {
+    tracing::info_span!(
+        target: TARGET,
+        "tma.validate",
+        tma.scheme = scheme.as_str(),
+        enduser.id = Empty,
+        tma.auth_age_ms = Empty,
+        tma.error_code = Empty,
+        otel.status_code = Empty,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::expiration::Expiration;
+    use crate::validator::Validator;
+
+    const BOT_TOKEN: &str = "5768337691:AAH5YkoiEuPk8-FZa32hStHTqXiLPtAEhx8";
+
+    fn validator() -> Validator {
+        Validator::new(BOT_TOKEN).expiration(Expiration::Never)
+    }
+
+    fn valid_init_data() -> String {
+        let base_data = r#"user={"id":42,"first_name":"Ada"}&auth_date=1000"#;
+        format!("{base_data}&hash={}", crate::sign(base_data, BOT_TOKEN).unwrap())
+    }
+
+    #[cfg(feature = "tracing")]
+    mod tracing_backend {
+        use std::fmt;
+        use std::sync::atomic::{AtomicU64, Ordering};
+        use std::sync::{Arc, Mutex};
+
+        use tracing::field::{Field, Visit};
+        use tracing::span::{Attributes, Id, Record};
+        use tracing::{Event, Metadata, Subscriber};
+
+        use super::*;
+
+        /// Collects the fields recorded on every span.
+        #[derive(Clone, Default)]
+        struct Fields(Arc<Mutex<Vec<(String, String)>>>, Arc<AtomicU64>);
+
+        impl Visit for Fields {
+            fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+                self.0
+                    .lock()
+                    .unwrap()
+                    .push((field.name().to_string(), format!("{value:?}")));
+            }
+        }
+
+        impl Subscriber for Fields {
+            fn enabled(&self, _: &Metadata<'_>) -> bool {
+                true
+            }
+
+            fn new_span(&self, span: &Attributes<'_>) -> Id {
+                span.record(&mut self.clone());
+                Id::from_u64(self.1.fetch_add(1, Ordering::Relaxed) + 1)
+            }
+
+            fn record(&self, _: &Id, values: &Record<'_>) {
+                values.record(&mut self.clone());
+            }
+
+            fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+            fn event(&self, _: &Event<'_>) {}
+
+            fn enter(&self, _: &Id) {}
+
+            fn exit(&self, _: &Id) {}
+        }
+
+        fn recorded(init_data: &str) -> Vec<(String, String)> {
+            let fields = Fields::default();
+            tracing::subscriber::with_default(fields.clone(), || validator().validate(init_data).ok());
+
+            let recorded = fields.0.lock().unwrap().clone();
+            recorded
+        }
+
+        #[test]
+        fn test_validation_span_success() {
+            let fields = recorded(&valid_init_data());
+
+            assert!(fields.contains(&("tma.scheme".into(), "\"hash\"".into())));
+            assert!(fields.contains(&("enduser.id".into(), "42".into())));
+            assert!(fields.iter().any(|(name, _)| name == "tma.auth_age_ms"));
+            assert!(!fields.iter().any(|(name, _)| name == "tma.error_code"));
+        }
+
+        #[test]
+        fn test_validation_span_failure() {
+            let fields = recorded("auth_date=1000&hash=00");
+
+            assert!(fields.contains(&("tma.error_code".into(), "\"hash_invalid\"".into())));
+            assert!(fields.contains(&("otel.status_code".into(), "\"ERROR\"".into())));
+            assert!(!fields.iter().any(|(name, _)| name == "enduser.id"));
+        }
+    }
+
+    #[cfg(feature = "log")]
+    mod log_backend {
+        use std::cell::RefCell;
+
+        use log::kv::{Key, Value, VisitSource};
+        use log::{Level, Log, Metadata, Record};
+
+        use super::*;
+        use crate::telemetry::TARGET;
+
+        struct KeyValues(Vec<(String, String)>);
+
+        impl<'kvs> VisitSource<'kvs> for KeyValues {
+            fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), log::kv::Error> {
+                self.0.push((key.to_string(), value.to_string()));
+                Ok(())
+            }
+        }
+
+        thread_local! {
+            // per thread, the logger is shared by every test running in parallel
+            static RECORDS: RefCell<Vec<(Level, KeyValues)>> = const { RefCell::new(Vec::new()) };
+        }
+
+        /// Collects the level and key-values of every record of this crate.
+        struct Records;
+
+        impl Log for Records {
+            fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+                metadata.target() == TARGET
+            }
+
+            fn log(&self, record: &Record<'_>) {
+                if self.enabled(record.metadata()) {
+                    let mut key_values = KeyValues(Vec::new());
+                    record.key_values().visit(&mut key_values).unwrap();
+
+                    // skips the span records of `tracing`, when another dependency enables its `log` feature
+                    if !key_values.0.is_empty() {
+                        RECORDS.with_borrow_mut(|records| records.push((record.level(), key_values)));
+                    }
+                }
+            }
+
+            fn flush(&self) {}
+        }
+
+        #[test]
+        fn test_validation_log_records() {
+            log::set_logger(&Records).unwrap();
+            log::set_max_level(log::LevelFilter::Debug);
+
+            let validator = validator();
+            validator.validate(&valid_init_data()).unwrap();
+            validator.validate("auth_date=1000&hash=00").unwrap_err();
+
+            let pair = |key: &str, value: &str| (key.to_string(), value.to_string());
+            RECORDS.with_borrow(|records| {
+                let (level, KeyValues(pairs)) = &records[0];
+                assert_eq!(*level, Level::Debug);
+                assert!(pairs.contains(&pair("tma.scheme", "hash")));
+                assert!(pairs.contains(&pair("enduser.id", "42")));
+
+                let (level, KeyValues(pairs)) = &records[1];
+                assert_eq!(*level, Level::Info);
+                assert!(pairs.contains(&pair("tma.error_code", "hash_invalid")));
+            });
+        }
+    }
+}
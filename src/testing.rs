@@ -0,0 +1,218 @@
+//! Helpers for testing services that validate init data, without Telegram.
+//!
+//! Enabled with the `testing` feature, meant for `[dev-dependencies]`.
+
+use std::fmt;
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD as base64_engine;
+use base64::Engine as _;
+
+use crate::crypto;
+use crate::error::InitDataError;
+use crate::model::InitData;
+use crate::scheme::bot_id;
+use crate::secret::BotToken;
+use crate::sign::sign;
+use crate::third_party_validation::Environment;
+
+/// Plays Telegram: issues init data carrying both a `hash` for the bot token and an Ed25519 `signature`
+/// for third-party validation.
+///
+/// The signing key is derived from the bot token unless a seed is given, so fixtures are reproducible.
+///
+/// # Example
+/// ```
+/// use init_data_rs::testing::MockTelegram;
+/// use init_data_rs::{validate, validate_third_party_in, Expiration, SystemClock};
+///
+/// let telegram = MockTelegram::new("12345:BOT_TOKEN").unwrap();
+/// let init_data = telegram
+///     .init_data(&[("auth_date", "1662771648"), ("user", r#"{"id":1,"first_name":"Test"}"#)])
+///     .unwrap();
+///
+/// assert!(validate(&init_data, "12345:BOT_TOKEN", Expiration::Never).is_ok());
+/// assert!(
+///     validate_third_party_in(&init_data, telegram.bot_id(), telegram.environment(), Expiration::Never, &SystemClock)
+///         .is_ok()
+/// );
+/// ```
+pub struct MockTelegram {
+    token: BotToken,
+    bot_id: i64,
+    seed: [u8; 32],
+}
+
+impl MockTelegram {
+    /// Creates a mock for the bot with the given token, whose id is the part before the `:`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`InitDataError::UnexpectedFormat`] if the token doesn't start with a bot id.
+    pub fn new(token: impl Into<BotToken>) -> Result<Self, InitDataError> {
+        let token = token.into();
+        let bot_id = bot_id(&token)?;
+        let seed = crypto::sha256(&[b"init-data-rs mock telegram\0".as_slice(), token.expose().as_bytes()].concat());
+
+        Ok(Self { token, bot_id, seed })
+    }
+
+    /// Uses the Ed25519 secret key (seed) `seed` instead of the one derived from the token.
+    #[must_use]
+    pub fn with_seed(mut self, seed: [u8; 32]) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Id of the bot, part of the message covered by the signature.
+    #[must_use]
+    pub fn bot_id(&self) -> i64 {
+        self.bot_id
+    }
+
+    /// Public key to validate the signature with, in place of Telegram's key.
+    #[must_use]
+    pub fn public_key(&self) -> [u8; 32] {
+        crypto::ed25519_public_key(&self.seed)
+    }
+
+    /// Environment to validate the signature in, in place of Telegram's, see [`crate::validate_third_party_in`].
+    #[must_use]
+    pub fn environment(&self) -> Environment {
+        Environment::Custom(self.public_key())
+    }
+
+    /// Encodes `params` as init data and appends a `signature` and a `hash`, like Telegram does.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`InitDataError::UnexpectedFormat`] if `params` is empty.
+    pub fn init_data(&self, params: &[(&str, &str)]) -> Result<String, InitDataError> {
+        if params.is_empty() {
+            return Err(InitDataError::UnexpectedFormat("params are empty".to_string()));
+        }
+
+        let mut sorted: Vec<_> = params.iter().map(|(key, value)| format!("{key}={value}")).collect();
+        sorted.sort();
+        let message = format!("{}:WebAppData\n{}", self.bot_id, sorted.join("\n"));
+        let signature = base64_engine.encode(crypto::sign_ed25519(&self.seed, message.as_bytes()));
+
+        let mut serializer = form_urlencoded::Serializer::new(String::new());
+        serializer.extend_pairs(params);
+        serializer.append_pair("signature", &signature);
+        let init_data = serializer.finish();
+
+        // The hash covers the signature as well
+        let hash = sign(&init_data, &self.token)?;
+        Ok(format!("{init_data}&hash={hash}"))
+    }
+
+    /// Signs `data` like [`MockTelegram::init_data`], its `hash` and `signature` being replaced. Nested objects
+    /// are encoded like Telegram does, see [`crate::User::to_telegram_json`].
+    ///
+    /// # Errors
+    ///
+    /// Never in practice, `auth_date` being always present.
+    pub fn sign_init_data(&self, data: &InitData) -> Result<String, InitDataError> {
+        let mut map = data.to_map();
+        map.remove("hash");
+        map.remove("signature");
+        let params: Vec<(&str, &str)> = map.iter().map(|(key, value)| (key.as_str(), value.as_str())).collect();
+
+        self.init_data(&params)
+    }
+}
+
+impl fmt::Debug for MockTelegram {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MockTelegram")
+            .field("bot_id", &self.bot_id)
+            .field("public_key", &hex::encode(self.public_key()))
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::Expiration;
+    use crate::{validate_scheme_with_clock, validate_third_party_in, ValidationScheme};
+
+    const BOT_TOKEN: &str = "7342037359:AAH5YkoiEuPk8-FZa32hStHTqXiLPtAEhx8";
+
+    #[test]
+    fn test_mock_telegram_both_schemes() {
+        let telegram = MockTelegram::new(BOT_TOKEN).unwrap();
+        let init_data = telegram
+            .init_data(&[
+                ("user", r#"{"id":279058397,"first_name":"Vladislav + - ? \/"}"#),
+                ("chat_type", "private"),
+                ("auth_date", "1000"),
+            ])
+            .unwrap();
+        let clock = || 1010;
+
+        let data = validate_scheme_with_clock(
+            &init_data,
+            ValidationScheme::BotTokenHmac,
+            BOT_TOKEN,
+            Duration::from_secs(60),
+            &clock,
+        )
+        .unwrap();
+        assert_eq!(data.user.as_ref().unwrap().first_name, "Vladislav + - ? /");
+
+        let data = validate_third_party_in(
+            &init_data,
+            7342037359,
+            telegram.environment(),
+            Duration::from_secs(60),
+            &clock,
+        )
+        .unwrap();
+        assert_eq!(data.auth_date, 1000);
+
+        let other = MockTelegram::new(BOT_TOKEN).unwrap().with_seed([1; 32]);
+        assert!(matches!(
+            validate_third_party_in(&init_data, 7342037359, other.environment(), Expiration::Default, &clock),
+            Err(InitDataError::SignatureInvalid(_))
+        ));
+        assert!(validate_third_party_in(&init_data, 1, telegram.environment(), Expiration::Default, &clock).is_err());
+    }
+
+    #[test]
+    fn test_mock_telegram_sign_init_data() {
+        let telegram = MockTelegram::new(BOT_TOKEN).unwrap();
+        let mut data = crate::parse(
+            &telegram
+                .init_data(&[
+                    (
+                        "user",
+                        r#"{"id":1,"first_name":"A","photo_url":"https:\/\/t.me\/a.svg"}"#,
+                    ),
+                    ("auth_date", "1000"),
+                ])
+                .unwrap(),
+        )
+        .unwrap();
+        data.start_param = Some("ref_42".to_string());
+
+        let init_data = telegram.sign_init_data(&data).unwrap();
+        let data = crate::validate(&init_data, BOT_TOKEN, crate::Expiration::Never).unwrap();
+        assert_eq!(data.start_param.as_deref(), Some("ref_42"));
+        assert!(validate_third_party_in(
+            &init_data,
+            7342037359,
+            telegram.environment(),
+            Expiration::Default,
+            &|| 1010
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn test_mock_telegram_requires_bot_id() {
+        assert!(MockTelegram::new("BOT_TOKEN").is_err());
+    }
+}
@@ -1,14 +1,57 @@
 use crate::parse;
 use base64::engine::general_purpose::URL_SAFE_NO_PAD as base64_engine;
 use base64::Engine as _;
-use ed25519_dalek::{Signature, Verifier, VerifyingKey};
-use hex::FromHex;
-use std::time::{SystemTime, UNIX_EPOCH};
+use smallvec::SmallVec;
+use std::borrow::Cow;
+use std::fmt::Write as _;
 
+use crate::clock::{Clock, SystemClock};
+use crate::crypto;
+use crate::expiration::Expiration;
 use crate::{InitData, InitDataError};
 
-const TEST_PUBLIC_KEY: &str = "40055058a4ee38156a06562e52eece92a771bcd8346a8c4615cb7376eddf72ec";
-const PROD_PUBLIC_KEY: &str = "e7bf03a2fa4602af4580703d88dda5bb59f32ed8b02a56c187fe7d34caed242d";
+const PROD_PUBLIC_KEY: [u8; 32] = [
+    0xe7, 0xbf, 0x03, 0xa2, 0xfa, 0x46, 0x02, 0xaf, 0x45, 0x80, 0x70, 0x3d, 0x88, 0xdd, 0xa5, 0xbb, 0x59, 0xf3, 0x2e,
+    0xd8, 0xb0, 0x2a, 0x56, 0xc1, 0x87, 0xfe, 0x7d, 0x34, 0xca, 0xed, 0x24, 0x2d,
+];
+const TEST_PUBLIC_KEY: [u8; 32] = [
+    0x40, 0x05, 0x50, 0x58, 0xa4, 0xee, 0x38, 0x15, 0x6a, 0x06, 0x56, 0x2e, 0x52, 0xee, 0xce, 0x92, 0xa7, 0x71, 0xbc,
+    0xd8, 0x34, 0x6a, 0x8c, 0x46, 0x15, 0xcb, 0x73, 0x76, 0xed, 0xdf, 0x72, 0xec,
+];
+
+/// The Telegram environment whose Ed25519 key signed the init data.
+///
+/// # Example
+/// ```
+/// use init_data_rs::Environment;
+///
+/// let environment = Environment::Custom([0x40; 32]);
+/// assert_eq!(environment.public_key(), &[0x40; 32]);
+/// assert_eq!(Environment::default(), Environment::Production);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Environment {
+    /// Telegram's production servers, key `e7bf03a2fa4602af4580703d88dda5bb59f32ed8b02a56c187fe7d34caed242d`.
+    #[default]
+    Production,
+    /// Telegram's test servers, key `40055058a4ee38156a06562e52eece92a771bcd8346a8c4615cb7376eddf72ec`.
+    Test,
+    /// Any other Ed25519 public key, e.g. the one of a `testing::MockTelegram`. An invalid key fails every
+    /// signature check.
+    Custom([u8; 32]),
+}
+
+impl Environment {
+    /// The Ed25519 public key of the environment.
+    #[must_use]
+    pub fn public_key(&self) -> &[u8; 32] {
+        match self {
+            Self::Production => &PROD_PUBLIC_KEY,
+            Self::Test => &TEST_PUBLIC_KEY,
+            Self::Custom(public_key) => public_key,
+        }
+    }
+}
 
 /// Validates data for third-party use
 ///
@@ -17,15 +60,14 @@ const PROD_PUBLIC_KEY: &str = "e7bf03a2fa4602af4580703d88dda5bb59f32ed8b02a56c18
 ///
 /// See: <https://core.telegram.org/bots/webapps#validating-data-for-third-party-use>
 ///
-/// Telegram provides the following Ed25519 public keys for signature verification:
-/// * `40055058a4ee38156a06562e52eece92a771bcd8346a8c4615cb7376eddf72ec` for test environment
-/// * `e7bf03a2fa4602af4580703d88dda5bb59f32ed8b02a56c187fe7d34caed242d` for production environment
+/// Telegram signs with a different Ed25519 key per [`Environment`].
 ///
 /// # Arguments
 /// * `init_data` - Raw init data string from Telegram Mini App
 /// * `bot_id` - Bot ID
-/// * `expires_in` - Optional expiration time in seconds
-/// * `is_test` - Whether to use the test public key
+/// * `expiration` - How long the init data stays valid, see [`crate::Expiration`]
+/// * `public_key` - Ed25519 public key the init data was signed with
+/// * `clock` - Source of the current time
 ///
 /// # Returns
 /// * `Ok(InitData)` - Parsed and validated init data
@@ -34,77 +76,143 @@ const PROD_PUBLIC_KEY: &str = "e7bf03a2fa4602af4580703d88dda5bb59f32ed8b02a56c18
 fn validate_third_party_with_signature(
     init_data: &str,
     bot_id: i64,
-    expires_in: Option<u64>,
-    is_test: bool,
+    expiration: Expiration,
+    public_key: &[u8; 32],
+    clock: &impl Clock,
 ) -> Result<InitData, InitDataError> {
-    if init_data.is_empty() || !init_data.contains('=') {
-        return Err(InitDataError::UnexpectedFormat(
-            "init_data is empty or malformed".to_string(),
-        ));
-    }
-
-    let pairs: Vec<(String, String)> = url::form_urlencoded::parse(init_data.as_bytes())
-        .map(|(k, v)| (k.into_owned(), v.into_owned()))
-        .collect();
+    // Like `verify` with `CheckOrder::Uniform`, every check runs before the first failure is reported: the
+    // signature is verified even when it is missing or malformed, against an all-zero placeholder.
+    let is_well_formed = !init_data.is_empty() && init_data.contains('=');
 
     let mut signature_b64 = None;
-    let mut filtered_pairs: Vec<(String, String)> = Vec::new();
+    let mut signature_count = 0;
+    let mut filtered_pairs: SmallVec<[(Cow<str>, Cow<str>); 16]> = SmallVec::new();
     let mut auth_date: Option<u64> = None;
-    for (k, v) in pairs {
-        if k == "signature" {
-            signature_b64 = Some(v);
-        } else if k == "auth_date" {
-            auth_date = v.parse().ok();
-            filtered_pairs.push((k, v));
-        } else if k != "hash" {
-            filtered_pairs.push((k, v));
+    for (k, v) in form_urlencoded::parse(init_data.as_bytes()) {
+        match k.as_ref() {
+            "signature" => {
+                signature_b64 = Some(v);
+                signature_count += 1;
+            }
+            "hash" => {}
+            "auth_date" => {
+                auth_date = v.parse().ok();
+                filtered_pairs.push((k, v));
+            }
+            _ => filtered_pairs.push((k, v)),
         }
     }
-    let signature_b64 = signature_b64.ok_or(InitDataError::SignatureMissing)?;
 
-    if let (Some(expires_in), Some(auth_date)) = (expires_in, auth_date) {
-        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
-        if auth_date + expires_in < now {
-            return Err(InitDataError::Expired);
+    filtered_pairs.sort_by(|a, b| a.0.cmp(&b.0));
+
+    // `{bot_id}:WebAppData\n{key}={value}\n...`, built in one buffer over the decoded pairs
+    let mut message = String::with_capacity(init_data.len() + 32);
+    let _ = writeln!(message, "{bot_id}:WebAppData");
+    for (index, (k, v)) in filtered_pairs.iter().enumerate() {
+        if index > 0 {
+            message.push('\n');
         }
+        message.push_str(k);
+        message.push('=');
+        message.push_str(v);
     }
 
-    filtered_pairs.sort_by(|a, b| a.0.cmp(&b.0));
+    let signature = signature_b64.as_deref().map(decode_signature);
 
-    let message = format!(
-        "{}:WebAppData\n{}",
-        bot_id,
-        filtered_pairs
-            .iter()
-            .map(|(k, v)| format!("{k}={v}"))
-            .collect::<Vec<_>>()
-            .join("\n")
+    let signature_matches = crypto::verify_ed25519(
+        public_key,
+        message.as_bytes(),
+        signature
+            .as_ref()
+            .and_then(|signature| signature.as_ref().ok())
+            .unwrap_or(&[0; 64]),
     );
 
-    let signature_bytes = base64_engine
-        .decode(signature_b64.as_bytes())
-        .map_err(|_| InitDataError::SignatureInvalid("Failed to decode signature from base64".to_string()))?;
+    let expired = match auth_date {
+        Some(auth_date) if expiration != Expiration::Never => expiration.is_expired(auth_date, clock.now()),
+        _ => false,
+    };
 
-    let signature = Signature::from_slice(&signature_bytes)
-        .map_err(|_| InitDataError::SignatureInvalid("Failed to parse signature".to_string()))?;
+    if !is_well_formed {
+        return Err(InitDataError::UnexpectedFormat(
+            "init_data is empty or malformed".to_string(),
+        ));
+    }
 
-    let public_key_hex = if is_test { TEST_PUBLIC_KEY } else { PROD_PUBLIC_KEY };
+    let signature = signature.ok_or(InitDataError::SignatureMissing)?;
 
-    let public_key_bytes = <[u8; 32]>::from_hex(public_key_hex)
-        .map_err(|_| InitDataError::SignatureInvalid("Failed to parse public key".to_string()))?;
+    // Several signatures are ambiguous, whichever one a proxy or framework would pick
+    if signature_count > 1 {
+        return Err(InitDataError::SignatureInvalid("Multiple signatures".to_string()));
+    }
 
-    let verifying_key = VerifyingKey::from_bytes(&public_key_bytes)
-        .map_err(|_| InitDataError::SignatureInvalid("Failed to parse public key".to_string()))?;
+    if expired {
+        return Err(InitDataError::Expired);
+    }
 
-    verifying_key
-        .verify(message.as_bytes(), &signature)
-        .map_err(|_| InitDataError::SignatureInvalid("Failed to verify signature".to_string()))?;
+    signature?;
+    if !signature_matches {
+        return Err(InitDataError::SignatureInvalid(
+            "Failed to verify signature".to_string(),
+        ));
+    }
 
     // 9. If valid, parse into InitData and return Ok
     let data = parse(init_data)?;
     Ok(data)
 }
 
+/// Checks a base64url `signature_b64` of `check_string` against the key of `environment`.
+///
+/// This is only the cryptographic core of [`validate_third_party`], for messages built by hand, e.g. when
+/// validating on behalf of a partner bot. `check_string` is signed as is, Telegram's format being
+/// `{bot_id}:WebAppData\n` followed by the sorted `key=value` lines of all parameters but `hash` and `signature`.
+///
+/// # Arguments
+/// * `check_string` - The exact message that was signed
+/// * `signature_b64` - Signature, base64url encoded without padding
+/// * `environment` - Environment whose key the message was signed with
+///
+/// # Returns
+/// Whether the signature matches.
+///
+/// # Example
+/// ```
+/// use init_data_rs::{verify_signature, Environment};
+///
+/// let signature = "A".repeat(86);
+/// assert!(!verify_signature("1:WebAppData\nauth_date=1", &signature, Environment::Production).unwrap());
+/// ```
+///
+/// # Errors
+///
+/// `InitDataError::SignatureInvalid` if `signature_b64` is not a base64url encoded Ed25519 signature.
+pub fn verify_signature(
+    check_string: &str,
+    signature_b64: &str,
+    environment: Environment,
+) -> Result<bool, InitDataError> {
+    let signature = decode_signature(signature_b64)?;
+
+    Ok(crypto::verify_ed25519(
+        environment.public_key(),
+        check_string.as_bytes(),
+        &signature,
+    ))
+}
+
+pub(crate) fn decode_signature(signature_b64: &str) -> Result<[u8; 64], InitDataError> {
+    let mut signature_bytes = [0u8; 64];
+    let signature_len = base64_engine
+        .decode_slice(signature_b64.as_bytes(), &mut signature_bytes)
+        .map_err(|_| InitDataError::SignatureInvalid("Failed to decode signature from base64".to_string()))?;
+
+    if signature_len != signature_bytes.len() {
+        return Err(InitDataError::SignatureInvalid("Failed to parse signature".to_string()));
+    }
+    Ok(signature_bytes)
+}
+
 /// Validates init data using both primary and third-party bot tokens.
 ///
 /// Similar to `validate()`, but accepts an additional third-party bot token
@@ -113,7 +221,7 @@ fn validate_third_party_with_signature(
 /// # Arguments
 /// * `init_data` - Raw init data string from Telegram Mini App
 /// * `bot_id` - Bot ID
-/// * `expires_in` - Optional expiration time in seconds
+/// * `expiration` - How long the init data stays valid, see [`crate::Expiration`]
 ///
 /// # Returns
 /// * `Ok(InitData)` - Parsed and validated init data
@@ -121,21 +229,65 @@ fn validate_third_party_with_signature(
 ///
 /// # Example
 /// ```
-/// use init_data_rs::validate_third_party;
+/// use init_data_rs::{validate_third_party, Expiration};
 ///
 /// let init_data = "query_id=123&auth_date=1662771648&hash=...&signature=...";
-/// let result = validate_third_party(init_data, 1234567890, None);
+/// let result = validate_third_party(init_data, 1234567890, Expiration::Default);
 /// ```
 ///
 /// # Errors
 ///
 /// See `init_data_rs::parse` for possible errors
-pub fn validate_third_party(init_data: &str, bot_id: i64, expires_in: Option<u64>) -> Result<InitData, InitDataError> {
-    validate_third_party_with_signature(init_data, bot_id, expires_in, false)
+pub fn validate_third_party(
+    init_data: &str,
+    bot_id: i64,
+    expiration: impl Into<Expiration>,
+) -> Result<InitData, InitDataError> {
+    validate_third_party_with_clock(init_data, bot_id, expiration, &SystemClock)
+}
+
+/// Same as [`validate_third_party`], but reads the current time from `clock`.
+///
+/// # Errors
+///
+/// See `init_data_rs::parse` for possible errors
+pub fn validate_third_party_with_clock(
+    init_data: &str,
+    bot_id: i64,
+    expiration: impl Into<Expiration>,
+    clock: &impl Clock,
+) -> Result<InitData, InitDataError> {
+    validate_third_party_in(init_data, bot_id, Environment::Production, expiration, clock)
+}
+
+/// Same as [`validate_third_party_with_clock`], but checks the signature with the key of `environment`
+/// instead of Telegram's production key.
+///
+/// # Example
+/// ```
+/// use init_data_rs::{validate_third_party_in, Environment, Expiration, SystemClock};
+///
+/// let init_data = "query_id=123&auth_date=1662771648&hash=...&signature=...";
+/// let result = validate_third_party_in(init_data, 1234567890, Environment::Test, Expiration::Default, &SystemClock);
+/// ```
+///
+/// # Errors
+///
+/// See `init_data_rs::parse` for possible errors
+pub fn validate_third_party_in(
+    init_data: &str,
+    bot_id: i64,
+    environment: Environment,
+    expiration: impl Into<Expiration>,
+    clock: &impl Clock,
+) -> Result<InitData, InitDataError> {
+    validate_third_party_with_signature(init_data, bot_id, expiration.into(), environment.public_key(), clock)
 }
 
 #[cfg(test)]
 mod tests {
+    use std::time::Duration;
+
     use super::*;
     // With signature
     const VALID_INIT_DATA: &str = "user=%7B%22id%22%3A279058397%2C%22first_name%22%3A%22Vladislav%20%2B%20-%20%3F%20%5C%2F%22%2C%22last_name%22%3A%22Kibenko%22%2C%22username%22%3A%22vdkfrost%22%2C%22language_code%22%3A%22ru%22%2C%22is_premium%22%3Atrue%2C%22allows_write_to_pm%22%3Atrue%2C%22photo_url%22%3A%22https%3A%5C%2F%5C%2Ft.me%5C%2Fi%5C%2Fuserpic%5C%2F320%5C%2F4FPEE4tmP3ATHa57u6MqTDih13LTOiMoKoLDRG4PnSA.svg%22%7D&chat_instance=8134722200314281151&chat_type=private&auth_date=1733584787&hash=2174df5b000556d044f3f020384e879c8efcab55ddea2ced4eb752e93e7080d6&signature=zL-ucjNyREiHDE8aihFwpfR9aggP2xiAo3NSpfe-p7IbCisNlDKlo7Kb6G4D0Ao2mBrSgEk4maLSdv6MLIlADQ";
@@ -143,10 +295,18 @@ mod tests {
 
     #[test]
     fn test_valid_third_party_signature() {
-        let result = validate_third_party(VALID_INIT_DATA, BOT_ID, None);
+        let result = validate_third_party(VALID_INIT_DATA, BOT_ID, Expiration::Never);
         assert!(result.is_ok(), "Expected Ok, got {result:?}");
     }
 
+    #[test]
+    fn test_reencoded_third_party_signature() {
+        let reencoded = crate::parse(VALID_INIT_DATA).unwrap().to_query_string();
+
+        assert_ne!(reencoded, VALID_INIT_DATA);
+        assert!(validate_third_party(&reencoded, BOT_ID, Expiration::Never).is_ok());
+    }
+
     #[test]
     fn test_invalid_signature() {
         // Tamper with the signature
@@ -154,7 +314,7 @@ mod tests {
             "zL-ucjNyREiHDE8aihFwpfR9aggP2xiAo3NSpfe-p7IbCisNlDKlo7Kb6G4D0Ao2mBrSgEk4maLSdv6MLIlADQ",
             "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA",
         );
-        let result = validate_third_party(&tampered, BOT_ID, None);
+        let result = validate_third_party(&tampered, BOT_ID, Expiration::Never);
         assert!(matches!(result, Err(InitDataError::SignatureInvalid(_))));
     }
 
@@ -162,7 +322,13 @@ mod tests {
     fn test_third_party_invalid_base64_signature() {
         let bad_data = "query_id=test&auth_date=123&signature=!!!notbase64!!!&hash=abc";
         let bot_id = 123456;
-        let result = validate_third_party_with_signature(bad_data, bot_id, None, true);
+        let result = validate_third_party_with_signature(
+            bad_data,
+            bot_id,
+            Expiration::Never,
+            Environment::Test.public_key(),
+            &SystemClock,
+        );
         assert!(matches!(result, Err(InitDataError::SignatureInvalid(_))));
     }
 
@@ -172,7 +338,13 @@ mod tests {
         let bot_id = 123456;
         // Use an invalid public key by temporarily changing the constant or by passing a custom function if your API allows
         // For this test, you might need to expose a version of your function that takes a public key string
-        let result = validate_third_party_with_signature(valid_data, bot_id, None, true); // with a purposely broken key
+        let result = validate_third_party_with_signature(
+            valid_data,
+            bot_id,
+            Expiration::Never,
+            Environment::Test.public_key(),
+            &SystemClock,
+        ); // with a purposely broken key
         assert!(matches!(result, Err(InitDataError::SignatureInvalid(_))));
     }
 
@@ -182,7 +354,13 @@ mod tests {
         let bad_sig = base64::engine::general_purpose::URL_SAFE_NO_PAD.encode([0u8; 64]);
         let bad_data = format!("query_id=test&auth_date=123&signature={bad_sig}&hash=abc");
         let bot_id = 123456;
-        let result = validate_third_party_with_signature(&bad_data, bot_id, None, true);
+        let result = validate_third_party_with_signature(
+            &bad_data,
+            bot_id,
+            Expiration::Never,
+            Environment::Test.public_key(),
+            &SystemClock,
+        );
         assert!(matches!(result, Err(InitDataError::SignatureInvalid(_))));
     }
 
@@ -192,7 +370,7 @@ mod tests {
         let mut parts: Vec<&str> = VALID_INIT_DATA.split('&').collect();
         parts.retain(|s| !s.starts_with("signature="));
         let no_sig = parts.join("&");
-        let result = validate_third_party(&no_sig, BOT_ID, None);
+        let result = validate_third_party(&no_sig, BOT_ID, Expiration::Never);
         assert!(matches!(result, Err(InitDataError::SignatureMissing)));
     }
 
@@ -200,27 +378,105 @@ mod tests {
     fn test_expired_data() {
         // Use a very old auth_date
         let expired_data = VALID_INIT_DATA.replace("auth_date=1733584787", "auth_date=1000000000");
-        let result = validate_third_party(&expired_data, BOT_ID, Some(86400));
+        let result = validate_third_party(&expired_data, BOT_ID, Duration::from_secs(86400));
+        assert!(matches!(result, Err(InitDataError::Expired)));
+    }
+
+    #[test]
+    fn test_third_party_with_clock() {
+        let result =
+            validate_third_party_with_clock(VALID_INIT_DATA, BOT_ID, Duration::from_secs(60), &|| 1733584787 + 30);
+        assert!(result.is_ok(), "Expected Ok, got {result:?}");
+
+        let result =
+            validate_third_party_with_clock(VALID_INIT_DATA, BOT_ID, Duration::from_secs(60), &|| 1733584787 + 61);
         assert!(matches!(result, Err(InitDataError::Expired)));
     }
 
     #[test]
     fn test_malformed_input() {
-        let result = validate_third_party("not_a_query_string", BOT_ID, None);
+        let result = validate_third_party("not_a_query_string", BOT_ID, Expiration::Never);
         assert!(matches!(result, Err(InitDataError::UnexpectedFormat(_))));
     }
 
     #[test]
     fn test_wrong_bot_id() {
         // Use a wrong bot_id (signature won't match)
-        let result = validate_third_party(VALID_INIT_DATA, 1234567890, None);
+        let result = validate_third_party(VALID_INIT_DATA, 1234567890, Expiration::Never);
         assert!(matches!(result, Err(InitDataError::SignatureInvalid(_))));
     }
 
     #[test]
     fn test_wrong_environment() {
         // Use test environment (signature won't match prod key)
-        let result = validate_third_party_with_signature(VALID_INIT_DATA, BOT_ID, None, true);
+        let result = validate_third_party_with_signature(
+            VALID_INIT_DATA,
+            BOT_ID,
+            Expiration::Never,
+            Environment::Test.public_key(),
+            &SystemClock,
+        );
         assert!(matches!(result, Err(InitDataError::SignatureInvalid(_))));
     }
+
+    #[test]
+    fn test_third_party_parameter_pollution() {
+        let (base_data, signature) = VALID_INIT_DATA.split_once("&signature=").unwrap();
+
+        // `&signature=` smuggled inside another value is not a parameter
+        let init_data = format!("{base_data}&start_param=x%26signature%3D{signature}");
+        let result = validate_third_party(&init_data, BOT_ID, Expiration::Never);
+        assert!(matches!(result, Err(InitDataError::SignatureMissing)));
+
+        let init_data = format!("{VALID_INIT_DATA}&signature={signature}");
+        let result = validate_third_party(&init_data, BOT_ID, Expiration::Never);
+        assert!(matches!(result, Err(InitDataError::SignatureInvalid(_))));
+    }
+
+    #[test]
+    fn test_environment() {
+        assert_eq!(Environment::default().public_key(), &PROD_PUBLIC_KEY);
+        assert_eq!(
+            Environment::Custom(TEST_PUBLIC_KEY).public_key(),
+            Environment::Test.public_key()
+        );
+
+        let result = validate_third_party_in(
+            VALID_INIT_DATA,
+            BOT_ID,
+            Environment::Test,
+            Expiration::Never,
+            &SystemClock,
+        );
+        assert!(matches!(result, Err(InitDataError::SignatureInvalid(_))));
+        assert!(validate_third_party_in(
+            VALID_INIT_DATA,
+            BOT_ID,
+            Environment::Production,
+            Expiration::Never,
+            &SystemClock
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn test_verify_signature() {
+        let signature = "zL-ucjNyREiHDE8aihFwpfR9aggP2xiAo3NSpfe-p7IbCisNlDKlo7Kb6G4D0Ao2mBrSgEk4maLSdv6MLIlADQ";
+        let user = r#"{"id":279058397,"first_name":"Vladislav + - ? \/","last_name":"Kibenko","username":"vdkfrost","language_code":"ru","is_premium":true,"allows_write_to_pm":true,"photo_url":"https:\/\/t.me\/i\/userpic\/320\/4FPEE4tmP3ATHa57u6MqTDih13LTOiMoKoLDRG4PnSA.svg"}"#;
+        let check_string =
+            format!("{BOT_ID}:WebAppData\nauth_date=1733584787\nchat_instance=8134722200314281151\nchat_type=private\nuser={user}");
+
+        assert!(verify_signature(&check_string, signature, Environment::Production).unwrap());
+        assert!(!verify_signature(&check_string, signature, Environment::Test).unwrap());
+        assert!(!verify_signature(
+            &check_string.replace("private", "group"),
+            signature,
+            Environment::Production
+        )
+        .unwrap());
+        assert!(matches!(
+            verify_signature(&check_string, "!!!", Environment::Production),
+            Err(InitDataError::SignatureInvalid(_))
+        ));
+    }
 }
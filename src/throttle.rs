@@ -0,0 +1,284 @@
+//! Rate limiting of failed validations.
+//!
+//! A [`ThrottleHook`] is consulted by [`crate::Validator::validate_from`] before any cryptography
+//! runs, so that brute-force attempts from one caller are turned away cheaply.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use crate::clock::{Clock, SystemClock};
+
+/// Number of throttled identities of a [`GovernorThrottle`] above which expired ones are forgotten.
+#[cfg(feature = "governor")]
+const MAX_BLOCKED: usize = 100_000;
+
+#[cfg(feature = "governor")]
+type IdentityStore = governor::state::keyed::DefaultKeyedStateStore<String>;
+
+/// Decides whether a caller may attempt a validation.
+///
+/// Identities are supplied by the caller, e.g. an IP address or a [`crate::Fingerprinter`] digest.
+pub trait ThrottleHook: Send + Sync {
+    /// Returns `false` to reject the attempt of `identity` without verifying it.
+    fn allow(&self, identity: &str) -> bool;
+
+    /// Records a failed validation of `identity`.
+    fn record_failure(&self, identity: &str);
+
+    /// Records a successful validation of `identity`.
+    fn record_success(&self, identity: &str) {
+        let _ = identity;
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    tokens: f64,
+    updated_at: u64,
+}
+
+/// In-memory token bucket per identity: every failed validation takes a token, tokens refill at a
+/// fixed rate, and an identity without tokens left is throttled. Successful validations are free.
+///
+/// # Example
+/// ```
+/// use init_data_rs::{TokenBucket, Validator};
+///
+/// // Up to 5 failures in a row, then one more attempt every 10 seconds
+/// let validator = Validator::new("BOT_TOKEN").with_throttle(TokenBucket::new(5, 0.1));
+/// let result = validator.validate_from("203.0.113.7", "query_id=123&auth_date=1662771648&hash=...");
+/// ```
+#[derive(Debug)]
+pub struct TokenBucket<C: Clock = SystemClock> {
+    burst: f64,
+    refill_per_second: f64,
+    max_identities: usize,
+    clock: C,
+    buckets: Mutex<HashMap<String, Bucket>>,
+}
+
+impl TokenBucket {
+    /// Allows `burst` failures per identity, refilled by `refill_per_second` tokens every second.
+    #[must_use]
+    pub fn new(burst: u32, refill_per_second: f64) -> Self {
+        Self::with_clock(burst, refill_per_second, SystemClock)
+    }
+}
+
+impl<C: Clock> TokenBucket<C> {
+    /// Same as [`TokenBucket::new`], but reads the current time from `clock`.
+    pub fn with_clock(burst: u32, refill_per_second: f64, clock: C) -> Self {
+        Self {
+            burst: f64::from(burst),
+            refill_per_second,
+            max_identities: 100_000,
+            clock,
+            buckets: Mutex::default(),
+        }
+    }
+
+    /// Bounds the number of tracked identities, 100 000 by default. When the limit is reached,
+    /// identities whose bucket has refilled completely are forgotten first.
+    #[must_use]
+    pub fn max_identities(mut self, max_identities: usize) -> Self {
+        self.max_identities = max_identities;
+        self
+    }
+
+    /// Tokens available to `identity` at `now`.
+    #[allow(clippy::cast_precision_loss)]
+    fn refill(&self, bucket: &Bucket, now: u64) -> f64 {
+        let elapsed = now.saturating_sub(bucket.updated_at) as f64;
+        (bucket.tokens + elapsed * self.refill_per_second).min(self.burst)
+    }
+}
+
+impl<C: Clock + Send + Sync> ThrottleHook for TokenBucket<C> {
+    fn allow(&self, identity: &str) -> bool {
+        let now = self.clock.now();
+        let buckets = self.buckets.lock().unwrap_or_else(std::sync::PoisonError::into_inner);
+
+        buckets
+            .get(identity)
+            .is_none_or(|bucket| self.refill(bucket, now) >= 1.0)
+    }
+
+    fn record_failure(&self, identity: &str) {
+        let now = self.clock.now();
+        let mut buckets = self.buckets.lock().unwrap_or_else(std::sync::PoisonError::into_inner);
+
+        if !buckets.contains_key(identity) && buckets.len() >= self.max_identities {
+            buckets.retain(|_, bucket| self.refill(bucket, now) < self.burst);
+            if buckets.len() >= self.max_identities {
+                buckets.clear();
+            }
+        }
+
+        let tokens = buckets
+            .get(identity)
+            .map_or(self.burst, |bucket| self.refill(bucket, now));
+        buckets.insert(
+            identity.to_string(),
+            Bucket {
+                tokens: (tokens - 1.0).max(0.0),
+                updated_at: now,
+            },
+        );
+    }
+}
+
+/// [`ThrottleHook`] backed by a keyed [`governor`](https://docs.rs/governor) rate limiter: failed validations
+/// of an identity consume its quota, and once a failure exceeds it the identity is rejected until the quota
+/// replenishes. Successful validations are free.
+///
+/// # Example
+/// ```
+/// use std::num::NonZeroU32;
+/// use governor::Quota;
+/// use init_data_rs::{GovernorThrottle, Validator};
+///
+/// // 5 failures per minute per client IP
+/// let quota = Quota::per_minute(NonZeroU32::new(5).unwrap());
+/// let validator = Validator::new("BOT_TOKEN").with_throttle(GovernorThrottle::new(quota));
+/// let result = validator.validate_from("203.0.113.7", "query_id=123&auth_date=1662771648&hash=...");
+/// ```
+#[cfg(feature = "governor")]
+pub struct GovernorThrottle<C: governor::clock::Clock = governor::clock::DefaultClock> {
+    limiter: governor::RateLimiter<String, IdentityStore, C, governor::middleware::NoOpMiddleware<C::Instant>>,
+    blocked: Mutex<HashMap<String, C::Instant>>,
+}
+
+#[cfg(feature = "governor")]
+impl GovernorThrottle {
+    /// Allows each identity `quota` failed validations.
+    #[must_use]
+    pub fn new(quota: governor::Quota) -> Self {
+        Self::with_clock(quota, governor::clock::DefaultClock::default())
+    }
+}
+
+#[cfg(feature = "governor")]
+impl<C: governor::clock::Clock> GovernorThrottle<C> {
+    /// Same as [`GovernorThrottle::new`], but reads the current time from `clock`.
+    pub fn with_clock(quota: governor::Quota, clock: C) -> Self {
+        Self {
+            limiter: governor::RateLimiter::new(quota, IdentityStore::default(), clock),
+            blocked: Mutex::default(),
+        }
+    }
+}
+
+#[cfg(feature = "governor")]
+impl<C> ThrottleHook for GovernorThrottle<C>
+where
+    C: governor::clock::Clock + Send + Sync,
+    C::Instant: Send,
+{
+    fn allow(&self, identity: &str) -> bool {
+        let mut blocked = self.blocked.lock().unwrap_or_else(std::sync::PoisonError::into_inner);
+
+        match blocked.get(identity) {
+            Some(until) if self.limiter.clock().now() < *until => false,
+            Some(_) => {
+                blocked.remove(identity);
+                true
+            }
+            None => true,
+        }
+    }
+
+    fn record_failure(&self, identity: &str) {
+        let Err(not_until) = self.limiter.check_key(&identity.to_string()) else {
+            return;
+        };
+
+        let now = self.limiter.clock().now();
+        let mut blocked = self.blocked.lock().unwrap_or_else(std::sync::PoisonError::into_inner);
+        if blocked.len() >= MAX_BLOCKED {
+            blocked.retain(|_, until| now < *until);
+            self.limiter.retain_recent();
+        }
+        blocked.insert(identity.to_string(), not_until.earliest_possible());
+    }
+}
+
+#[cfg(feature = "governor")]
+impl<C: governor::clock::Clock> std::fmt::Debug for GovernorThrottle<C> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GovernorThrottle").finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::Arc;
+
+    fn bucket(burst: u32, refill_per_second: f64) -> (TokenBucket<impl Clock + Send + Sync>, Arc<AtomicU64>) {
+        let now = Arc::new(AtomicU64::new(1_000));
+        let clock = {
+            let now = Arc::clone(&now);
+            move || now.load(Ordering::Relaxed)
+        };
+        (TokenBucket::with_clock(burst, refill_per_second, clock), now)
+    }
+
+    #[test]
+    fn test_token_bucket_throttles_and_refills() {
+        let (bucket, now) = bucket(2, 0.5);
+
+        assert!(bucket.allow("a"));
+        bucket.record_failure("a");
+        assert!(bucket.allow("a"));
+        bucket.record_failure("a");
+        assert!(!bucket.allow("a"));
+        assert!(bucket.allow("b"));
+
+        now.fetch_add(1, Ordering::Relaxed);
+        assert!(!bucket.allow("a"));
+        now.fetch_add(1, Ordering::Relaxed);
+        assert!(bucket.allow("a"));
+    }
+
+    #[test]
+    fn test_token_bucket_max_identities() {
+        let (bucket, now) = bucket(1, 1.0);
+        let bucket = bucket.max_identities(2);
+
+        bucket.record_failure("a");
+        now.fetch_add(5, Ordering::Relaxed);
+        bucket.record_failure("b");
+        bucket.record_failure("c");
+
+        let buckets = bucket.buckets.lock().unwrap();
+        assert!(!buckets.contains_key("a"));
+        assert_eq!(buckets.len(), 2);
+    }
+
+    #[cfg(feature = "governor")]
+    #[test]
+    fn test_governor_throttle() {
+        use std::num::NonZeroU32;
+        use std::time::Duration;
+
+        use governor::clock::FakeRelativeClock;
+        use governor::Quota;
+
+        let clock = FakeRelativeClock::default();
+        let quota = Quota::per_second(NonZeroU32::new(1).unwrap()).allow_burst(NonZeroU32::new(2).unwrap());
+        let throttle = GovernorThrottle::with_clock(quota, clock.clone());
+
+        throttle.record_failure("a");
+        throttle.record_failure("a");
+        assert!(throttle.allow("a"));
+        throttle.record_failure("a");
+        assert!(!throttle.allow("a"));
+        assert!(throttle.allow("b"));
+
+        throttle.record_success("a");
+        assert!(!throttle.allow("a"));
+        clock.advance(Duration::from_secs(1));
+        assert!(throttle.allow("a"));
+    }
+}
@@ -0,0 +1,107 @@
+//! Bans and allowlists of users, enforced when init data is validated.
+
+use std::future::Future;
+
+use crate::error::InitDataError;
+use crate::model::InitData;
+
+/// Whether a [`UserGate`] lets a user in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GateDecision {
+    /// The user may proceed.
+    Allow,
+    /// The user is turned away with [`InitDataError::UserDenied`].
+    Deny,
+    /// Same as [`GateDecision::Deny`], with a reason for logs or the client, e.g. `banned until 2026-12-01`.
+    DenyWithReason(String),
+}
+
+impl GateDecision {
+    /// `Ok` if the user is allowed, [`InitDataError::UserDenied`] otherwise.
+    ///
+    /// # Errors
+    ///
+    /// [`InitDataError::UserDenied`] with the reason, `denied` if none was given.
+    pub fn into_result(self) -> Result<(), InitDataError> {
+        match self {
+            Self::Allow => Ok(()),
+            Self::Deny => Err(InitDataError::UserDenied("denied".to_string())),
+            Self::DenyWithReason(reason) => Err(InitDataError::UserDenied(reason)),
+        }
+    }
+}
+
+impl From<bool> for GateDecision {
+    /// `true` allows, `false` denies.
+    fn from(allowed: bool) -> Self {
+        if allowed {
+            Self::Allow
+        } else {
+            Self::Deny
+        }
+    }
+}
+
+/// Decides whether the user of validated init data may proceed, see [`crate::Validator::with_user_gate`].
+///
+/// Closures taking the user id are gates.
+///
+/// # Example
+/// ```
+/// use std::collections::HashSet;
+/// use init_data_rs::{GateDecision, Validator};
+///
+/// let banned: HashSet<i64> = HashSet::from([279058397]);
+/// let validator = Validator::new("BOT_TOKEN").with_user_gate(move |user_id| {
+///     if banned.contains(&user_id) {
+///         GateDecision::DenyWithReason("banned".to_string())
+///     } else {
+///         GateDecision::Allow
+///     }
+/// });
+/// ```
+pub trait UserGate: Send + Sync {
+    /// Whether the user `user_id` may proceed.
+    fn check(&self, user_id: i64) -> GateDecision;
+}
+
+impl<F: Fn(i64) -> GateDecision + Send + Sync> UserGate for F {
+    fn check(&self, user_id: i64) -> GateDecision {
+        self(user_id)
+    }
+}
+
+/// Same as [`UserGate`], for gates behind a database or another service, see
+/// [`crate::Validator::validate_gated`].
+pub trait AsyncUserGate: Sync {
+    /// Whether the user `user_id` may proceed.
+    ///
+    /// # Errors
+    ///
+    /// Whatever error the gate reports, e.g. [`InitDataError::Internal`] if the database can't be reached.
+    fn check(&self, user_id: i64) -> impl Future<Output = Result<GateDecision, InitDataError>> + Send;
+}
+
+/// The id of the user of `data`, which a gate needs.
+pub(crate) fn user_id(data: &InitData) -> Result<i64, InitDataError> {
+    data.user.as_ref().map(|user| user.id).ok_or(InitDataError::UserMissing)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gate_decision() {
+        assert!(GateDecision::Allow.into_result().is_ok());
+        assert!(matches!(
+            GateDecision::DenyWithReason("banned".to_string()).into_result(),
+            Err(InitDataError::UserDenied(reason)) if reason == "banned"
+        ));
+        assert_eq!(GateDecision::from(false), GateDecision::Deny);
+        assert_eq!(
+            (|user_id: i64| GateDecision::from(user_id > 0)).check(-1),
+            GateDecision::Deny
+        );
+    }
+}
@@ -4,37 +4,47 @@
 //! of init data passed from Telegram to Mini Apps. It includes support for both
 //! standard validation and third-party bot validation.
 
-use std::time::{SystemTime, UNIX_EPOCH};
-
+use crate::buffers::ValidationBuffers;
+use crate::check_order::CheckOrder;
+use crate::clock::{Clock, SystemClock};
+use crate::compat::Compat;
 use crate::error::InitDataError;
-use crate::model::InitData;
-use crate::{parse, sign};
+#[cfg(feature = "model")]
+use std::time::Duration;
 
-/// Default expiration time for init data in seconds (24 hours)
-const DEFAULT_EXPIRATION: u64 = 86400;
+use crate::expiration::Expiration;
+#[cfg(feature = "model")]
+use crate::expiration::Freshness;
+#[cfg(feature = "model")]
+use crate::model::InitData;
+#[cfg(feature = "model")]
+use crate::parse;
+use crate::secret::{BotSecret, TokenProvider};
+use crate::{query, sign};
 
 /// Extracts and validates the hash from init data string.
 ///
+/// Only top-level parameters are considered, so `&hash=` hidden inside a percent-encoded value can't
+/// be mistaken for the hash, and a payload carrying several hashes is rejected.
+///
 /// # Arguments
 /// * `init_data` - The raw init data string containing the hash
 ///
 /// # Returns
-/// * `Ok((base_data, hash))` - Tuple containing the base data and valid hash
+/// * `Ok(hash)` - The valid, still hex encoded hash
 /// * `Err(InitDataError)` - Error if hash is missing, invalid, or malformed
-fn extract_hash(init_data: &str) -> Result<(String, String), InitDataError> {
-    let (base_data, hash) = if let Some(pos) = init_data.find("&hash=") {
-        let (base, hash_part) = init_data.split_at(pos);
-        let hash = &hash_part[6..]; // Skip "&hash="
-        (base.to_string(), hash.to_string())
-    } else {
+fn extract_hash(init_data: &str) -> Result<&str, InitDataError> {
+    let mut hashes = query::raw_pairs(init_data).filter(|(key, _)| query::decoded_eq(key, "hash"));
+
+    let Some((_, hash)) = hashes.next() else {
         return Err(InitDataError::HashMissing);
     };
 
-    if !hash.chars().all(|c| c.is_ascii_hexdigit()) || hash.len() != 64 {
+    if hashes.next().is_some() || !hash.chars().all(|c| c.is_ascii_hexdigit()) || hash.len() != 64 {
         return Err(InitDataError::HashInvalid);
     }
 
-    Ok((base_data, hash))
+    Ok(hash)
 }
 
 /// Validates the authenticity and integrity of Telegram Mini Apps init data.
@@ -47,8 +57,8 @@ fn extract_hash(init_data: &str) -> Result<(String, String), InitDataError> {
 ///
 /// # Arguments
 /// * `init_data` - Raw init data string from Telegram Mini App
-/// * `token` - Bot token used for validation
-/// * `expires_in` - Optional expiration time in seconds (defaults to 24 hours), set to 0 to disable expiration check
+/// * `token` - Bot token used for validation, see [`crate::TokenProvider`]
+/// * `expiration` - How long the init data stays valid, see [`crate::Expiration`]
 ///
 /// # Returns
 /// * `Ok(InitData)` - Parsed and validated init data
@@ -56,10 +66,10 @@ fn extract_hash(init_data: &str) -> Result<(String, String), InitDataError> {
 ///
 /// # Example
 /// ```
-/// use init_data_rs::validate;
+/// use init_data_rs::{validate, Expiration};
 ///
 /// let init_data = "query_id=123&auth_date=1662771648&hash=...";
-/// let result = validate(init_data, "BOT_TOKEN", None);
+/// let result = validate(init_data, "BOT_TOKEN", Expiration::Default);
 /// ```
 ///
 /// # Errors
@@ -70,36 +80,423 @@ fn extract_hash(init_data: &str) -> Result<(String, String), InitDataError> {
 ///
 /// This function panics if `SystemTime::now` returns a date less than `UNIX_EPOCH`.
 /// Meaning the function should panic only if the device time is really, REALLY bad.
-pub fn validate(init_data: &str, token: &str, expires_in: Option<u64>) -> Result<InitData, InitDataError> {
-    if init_data.is_empty() || !init_data.contains('=') {
-        return Err(InitDataError::UnexpectedFormat(
-            "init_data is empty or malformed".to_string(),
-        ));
+/// On `wasm32-unknown-unknown` enable the `wasm` feature or use [`validate_with_clock`].
+#[cfg(feature = "model")]
+pub fn validate(
+    init_data: &str,
+    token: &(impl TokenProvider + ?Sized),
+    expiration: impl Into<Expiration>,
+) -> Result<InitData, InitDataError> {
+    validate_with_clock(init_data, token, expiration, &SystemClock)
+}
+
+/// Same as [`validate`], but reads the current time from `clock`.
+///
+/// Use this on targets without a system clock (e.g. `wasm32-unknown-unknown` without the `wasm` feature)
+/// or to validate against a fixed point in time.
+///
+/// # Example
+/// ```
+/// use init_data_rs::{validate_with_clock, Expiration};
+///
+/// let init_data = "query_id=123&auth_date=1662771648&hash=...";
+/// let result = validate_with_clock(init_data, "BOT_TOKEN", Expiration::Default, &|| 1662771700);
+/// ```
+///
+/// # Errors
+///
+/// See `init_data_rs::parse` for possible errors
+#[cfg(feature = "model")]
+pub fn validate_with_clock(
+    init_data: &str,
+    token: &(impl TokenProvider + ?Sized),
+    expiration: impl Into<Expiration>,
+    clock: &impl Clock,
+) -> Result<InitData, InitDataError> {
+    verify_with_clock(init_data, token, expiration, clock)?;
+
+    parse(init_data)
+}
+
+/// Same as [`validate`], but accepts init data up to `grace` past its expiration, reporting it as
+/// [`Freshness::Stale`] along with how long ago it expired.
+///
+/// Lets services degrade gracefully, e.g. allow read-only access, instead of failing users whose Mini
+/// App stayed open for long.
+///
+/// # Example
+/// ```
+/// use std::time::Duration;
+/// use init_data_rs::{validate_graceful, Expiration, Freshness};
+///
+/// let init_data = "query_id=123&auth_date=1662771648&hash=...";
+/// if let Ok((data, freshness)) = validate_graceful(init_data, "BOT_TOKEN", Expiration::Default, Duration::from_secs(3600)) {
+///     let read_only = freshness.is_stale();
+/// }
+/// ```
+///
+/// # Errors
+///
+/// `InitDataError::Expired` if the init data expired more than `grace` ago, see [`validate`] for the others.
+///
+/// # Panics
+///
+/// See [`validate`].
+#[cfg(feature = "model")]
+pub fn validate_graceful(
+    init_data: &str,
+    token: &(impl TokenProvider + ?Sized),
+    expiration: impl Into<Expiration>,
+    grace: Duration,
+) -> Result<(InitData, Freshness), InitDataError> {
+    validate_graceful_with_clock(init_data, token, expiration, grace, &SystemClock)
+}
+
+/// Same as [`validate_graceful`], but reads the current time from `clock`.
+///
+/// # Errors
+///
+/// See [`validate_graceful`].
+#[cfg(feature = "model")]
+pub fn validate_graceful_with_clock(
+    init_data: &str,
+    token: &(impl TokenProvider + ?Sized),
+    expiration: impl Into<Expiration>,
+    grace: Duration,
+    clock: &impl Clock,
+) -> Result<(InitData, Freshness), InitDataError> {
+    let data = validate_with_clock(init_data, token, Expiration::Never, clock)?;
+
+    match expiration.into().overshoot(data.auth_date, clock.now()) {
+        None => Ok((data, Freshness::Fresh)),
+        Some(overshoot) if overshoot <= grace => Ok((data, Freshness::Stale { overshoot })),
+        Some(_) => Err(InitDataError::Expired),
+    }
+}
+
+/// Validates raw init data in method position, e.g. `raw.validate_tma(token, Expiration::Default)?` in handlers.
+///
+/// Implemented for [`str`], so it is available on `String` and `&str` alike.
+///
+/// # Example
+/// ```
+/// use init_data_rs::{Expiration, ValidateExt};
+///
+/// let init_data = String::from("query_id=123&auth_date=1662771648&hash=...");
+/// let result = init_data.validate_tma("BOT_TOKEN", Expiration::Default);
+/// ```
+#[cfg(feature = "model")]
+pub trait ValidateExt {
+    /// Same as [`validate`] with `self` as the init data.
+    ///
+    /// # Errors
+    ///
+    /// See [`validate`].
+    fn validate_tma(
+        &self,
+        token: &(impl TokenProvider + ?Sized),
+        expiration: impl Into<Expiration>,
+    ) -> Result<InitData, InitDataError>;
+}
+
+#[cfg(feature = "model")]
+impl ValidateExt for str {
+    fn validate_tma(
+        &self,
+        token: &(impl TokenProvider + ?Sized),
+        expiration: impl Into<Expiration>,
+    ) -> Result<InitData, InitDataError> {
+        validate(self, token, expiration)
     }
+}
 
-    let (base_data, hash) = extract_hash(init_data)?;
+/// Checks the hash and the expiration of init data without building an [`InitData`].
+///
+/// This is the cheap path for services that only need a yes/no answer: it performs the same
+/// cryptographic and expiration checks as [`validate`], but skips deserializing the fields.
+///
+/// # Arguments
+/// * `init_data` - Raw init data string from Telegram Mini App
+/// * `token` - Bot token used for validation, see [`crate::TokenProvider`]
+/// * `expiration` - How long the init data stays valid, see [`crate::Expiration`]
+///
+/// # Example
+/// ```
+/// use init_data_rs::{verify, Expiration};
+///
+/// let init_data = "query_id=123&auth_date=1662771648&hash=...";
+/// let is_valid = verify(init_data, "BOT_TOKEN", Expiration::Default).is_ok();
+/// ```
+///
+/// # Errors
+///
+/// This function returns an `Err` if the init data is malformed, the hash is missing or doesn't match,
+/// `auth_date` is missing or malformed, or the data is expired.
+///
+/// # Timing
+///
+/// The hash is compared in constant time, but checks run in [`CheckOrder::CheapestFirst`] order: malformed and
+/// expired payloads are rejected before the HMAC is computed, faster than a wrong hash. Use [`verify_with_order`]
+/// or [`crate::Validator::check_order`] with [`CheckOrder::Uniform`] for a malformed payload, a wrong hash and an
+/// expired payload of the same length to take the same time to reject.
+///
+/// # Panics
+///
+/// See [`validate`].
+pub fn verify(
+    init_data: &str,
+    token: &(impl TokenProvider + ?Sized),
+    expiration: impl Into<Expiration>,
+) -> Result<(), InitDataError> {
+    verify_with_clock(init_data, token, expiration, &SystemClock)
+}
 
-    let expected_hash = sign(&base_data, token)?;
+/// Same as [`verify`], but reads the current time from `clock`.
+///
+/// # Errors
+///
+/// See [`verify`].
+pub fn verify_with_clock(
+    init_data: &str,
+    token: &(impl TokenProvider + ?Sized),
+    expiration: impl Into<Expiration>,
+    clock: &impl Clock,
+) -> Result<(), InitDataError> {
+    verify_with_buffers(
+        init_data,
+        token,
+        expiration.into(),
+        clock,
+        &mut ValidationBuffers::default(),
+        Compat::default(),
+        CheckOrder::default(),
+    )
+}
 
-    if hash != expected_hash {
-        return Err(InitDataError::HashInvalid);
+/// Same as [`verify`], but computes the data-check-string like another implementation would, see [`Compat`].
+///
+/// # Errors
+///
+/// See [`verify`].
+///
+/// # Panics
+///
+/// See [`validate`].
+pub fn verify_with_compat(
+    init_data: &str,
+    token: &(impl TokenProvider + ?Sized),
+    expiration: impl Into<Expiration>,
+    compat: Compat,
+) -> Result<(), InitDataError> {
+    verify_with_buffers(
+        init_data,
+        token,
+        expiration.into(),
+        &SystemClock,
+        &mut ValidationBuffers::default(),
+        compat,
+        CheckOrder::default(),
+    )
+}
+
+/// Same as [`verify`], but runs the checks in the given `order`, see [`CheckOrder`].
+///
+/// # Example
+/// ```
+/// use init_data_rs::{verify_with_order, CheckOrder, Expiration};
+///
+/// let init_data = "query_id=123&auth_date=1662771648&hash=...";
+/// let is_valid = verify_with_order(init_data, "BOT_TOKEN", Expiration::Default, CheckOrder::Uniform).is_ok();
+/// ```
+///
+/// # Errors
+///
+/// See [`verify`].
+///
+/// # Panics
+///
+/// See [`validate`].
+pub fn verify_with_order(
+    init_data: &str,
+    token: &(impl TokenProvider + ?Sized),
+    expiration: impl Into<Expiration>,
+    order: CheckOrder,
+) -> Result<(), InitDataError> {
+    verify_with_order_with_clock(init_data, token, expiration, order, &SystemClock)
+}
+
+/// Same as [`verify_with_order`], but reads the current time from `clock`.
+///
+/// # Errors
+///
+/// See [`verify`].
+pub fn verify_with_order_with_clock(
+    init_data: &str,
+    token: &(impl TokenProvider + ?Sized),
+    expiration: impl Into<Expiration>,
+    order: CheckOrder,
+    clock: &impl Clock,
+) -> Result<(), InitDataError> {
+    verify_with_buffers(
+        init_data,
+        token,
+        expiration.into(),
+        clock,
+        &mut ValidationBuffers::default(),
+        Compat::default(),
+        order,
+    )
+}
+
+/// Same as [`verify_with_clock`], but decodes into reusable `buffers`.
+pub(crate) fn verify_with_buffers(
+    init_data: &str,
+    token: &(impl TokenProvider + ?Sized),
+    expiration: Expiration,
+    clock: &impl Clock,
+    buffers: &mut ValidationBuffers,
+    compat: Compat,
+    order: CheckOrder,
+) -> Result<(), InitDataError> {
+    let secret = BotSecret::from_token(token)?;
+
+    let precheck = Precheck::new(init_data);
+    precheck.check_before_hmac(order, expiration, clock)?;
+    // The data-check-string leaves out the `hash` parameter by itself
+    let hash_matches = sign::check_hash(init_data, &secret, &precheck.hash(), buffers, compat).is_ok();
+
+    precheck.finish(hash_matches, expiration, clock)
+}
+
+/// The checks of [`verify`] besides the hash, so that their results can be reported after the hash is compared.
+pub(crate) struct Precheck<'a> {
+    is_well_formed: bool,
+    extracted_hash: Result<&'a str, InitDataError>,
+    hash: Option<[u8; 32]>,
+    auth_date: Result<u64, InitDataError>,
+}
+
+impl<'a> Precheck<'a> {
+    pub(crate) fn new(init_data: &'a str) -> Self {
+        let extracted_hash = extract_hash(init_data);
+
+        Self {
+            is_well_formed: !init_data.is_empty() && init_data.contains('='),
+            hash: extracted_hash.as_ref().ok().and_then(|hash| sign::decode_hash(hash)),
+            extracted_hash,
+            auth_date: extract_auth_date(init_data),
+        }
+    }
+
+    /// The hash to compare the computed one with, all zeros if it is missing or malformed.
+    pub(crate) fn hash(&self) -> [u8; 32] {
+        self.hash.unwrap_or([0; 32])
+    }
+
+    /// Reports the checks that fail before the HMAC is computed, none with [`CheckOrder::Uniform`].
+    pub(crate) fn check_before_hmac(
+        &self,
+        order: CheckOrder,
+        expiration: Expiration,
+        clock: &impl Clock,
+    ) -> Result<(), InitDataError> {
+        match order {
+            CheckOrder::CheapestFirst => {
+                self.check_structure()?;
+                self.check_expiry(expiration, clock)
+            }
+            CheckOrder::Uniform => Ok(()),
+        }
     }
 
-    let data = parse(init_data)?;
+    /// Reports the first failed check, given whether the computed hash matched [`Precheck::hash`].
+    ///
+    /// Every check runs before any result is reported, so that the time taken doesn't reveal which one failed.
+    /// The HMAC dominates the cost and is computed even for malformed input, against an all-zero hash.
+    pub(crate) fn finish(
+        self,
+        hash_matches: bool,
+        expiration: Expiration,
+        clock: &impl Clock,
+    ) -> Result<(), InitDataError> {
+        let expiry = self.check_expiry(expiration, clock);
+
+        self.check_structure()?;
+        if !hash_matches {
+            return Err(InitDataError::HashInvalid);
+        }
+
+        expiry
+    }
 
-    let expires_in = expires_in.unwrap_or(DEFAULT_EXPIRATION);
-    if expires_in > 0 {
-        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+    fn check_structure(&self) -> Result<(), InitDataError> {
+        if !self.is_well_formed {
+            return Err(InitDataError::UnexpectedFormat(
+                "init_data is empty or malformed".to_string(),
+            ));
+        }
 
-        if data.auth_date + expires_in < now {
+        self.extracted_hash.as_ref().map_err(Clone::clone)?;
+        if self.hash.is_none() {
+            return Err(InitDataError::HashInvalid);
+        }
+
+        Ok(())
+    }
+
+    fn check_expiry(&self, expiration: Expiration, clock: &impl Clock) -> Result<(), InitDataError> {
+        let now = if expiration == Expiration::Never {
+            0
+        } else {
+            clock.now()
+        };
+        let auth_date = self.auth_date.clone()?;
+
+        if expiration.is_expired(auth_date, now) {
             return Err(InitDataError::Expired);
         }
+
+        Ok(())
+    }
+}
+
+/// Reads `auth_date` straight from the query string, without parsing the other fields.
+///
+/// It must be a Unix time in seconds: only digits, no sign, and small enough for a `u64`.
+pub(crate) fn extract_auth_date(init_data: &str) -> Result<u64, InitDataError> {
+    let (_, raw_auth_date) = query::raw_pairs(init_data)
+        .filter(|(key, _)| query::decoded_eq(key, "auth_date"))
+        .last()
+        .ok_or(InitDataError::AuthDateMissing)?;
+
+    let mut digits = query::decode(raw_auth_date).peekable();
+    let mut auth_date: u64 = 0;
+    let mut is_number = digits.peek().is_some();
+
+    for digit in digits {
+        let next = digit
+            .is_ascii_digit()
+            .then(|| auth_date.checked_mul(10)?.checked_add(u64::from(digit - b'0')))
+            .flatten();
+
+        if let Some(next) = next {
+            auth_date = next;
+        } else {
+            is_number = false;
+            break;
+        }
+    }
+
+    if !is_number {
+        // Only the start of the value is echoed back, it may be arbitrarily long
+        let value = String::from_utf8_lossy(&query::decode(raw_auth_date).take(32).collect::<Vec<_>>()).into_owned();
+        return Err(InitDataError::AuthDateInvalid { value });
     }
 
-    Ok(data)
+    Ok(auth_date)
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "model"))]
 mod tests {
     use super::*;
 
@@ -110,13 +507,13 @@ mod tests {
 
     #[test]
     fn test_validate_empty_data() {
-        let result = validate("", BOT_TOKEN, None);
+        let result = validate("", BOT_TOKEN, Expiration::Default);
         assert!(matches!(result, Err(InitDataError::UnexpectedFormat(_))));
     }
 
     #[test]
     fn test_validate_invalid_format() {
-        let result = validate("invalid_format", BOT_TOKEN, None);
+        let result = validate("invalid_format", BOT_TOKEN, Expiration::Default);
         assert!(matches!(result, Err(InitDataError::UnexpectedFormat(_))));
     }
 
@@ -124,13 +521,13 @@ mod tests {
     fn test_validate_missing_hash() {
         let data = "query_id=test&auth_date=123";
         let token = "valid:token";
-        let result = validate(data, token, None);
+        let result = validate(data, token, Expiration::Default);
         assert!(matches!(result, Err(InitDataError::HashMissing)));
     }
 
     #[test]
     fn test_validate_invalid_hash() {
-        let result = validate("query_id=test123&hash=invalid", BOT_TOKEN, None);
+        let result = validate("query_id=test123&hash=invalid", BOT_TOKEN, Expiration::Default);
         assert!(matches!(result, Err(InitDataError::HashInvalid)));
     }
 
@@ -145,7 +542,7 @@ mod tests {
 
         let hash = sign(&base_data, BOT_TOKEN).unwrap();
         let init_data = format!("{base_data}&hash={hash}");
-        let result = validate(&init_data, BOT_TOKEN, Some(86400));
+        let result = validate(&init_data, BOT_TOKEN, Duration::from_secs(86400));
         assert!(matches!(result, Err(InitDataError::Expired)));
     }
 
@@ -160,7 +557,7 @@ mod tests {
 
         let hash = sign(&base_data, BOT_TOKEN).unwrap();
         let init_data = format!("{base_data}&hash={hash}");
-        let result = validate(&init_data, BOT_TOKEN, Some(0));
+        let result = validate(&init_data, BOT_TOKEN, Expiration::Never);
         println!("result: {result:?}");
 
         assert!(result.is_ok());
@@ -168,7 +565,7 @@ mod tests {
 
     #[test]
     fn test_validate_valid_data() {
-        let result = validate(VALID_INIT_DATA, BOT_TOKEN, Some(0)); // Disable expiration check for test
+        let result = validate(VALID_INIT_DATA, BOT_TOKEN, Expiration::Never); // Disable expiration check for test
 
         assert!(result.is_ok());
 
@@ -188,17 +585,21 @@ mod tests {
 
     #[test]
     fn test_validate_malformed_hash() {
-        let result = validate("query_id=test123&hash=", BOT_TOKEN, None);
+        let result = validate("query_id=test123&hash=", BOT_TOKEN, Expiration::Default);
         assert!(matches!(result, Err(InitDataError::HashInvalid)));
     }
 
     #[test]
     fn test_validate_hash_format_length() {
-        let result = validate("query_id=test123&hash=abc123", BOT_TOKEN, None);
+        let result = validate("query_id=test123&hash=abc123", BOT_TOKEN, Expiration::Default);
         assert!(matches!(result, Err(InitDataError::HashInvalid)));
 
         // Test hash that's too long
-        let result = validate(&format!("query_id=test123&hash={INVALID_HASH}0"), BOT_TOKEN, None);
+        let result = validate(
+            &format!("query_id=test123&hash={INVALID_HASH}0"),
+            BOT_TOKEN,
+            Expiration::Default,
+        );
         assert!(matches!(result, Err(InitDataError::HashInvalid)));
     }
 
@@ -208,7 +609,7 @@ mod tests {
         let result = validate(
             "query_id=test123&hash=gggggggggggggggggggggggggggggggggggggggggggggggggggggggggggggggg",
             BOT_TOKEN,
-            None,
+            Expiration::Default,
         );
         assert!(matches!(result, Err(InitDataError::HashInvalid)));
     }
@@ -216,11 +617,11 @@ mod tests {
     #[test]
     fn test_validate_hash_extraction_failure() {
         // Test case where hash= is at the end without a value
-        let result = validate("query_id=test123&hash=&other=value", BOT_TOKEN, None);
+        let result = validate("query_id=test123&hash=&other=value", BOT_TOKEN, Expiration::Default);
         assert!(matches!(result, Err(InitDataError::HashInvalid)));
 
         // Test case where hash= is in the middle without a value
-        let result = validate("query_id=test123&hash=&auth_date=123", BOT_TOKEN, None);
+        let result = validate("query_id=test123&hash=&auth_date=123", BOT_TOKEN, Expiration::Default);
         assert!(matches!(result, Err(InitDataError::HashInvalid)));
     }
 
@@ -228,22 +629,22 @@ mod tests {
     fn test_validate_impossible_hash_extraction() {
         // This test is for line 35
         // We check for &hash= first, but try to force the else branch
-        let result = validate("query_id=test123&hash=abc\n&hash=def", BOT_TOKEN, None);
+        let result = validate("query_id=test123&hash=abc\n&hash=def", BOT_TOKEN, Expiration::Default);
         assert!(matches!(result, Err(InitDataError::HashInvalid)));
     }
 
     #[test]
     fn test_validate_hash_extraction_corner_case() {
         // Test case where hash= is at the end of string (no value, no other params)
-        let result = validate("query_id=test123&hash=", BOT_TOKEN, None);
+        let result = validate("query_id=test123&hash=", BOT_TOKEN, Expiration::Default);
         assert!(matches!(result, Err(InitDataError::HashInvalid)));
 
         // Test with escaped &
-        let result = validate("query_id=test123%26hash=abc", BOT_TOKEN, None);
+        let result = validate("query_id=test123%26hash=abc", BOT_TOKEN, Expiration::Default);
         assert!(matches!(result, Err(InitDataError::HashMissing)));
 
         // Test with URL-encoded &hash=
-        let result = validate("query_id=test123%26hash%3Dabc", BOT_TOKEN, None);
+        let result = validate("query_id=test123%26hash%3Dabc", BOT_TOKEN, Expiration::Default);
         assert!(matches!(result, Err(InitDataError::HashMissing)));
     }
 
@@ -252,10 +653,14 @@ mod tests {
         // Test valid hash extraction
         let init_data = "query_id=test123&hash=1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef";
         let result = extract_hash(init_data);
-        assert!(result.is_ok());
-        let (base, hash) = result.unwrap();
-        assert_eq!(base, "query_id=test123");
-        assert_eq!(hash, "1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef");
+        assert_eq!(
+            result.unwrap(),
+            "1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef"
+        );
+
+        // The hash doesn't have to be the last or even the second parameter
+        let init_data = "hash=1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef&query_id=test123";
+        assert!(extract_hash(init_data).is_ok());
 
         // Test missing hash
         let result = extract_hash("query_id=test123");
@@ -266,13 +671,192 @@ mod tests {
         assert!(matches!(result, Err(InitDataError::HashInvalid)));
     }
 
+    #[test]
+    fn test_extract_hash_parameter_pollution() {
+        const HASH: &str = "1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef";
+
+        // `&hash=` smuggled inside another value is not a parameter
+        for init_data in [
+            format!("query_id=test%26hash%3D{HASH}"),
+            format!("start_param=x%26hash={HASH}"),
+            format!("user=%7B%22a%22%3A%22%26hash%3D{HASH}%22%7D"),
+        ] {
+            assert!(
+                matches!(extract_hash(&init_data), Err(InitDataError::HashMissing)),
+                "{init_data}"
+            );
+        }
+
+        // Several hashes are ambiguous, whichever one a proxy or framework would pick
+        let init_data = format!("query_id=test&hash={HASH}&hash={HASH}");
+        assert!(matches!(extract_hash(&init_data), Err(InitDataError::HashInvalid)));
+        let init_data = format!("query_id=test&hash={HASH}&h%61sh={HASH}");
+        assert!(matches!(extract_hash(&init_data), Err(InitDataError::HashInvalid)));
+    }
+
+    #[test]
+    fn test_validate_hash_embedded_in_value() {
+        // A valid hash moved into another value must not validate the remaining parameters
+        let (base_data, hash) = VALID_INIT_DATA.split_once("&hash=").unwrap();
+        let init_data = format!("{base_data}&start_param=x%26hash%3D{hash}");
+        assert!(matches!(
+            validate(&init_data, BOT_TOKEN, Expiration::Never),
+            Err(InitDataError::HashMissing)
+        ));
+    }
+
     #[test]
     fn test_validate_incorrect_hash() {
         let base_data = "query_id=AAHdF6IQAAAAAN0XohDhrOrc&user=%7B%22id%22%3A279058397%2C%22first_name%22%3A%22Vladislav%22%2C%22last_name%22%3A%22Kibenko%22%2C%22username%22%3A%22vdkfrost%22%2C%22language_code%22%3A%22ru%22%2C%22is_premium%22%3Atrue%7D&auth_date=1662771648";
         // Use an obviously invalid hash (all zeros)
         let invalid_hash = "0000000000000000000000000000000000000000000000000000000000000000";
         let init_data = format!("{base_data}&hash={invalid_hash}");
-        let result = validate(&init_data, BOT_TOKEN, None);
+        let result = validate(&init_data, BOT_TOKEN, Expiration::Never);
         assert!(matches!(result, Err(InitDataError::HashInvalid)));
     }
+
+    #[test]
+    fn test_verify_check_order() {
+        let forged = VALID_INIT_DATA.replace("Vladislav", "Mallory");
+        let verify = |init_data: &str, order| {
+            verify_with_buffers(
+                init_data,
+                BOT_TOKEN,
+                Expiration::After(Duration::from_secs(60)),
+                &|| 1662771648 + 3600,
+                &mut ValidationBuffers::default(),
+                Compat::default(),
+                order,
+            )
+        };
+
+        // Expired and forged: the expiry is checked before the HMAC, unless every check runs
+        assert!(matches!(
+            verify(&forged, CheckOrder::CheapestFirst),
+            Err(InitDataError::Expired)
+        ));
+        assert!(matches!(
+            verify(&forged, CheckOrder::Uniform),
+            Err(InitDataError::HashInvalid)
+        ));
+        for order in [CheckOrder::CheapestFirst, CheckOrder::Uniform] {
+            assert!(matches!(verify(VALID_INIT_DATA, order), Err(InitDataError::Expired)));
+            assert!(matches!(verify("hash=", order), Err(InitDataError::HashInvalid)));
+            assert!(matches!(verify("", order), Err(InitDataError::UnexpectedFormat(_))));
+        }
+    }
+
+    #[test]
+    fn test_verify_with_order() {
+        let forged = VALID_INIT_DATA.replace("Vladislav", "Mallory");
+        let now = || 1662771648 + 3600;
+
+        assert!(matches!(
+            verify_with_order_with_clock(
+                &forged,
+                BOT_TOKEN,
+                Duration::from_secs(60),
+                CheckOrder::CheapestFirst,
+                &now
+            ),
+            Err(InitDataError::Expired)
+        ));
+        assert!(matches!(
+            verify_with_order_with_clock(&forged, BOT_TOKEN, Duration::from_secs(60), CheckOrder::Uniform, &now),
+            Err(InitDataError::HashInvalid)
+        ));
+        assert!(
+            verify_with_order_with_clock(VALID_INIT_DATA, BOT_TOKEN, Expiration::Never, CheckOrder::Uniform, &now)
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_verify() {
+        assert!(verify_with_clock(VALID_INIT_DATA, BOT_TOKEN, Duration::from_secs(60), &|| 1662771648 + 30).is_ok());
+        assert!(matches!(
+            verify_with_clock(VALID_INIT_DATA, BOT_TOKEN, Duration::from_secs(60), &|| 1662771648 + 61),
+            Err(InitDataError::Expired)
+        ));
+        assert!(matches!(
+            verify(
+                &VALID_INIT_DATA.replace("Vladislav", "Mallory"),
+                BOT_TOKEN,
+                Expiration::Never
+            ),
+            Err(InitDataError::HashInvalid)
+        ));
+    }
+
+    #[test]
+    fn test_extract_auth_date() {
+        assert_eq!(extract_auth_date("a=1&auth_date=1662771648").unwrap(), 1662771648);
+        assert!(matches!(extract_auth_date("a=1"), Err(InitDataError::AuthDateMissing)));
+        assert!(matches!(
+            extract_auth_date("auth_date=-1"),
+            Err(InitDataError::AuthDateInvalid { value }) if value == "-1"
+        ));
+        assert!(matches!(
+            extract_auth_date("auth_date=99999999999999999999"),
+            Err(InitDataError::AuthDateInvalid { .. })
+        ));
+        assert!(matches!(
+            extract_auth_date("auth_date="),
+            Err(InitDataError::AuthDateInvalid { value }) if value.is_empty()
+        ));
+        assert!(matches!(
+            extract_auth_date(&format!("auth_date={}", "x".repeat(1000))),
+            Err(InitDataError::AuthDateInvalid { value }) if value.len() == 32
+        ));
+    }
+
+    #[test]
+    fn test_validate_with_clock() {
+        let result = validate_with_clock(VALID_INIT_DATA, BOT_TOKEN, Duration::from_secs(60), &|| 1662771648 + 30);
+        assert!(result.is_ok());
+
+        let result = validate_with_clock(VALID_INIT_DATA, BOT_TOKEN, Duration::from_secs(60), &|| 1662771648 + 61);
+        assert!(matches!(result, Err(InitDataError::Expired)));
+    }
+
+    #[test]
+    fn test_validate_ext() {
+        let init_data = VALID_INIT_DATA.to_string();
+        let data = init_data.validate_tma(BOT_TOKEN, Expiration::Never).unwrap();
+        assert_eq!(data.user.unwrap().id, 279058397);
+        assert!(matches!(
+            "query_id=test123&hash=invalid".validate_tma(BOT_TOKEN, Expiration::Default),
+            Err(InitDataError::HashInvalid)
+        ));
+    }
+
+    #[test]
+    fn test_validate_graceful() {
+        let grace = Duration::from_secs(60);
+        let expiration = Duration::from_secs(3600);
+        let auth_date = 1_662_771_648;
+
+        let (_, freshness) =
+            validate_graceful_with_clock(VALID_INIT_DATA, BOT_TOKEN, expiration, grace, &|| auth_date + 3600).unwrap();
+        assert_eq!(freshness, Freshness::Fresh);
+
+        let (data, freshness) =
+            validate_graceful_with_clock(VALID_INIT_DATA, BOT_TOKEN, expiration, grace, &|| auth_date + 3650).unwrap();
+        assert_eq!(
+            freshness,
+            Freshness::Stale {
+                overshoot: Duration::from_secs(50)
+            }
+        );
+        assert_eq!(data.auth_date, auth_date);
+
+        assert!(matches!(
+            validate_graceful_with_clock(VALID_INIT_DATA, BOT_TOKEN, expiration, grace, &|| auth_date + 3661),
+            Err(InitDataError::Expired)
+        ));
+        assert!(matches!(
+            validate_graceful_with_clock("query_id=test123&hash=invalid", BOT_TOKEN, expiration, grace, &|| 0),
+            Err(InitDataError::HashInvalid)
+        ));
+    }
 }
@@ -0,0 +1,257 @@
+//! Cache of recently validated init data.
+//!
+//! Frontends send the same init data string with every request. A [`ValidationCache`] set with
+//! [`crate::Validator::with_validation_cache`] lets the validator answer repeated payloads without
+//! verifying and parsing them again.
+//!
+//! Entries are keyed on an HMAC of the raw init data with the bot secret and the settings of the validator,
+//! so that validators sharing a cache never answer from entries another token or stricter policies would
+//! have rejected. They never outlive the init data: the validator passes `auth_date + expires_in` as the
+//! expiry of every entry, and re-checks it on every hit.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+
+use crate::clock::{Clock, SystemClock};
+use crate::model::InitData;
+
+/// Key of validated init data, an HMAC of the raw init data with the bot secret and the validator's settings.
+pub type PayloadHash = [u8; 32];
+
+/// Stores init data that passed validation.
+pub trait ValidationCache: Send + Sync {
+    /// Returns the init data validated for `key`, if it is cached.
+    fn get(&self, key: &PayloadHash) -> Option<InitData>;
+
+    /// Remembers validated init data. `expires_at` is the Unix time after which the init data expires,
+    /// `None` if expiration checks are disabled.
+    fn insert(&self, key: PayloadHash, data: InitData, expires_at: Option<u64>);
+}
+
+/// Shares one cache between several validators. Their tokens and settings are part of the keys, so each one
+/// only finds the entries it validated itself or a validator configured the same way did.
+impl<T: ValidationCache + ?Sized> ValidationCache for Arc<T> {
+    fn get(&self, key: &PayloadHash) -> Option<InitData> {
+        (**self).get(key)
+    }
+
+    fn insert(&self, key: PayloadHash, data: InitData, expires_at: Option<u64>) {
+        (**self).insert(key, data, expires_at);
+    }
+}
+
+#[derive(Clone)]
+struct Entry {
+    data: InitData,
+    expires_at: Option<u64>,
+}
+
+/// In-memory [`ValidationCache`] holding up to `capacity` entries, evicting the oldest one first.
+///
+/// # Example
+/// ```
+/// use init_data_rs::{MemoryValidationCache, Validator};
+///
+/// let validator = Validator::new("BOT_TOKEN").with_validation_cache(MemoryValidationCache::new(10_000));
+/// ```
+pub struct MemoryValidationCache<C: Clock = SystemClock> {
+    capacity: usize,
+    clock: C,
+    entries: Mutex<(VecDeque<PayloadHash>, HashMap<PayloadHash, Entry>)>,
+}
+
+impl MemoryValidationCache {
+    /// Creates a cache holding up to `capacity` entries.
+    #[must_use]
+    pub fn new(capacity: usize) -> Self {
+        Self::with_clock(capacity, SystemClock)
+    }
+}
+
+impl<C: Clock> MemoryValidationCache<C> {
+    /// Same as [`MemoryValidationCache::new`], but reads the current time from `clock`.
+    pub fn with_clock(capacity: usize, clock: C) -> Self {
+        Self {
+            capacity,
+            clock,
+            entries: Mutex::default(),
+        }
+    }
+
+    /// Number of entries currently cached, expired ones included until they are looked up or evicted.
+    pub fn len(&self) -> usize {
+        self.entries
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .0
+            .len()
+    }
+
+    /// Whether the cache holds no entries.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<C: Clock + Send + Sync> ValidationCache for MemoryValidationCache<C> {
+    fn get(&self, key: &PayloadHash) -> Option<InitData> {
+        let mut entries = self.entries.lock().unwrap_or_else(std::sync::PoisonError::into_inner);
+        let entry = entries.1.get(key)?;
+
+        if entry.expires_at.is_some_and(|expires_at| expires_at < self.clock.now()) {
+            entries.1.remove(key);
+            entries.0.retain(|cached| cached != key);
+            return None;
+        }
+
+        Some(entry.data.clone())
+    }
+
+    fn insert(&self, key: PayloadHash, data: InitData, expires_at: Option<u64>) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        let mut entries = self.entries.lock().unwrap_or_else(std::sync::PoisonError::into_inner);
+        if entries.1.insert(key, Entry { data, expires_at }).is_some() {
+            return;
+        }
+
+        entries.0.push_back(key);
+        if entries.0.len() > self.capacity {
+            if let Some(oldest) = entries.0.pop_front() {
+                entries.1.remove(&oldest);
+            }
+        }
+    }
+}
+
+/// [`ValidationCache`] backed by a concurrent [`moka`](https://docs.rs/moka) cache, bounded to
+/// `max_capacity` entries. Every entry is evicted once its init data expires.
+///
+/// # Example
+/// ```
+/// use init_data_rs::{MokaValidationCache, Validator};
+///
+/// let validator = Validator::new("BOT_TOKEN").with_validation_cache(MokaValidationCache::new(100_000));
+/// ```
+#[cfg(feature = "moka")]
+pub struct MokaValidationCache {
+    cache: moka::sync::Cache<PayloadHash, Entry>,
+}
+
+#[cfg(feature = "moka")]
+impl MokaValidationCache {
+    /// Creates a cache holding up to `max_capacity` entries.
+    #[must_use]
+    pub fn new(max_capacity: u64) -> Self {
+        Self::with_clock(max_capacity, SystemClock)
+    }
+
+    /// Same as [`MokaValidationCache::new`], but reads the current time from `clock` to compute the
+    /// remaining lifetime of the entries.
+    pub fn with_clock(max_capacity: u64, clock: impl Clock + Send + Sync + 'static) -> Self {
+        Self {
+            cache: moka::sync::Cache::builder()
+                .max_capacity(max_capacity)
+                .expire_after(UntilExpiry(clock))
+                .build(),
+        }
+    }
+
+    /// Approximate number of entries currently cached.
+    #[must_use]
+    pub fn entry_count(&self) -> u64 {
+        self.cache.entry_count()
+    }
+}
+
+#[cfg(feature = "moka")]
+impl ValidationCache for MokaValidationCache {
+    fn get(&self, key: &PayloadHash) -> Option<InitData> {
+        self.cache.get(key).map(|entry| entry.data)
+    }
+
+    fn insert(&self, key: PayloadHash, data: InitData, expires_at: Option<u64>) {
+        self.cache.insert(key, Entry { data, expires_at });
+    }
+}
+
+/// Expires entries at the `expires_at` of their init data.
+#[cfg(feature = "moka")]
+struct UntilExpiry<C>(C);
+
+#[cfg(feature = "moka")]
+impl<C: Clock> moka::Expiry<PayloadHash, Entry> for UntilExpiry<C> {
+    fn expire_after_create(
+        &self,
+        _: &PayloadHash,
+        entry: &Entry,
+        _: std::time::Instant,
+    ) -> Option<std::time::Duration> {
+        entry
+            .expires_at
+            .map(|expires_at| std::time::Duration::from_secs(expires_at.saturating_sub(self.0.now())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    use super::*;
+    use crate::parse;
+
+    fn data(auth_date: u64) -> InitData {
+        parse(&format!(
+            "auth_date={auth_date}&hash=c501b71e775f74ce10e377dea85a7ea24ecd640b223ea86dfe453e0eaed2e2b2"
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_memory_validation_cache() {
+        let now = Arc::new(AtomicU64::new(100));
+        let clock = {
+            let now = Arc::clone(&now);
+            move || now.load(Ordering::Relaxed)
+        };
+        let cache = MemoryValidationCache::with_clock(2, clock);
+
+        cache.insert([1; 32], data(1), Some(150));
+        cache.insert([2; 32], data(2), None);
+        assert_eq!(cache.get(&[1; 32]).unwrap().auth_date, 1);
+
+        cache.insert([3; 32], data(3), None);
+        assert!(cache.get(&[1; 32]).is_none());
+        assert_eq!(cache.len(), 2);
+
+        now.store(u64::MAX, Ordering::Relaxed);
+        assert_eq!(cache.get(&[2; 32]).unwrap().auth_date, 2);
+    }
+
+    #[test]
+    fn test_memory_validation_cache_expires_entries() {
+        let cache = MemoryValidationCache::with_clock(2, || 200);
+
+        cache.insert([1; 32], data(1), Some(199));
+
+        assert!(cache.get(&[1; 32]).is_none());
+        assert!(cache.is_empty());
+    }
+
+    #[cfg(feature = "moka")]
+    #[test]
+    fn test_moka_validation_cache() {
+        let cache = MokaValidationCache::with_clock(16, || 100);
+
+        cache.insert([1; 32], data(1), Some(1000));
+        cache.insert([2; 32], data(2), None);
+        cache.insert([3; 32], data(3), Some(100));
+
+        assert_eq!(cache.get(&[1; 32]).unwrap().auth_date, 1);
+        assert_eq!(cache.get(&[2; 32]).unwrap().auth_date, 2);
+        // no lifetime left
+        assert!(cache.get(&[3; 32]).is_none());
+    }
+}
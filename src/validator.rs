@@ -0,0 +1,1295 @@
+//! Reusable validator for services validating init data on every request.
+
+use std::cell::RefCell;
+use std::sync::{Arc, OnceLock, PoisonError, RwLock, RwLockReadGuard};
+use std::time::Instant;
+
+#[cfg(feature = "json")]
+use crate::body::extract_from_json;
+use crate::body::{extract_from_form, extract_from_multipart};
+use crate::buffers::ValidationBuffers;
+use crate::chat_policy::ChatPolicy;
+use crate::check_order::CheckOrder;
+use crate::clock::{Clock, SystemClock};
+use crate::compat::Compat;
+use crate::crypto::{self, Hmac};
+#[cfg(feature = "diagnostics")]
+use crate::diagnostics::{FailedPayload, FailureSampler};
+use crate::error::InitDataError;
+use crate::expiration::{Expiration, ExpirationPolicy};
+use crate::launch_params::{extract_from_query, ClientPolicy, LaunchParams};
+use crate::metrics_sink::MetricsSink;
+use crate::model::InitData;
+use crate::parse::{parse_into, ParseOptions};
+use crate::privacy::PrivacyMode;
+use crate::rejection_cache::{RejectionCache, RejectionCacheStats};
+use crate::scheme::{self, ValidationScheme, Verified};
+use crate::secret::{BotSecret, BotToken};
+use crate::start_param::StartParamPolicy;
+#[cfg(feature = "tracing")]
+use crate::telemetry;
+#[cfg(any(feature = "tracing", feature = "log"))]
+use crate::telemetry::ValidationEvent;
+use crate::third_party_validation::{validate_third_party_in, Environment};
+use crate::throttle::ThrottleHook;
+use crate::user_gate::{self, AsyncUserGate, UserGate};
+use crate::validation::verify_with_buffers;
+use crate::validation_cache::{PayloadHash, ValidationCache};
+
+thread_local! {
+    static BUFFERS: RefCell<ValidationBuffers> = RefCell::default();
+}
+
+/// Validates init data with a fixed bot token and settings.
+///
+/// With the `tracing` feature, every validation runs in a `tma.validate` span (target `init_data_rs`)
+/// carrying `tma.scheme`, then `enduser.id` and `tma.auth_age_ms` on success or `tma.error_code` (see
+/// [`InitDataError::kind`]) and `otel.status_code` on failure. The `log` feature emits the same attributes
+/// as key-values of a record per validation, `debug` on success and `info` on failure.
+///
+/// # Example
+/// ```
+/// use std::time::Duration;
+/// use init_data_rs::{Expiration, Validator};
+///
+/// let validator = Validator::new("BOT_TOKEN")
+///     .expiration(Expiration::After(Duration::from_secs(3600)))
+///     .with_rejection_cache(10_000);
+/// let result = validator.validate("query_id=123&auth_date=1662771648&hash=...");
+/// ```
+pub struct Validator {
+    token: Arc<RwLock<BotToken>>,
+    previous_token: Option<(BotToken, u64)>,
+    expiration: ExpirationPolicy,
+    client_policy: ClientPolicy,
+    start_param_policy: StartParamPolicy,
+    chat_policy: ChatPolicy,
+    environment: Environment,
+    parse_options: ParseOptions,
+    privacy_mode: PrivacyMode,
+    scheme: Option<ValidationScheme>,
+    compat: Compat,
+    check_order: CheckOrder,
+    rejection_cache: Option<Arc<RejectionCache>>,
+    validation_cache: Option<Box<dyn ValidationCache>>,
+    /// SHA-256 of the settings the validation cache keys depend on, computed on the first lookup.
+    settings_fingerprint: OnceLock<[u8; 32]>,
+    throttle: Option<Box<dyn ThrottleHook>>,
+    user_gate: Option<Box<dyn UserGate>>,
+    metrics: Option<Box<dyn MetricsSink>>,
+    #[cfg(feature = "diagnostics")]
+    failure_sampler: Option<FailureSampler>,
+}
+
+impl Validator {
+    /// Creates a validator for the given bot token with the default expiration of 24 hours.
+    pub fn new(token: impl Into<BotToken>) -> Self {
+        Self {
+            token: Arc::new(RwLock::new(token.into())),
+            previous_token: None,
+            expiration: ExpirationPolicy::default(),
+            client_policy: ClientPolicy::default(),
+            start_param_policy: StartParamPolicy::default(),
+            chat_policy: ChatPolicy::default(),
+            environment: Environment::Production,
+            parse_options: ParseOptions::default(),
+            privacy_mode: PrivacyMode::default(),
+            scheme: None,
+            compat: Compat::default(),
+            check_order: CheckOrder::default(),
+            rejection_cache: None,
+            validation_cache: None,
+            settings_fingerprint: OnceLock::new(),
+            throttle: None,
+            user_gate: None,
+            metrics: None,
+            #[cfg(feature = "diagnostics")]
+            failure_sampler: None,
+        }
+    }
+
+    /// Creates a validator configured from the environment:
+    /// * `TELEGRAM_BOT_TOKEN` - Bot token, required
+    /// * `INIT_DATA_TTL` - Expiration time in seconds, `0` to disable it, 24 hours if unset
+    /// * `INIT_DATA_ENV` - `production` or `test`, the environment of third-party signatures, production if unset
+    ///
+    /// # Example
+    /// ```no_run
+    /// use init_data_rs::Validator;
+    ///
+    /// let validator = Validator::from_env().expect("TELEGRAM_BOT_TOKEN is set");
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns [`InitDataError::Config`] naming the variable that is missing or invalid.
+    pub fn from_env() -> Result<Self, InitDataError> {
+        Self::from_vars(|name| std::env::var(name))
+    }
+
+    fn from_vars(var: impl Fn(&str) -> Result<String, std::env::VarError>) -> Result<Self, InitDataError> {
+        let optional = |name: &str| match var(name) {
+            Ok(value) => Ok(Some(value)),
+            Err(std::env::VarError::NotPresent) => Ok(None),
+            Err(std::env::VarError::NotUnicode(_)) => Err(InitDataError::Config(format!("{name} is not valid UTF-8"))),
+        };
+
+        let token = optional("TELEGRAM_BOT_TOKEN")?
+            .filter(|token| !token.is_empty())
+            .ok_or_else(|| InitDataError::Config("TELEGRAM_BOT_TOKEN is not set".to_string()))?;
+        let mut validator = Self::new(token);
+
+        if let Some(ttl) = optional("INIT_DATA_TTL")? {
+            let ttl = ttl.trim().parse::<u64>().map_err(|_| {
+                InitDataError::Config(format!("INIT_DATA_TTL must be a number of seconds, got `{ttl}`"))
+            })?;
+            validator = validator.expiration(Expiration::from_seconds(Some(ttl)));
+        }
+
+        if let Some(environment) = optional("INIT_DATA_ENV")? {
+            validator.environment = match environment.trim().to_ascii_lowercase().as_str() {
+                "production" | "prod" => Environment::Production,
+                "test" => Environment::Test,
+                _ => {
+                    return Err(InitDataError::Config(format!(
+                        "INIT_DATA_ENV must be `production` or `test`, got `{environment}`"
+                    )))
+                }
+            };
+        }
+
+        Ok(validator)
+    }
+
+    /// Creates a validator for a bot token that was just regenerated: init data signed with the `previous`
+    /// token is still accepted until `cutoff`, a Unix time in seconds, so that Mini Apps opened before the
+    /// rotation keep working.
+    ///
+    /// [`Validator::validate_rotating`] reports which token verified the init data.
+    ///
+    /// # Example
+    /// ```
+    /// use init_data_rs::Validator;
+    ///
+    /// let validator = Validator::with_rotating_tokens("NEW_BOT_TOKEN", "OLD_BOT_TOKEN", 1_700_086_400);
+    /// ```
+    pub fn with_rotating_tokens(current: impl Into<BotToken>, previous: impl Into<BotToken>, cutoff: u64) -> Self {
+        Self {
+            previous_token: Some((previous.into(), cutoff)),
+            ..Self::new(current)
+        }
+    }
+
+    /// Sets how long init data stays valid, see [`Expiration`].
+    #[must_use]
+    pub fn expiration(self, expiration: Expiration) -> Self {
+        self.expiration_policy(ExpirationPolicy::new(expiration))
+    }
+
+    /// Sets how long init data stays valid depending on the chat it was opened from, see [`ExpirationPolicy`].
+    #[must_use]
+    pub fn expiration_policy(mut self, policy: ExpirationPolicy) -> Self {
+        self.expiration = policy;
+        self.settings_fingerprint.take();
+        self
+    }
+
+    /// Sets the expiration time in seconds, `Some(0)` disabling it.
+    #[deprecated(note = "use `Validator::expiration`, `Some(0)` is `Expiration::Never`")]
+    #[must_use]
+    pub fn expires_in(self, expires_in: Option<u64>) -> Self {
+        self.expiration(Expiration::from_seconds(expires_in))
+    }
+
+    /// Sets the requirements on the Telegram client checked by [`Validator::validate_launch_params`].
+    #[must_use]
+    pub fn client_policy(mut self, policy: ClientPolicy) -> Self {
+        self.client_policy = policy;
+        self
+    }
+
+    /// Sets the requirements on the `start_param` of init data, checked once the hash or signature is valid.
+    #[must_use]
+    pub fn start_param_policy(mut self, policy: StartParamPolicy) -> Self {
+        self.start_param_policy = policy;
+        self.settings_fingerprint.take();
+        self
+    }
+
+    /// Sets the chats init data is accepted from, checked once the hash or signature is valid.
+    #[must_use]
+    pub fn chat_policy(mut self, policy: ChatPolicy) -> Self {
+        self.chat_policy = policy;
+        self.settings_fingerprint.take();
+        self
+    }
+
+    /// Sets the environment whose key third-party signatures are checked with, see [`Environment`].
+    #[must_use]
+    pub fn environment(mut self, environment: Environment) -> Self {
+        self.environment = environment;
+        self
+    }
+
+    /// Sets the size and depth limits enforced before deserializing, see [`ParseOptions`].
+    #[must_use]
+    pub fn parse_options(mut self, parse_options: ParseOptions) -> Self {
+        self.parse_options = parse_options;
+        self.settings_fingerprint.take();
+        self
+    }
+
+    /// Blanks the personal fields selected by `mode` from validated init data, see [`PrivacyMode`].
+    #[must_use]
+    pub fn privacy_mode(mut self, mode: PrivacyMode) -> Self {
+        self.privacy_mode = mode;
+        self.settings_fingerprint.take();
+        self
+    }
+
+    /// Makes [`Validator::validate_auto`] validate with `scheme` only, instead of the scheme the init data
+    /// carries, e.g. so that a service expecting bot token hashes never accepts third-party signatures.
+    #[must_use]
+    pub fn scheme(mut self, scheme: ValidationScheme) -> Self {
+        self.scheme = Some(scheme);
+        self
+    }
+
+    /// Verifies and parses init data like another implementation would, see [`Compat`]. Sets
+    /// [`ParseOptions::empty_strings_as_none`] too.
+    #[must_use]
+    pub fn compat(mut self, compat: Compat) -> Self {
+        self.compat = compat;
+        self.settings_fingerprint.take();
+        self.parse_options.empty_strings_as_none = compat.empty_strings_as_none;
+        self
+    }
+
+    /// Sets the order of the checks, [`CheckOrder::CheapestFirst`] by default.
+    #[must_use]
+    pub fn check_order(mut self, order: CheckOrder) -> Self {
+        self.check_order = order;
+        self
+    }
+
+    /// Remembers up to `capacity` recently rejected payloads, so that replays of the same forged
+    /// init data are rejected without verifying them again.
+    #[must_use]
+    pub fn with_rejection_cache(mut self, capacity: usize) -> Self {
+        self.rejection_cache = Some(Arc::new(RejectionCache::new(capacity)));
+        self
+    }
+
+    /// Answers init data that already passed validation from `cache`, until it expires, see
+    /// [`crate::ValidationCache`].
+    #[must_use]
+    pub fn with_validation_cache(mut self, cache: impl ValidationCache + 'static) -> Self {
+        self.validation_cache = Some(Box::new(cache));
+        self
+    }
+
+    /// Consults `throttle` in [`Validator::validate_from`] before verifying, and reports the outcome to it.
+    #[must_use]
+    pub fn with_throttle(mut self, throttle: impl ThrottleHook + 'static) -> Self {
+        self.throttle = Some(Box::new(throttle));
+        self
+    }
+
+    /// Consults `gate` with the id of the user of every validated init data, rejecting denied users with
+    /// [`InitDataError::UserDenied`] and init data without a user with [`InitDataError::UserMissing`].
+    ///
+    /// The gate runs on every validation, also for init data answered from the validation cache, so that a ban
+    /// takes effect right away.
+    #[must_use]
+    pub fn with_user_gate(mut self, gate: impl UserGate + 'static) -> Self {
+        self.user_gate = Some(Box::new(gate));
+        self
+    }
+
+    /// Reports the outcome and latency of every validation to `metrics`.
+    #[must_use]
+    pub fn with_metrics(mut self, metrics: impl MetricsSink + 'static) -> Self {
+        self.metrics = Some(Box::new(metrics));
+        self
+    }
+
+    /// Keeps the last `capacity` rejected payloads with their error, see [`Validator::failure_samples`].
+    ///
+    /// The `hash` and `signature` are stripped from the samples, and so are `user`, `receiver` and `chat` if
+    /// `mask_pii` is set.
+    #[cfg(feature = "diagnostics")]
+    #[must_use]
+    pub fn with_failure_sampling(mut self, capacity: usize, mask_pii: bool) -> Self {
+        self.failure_sampler = Some(FailureSampler::new(capacity, mask_pii));
+        self
+    }
+
+    /// The latest rejected payloads, oldest first, empty unless [`Validator::with_failure_sampling`] is set.
+    #[cfg(feature = "diagnostics")]
+    #[must_use]
+    pub fn failure_samples(&self) -> Vec<FailedPayload> {
+        self.failure_sampler
+            .as_ref()
+            .map(FailureSampler::samples)
+            .unwrap_or_default()
+    }
+
+    /// Hit/miss counters of the rejection cache, if it is enabled.
+    pub fn rejection_cache_stats(&self) -> Option<RejectionCacheStats> {
+        self.rejection_cache.as_deref().map(RejectionCache::stats)
+    }
+
+    /// Replaces the bot token, e.g. after it was rotated in a secrets manager, without rebuilding the validator.
+    ///
+    /// Validations already running finish with the former token. The rejection cache is cleared, since
+    /// init data signed with the new token may have been rejected before the swap. Entries of the
+    /// validation cache are keyed with the token, so those verified with the former one are no longer served.
+    pub fn set_token(&self, token: impl Into<BotToken>) {
+        self.token_handle().set(token);
+    }
+
+    /// A handle replacing the bot token of this validator from elsewhere, e.g. a task watching a secrets
+    /// manager while the validator is owned by a middleware. Take it once the validator is configured.
+    ///
+    /// # Example
+    /// ```
+    /// use init_data_rs::Validator;
+    ///
+    /// let validator = Validator::new("OLD_BOT_TOKEN").with_rejection_cache(10_000);
+    /// let handle = validator.token_handle();
+    ///
+    /// std::thread::spawn(move || handle.set("NEW_BOT_TOKEN")).join().unwrap();
+    /// ```
+    #[must_use]
+    pub fn token_handle(&self) -> TokenHandle {
+        TokenHandle {
+            token: Arc::clone(&self.token),
+            rejection_cache: self.rejection_cache.clone(),
+        }
+    }
+
+    fn current_token(&self) -> RwLockReadGuard<'_, BotToken> {
+        self.token.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Validates init data, see [`crate::validate`].
+    ///
+    /// Decoding reuses scratch buffers kept per thread, so steady-state validation only allocates the
+    /// returned [`InitData`].
+    ///
+    /// # Errors
+    ///
+    /// See `init_data_rs::parse` for possible errors
+    pub fn validate(&self, init_data: &str) -> Result<InitData, InitDataError> {
+        self.measure(ValidationScheme::BotTokenHmac, init_data, || {
+            self.validate_hmac(init_data)
+        })
+    }
+
+    fn validate_hmac(&self, init_data: &str) -> Result<InitData, InitDataError> {
+        with_buffers(|buffers| self.validate_cached(init_data, buffers))
+    }
+
+    /// Same as [`Validator::validate`], but also reports which token verified the init data, see
+    /// [`Validator::with_rotating_tokens`]. The validation cache is bypassed, the rejection cache too.
+    ///
+    /// # Errors
+    ///
+    /// See `init_data_rs::parse` for possible errors
+    pub fn validate_rotating(&self, init_data: &str) -> Result<(InitData, TokenSlot), InitDataError> {
+        let mut slot = TokenSlot::Current;
+        let data = self.measure(ValidationScheme::BotTokenHmac, init_data, || {
+            with_buffers(|buffers| {
+                let (data, verified_by) = self.validate_uncached_with_slot(init_data, buffers)?;
+                slot = verified_by;
+                Ok(data)
+            })
+        })?;
+
+        Ok((data, slot))
+    }
+
+    /// Validates init data with the given scheme only, see [`crate::validate_scheme`].
+    ///
+    /// # Errors
+    ///
+    /// See [`crate::validate_scheme`].
+    pub fn validate_scheme(
+        &self,
+        init_data: &str,
+        scheme: ValidationScheme,
+    ) -> Result<Verified<InitData>, InitDataError> {
+        let data = self.measure(scheme, init_data, || match scheme {
+            ValidationScheme::BotTokenHmac => self.validate_hmac(init_data),
+            ValidationScheme::Ed25519ThirdParty => validate_third_party_in(
+                init_data,
+                scheme::bot_id(&*self.current_token())?,
+                self.environment,
+                self.expiration.before_parsing(),
+                &SystemClock,
+            )
+            .and_then(|data| self.check_parsed(data)),
+        })?;
+
+        Ok(Verified::new(data, scheme))
+    }
+
+    /// Validates init data with the scheme it carries, see [`crate::validate_auto`], or only with the one set by
+    /// [`Validator::scheme`].
+    ///
+    /// # Errors
+    ///
+    /// See [`crate::validate_auto`].
+    pub fn validate_auto(&self, init_data: &str) -> Result<Verified<InitData>, InitDataError> {
+        match self.scheme {
+            Some(scheme) => self.validate_scheme(init_data, scheme),
+            None => self.validate_scheme(init_data, ValidationScheme::detect(init_data)?),
+        }
+    }
+
+    /// Issues a session token for init data validated by this validator, see [`crate::issue_jwt`].
+    ///
+    /// # Errors
+    ///
+    /// See [`crate::issue_jwt`].
+    #[cfg(feature = "jwt")]
+    #[allow(clippy::unused_self)]
+    pub fn issue_jwt(&self, verified: &Verified<InitData>, key: &[u8], ttl: u64) -> Result<String, InitDataError> {
+        crate::jwt::issue_jwt(verified, key, ttl)
+    }
+
+    /// Validates the init data of launch parameters, after checking the client against the
+    /// [`ClientPolicy`]. See [`LaunchParams::parse`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`InitDataError::ClientNotAllowed`] if the policy rejects the client,
+    /// [`InitDataError::UnexpectedFormat`] if the launch parameters are invalid or carry no init data, see
+    /// `init_data_rs::parse` for the other errors.
+    pub fn validate_launch_params(&self, launch_params: &str) -> Result<(LaunchParams, InitData), InitDataError> {
+        let params = LaunchParams::parse(launch_params)?;
+        self.client_policy.check(&params)?;
+
+        let init_data = params
+            .init_data
+            .as_deref()
+            .ok_or_else(|| InitDataError::UnexpectedFormat("tgWebAppData is missing".to_string()))?;
+        let data = self.validate(init_data)?;
+
+        Ok((params, data))
+    }
+
+    /// Validates init data sent as the parameter `param` of a URL query string, see
+    /// [`crate::extract_from_query`].
+    ///
+    /// # Errors
+    ///
+    /// See [`crate::extract_from_query`] and `init_data_rs::parse`.
+    pub fn validate_query(&self, url_query: &str, param: &str) -> Result<InitData, InitDataError> {
+        self.validate(&extract_from_query(url_query, param)?)
+    }
+
+    /// Validates init data posted as the string field `field` of a JSON object, see [`crate::extract_from_json`].
+    ///
+    /// # Errors
+    ///
+    /// See [`crate::extract_from_json`] and `init_data_rs::parse`.
+    #[cfg(feature = "json")]
+    pub fn validate_json(&self, body: &[u8], field: &str) -> Result<InitData, InitDataError> {
+        self.validate(&extract_from_json(body, field)?)
+    }
+
+    /// Validates init data posted as the field `field` of an `application/x-www-form-urlencoded` body, see
+    /// [`crate::extract_from_form`].
+    ///
+    /// # Errors
+    ///
+    /// See [`crate::extract_from_form`] and `init_data_rs::parse`.
+    pub fn validate_form(&self, body: &str, field: &str) -> Result<InitData, InitDataError> {
+        self.validate(&extract_from_form(body, field)?)
+    }
+
+    /// Validates init data posted as the field `field` of a `multipart/form-data` body, see
+    /// [`crate::extract_from_multipart`].
+    ///
+    /// # Errors
+    ///
+    /// See [`crate::extract_from_multipart`] and `init_data_rs::parse`.
+    pub fn validate_multipart(&self, body: &[u8], content_type: &str, field: &str) -> Result<InitData, InitDataError> {
+        self.validate(&extract_from_multipart(body, content_type, field)?)
+    }
+
+    /// Same as [`Validator::validate`], then consults the async `gate` with the id of the user, e.g. to look
+    /// bans up in a database. The gate set with [`Validator::with_user_gate`], if any, runs first.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`InitDataError::UserDenied`] if the gate denies the user, [`InitDataError::UserMissing`] if the
+    /// init data has no user, the errors of the gate and see `init_data_rs::parse` for the others.
+    pub async fn validate_gated(
+        &self,
+        init_data: &str,
+        gate: &(impl AsyncUserGate + ?Sized),
+    ) -> Result<InitData, InitDataError> {
+        let data = self.validate(init_data)?;
+        gate.check(user_gate::user_id(&data)?).await?.into_result()?;
+
+        Ok(data)
+    }
+
+    /// Same as [`Validator::validate`], but rate limits the failed attempts of `identity`
+    /// (e.g. the client IP) with the throttle hook, if one is set.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`InitDataError::Throttled`] without verifying the init data if the hook rejects
+    /// `identity`, see `init_data_rs::parse` for the other errors.
+    pub fn validate_from(&self, identity: &str, init_data: &str) -> Result<InitData, InitDataError> {
+        let Some(throttle) = &self.throttle else {
+            return self.validate(init_data);
+        };
+
+        if !throttle.allow(identity) {
+            return Err(InitDataError::Throttled);
+        }
+
+        let result = self.validate(init_data);
+        match &result {
+            Ok(_) => throttle.record_success(identity),
+            Err(InitDataError::Internal(_)) => {}
+            Err(_) => throttle.record_failure(identity),
+        }
+
+        result
+    }
+
+    /// Same as [`Validator::validate`], but decodes into caller-provided `buffers`, e.g. taken from a pool.
+    ///
+    /// # Errors
+    ///
+    /// See `init_data_rs::parse` for possible errors
+    pub fn validate_with_buffers(
+        &self,
+        init_data: &str,
+        buffers: &mut ValidationBuffers,
+    ) -> Result<InitData, InitDataError> {
+        self.measure(ValidationScheme::BotTokenHmac, init_data, || {
+            self.validate_cached(init_data, buffers)
+        })
+    }
+
+    fn validate_cached(&self, init_data: &str, buffers: &mut ValidationBuffers) -> Result<InitData, InitDataError> {
+        if self.validation_cache.is_none() && self.rejection_cache.is_none() {
+            return self.validate_uncached(init_data, buffers);
+        }
+
+        let fingerprint = RejectionCache::fingerprint(init_data);
+        let cached = match &self.validation_cache {
+            Some(cache) => {
+                let secret = self.current_token().secret()?;
+                Some((cache, self.validation_cache_key(&secret, init_data)?, secret))
+            }
+            None => None,
+        };
+        if let Some(data) = cached.as_ref().and_then(|(cache, key, _)| cache.get(key)) {
+            // The cache may not honor the expiry, expired init data goes through the full validation
+            if self
+                .expires_at(&data)
+                .is_none_or(|expires_at| expires_at >= SystemClock.now())
+            {
+                return Ok(data);
+            }
+        }
+        if let Some(error) = self.rejection_cache.as_ref().and_then(|cache| cache.get(&fingerprint)) {
+            return Err(error);
+        }
+
+        let result = self.validate_uncached_with_slot(init_data, buffers);
+        match (&result, cached, &self.rejection_cache) {
+            // Only cached under the token it was verified with, the previous one stops verifying at its cutoff
+            (Ok((data, TokenSlot::Current)), Some((cache, key, secret)), _)
+                if self.current_token().secret().is_ok_and(|current| current == secret) =>
+            {
+                cache.insert(key, data.clone(), self.expires_at(data));
+            }
+            (Err(error), _, Some(cache)) => cache.insert(fingerprint, error),
+            _ => {}
+        }
+
+        result.map(|(data, _)| data)
+    }
+
+    /// Key of `init_data` in the validation cache, an HMAC with the bot secret over the settings and the payload:
+    /// validators sharing a cache only answer from entries they would have accepted themselves.
+    fn validation_cache_key(&self, secret: &BotSecret, init_data: &str) -> Result<PayloadHash, InitDataError> {
+        let settings = self.settings_fingerprint.get_or_init(|| {
+            let settings = format!(
+                "{:?}|{:?}|{:?}|{:?}|{}|{}",
+                self.expiration,
+                self.parse_options,
+                self.privacy_mode,
+                self.compat,
+                self.start_param_policy.fingerprint(),
+                self.chat_policy.fingerprint(),
+            );
+            crypto::sha256(settings.as_bytes())
+        });
+
+        let mut hmac = Hmac::new(secret.as_bytes())?;
+        hmac.update(settings);
+        hmac.update(init_data.as_bytes());
+        hmac.finalize()
+    }
+
+    /// Unix time after which `data` expires, `None` if expiration is disabled.
+    fn expires_at(&self, data: &InitData) -> Option<u64> {
+        self.expiration
+            .expiration_for(data)
+            .ttl()
+            .map(|ttl| data.auth_date.saturating_add(ttl.as_secs()))
+    }
+
+    /// Rejects `data` that expired under a per-chat-type policy, which is only known after parsing, or whose
+    /// `start_param` or chat breaks the policies, then applies the privacy mode.
+    fn check_parsed(&self, mut data: InitData) -> Result<InitData, InitDataError> {
+        if self.expiration.is_expired(&data, SystemClock.now()) {
+            return Err(InitDataError::Expired);
+        }
+        self.start_param_policy.validate(data.start_param.as_deref())?;
+        self.chat_policy.validate(&data)?;
+        // Before the data reaches the validation cache
+        self.privacy_mode.apply(&mut data);
+
+        Ok(data)
+    }
+
+    #[cfg_attr(not(feature = "diagnostics"), allow(unused_variables))]
+    fn measure(
+        &self,
+        scheme: ValidationScheme,
+        init_data: &str,
+        validate: impl FnOnce() -> Result<InitData, InitDataError>,
+    ) -> Result<InitData, InitDataError> {
+        let validate = || validate().and_then(|data| self.check_user(data));
+        #[cfg(feature = "tracing")]
+        let span = telemetry::validation_span(scheme);
+        #[cfg(feature = "tracing")]
+        let validate = || span.in_scope(validate);
+
+        let result = self.record_metrics(scheme, validate);
+
+        #[cfg(any(feature = "tracing", feature = "log"))]
+        {
+            let event = ValidationEvent::new(scheme, &result);
+            #[cfg(feature = "tracing")]
+            event.record(&span);
+            #[cfg(feature = "log")]
+            event.log();
+        }
+        #[cfg(feature = "diagnostics")]
+        if let (Some(sampler), Err(error)) = (&self.failure_sampler, &result) {
+            sampler.record(init_data, error, SystemClock.now());
+        }
+
+        result
+    }
+
+    fn check_user(&self, data: InitData) -> Result<InitData, InitDataError> {
+        if let Some(gate) = &self.user_gate {
+            gate.check(user_gate::user_id(&data)?).into_result()?;
+        }
+
+        Ok(data)
+    }
+
+    fn record_metrics(
+        &self,
+        scheme: ValidationScheme,
+        validate: impl FnOnce() -> Result<InitData, InitDataError>,
+    ) -> Result<InitData, InitDataError> {
+        // `Instant` isn't available on every target, only read it when someone listens
+        let Some(metrics) = &self.metrics else {
+            return validate();
+        };
+
+        let start = Instant::now();
+        let result = validate();
+        match &result {
+            Ok(_) => metrics.record_success(scheme, start.elapsed()),
+            Err(error) => metrics.record_failure(scheme, error, start.elapsed()),
+        }
+
+        result
+    }
+
+    fn validate_uncached(&self, init_data: &str, buffers: &mut ValidationBuffers) -> Result<InitData, InitDataError> {
+        self.validate_uncached_with_slot(init_data, buffers)
+            .map(|(data, _)| data)
+    }
+
+    fn validate_uncached_with_slot(
+        &self,
+        init_data: &str,
+        buffers: &mut ValidationBuffers,
+    ) -> Result<(InitData, TokenSlot), InitDataError> {
+        let slot = self.verify_rotating(init_data, buffers)?;
+        let data = self.check_parsed(parse_into(init_data, &self.parse_options, &mut buffers.json)?)?;
+
+        Ok((data, slot))
+    }
+
+    /// Verifies with the current token, then with the previous one until its cutoff.
+    fn verify_rotating(&self, init_data: &str, buffers: &mut ValidationBuffers) -> Result<TokenSlot, InitDataError> {
+        let expiration = self.expiration.before_parsing();
+
+        match verify_with_buffers(
+            init_data,
+            &*self.current_token(),
+            expiration,
+            &SystemClock,
+            buffers,
+            self.compat,
+            self.check_order,
+        ) {
+            Err(InitDataError::HashInvalid) => match &self.previous_token {
+                Some((previous, cutoff)) if SystemClock.now() < *cutoff => {
+                    verify_with_buffers(
+                        init_data,
+                        previous,
+                        expiration,
+                        &SystemClock,
+                        buffers,
+                        self.compat,
+                        self.check_order,
+                    )?;
+                    Ok(TokenSlot::Previous)
+                }
+                _ => Err(InitDataError::HashInvalid),
+            },
+            result => result.map(|()| TokenSlot::Current),
+        }
+    }
+}
+
+/// Replaces the bot token of a [`Validator`], see [`Validator::token_handle`].
+#[derive(Clone)]
+pub struct TokenHandle {
+    token: Arc<RwLock<BotToken>>,
+    rejection_cache: Option<Arc<RejectionCache>>,
+}
+
+impl TokenHandle {
+    /// Replaces the bot token, see [`Validator::set_token`].
+    pub fn set(&self, token: impl Into<BotToken>) {
+        *self.token.write().unwrap_or_else(PoisonError::into_inner) = token.into();
+
+        if let Some(cache) = &self.rejection_cache {
+            cache.clear();
+        }
+    }
+}
+
+impl std::fmt::Debug for TokenHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TokenHandle").finish_non_exhaustive()
+    }
+}
+
+/// Which token of a [`Validator::with_rotating_tokens`] validator verified the init data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenSlot {
+    /// The current bot token.
+    Current,
+    /// The previous bot token, before its cutoff.
+    Previous,
+}
+
+/// Runs `f` with the scratch buffers of the thread, or with fresh ones when they are already in use.
+fn with_buffers<R>(f: impl FnOnce(&mut ValidationBuffers) -> R) -> R {
+    BUFFERS.with(|buffers| match buffers.try_borrow_mut() {
+        Ok(mut buffers) => f(&mut buffers),
+        Err(_) => f(&mut ValidationBuffers::default()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::user_gate::GateDecision;
+
+    const BOT_TOKEN: &str = "5768337691:AAH5YkoiEuPk8-FZa32hStHTqXiLPtAEhx8";
+    const VALID_INIT_DATA: &str = "query_id=AAHdF6IQAAAAAN0XohDhrOrc&user=%7B%22id%22%3A279058397%2C%22first_name%22%3A%22Vladislav%22%2C%22last_name%22%3A%22Kibenko%22%2C%22username%22%3A%22vdkfrost%22%2C%22language_code%22%3A%22ru%22%2C%22is_premium%22%3Atrue%7D&auth_date=1662771648&hash=c501b71e775f74ce10e377dea85a7ea24ecd640b223ea86dfe453e0eaed2e2b2";
+
+    #[test]
+    fn test_validator_validate() {
+        let validator = Validator::new(BOT_TOKEN).expiration(Expiration::Never);
+        assert!(validator.validate(VALID_INIT_DATA).is_ok());
+        assert!(validator.rejection_cache_stats().is_none());
+    }
+
+    #[test]
+    fn test_validator_reuses_buffers() {
+        let validator = Validator::new(BOT_TOKEN).expiration(Expiration::Never);
+        let mut buffers = ValidationBuffers::new();
+
+        assert!(validator.validate_with_buffers(VALID_INIT_DATA, &mut buffers).is_ok());
+        let capacities = (
+            buffers.decoded.capacity(),
+            buffers.params.capacity(),
+            buffers.json.capacity(),
+        );
+        assert!(validator.validate_with_buffers(VALID_INIT_DATA, &mut buffers).is_ok());
+
+        assert_eq!(
+            (
+                buffers.decoded.capacity(),
+                buffers.params.capacity(),
+                buffers.json.capacity()
+            ),
+            capacities
+        );
+    }
+
+    #[test]
+    fn test_validator_throttle() {
+        use crate::throttle::TokenBucket;
+
+        let validator = Validator::new(BOT_TOKEN)
+            .expiration(Expiration::Never)
+            .with_throttle(TokenBucket::with_clock(1, 0.0, || 0));
+        let forged = VALID_INIT_DATA.replace("Vladislav", "Mallory");
+
+        assert!(validator.validate_from("10.0.0.1", VALID_INIT_DATA).is_ok());
+        assert!(matches!(
+            validator.validate_from("10.0.0.1", &forged),
+            Err(InitDataError::HashInvalid)
+        ));
+        assert!(matches!(
+            validator.validate_from("10.0.0.1", VALID_INIT_DATA),
+            Err(InitDataError::Throttled)
+        ));
+        assert!(validator.validate_from("10.0.0.2", VALID_INIT_DATA).is_ok());
+    }
+
+    #[test]
+    fn test_validator_rejection_cache() {
+        let validator = Validator::new(BOT_TOKEN)
+            .expiration(Expiration::Never)
+            .with_rejection_cache(16);
+        let forged = VALID_INIT_DATA.replace("Vladislav", "Mallory");
+
+        assert!(matches!(validator.validate(&forged), Err(InitDataError::HashInvalid)));
+        assert!(matches!(validator.validate(&forged), Err(InitDataError::HashInvalid)));
+        assert!(validator.validate(VALID_INIT_DATA).is_ok());
+
+        let stats = validator.rejection_cache_stats().unwrap();
+        assert_eq!((stats.hits, stats.misses, stats.len), (1, 2, 1));
+    }
+
+    #[cfg(feature = "diagnostics")]
+    #[test]
+    fn test_validator_failure_sampling() {
+        let validator = Validator::new(BOT_TOKEN)
+            .expiration(Expiration::Never)
+            .with_failure_sampling(4, true);
+        let forged = VALID_INIT_DATA.replace("Vladislav", "Mallory");
+
+        assert!(validator.validate(VALID_INIT_DATA).is_ok());
+        assert!(validator.validate(&forged).is_err());
+
+        let samples = validator.failure_samples();
+        assert_eq!(samples.len(), 1);
+        assert_eq!(samples[0].error_kind, "hash_invalid");
+        assert!(samples[0]
+            .payload
+            .starts_with("query_id=AAHdF6IQAAAAAN0XohDhrOrc&user=REDACTED&"));
+        assert!(Validator::new(BOT_TOKEN).failure_samples().is_empty());
+    }
+
+    #[test]
+    fn test_validator_metrics() {
+        use std::sync::{Arc, Mutex};
+        use std::time::Duration;
+
+        #[derive(Clone, Default)]
+        struct Recorder(Arc<Mutex<Vec<String>>>);
+
+        impl MetricsSink for Recorder {
+            fn record_success(&self, scheme: ValidationScheme, _: Duration) {
+                self.0.lock().unwrap().push(format!("success {scheme}"));
+            }
+
+            fn record_failure(&self, scheme: ValidationScheme, error: &InitDataError, _: Duration) {
+                self.0
+                    .lock()
+                    .unwrap()
+                    .push(format!("failure {scheme} {}", error.kind()));
+            }
+        }
+
+        let recorder = Recorder::default();
+        let validator = Validator::new(BOT_TOKEN)
+            .expiration(Expiration::Never)
+            .with_metrics(recorder.clone());
+
+        assert!(validator.validate(VALID_INIT_DATA).is_ok());
+        assert!(validator
+            .validate_scheme(VALID_INIT_DATA, ValidationScheme::Ed25519ThirdParty)
+            .is_err());
+        assert!(validator.validate("auth_date=1").is_err());
+
+        assert_eq!(
+            *recorder.0.lock().unwrap(),
+            [
+                "success hash",
+                "failure signature signature_missing",
+                "failure hash hash_missing"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validator_validation_cache() {
+        use crate::validation_cache::MemoryValidationCache;
+        use std::sync::Arc;
+
+        let cache = Arc::new(MemoryValidationCache::new(16));
+        let validator = Validator::new(BOT_TOKEN)
+            .expiration(Expiration::Never)
+            .with_validation_cache(Arc::clone(&cache));
+        let forged = VALID_INIT_DATA.replace("Vladislav", "Mallory");
+
+        assert!(validator.validate(VALID_INIT_DATA).is_ok());
+        assert!(validator.validate(&forged).is_err());
+        assert_eq!(cache.len(), 1);
+        assert!(validator.validate(VALID_INIT_DATA).is_ok());
+
+        // Entries outliving the init data are not served
+        let validator = Validator::new(BOT_TOKEN).with_validation_cache(Arc::clone(&cache));
+        assert!(matches!(
+            validator.validate(VALID_INIT_DATA),
+            Err(InitDataError::Expired)
+        ));
+    }
+
+    #[test]
+    fn test_validator_shared_validation_cache() {
+        use crate::validation_cache::MemoryValidationCache;
+        use std::sync::Arc;
+
+        let cache = Arc::new(MemoryValidationCache::new(16));
+        let validator = Validator::new(BOT_TOKEN)
+            .expiration(Expiration::Never)
+            .with_validation_cache(Arc::clone(&cache));
+        assert!(validator.validate(VALID_INIT_DATA).is_ok());
+        assert_eq!(cache.len(), 1);
+
+        // Another token doesn't find the entry
+        let other_token = Validator::new("1234567890:OTHER-TOKEN")
+            .expiration(Expiration::Never)
+            .with_validation_cache(Arc::clone(&cache));
+        assert!(matches!(
+            other_token.validate(VALID_INIT_DATA),
+            Err(InitDataError::HashInvalid)
+        ));
+
+        // Neither do stricter policies
+        let other_policy = Validator::new(BOT_TOKEN)
+            .expiration(Expiration::Never)
+            .chat_policy(ChatPolicy::new().allow_chats([1]))
+            .with_validation_cache(Arc::clone(&cache));
+        assert!(matches!(
+            other_policy.validate(VALID_INIT_DATA),
+            Err(InitDataError::ChatMissing)
+        ));
+
+        // The same settings do
+        let same = Validator::new(BOT_TOKEN)
+            .expiration(Expiration::Never)
+            .with_validation_cache(Arc::clone(&cache));
+        assert!(same.validate(VALID_INIT_DATA).is_ok());
+        assert_eq!(cache.len(), 1);
+
+        // Nor does the validator once its token is replaced
+        validator.set_token("1234567890:OTHER-TOKEN");
+        assert!(matches!(
+            validator.validate(VALID_INIT_DATA),
+            Err(InitDataError::HashInvalid)
+        ));
+    }
+
+    #[test]
+    fn test_validator_expiration_policy() {
+        use crate::model::ChatType;
+
+        let chat_data = VALID_INIT_DATA.replace("&auth_date=", "&chat_type=group&auth_date=");
+        let hash = crate::sign(&chat_data, BOT_TOKEN).unwrap();
+        let chat_data = chat_data.replace(
+            "c501b71e775f74ce10e377dea85a7ea24ecd640b223ea86dfe453e0eaed2e2b2",
+            &hash,
+        );
+
+        let validator = Validator::new(BOT_TOKEN).expiration_policy(
+            ExpirationPolicy::new(Expiration::Never).chat_type(ChatType::Group, Expiration::Default),
+        );
+        assert!(validator.validate(VALID_INIT_DATA).is_ok());
+        assert!(matches!(validator.validate(&chat_data), Err(InitDataError::Expired)));
+    }
+
+    #[test]
+    fn test_validator_start_param_policy() {
+        let with_start_param = |start_param: &str| {
+            let data = VALID_INIT_DATA.replace("&auth_date=", &format!("&start_param={start_param}&auth_date="));
+            let hash = crate::sign(&data, BOT_TOKEN).unwrap();
+            data.replace(
+                "c501b71e775f74ce10e377dea85a7ea24ecd640b223ea86dfe453e0eaed2e2b2",
+                &hash,
+            )
+        };
+
+        let validator = Validator::new(BOT_TOKEN)
+            .expiration(Expiration::Never)
+            .start_param_policy(StartParamPolicy::telegram().check(|start_param| start_param.starts_with("ref_")));
+        assert!(validator.validate(&with_start_param("ref_42")).is_ok());
+        assert!(validator.validate(VALID_INIT_DATA).is_ok());
+        assert!(matches!(
+            validator.validate(&with_start_param("ref_%3Cscript%3E")),
+            Err(InitDataError::StartParamInvalid(_))
+        ));
+        assert!(matches!(
+            validator.validate(&with_start_param("promo")),
+            Err(InitDataError::StartParamInvalid(_))
+        ));
+    }
+
+    #[test]
+    fn test_validator_privacy_mode() {
+        let validator = Validator::new(BOT_TOKEN)
+            .expiration(Expiration::Never)
+            .privacy_mode(PrivacyMode::strict());
+        let data = validator.validate(VALID_INIT_DATA).unwrap();
+        let user = data.user.as_ref().unwrap();
+
+        assert_eq!(user.id, 279_058_397);
+        assert!(user.first_name.is_empty() && user.username.is_none());
+        assert!(!format!("{data:?}").contains("vdkfrost"));
+    }
+
+    #[test]
+    fn test_validator_scheme() {
+        let validator = Validator::new(BOT_TOKEN).expiration(Expiration::Never);
+        assert!(validator.validate_auto(VALID_INIT_DATA).is_ok());
+
+        let validator = validator.scheme(ValidationScheme::Ed25519ThirdParty);
+        assert!(validator.validate_auto(VALID_INIT_DATA).is_err());
+        assert!(validator
+            .validate_scheme(VALID_INIT_DATA, ValidationScheme::BotTokenHmac)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_validator_compat() {
+        let compat = Compat {
+            exclude_signature: true,
+            empty_strings_as_none: true,
+            ..Compat::default()
+        };
+        let base_data = "user=%7B%22id%22%3A1%2C%22first_name%22%3A%22A%22%2C%22last_name%22%3A%22%22%7D&auth_date=1662771648&signature=abc";
+        let init_data = format!(
+            "{base_data}&hash={}",
+            crate::sign_with_compat(base_data, BOT_TOKEN, compat).unwrap()
+        );
+
+        let validator = Validator::new(BOT_TOKEN).expiration(Expiration::Never);
+        assert!(matches!(
+            validator.validate(&init_data),
+            Err(InitDataError::HashInvalid)
+        ));
+
+        let data = validator.compat(compat).validate(&init_data).unwrap();
+        assert!(data.user.unwrap().last_name.is_none());
+    }
+
+    #[test]
+    fn test_validator_user_gate() {
+        let validator = Validator::new(BOT_TOKEN)
+            .expiration(Expiration::Never)
+            .with_validation_cache(crate::MemoryValidationCache::new(16))
+            .with_user_gate(|user_id| GateDecision::from(user_id != 279_058_397));
+
+        // Also when answered from the validation cache
+        for _ in 0..2 {
+            assert!(matches!(
+                validator.validate(VALID_INIT_DATA),
+                Err(InitDataError::UserDenied(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_validator_validate_gated() {
+        use std::future::Future;
+        use std::task::{Context, Poll, Waker};
+
+        struct Banned;
+
+        impl AsyncUserGate for Banned {
+            async fn check(&self, user_id: i64) -> Result<GateDecision, InitDataError> {
+                Ok(if user_id == 279_058_397 {
+                    GateDecision::DenyWithReason("banned".to_string())
+                } else {
+                    GateDecision::Allow
+                })
+            }
+        }
+
+        let validator = Validator::new(BOT_TOKEN).expiration(Expiration::Never);
+        let mut future = std::pin::pin!(validator.validate_gated(VALID_INIT_DATA, &Banned));
+        let Poll::Ready(result) = future.as_mut().poll(&mut Context::from_waker(Waker::noop())) else {
+            panic!("the test gate never waits");
+        };
+
+        assert!(matches!(result, Err(InitDataError::UserDenied(reason)) if reason == "banned"));
+    }
+
+    #[test]
+    fn test_validator_rotating_tokens() {
+        let validator =
+            Validator::with_rotating_tokens("123:NEW_TOKEN", BOT_TOKEN, u64::MAX).expiration(Expiration::Never);
+        let (_, slot) = validator.validate_rotating(VALID_INIT_DATA).unwrap();
+        assert_eq!(slot, TokenSlot::Previous);
+        assert!(validator.validate(VALID_INIT_DATA).is_ok());
+
+        let validator = Validator::with_rotating_tokens(BOT_TOKEN, "123:OLD_TOKEN", 0).expiration(Expiration::Never);
+        assert_eq!(
+            validator.validate_rotating(VALID_INIT_DATA).unwrap().1,
+            TokenSlot::Current
+        );
+
+        // Past the cutoff the previous token is rejected
+        let validator = Validator::with_rotating_tokens("123:NEW_TOKEN", BOT_TOKEN, 1).expiration(Expiration::Never);
+        assert!(matches!(
+            validator.validate(VALID_INIT_DATA),
+            Err(InitDataError::HashInvalid)
+        ));
+    }
+
+    #[test]
+    fn test_validator_from_vars() {
+        use std::env::VarError;
+
+        let vars = |vars: &'static [(&str, &str)]| {
+            move |name: &str| {
+                vars.iter()
+                    .find(|(key, _)| *key == name)
+                    .map(|(_, value)| (*value).to_string())
+                    .ok_or(VarError::NotPresent)
+            }
+        };
+
+        let validator = Validator::from_vars(vars(&[
+            ("TELEGRAM_BOT_TOKEN", BOT_TOKEN),
+            ("INIT_DATA_TTL", "0"),
+            ("INIT_DATA_ENV", "Test"),
+        ]))
+        .unwrap();
+        assert!(validator.validate(VALID_INIT_DATA).is_ok());
+        assert_eq!(validator.environment, Environment::Test);
+
+        assert!(matches!(
+            Validator::from_vars(vars(&[("TELEGRAM_BOT_TOKEN", BOT_TOKEN)]))
+                .unwrap()
+                .validate(VALID_INIT_DATA),
+            Err(InitDataError::Expired)
+        ));
+        assert!(matches!(Validator::from_vars(vars(&[])), Err(InitDataError::Config(_))));
+        assert!(matches!(
+            Validator::from_vars(vars(&[("TELEGRAM_BOT_TOKEN", BOT_TOKEN), ("INIT_DATA_TTL", "1h")])),
+            Err(InitDataError::Config(_))
+        ));
+        assert!(matches!(
+            Validator::from_vars(vars(&[("TELEGRAM_BOT_TOKEN", BOT_TOKEN), ("INIT_DATA_ENV", "staging")])),
+            Err(InitDataError::Config(_))
+        ));
+    }
+
+    #[test]
+    fn test_validator_set_token() {
+        let validator = Validator::new("123:OLD_TOKEN")
+            .expiration(Expiration::Never)
+            .with_rejection_cache(16);
+        assert!(matches!(
+            validator.validate(VALID_INIT_DATA),
+            Err(InitDataError::HashInvalid)
+        ));
+
+        validator.token_handle().set(BOT_TOKEN);
+        assert!(validator.validate(VALID_INIT_DATA).is_ok());
+        assert_eq!(validator.rejection_cache_stats().unwrap().len, 0);
+
+        validator.set_token("123:OLD_TOKEN");
+        assert!(validator.validate(VALID_INIT_DATA).is_err());
+    }
+
+    #[test]
+    fn test_validator_validate_query() {
+        let validator = Validator::new(BOT_TOKEN).expiration(Expiration::Never);
+        let encoded: String = form_urlencoded::byte_serialize(VALID_INIT_DATA.as_bytes()).collect();
+
+        assert!(validator
+            .validate_query(&format!("?initData={encoded}&page=1"), "initData")
+            .is_ok());
+        assert!(matches!(
+            validator.validate_query("page=1", "initData"),
+            Err(InitDataError::UnexpectedFormat(_))
+        ));
+    }
+
+    #[test]
+    fn test_validator_validate_body() {
+        let validator = Validator::new(BOT_TOKEN).expiration(Expiration::Never);
+        let encoded: String = form_urlencoded::byte_serialize(VALID_INIT_DATA.as_bytes()).collect();
+        let multipart =
+            format!("--x\r\nContent-Disposition: form-data; name=\"initData\"\r\n\r\n{VALID_INIT_DATA}\r\n--x--\r\n");
+
+        assert!(validator
+            .validate_form(&format!("initData={encoded}"), "initData")
+            .is_ok());
+        assert!(validator
+            .validate_multipart(multipart.as_bytes(), "multipart/form-data; boundary=x", "initData")
+            .is_ok());
+        #[cfg(feature = "json")]
+        assert!(validator
+            .validate_json(format!(r#"{{"initData":"{VALID_INIT_DATA}"}}"#).as_bytes(), "initData")
+            .is_ok());
+    }
+
+    #[test]
+    fn test_validator_validate_launch_params() {
+        use crate::launch_params::Platform;
+
+        let validator = Validator::new(BOT_TOKEN)
+            .expiration(Expiration::Never)
+            .client_policy(ClientPolicy::new().platforms([Platform::Ios]));
+        let encoded: String = form_urlencoded::byte_serialize(VALID_INIT_DATA.as_bytes()).collect();
+
+        let (params, data) = validator
+            .validate_launch_params(&format!(
+                "#tgWebAppData={encoded}&tgWebAppVersion=8.0&tgWebAppPlatform=ios"
+            ))
+            .unwrap();
+        assert_eq!(params.platform, Platform::Ios);
+        assert_eq!(data.auth_date, 1_662_771_648);
+
+        assert!(matches!(
+            validator.validate_launch_params(&format!(
+                "tgWebAppData={encoded}&tgWebAppVersion=8.0&tgWebAppPlatform=web"
+            )),
+            Err(InitDataError::ClientNotAllowed(_))
+        ));
+        assert!(matches!(
+            validator.validate_launch_params("tgWebAppVersion=8.0&tgWebAppPlatform=ios"),
+            Err(InitDataError::UnexpectedFormat(_))
+        ));
+    }
+}
@@ -0,0 +1,217 @@
+//! JavaScript/TypeScript bindings built with `wasm-bindgen`.
+//!
+//! Enabled with the `wasm` feature. The exported functions mirror the Rust API so that
+//! Node and edge runtimes run exactly the same validation logic as the Rust backend. Build the
+//! module with `--crate-type cdylib` and generate the JavaScript glue with `wasm-bindgen`:
+//!
+//! ```sh
+//! cargo rustc --lib --release --target wasm32-unknown-unknown --features wasm --crate-type cdylib
+//! wasm-bindgen --target nodejs --out-dir pkg target/wasm32-unknown-unknown/release/init_data_rs.wasm
+//! ```
+//!
+//! Like the napi addon, object properties and parameters are exposed in camelCase. 64-bit integers
+//! (`authDate`, ids, `chatInstance`, `botId`, `expiresIn`) are exchanged as JavaScript `bigint`s, since
+//! Telegram ids do not fit into a `number` without losing precision.
+//!
+//! `expiresIn` is in seconds: `undefined` for [`crate::Expiration::Default`] and `0n` for
+//! [`crate::Expiration::Never`].
+
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+use crate::{ChatType, Expiration, InitDataError};
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_TYPES: &str = r#"
+export type ChatType = "sender" | "private" | "group" | "supergroup" | "channel";
+
+export interface User {
+    addedToAttachmentMenu?: boolean;
+    allowsWriteToPm?: boolean;
+    firstName: string;
+    id: bigint;
+    isBot?: boolean;
+    isPremium?: boolean;
+    lastName?: string;
+    languageCode?: string;
+    photoUrl?: string;
+    username?: string;
+}
+
+export interface Chat {
+    id: bigint;
+    photoUrl?: string;
+    type: ChatType;
+    title: string;
+    username?: string;
+}
+
+export interface InitData {
+    authDate: bigint;
+    canSendAfter?: number;
+    chat?: Chat;
+    chatType?: ChatType;
+    chatInstance?: bigint;
+    hash: string;
+    queryId?: string;
+    receiver?: User;
+    startParam?: string;
+    user?: User;
+    signature?: string;
+}
+"#;
+
+/// [`crate::User`] with the camelCase keys of `TS_TYPES`.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct User<'a> {
+    added_to_attachment_menu: Option<bool>,
+    allows_write_to_pm: Option<bool>,
+    first_name: &'a str,
+    id: i64,
+    is_bot: Option<bool>,
+    is_premium: Option<bool>,
+    last_name: Option<&'a str>,
+    language_code: Option<&'a str>,
+    photo_url: Option<&'a str>,
+    username: Option<&'a str>,
+}
+
+/// [`crate::Chat`] with the camelCase keys of `TS_TYPES`.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Chat<'a> {
+    id: i64,
+    photo_url: Option<&'a str>,
+    r#type: &'a ChatType,
+    title: &'a str,
+    username: Option<&'a str>,
+}
+
+/// [`crate::InitData`] with the camelCase keys of `TS_TYPES`.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct InitData<'a> {
+    auth_date: u64,
+    can_send_after: Option<u32>,
+    chat: Option<Chat<'a>>,
+    chat_type: Option<&'a ChatType>,
+    chat_instance: Option<i64>,
+    hash: &'a str,
+    query_id: Option<&'a str>,
+    receiver: Option<User<'a>>,
+    start_param: Option<&'a str>,
+    user: Option<User<'a>>,
+    signature: Option<&'a str>,
+}
+
+impl<'a> From<&'a crate::User> for User<'a> {
+    fn from(user: &'a crate::User) -> Self {
+        Self {
+            added_to_attachment_menu: user.added_to_attachment_menu,
+            allows_write_to_pm: user.allows_write_to_pm,
+            first_name: &user.first_name,
+            id: user.id,
+            is_bot: user.is_bot,
+            is_premium: user.is_premium,
+            last_name: user.last_name.as_deref(),
+            language_code: user.language_code.as_deref(),
+            photo_url: user.photo_url.as_deref(),
+            username: user.username.as_deref(),
+        }
+    }
+}
+
+impl<'a> From<&'a crate::Chat> for Chat<'a> {
+    fn from(chat: &'a crate::Chat) -> Self {
+        Self {
+            id: chat.id,
+            photo_url: chat.photo_url.as_deref(),
+            r#type: &chat.chat_type,
+            title: &chat.title,
+            username: chat.username.as_deref(),
+        }
+    }
+}
+
+impl<'a> From<&'a crate::InitData> for InitData<'a> {
+    fn from(data: &'a crate::InitData) -> Self {
+        Self {
+            auth_date: data.auth_date,
+            can_send_after: data.can_send_after,
+            chat: data.chat.as_ref().map(Into::into),
+            chat_type: data.chat_type.as_ref(),
+            chat_instance: data.chat_instance,
+            hash: &data.hash,
+            query_id: data.query_id.as_deref(),
+            receiver: data.receiver.as_ref().map(Into::into),
+            start_param: data.start_param.as_deref(),
+            user: data.user.as_ref().map(Into::into),
+            signature: data.signature.as_deref(),
+        }
+    }
+}
+
+fn to_js_error(error: &InitDataError) -> JsError {
+    JsError::new(&error.to_string())
+}
+
+fn to_js_value(data: &crate::InitData) -> Result<JsValue, JsError> {
+    let serializer = serde_wasm_bindgen::Serializer::new().serialize_large_number_types_as_bigints(true);
+    InitData::from(data)
+        .serialize(&serializer)
+        .map_err(|error| to_js_error(&InitDataError::Internal(error.to_string())))
+}
+
+/// Validates init data using the bot token, see [`crate::validate`].
+///
+/// # Errors
+///
+/// Throws a JavaScript `Error` carrying the message of the underlying [`InitDataError`].
+#[wasm_bindgen(js_name = validate, unchecked_return_type = "InitData")]
+pub fn validate(
+    #[wasm_bindgen(js_name = initData)] init_data: &str,
+    token: &str,
+    #[wasm_bindgen(js_name = expiresIn)] expires_in: Option<u64>,
+) -> Result<JsValue, JsError> {
+    let data =
+        crate::validate(init_data, token, Expiration::from_seconds(expires_in)).map_err(|error| to_js_error(&error))?;
+    to_js_value(&data)
+}
+
+/// Validates init data for third-party use, see [`crate::validate_third_party`].
+///
+/// # Errors
+///
+/// Throws a JavaScript `Error` carrying the message of the underlying [`InitDataError`].
+#[wasm_bindgen(js_name = validateThirdParty, unchecked_return_type = "InitData")]
+pub fn validate_third_party(
+    #[wasm_bindgen(js_name = initData)] init_data: &str,
+    #[wasm_bindgen(js_name = botId)] bot_id: i64,
+    #[wasm_bindgen(js_name = expiresIn)] expires_in: Option<u64>,
+) -> Result<JsValue, JsError> {
+    let data = crate::validate_third_party(init_data, bot_id, Expiration::from_seconds(expires_in))
+        .map_err(|error| to_js_error(&error))?;
+    to_js_value(&data)
+}
+
+/// Parses init data without validating it, see [`crate::parse`].
+///
+/// # Errors
+///
+/// Throws a JavaScript `Error` carrying the message of the underlying [`InitDataError`].
+#[wasm_bindgen(js_name = parse, unchecked_return_type = "InitData")]
+pub fn parse(#[wasm_bindgen(js_name = initData)] init_data: &str) -> Result<JsValue, JsError> {
+    let data = crate::parse(init_data).map_err(|error| to_js_error(&error))?;
+    to_js_value(&data)
+}
+
+/// Computes the hash of init data using the bot token, see [`crate::sign`].
+///
+/// # Errors
+///
+/// Throws a JavaScript `Error` carrying the message of the underlying [`InitDataError`].
+#[wasm_bindgen(js_name = sign)]
+pub fn sign(#[wasm_bindgen(js_name = initData)] init_data: &str, token: &str) -> Result<String, JsError> {
+    crate::sign(init_data, token).map_err(|error| to_js_error(&error))
+}
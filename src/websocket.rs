@@ -0,0 +1,161 @@
+//! Authenticating WebSocket connections of Mini Apps.
+//!
+//! Browsers can't set headers on WebSocket handshakes, so init data reaches the server either in the
+//! `Sec-WebSocket-Protocol` header or in the first message of the connection.
+
+use std::time::{Duration, Instant};
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD as base64_engine;
+use base64::Engine as _;
+
+use crate::error::InitDataError;
+use crate::launch_params::extract_from_authorization_header;
+use crate::model::InitData;
+use crate::validator::Validator;
+
+/// Prefix of the subprotocol carrying init data, followed by the base64url encoded init data.
+const PROTOCOL_PREFIX: &str = "tma.";
+
+/// Encodes init data as a WebSocket subprotocol, `tma.` followed by the base64url encoded init data,
+/// since query strings aren't valid subprotocol tokens.
+///
+/// The frontend passes it next to the protocol of the application:
+/// `new WebSocket(url, ["chat.v1", "tma." + base64url(initData)])`.
+///
+/// # Example
+/// ```
+/// use init_data_rs::{init_data_from_protocols, tma_protocol};
+///
+/// let header = format!("chat.v1, {}", tma_protocol("query_id=123&auth_date=1662771648&hash=..."));
+/// assert_eq!(
+///     init_data_from_protocols(&header).unwrap().as_deref(),
+///     Some("query_id=123&auth_date=1662771648&hash=...")
+/// );
+/// ```
+#[must_use]
+pub fn tma_protocol(init_data: &str) -> String {
+    format!("{PROTOCOL_PREFIX}{}", base64_engine.encode(init_data))
+}
+
+/// Reads init data from the value of a `Sec-WebSocket-Protocol` header, see [`tma_protocol`].
+///
+/// The server must not select the `tma.` subprotocol in its response, as that would echo the init data,
+/// but the protocol of the application.
+///
+/// # Returns
+/// `None` if no offered subprotocol carries init data.
+///
+/// # Errors
+///
+/// Returns [`InitDataError::UnexpectedFormat`] if the subprotocol isn't valid base64url or UTF-8.
+pub fn init_data_from_protocols(header: &str) -> Result<Option<String>, InitDataError> {
+    let Some(encoded) = header
+        .split(',')
+        .find_map(|protocol| protocol.trim().strip_prefix(PROTOCOL_PREFIX))
+    else {
+        return Ok(None);
+    };
+
+    let decoded = base64_engine
+        .decode(encoded)
+        .map_err(|error| InitDataError::UnexpectedFormat(format!("invalid tma subprotocol: {error}")))?;
+
+    String::from_utf8(decoded)
+        .map(Some)
+        .map_err(|_| InitDataError::UnexpectedFormat("invalid tma subprotocol: not UTF-8".to_string()))
+}
+
+/// Authenticates a WebSocket connection by the first message it receives, which must arrive before a
+/// deadline and hold the init data, optionally prefixed with `tma ` like the `Authorization` header.
+///
+/// # Example
+/// ```
+/// use std::time::Duration;
+/// use init_data_rs::{FirstMessageAuth, Validator};
+///
+/// let validator = Validator::new("BOT_TOKEN");
+///
+/// // on connection
+/// let auth = FirstMessageAuth::new(Duration::from_secs(5));
+///
+/// // wait at most `auth.remaining()` for the first text message
+/// let message = "tma query_id=123&auth_date=1662771648&hash=...";
+/// let result = auth.validate(&validator, message);
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct FirstMessageAuth {
+    deadline: Instant,
+}
+
+impl FirstMessageAuth {
+    /// Starts waiting for the first message, which must arrive within `timeout`.
+    #[must_use]
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            deadline: Instant::now() + timeout,
+        }
+    }
+
+    /// Time left to receive the first message, e.g. for `tokio::time::timeout`.
+    #[must_use]
+    pub fn remaining(&self) -> Duration {
+        self.deadline.saturating_duration_since(Instant::now())
+    }
+
+    /// Validates the first message with `validator`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`InitDataError::DeadlineExceeded`] if the deadline has passed, see [`Validator::validate`]
+    /// for the other errors.
+    pub fn validate(&self, validator: &Validator, message: &str) -> Result<InitData, InitDataError> {
+        if Instant::now() > self.deadline {
+            return Err(InitDataError::DeadlineExceeded);
+        }
+
+        let message = message.trim();
+        validator.validate(extract_from_authorization_header(message).unwrap_or(message))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::expiration::Expiration;
+
+    const BOT_TOKEN: &str = "5768337691:AAH5YkoiEuPk8-FZa32hStHTqXiLPtAEhx8";
+    const VALID_INIT_DATA: &str = "query_id=AAHdF6IQAAAAAN0XohDhrOrc&user=%7B%22id%22%3A279058397%2C%22first_name%22%3A%22Vladislav%22%2C%22last_name%22%3A%22Kibenko%22%2C%22username%22%3A%22vdkfrost%22%2C%22language_code%22%3A%22ru%22%2C%22is_premium%22%3Atrue%7D&auth_date=1662771648&hash=c501b71e775f74ce10e377dea85a7ea24ecd640b223ea86dfe453e0eaed2e2b2";
+
+    #[test]
+    fn test_init_data_from_protocols() {
+        let header = format!("chat.v1,{}", tma_protocol(VALID_INIT_DATA));
+        assert_eq!(
+            init_data_from_protocols(&header).unwrap().as_deref(),
+            Some(VALID_INIT_DATA)
+        );
+
+        assert_eq!(init_data_from_protocols("chat.v1, chat.v2").unwrap(), None);
+        assert!(matches!(
+            init_data_from_protocols("tma.%%%"),
+            Err(InitDataError::UnexpectedFormat(_))
+        ));
+    }
+
+    #[test]
+    fn test_first_message_auth() {
+        let validator = Validator::new(BOT_TOKEN).expiration(Expiration::Never);
+
+        let auth = FirstMessageAuth::new(Duration::from_secs(60));
+        assert!(auth.remaining() > Duration::ZERO);
+        assert!(auth.validate(&validator, VALID_INIT_DATA).is_ok());
+        assert!(auth.validate(&validator, &format!("tma {VALID_INIT_DATA}\n")).is_ok());
+
+        let expired = FirstMessageAuth::new(Duration::ZERO);
+        std::thread::sleep(Duration::from_millis(1));
+        assert_eq!(expired.remaining(), Duration::ZERO);
+        assert!(matches!(
+            expired.validate(&validator, VALID_INIT_DATA),
+            Err(InitDataError::DeadlineExceeded)
+        ));
+    }
+}
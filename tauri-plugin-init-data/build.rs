@@ -0,0 +1,3 @@
+fn main() {
+    tauri_plugin::Builder::new(&["validate_init_data"]).build();
+}
@@ -0,0 +1,76 @@
+//! Tauri plugin validating the init data of a Mini App wrapped in a Tauri shell.
+//!
+//! The plugin keeps a [`Validator`] in the app state and registers the `validate_init_data` command, which the
+//! webview invokes with the init data it received from Telegram:
+//!
+//! ```js
+//! import { invoke } from "@tauri-apps/api/core";
+//!
+//! const initData = await invoke("plugin:init-data|validate_init_data", {
+//!   initData: window.Telegram.WebApp.initData,
+//! });
+//! ```
+//!
+//! The command is denied until a capability of the app grants `init-data:default`. Errors reach the webview as
+//! [`ProblemDetails`], without anything taken from the init data.
+
+#![warn(clippy::pedantic)]
+
+use tauri::plugin::{Builder, TauriPlugin};
+use tauri::{Manager, Runtime, State};
+
+use init_data_rs::{InitData, ProblemDetails, Validator, ValidatorConfig};
+
+/// Name of the plugin, the prefix of its commands and permissions, which Tauri derives from the crate name
+/// without its `tauri-plugin-` prefix.
+pub const PLUGIN_NAME: &str = "init-data";
+
+/// Creates the plugin with its settings read from `plugins.init-data` in `tauri.conf.json`, see
+/// [`ValidatorConfig`].
+///
+/// ```json
+/// {
+///   "plugins": {
+///     "init-data": { "token": { "env": "TELEGRAM_BOT_TOKEN" }, "ttl": 3600 }
+///   }
+/// }
+/// ```
+///
+/// The app fails to start if the settings are missing or invalid, e.g. the token variable is not set.
+///
+/// # Example
+/// ```no_run
+/// tauri::Builder::default()
+///     .plugin(tauri_plugin_init_data::init())
+///     .run(tauri::generate_context!())
+///     .expect("error while running tauri application");
+/// ```
+#[must_use]
+pub fn init<R: Runtime>() -> TauriPlugin<R, ValidatorConfig> {
+    Builder::<R, ValidatorConfig>::new(PLUGIN_NAME)
+        .invoke_handler(tauri::generate_handler![validate_init_data])
+        .setup(|app, api| {
+            app.manage(Validator::from_config(api.config())?);
+            Ok(())
+        })
+        .build()
+}
+
+/// Same as [`init`], with a validator built by the app instead of settings from `tauri.conf.json`.
+#[must_use]
+pub fn init_with<R: Runtime>(validator: Validator) -> TauriPlugin<R> {
+    Builder::new(PLUGIN_NAME)
+        .invoke_handler(tauri::generate_handler![validate_init_data])
+        .setup(move |app, _api| {
+            app.manage(validator);
+            Ok(())
+        })
+        .build()
+}
+
+/// Validates the init data sent by the webview with the validator of the plugin.
+#[tauri::command]
+#[allow(clippy::needless_pass_by_value)]
+fn validate_init_data(validator: State<'_, Validator>, init_data: String) -> Result<InitData, ProblemDetails> {
+    validator.validate(&init_data).map_err(|err| ProblemDetails::new(&err))
+}